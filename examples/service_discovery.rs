@@ -13,5 +13,5 @@ async fn main() {
         .await
         .unwrap();
     // print
-    println!("{:?}", client.list_local_services().await.unwrap());
+    println!("{:?}", client.list_local_services(None).await.unwrap());
 }
@@ -0,0 +1,204 @@
+use std::{collections::HashMap, fmt::Debug};
+
+use async_trait::async_trait;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{sealed::Sealed, Client, ConsulResult, WriteMeta, WriteOptions};
+
+/// An ACL auth method, used to authenticate with Consul using a trusted
+/// external party such as Kubernetes.
+///
+/// This type is used as both the request payload and the response of the auth
+/// method endpoints. For more information, see the [API documentation].
+///
+/// [API documentation]: https://www.consul.io/api-docs/acl/auth-methods
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AclAuthMethod {
+    /// Specifies a name for the ACL auth method. The name can contain
+    /// alphanumeric characters, dashes `-`, and underscores `_`.
+    pub name: String,
+    /// The type of auth method being configured, such as `kubernetes`, `jwt`
+    /// or `oidc`.
+    #[serde(rename = "Type")]
+    pub kind: String,
+    /// An optional name to use instead of the name field when displaying
+    /// information about this auth method.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Free form human readable description of the auth method.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The maximum life of any token created by this auth method, in the form
+    /// of a duration such as "10m".
+    #[serde(rename = "MaxTokenTTL", skip_serializing_if = "Option::is_none")]
+    pub max_token_ttl: Option<String>,
+    /// Either `local` or `global`, determining whether tokens created by this
+    /// auth method are local to the current datacenter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_locality: Option<String>,
+    /// The raw configuration to use for the chosen auth method. Contents will
+    /// vary depending upon the type chosen.
+    pub config: HashMap<String, Value>,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+/// This trait provides methods for interacting with the `/acl/auth-method`
+/// endpoints.
+#[async_trait]
+pub trait AclAuthMethods: Sealed {
+    /// This method creates a new ACL auth method.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/auth-methods#create-an-auth-method
+    async fn create_auth_method(
+        &self,
+        method: AclAuthMethod,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(AclAuthMethod, WriteMeta)>;
+
+    /// This method reads an ACL auth method with the given name.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/auth-methods#read-an-auth-method
+    async fn read_auth_method<S: AsRef<str> + Send + Debug>(
+        &self,
+        name: S,
+    ) -> ConsulResult<AclAuthMethod>;
+
+    /// This method updates an existing ACL auth method. The name and type of
+    /// an auth method cannot be changed.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/auth-methods#update-an-auth-method
+    async fn update_auth_method(
+        &self,
+        method: AclAuthMethod,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(AclAuthMethod, WriteMeta)>;
+
+    /// This method deletes an ACL auth method, along with any tokens created
+    /// by it.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/auth-methods#delete-an-auth-method
+    async fn delete_auth_method<S: AsRef<str> + Send + Debug>(
+        &self,
+        name: S,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+
+    /// This method lists all the ACL auth methods. The `config` field of the
+    /// returned auth methods is not populated.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/auth-methods#list-auth-methods
+    async fn list_auth_methods(&self) -> ConsulResult<Vec<AclAuthMethod>>;
+}
+
+#[async_trait]
+impl AclAuthMethods for Client {
+    #[tracing::instrument]
+    async fn create_auth_method(
+        &self,
+        method: AclAuthMethod,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(AclAuthMethod, WriteMeta)> {
+        self.put("/v1/acl/auth-method", method, None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn read_auth_method<S: AsRef<str> + Send + Debug>(
+        &self,
+        name: S,
+    ) -> ConsulResult<AclAuthMethod> {
+        self.get(format!("/v1/acl/auth-method/{}", name.as_ref()), None).await
+    }
+
+    #[tracing::instrument]
+    async fn update_auth_method(
+        &self,
+        method: AclAuthMethod,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(AclAuthMethod, WriteMeta)> {
+        self.put(format!("/v1/acl/auth-method/{}", method.name), &method, None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn delete_auth_method<S: AsRef<str> + Send + Debug>(
+        &self,
+        name: S,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        self.delete(format!("/v1/acl/auth-method/{}", name.as_ref()), None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn list_auth_methods(&self) -> ConsulResult<Vec<AclAuthMethod>> {
+        self.get("/v1/acl/auth-methods", None).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{AclAuthMethod, AclAuthMethods};
+    use crate::request::tests::recording_client;
+
+    fn method() -> AclAuthMethod {
+        AclAuthMethod {
+            name: String::from("minikube"),
+            kind: String::from("kubernetes"),
+            config: [(String::from("Host"), json!("https://192.0.2.42:8443"))].into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_serialize() {
+        assert_eq!(
+            serde_json::to_value(method()).unwrap(),
+            json!({
+                "Name": "minikube",
+                "Type": "kubernetes",
+                "Config": {"Host": "https://192.0.2.42:8443"},
+                "CreateIndex": 0,
+                "ModifyIndex": 0,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auth_method_paths() {
+        let (client, requests) = recording_client(&[
+            (200, &[], "{}"),
+            (200, &[], "{}"),
+            (200, &[], "{}"),
+            (200, &[], "true"),
+            (200, &[], "[]"),
+        ]);
+        client.create_auth_method(method(), None).await.unwrap();
+        client.read_auth_method("minikube").await.unwrap();
+        client.update_auth_method(method(), None).await.unwrap();
+        assert!(client.delete_auth_method("minikube", None).await.unwrap().0);
+        client.list_auth_methods().await.unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "PUT /v1/acl/auth-method",
+                "GET /v1/acl/auth-method/minikube",
+                "PUT /v1/acl/auth-method/minikube",
+                "DELETE /v1/acl/auth-method/minikube",
+                "GET /v1/acl/auth-methods",
+            ]
+        );
+    }
+}
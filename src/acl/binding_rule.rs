@@ -0,0 +1,230 @@
+use std::{collections::HashMap, fmt::Debug};
+
+use async_trait::async_trait;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{sealed::Sealed, Client, ConsulResult, WriteMeta, WriteOptions};
+
+/// The kind of resource a binding rule binds authenticated identities to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AclBindType {
+    /// The computed bind name value is used as the service name of a service
+    /// identity.
+    #[default]
+    Service,
+    /// The computed bind name value is used as the node name of a node
+    /// identity.
+    Node,
+    /// The computed bind name value is used as the name of a role that must
+    /// already exist.
+    Role,
+    /// The computed bind name value is used as the name of a policy that must
+    /// already exist.
+    Policy,
+}
+
+/// An ACL binding rule, used to map the identities returned by an auth method
+/// to ACL roles, policies and identities.
+///
+/// This type is used as both the request payload and the response of the
+/// binding rule endpoints. For more information, see the [API documentation].
+///
+/// [API documentation]: https://www.consul.io/api-docs/acl/binding-rules
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AclBindingRule {
+    /// The unique ID of the binding rule. This is assigned by Consul on
+    /// creation.
+    #[serde(rename = "ID", skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    /// Free form human readable description of the binding rule.
+    pub description: String,
+    /// The name of the auth method that this rule applies to.
+    pub auth_method: String,
+    /// An expression that matches against verified identity attributes returned
+    /// from the auth method during login. If empty, the rule matches all
+    /// identities.
+    pub selector: String,
+    /// The kind of resource the matched identities are bound to.
+    pub bind_type: AclBindType,
+    /// The name to bind to, which may include interpolated identity attributes
+    /// such as `${serviceaccount.name}`.
+    pub bind_name: String,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+/// This trait provides methods for interacting with the `/acl/binding-rule`
+/// endpoints.
+#[async_trait]
+pub trait AclBindingRules: Sealed {
+    /// This method creates a new ACL binding rule.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/binding-rules#create-a-binding-rule
+    async fn create_binding_rule(
+        &self,
+        rule: AclBindingRule,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(AclBindingRule, WriteMeta)>;
+
+    /// This method reads an ACL binding rule with the given ID.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/binding-rules#read-a-binding-rule
+    async fn read_binding_rule<S: AsRef<str> + Send + Debug>(
+        &self,
+        id: S,
+    ) -> ConsulResult<AclBindingRule>;
+
+    /// This method updates an existing ACL binding rule, identified by its
+    /// `id` field. The auth method of a binding rule cannot be changed.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/binding-rules#update-a-binding-rule
+    async fn update_binding_rule(
+        &self,
+        rule: AclBindingRule,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(AclBindingRule, WriteMeta)>;
+
+    /// This method deletes an ACL binding rule.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/binding-rules#delete-a-binding-rule
+    async fn delete_binding_rule<S: AsRef<str> + Send + Debug>(
+        &self,
+        id: S,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+
+    /// This method lists all the ACL binding rules, optionally only those for
+    /// the given auth method.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/binding-rules#list-binding-rules
+    async fn list_binding_rules(
+        &self,
+        auth_method: Option<&str>,
+    ) -> ConsulResult<Vec<AclBindingRule>>;
+}
+
+#[async_trait]
+impl AclBindingRules for Client {
+    #[tracing::instrument]
+    async fn create_binding_rule(
+        &self,
+        rule: AclBindingRule,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(AclBindingRule, WriteMeta)> {
+        self.put("/v1/acl/binding-rule", rule, None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn read_binding_rule<S: AsRef<str> + Send + Debug>(
+        &self,
+        id: S,
+    ) -> ConsulResult<AclBindingRule> {
+        self.get(format!("/v1/acl/binding-rule/{}", id.as_ref()), None).await
+    }
+
+    #[tracing::instrument]
+    async fn update_binding_rule(
+        &self,
+        rule: AclBindingRule,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(AclBindingRule, WriteMeta)> {
+        self.put(format!("/v1/acl/binding-rule/{}", rule.id), &rule, None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn delete_binding_rule<S: AsRef<str> + Send + Debug>(
+        &self,
+        id: S,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        self.delete(format!("/v1/acl/binding-rule/{}", id.as_ref()), None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn list_binding_rules(
+        &self,
+        auth_method: Option<&str>,
+    ) -> ConsulResult<Vec<AclBindingRule>> {
+        let mut params = HashMap::new();
+        if let Some(auth_method) = auth_method {
+            params.insert(String::from("authmethod"), auth_method.to_owned());
+        }
+        self.get_with_params("/v1/acl/binding-rules", Some(params), None).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{AclBindType, AclBindingRule, AclBindingRules};
+    use crate::request::tests::recording_client;
+
+    fn rule(id: &str) -> AclBindingRule {
+        AclBindingRule {
+            id: String::from(id),
+            auth_method: String::from("minikube"),
+            bind_type: AclBindType::Service,
+            bind_name: String::from("${serviceaccount.name}"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_serialize() {
+        assert_eq!(
+            serde_json::to_value(rule("")).unwrap(),
+            json!({
+                "Description": "",
+                "AuthMethod": "minikube",
+                "Selector": "",
+                "BindType": "service",
+                "BindName": "${serviceaccount.name}",
+                "CreateIndex": 0,
+                "ModifyIndex": 0,
+            })
+        );
+        assert_eq!(serde_json::to_value(rule("000ed53c")).unwrap()["ID"], "000ed53c");
+    }
+
+    #[tokio::test]
+    async fn test_binding_rule_paths() {
+        let (client, requests) = recording_client(&[
+            (200, &[], "{}"),
+            (200, &[], "{}"),
+            (200, &[], "{}"),
+            (200, &[], "true"),
+            (200, &[], "[]"),
+            (200, &[], "[]"),
+        ]);
+        client.create_binding_rule(rule(""), None).await.unwrap();
+        client.read_binding_rule("000ed53c").await.unwrap();
+        client.update_binding_rule(rule("000ed53c"), None).await.unwrap();
+        assert!(client.delete_binding_rule("000ed53c", None).await.unwrap().0);
+        client.list_binding_rules(None).await.unwrap();
+        client.list_binding_rules(Some("minikube")).await.unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "PUT /v1/acl/binding-rule",
+                "GET /v1/acl/binding-rule/000ed53c",
+                "PUT /v1/acl/binding-rule/000ed53c",
+                "DELETE /v1/acl/binding-rule/000ed53c",
+                "GET /v1/acl/binding-rules",
+                "GET /v1/acl/binding-rules?authmethod=minikube",
+            ]
+        );
+    }
+}
@@ -1,12 +1,22 @@
 use std::collections::HashMap;
 
 use async_trait::async_trait;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{Client, ConsulResult};
+use crate::{sealed::Sealed, Client, ConsulResult, WriteMeta};
 
+mod auth_method;
+mod binding_rule;
+mod policy;
+#[cfg(feature = "token-renewal")]
+mod renewal;
 mod token;
 
+pub use auth_method::*;
+pub use binding_rule::*;
+pub use policy::*;
+#[cfg(feature = "token-renewal")]
+pub use renewal::*;
 pub use token::*;
 
 /// An access control list.
@@ -22,6 +32,8 @@ pub struct ConsulAcl {
     pub policies: Vec<Policy>,
     pub local: bool,
     pub create_time: String,
+    /// The time after which the token is revoked, if it expires.
+    pub expiration_time: Option<String>,
     pub hash: String,
     pub create_index: i64,
     pub modify_index: i64,
@@ -36,7 +48,7 @@ pub struct Policy {
 }
 
 /// The ACL replication state of a datacenter.
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct AclReplication {
     /// Reports whether ACL replication is enabled for the datacenter.
@@ -92,6 +104,18 @@ impl<'de> Deserialize<'de> for ReplicationKind {
     }
 }
 
+impl Serialize for ReplicationKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(match self {
+            ReplicationKind::Policies => "policies",
+            ReplicationKind::Tokens => "tokens",
+        })
+    }
+}
+
 /// A service identity block.
 ///
 /// Service identities are used during the authorization process to
@@ -110,7 +134,7 @@ pub struct AclServiceIdentity {
     pub datacenters: Option<Vec<String>>,
 }
 
-/// Request payload for the [Acls::login] method.
+/// Request payload for the [Acl::login_to_auth_method] method.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct LoginPayload {
@@ -124,13 +148,14 @@ pub struct LoginPayload {
     pub meta: Option<HashMap<String, String>>,
 }
 
+/// This trait provides methods for interacting with the `/acl` endpoints.
 #[async_trait]
-pub trait Acl {
+pub trait Acl: Sealed {
     /// This method does a special one-time bootstrap of the ACL system,
     /// making the first management token if the acl.tokens.initial_management
     /// configuration entry is not specified in the Consul server configuration
     /// and if the cluster has not been bootstrapped previously.
-    async fn bootstrap_acls(&self) -> ConsulResult<Vec<ConsulAcl>>;
+    async fn bootstrap_acls(&self) -> ConsulResult<(Vec<ConsulAcl>, WriteMeta)>;
 
     /// This method returns the status of the ACL replication processes in the
     /// datacenter. This is intended to be used by operators or by automation
@@ -139,17 +164,30 @@ pub trait Acl {
 
     /// This method is used to exchange an auth method bearer token for a
     /// newly-created Consul ACL token.
-    async fn login_to_auth_method(&self, payload: LoginPayload) -> ConsulResult<ConsulAcl>;
+    async fn login_to_auth_method(
+        &self,
+        payload: LoginPayload,
+    ) -> ConsulResult<(ConsulAcl, WriteMeta)>;
+
+    /// This method is used to destroy a token created via the
+    /// [Acl::login_to_auth_method] method. The token deleted is specified
+    /// with the X-Consul-Token header or the token query parameter.
+    async fn logout_from_auth_method(&self) -> ConsulResult<WriteMeta>;
 
-    /// This method is used to destroy a token created via the [Acl::login]
-    /// method. The token deleted is specified with the X-Consul-Token header or
-    /// the token query parameter.
-    async fn logout_from_auth_method(&self) -> ConsulResult<()>;
+    /// This method exchanges an auth method bearer token for a newly-created
+    /// Consul ACL token, returning it alongside a clone of the client using it
+    /// for subsequent requests. The client itself keeps its previous token.
+    async fn login(&self, payload: LoginPayload) -> ConsulResult<(Client, ConsulAcl)>;
+
+    /// This method destroys the token used by the client, such as one
+    /// obtained by [Acl::login], consuming the client. The client it was
+    /// cloned from is unaffected.
+    async fn logout(self) -> ConsulResult<()>;
 }
 
 #[async_trait]
 impl Acl for Client {
-    async fn bootstrap_acls(&self) -> ConsulResult<Vec<ConsulAcl>> {
+    async fn bootstrap_acls(&self) -> ConsulResult<(Vec<ConsulAcl>, WriteMeta)> {
         self.put("/v1/acl/bootstrap", (), None, None).await
     }
 
@@ -157,19 +195,35 @@ impl Acl for Client {
         self.get("/v1/acl/replication", None).await
     }
 
-    async fn login_to_auth_method(&self, payload: LoginPayload) -> ConsulResult<ConsulAcl> {
+    async fn login_to_auth_method(
+        &self,
+        payload: LoginPayload,
+    ) -> ConsulResult<(ConsulAcl, WriteMeta)> {
         self.post("/v1/acl/login", payload, None, None).await
     }
 
-    async fn logout_from_auth_method(&self) -> ConsulResult<()> {
-        self.post_with_empty("/v1/acl/logout", (), None, None).await.map(|_: Option<()>| ())
+    async fn logout_from_auth_method(&self) -> ConsulResult<WriteMeta> {
+        self.post_with_empty("/v1/acl/logout", (), None, None)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    async fn login(&self, payload: LoginPayload) -> ConsulResult<(Client, ConsulAcl)> {
+        let (token, _) = self.login_to_auth_method(payload).await?;
+        let mut client = self.clone();
+        client.config.token = Some(token.secret_id.clone());
+        Ok((client, token))
+    }
+
+    async fn logout(self) -> ConsulResult<()> {
+        self.logout_from_auth_method().await.map(|_| ())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Acl;
-    use crate::{Client, Config};
+    use super::{Acl, LoginPayload};
+    use crate::{request::tests::recording_client_with, Client, Config};
 
     #[tokio::test]
     async fn test_check_acl_replication() {
@@ -179,4 +233,48 @@ mod tests {
         // TODO: devise non-erroring test instance
         client.check_acl_replication().await.unwrap_err();
     }
+
+    const TOKEN: &str = r#"{
+        "AccessorID": "926e2bd2-b344-d91b-0c83-ae89f372cd9b",
+        "SecretID": "login",
+        "Description": "token created via login",
+        "Policies": [],
+        "Local": true,
+        "CreateTime": "2019-04-29T10:08:08.404370762-05:00",
+        "Hash": "nLimyD+7l6miiHEBmN/tvCelAmE/SbIXxcnTzG3pbGY=",
+        "CreateIndex": 36,
+        "ModifyIndex": 36
+    }"#;
+
+    #[tokio::test]
+    async fn test_login_and_logout() {
+        let (client, requests) = recording_client_with(
+            Config::builder().token("initial"),
+            &[
+                (403, &[], "Permission denied"),
+                (200, &[], TOKEN),
+                (200, &[], ""),
+                (403, &[], "Permission denied"),
+            ],
+        );
+        let payload = LoginPayload {
+            auth_method: String::from("minikube"),
+            bearer_token: String::from("jwt"),
+            meta: None,
+        };
+        client.check_acl_replication().await.unwrap_err();
+        let (logged_in, token) = client.login(payload).await.unwrap();
+        assert_eq!(token.secret_id, "login");
+        logged_in.logout().await.unwrap();
+        client.check_acl_replication().await.unwrap_err();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "GET /v1/acl/replication token=initial",
+                "POST /v1/acl/login token=initial",
+                "POST /v1/acl/logout token=login",
+                "GET /v1/acl/replication token=initial",
+            ]
+        );
+    }
 }
@@ -0,0 +1,218 @@
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{sealed::Sealed, Client, ConsulResult, WriteMeta, WriteOptions};
+
+/// An ACL policy.
+///
+/// For more information, see the [API documentation].
+///
+/// [API documentation]: https://www.consul.io/api-docs/acl/policies
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AclPolicy {
+    /// The unique ID of the policy.
+    #[serde(rename = "ID")]
+    pub id: String,
+    /// The name of the policy.
+    pub name: String,
+    /// Free form human readable description of the policy.
+    pub description: String,
+    /// The policy rules, in HCL or JSON format. This is not returned by
+    /// [AclPolicies::list_policies].
+    pub rules: String,
+    /// The datacenters the policy is valid within. If empty, the policy is
+    /// valid within all datacenters.
+    pub datacenters: Option<Vec<String>>,
+    pub hash: String,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+/// Request payload for the [AclPolicies::create_policy] and
+/// [AclPolicies::update_policy] methods.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AclPolicyPayload {
+    /// Specifies a name for the ACL policy. The name can contain alphanumeric
+    /// characters, dashes `-`, and underscores `_`. This field is required
+    /// during creation and is used to identify the policy.
+    pub name: String,
+    /// Free form human readable description of the policy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Specifies rules for the ACL policy, in HCL or JSON format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules: Option<String>,
+    /// Specifies the datacenters the policy is valid within. When no
+    /// datacenters are provided the policy is valid in all datacenters
+    /// including those which do not yet exist but may in the future.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datacenters: Option<Vec<String>>,
+}
+
+/// This trait provides methods for interacting with the `/acl/policy`
+/// endpoints.
+#[async_trait]
+pub trait AclPolicies: Sealed {
+    /// This method creates a new ACL policy.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/policies#create-a-policy
+    async fn create_policy(
+        &self,
+        payload: AclPolicyPayload,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(AclPolicy, WriteMeta)>;
+
+    /// This method reads an ACL policy with the given ID.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/policies#read-a-policy
+    async fn read_policy<S: AsRef<str> + Send + Debug>(&self, id: S) -> ConsulResult<AclPolicy>;
+
+    /// This method reads an ACL policy with the given name.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/policies#read-a-policy-by-name
+    async fn read_policy_by_name<S: AsRef<str> + Send + Debug>(
+        &self,
+        name: S,
+    ) -> ConsulResult<AclPolicy>;
+
+    /// This method updates an existing ACL policy.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/policies#update-a-policy
+    async fn update_policy<S: AsRef<str> + Send + Debug>(
+        &self,
+        id: S,
+        payload: AclPolicyPayload,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(AclPolicy, WriteMeta)>;
+
+    /// This method deletes an ACL policy.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/policies#delete-a-policy
+    async fn delete_policy<S: AsRef<str> + Send + Debug>(
+        &self,
+        id: S,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+
+    /// This method lists all the ACL policies. The `rules` field of the
+    /// returned policies is not populated.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/acl/policies#list-policies
+    async fn list_policies(&self) -> ConsulResult<Vec<AclPolicy>>;
+}
+
+#[async_trait]
+impl AclPolicies for Client {
+    #[tracing::instrument]
+    async fn create_policy(
+        &self,
+        payload: AclPolicyPayload,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(AclPolicy, WriteMeta)> {
+        self.put("/v1/acl/policy", payload, None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn read_policy<S: AsRef<str> + Send + Debug>(&self, id: S) -> ConsulResult<AclPolicy> {
+        self.get(format!("/v1/acl/policy/{}", id.as_ref()), None).await
+    }
+
+    #[tracing::instrument]
+    async fn read_policy_by_name<S: AsRef<str> + Send + Debug>(
+        &self,
+        name: S,
+    ) -> ConsulResult<AclPolicy> {
+        self.get(format!("/v1/acl/policy/name/{}", name.as_ref()), None).await
+    }
+
+    #[tracing::instrument]
+    async fn update_policy<S: AsRef<str> + Send + Debug>(
+        &self,
+        id: S,
+        payload: AclPolicyPayload,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(AclPolicy, WriteMeta)> {
+        self.put(format!("/v1/acl/policy/{}", id.as_ref()), payload, None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn delete_policy<S: AsRef<str> + Send + Debug>(
+        &self,
+        id: S,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        self.delete(format!("/v1/acl/policy/{}", id.as_ref()), None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn list_policies(&self) -> ConsulResult<Vec<AclPolicy>> {
+        self.get("/v1/acl/policies", None).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{AclPolicies, AclPolicyPayload};
+    use crate::request::tests::recording_client;
+
+    #[test]
+    fn test_payload() {
+        let payload = AclPolicyPayload {
+            name: String::from("node-read"),
+            rules: Some(String::from(r#"node_prefix "" { policy = "read" }"#)),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            json!({"Name": "node-read", "Rules": "node_prefix \"\" { policy = \"read\" }"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_policy_paths() {
+        let (client, requests) = recording_client(&[
+            (200, &[], "{}"),
+            (200, &[], "{}"),
+            (200, &[], "{}"),
+            (200, &[], "{}"),
+            (200, &[], "true"),
+            (200, &[], "[]"),
+        ]);
+        let payload = AclPolicyPayload { name: String::from("node-read"), ..Default::default() };
+        client.create_policy(payload.clone(), None).await.unwrap();
+        client.read_policy("e359bd81").await.unwrap();
+        client.read_policy_by_name("node-read").await.unwrap();
+        client.update_policy("e359bd81", payload, None).await.unwrap();
+        assert!(client.delete_policy("e359bd81", None).await.unwrap().0);
+        client.list_policies().await.unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "PUT /v1/acl/policy",
+                "GET /v1/acl/policy/e359bd81",
+                "GET /v1/acl/policy/name/node-read",
+                "PUT /v1/acl/policy/e359bd81",
+                "DELETE /v1/acl/policy/e359bd81",
+                "GET /v1/acl/policies",
+            ]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use std::{fmt, time::Duration};
+
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use tokio::task::JoinHandle;
+
+use super::{Acl, AclTokens, LoginPayload};
+use crate::{Client, ConsulResult, SharedToken};
+
+/// The delay before retrying a renewal that failed.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// An ACL token obtained by logging in to an auth method, which is renewed in
+/// the background before it expires.
+///
+/// The renewer logs in with the given payload, and configures the client to
+/// use the token as its [SharedToken], so that the client and its later clones
+/// always use the current token. Once two thirds of the remaining lifetime of
+/// the token have elapsed, according to the `ExpirationTime` returned by the
+/// token read endpoint, it logs in again, replaces the token, and destroys the
+/// previous one. Tokens without an expiration time are never renewed.
+///
+/// Dropping the renewer stops renewing the token, which remains in use until
+/// it expires. To also destroy it, use [TokenRenewer::logout] instead.
+///
+/// ```no_run
+/// # async fn run() -> consul_oxide::ConsulResult<()> {
+/// use consul_oxide::{Client, Config, LoginPayload, TokenRenewer};
+///
+/// let mut client = Client::new(Config::default());
+/// let payload = LoginPayload {
+///     auth_method: String::from("kubernetes"),
+///     bearer_token: std::fs::read_to_string("/var/run/secrets/kubernetes.io/serviceaccount/token")
+///         .unwrap(),
+///     meta: None,
+/// };
+/// let renewer = TokenRenewer::login(&mut client, payload).await?;
+/// // requests of the client and its clones use the renewed token
+/// renewer.logout().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TokenRenewer {
+    client: Client,
+    token: SharedToken,
+    task: JoinHandle<()>,
+}
+
+impl TokenRenewer {
+    /// This method logs in to the auth method of the given payload,
+    /// configures the client to use the token obtained, and starts renewing
+    /// it in the background.
+    #[tracing::instrument(skip(payload))]
+    pub async fn login(client: &mut Client, payload: LoginPayload) -> ConsulResult<Self> {
+        let (acl, _) = client.login_to_auth_method(payload.clone()).await?;
+        let token = SharedToken::new(acl.secret_id);
+        client.config.token = None;
+        client.config.shared_token = Some(token.clone());
+        let expiration = expiration_time(client).await?;
+        let task = tokio::spawn(renew(client.clone(), token.clone(), payload, expiration));
+        Ok(TokenRenewer { client: client.clone(), token, task })
+    }
+
+    /// This method returns the secret ID of the current token.
+    pub fn token(&self) -> String {
+        self.token.get()
+    }
+
+    /// This method stops renewing the token, and destroys it.
+    #[tracing::instrument]
+    pub async fn logout(self) -> ConsulResult<()> {
+        self.task.abort();
+        self.client.logout_from_auth_method().await.map(|_| ())
+    }
+}
+
+impl Drop for TokenRenewer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl fmt::Debug for TokenRenewer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenRenewer").finish_non_exhaustive()
+    }
+}
+
+/// This function renews the token of the given client before it expires, for
+/// as long as the task is not aborted.
+async fn renew(
+    client: Client,
+    token: SharedToken,
+    payload: LoginPayload,
+    mut expiration: Option<OffsetDateTime>,
+) {
+    while let Some(expires) = expiration {
+        tokio::time::sleep(renewal_delay(expires, OffsetDateTime::now_utc())).await;
+        expiration = loop {
+            match relogin(&client, &token, &payload).await {
+                Ok(expiration) => break expiration,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to renew token");
+                    tokio::time::sleep(RETRY_DELAY).await;
+                }
+            }
+        };
+    }
+}
+
+/// This function logs in again, replaces the shared token of the client and
+/// destroys the previous one, returning the expiration time of the new token.
+async fn relogin(
+    client: &Client,
+    token: &SharedToken,
+    payload: &LoginPayload,
+) -> ConsulResult<Option<OffsetDateTime>> {
+    let (acl, _) = client.login_to_auth_method(payload.clone()).await?;
+    let mut previous = client.clone();
+    previous.config.shared_token = None;
+    previous.config.token = Some(token.get());
+    token.set(acl.secret_id);
+    // requests still using the previous token may fail once it is destroyed,
+    // but it would expire shortly anyway
+    if let Err(e) = previous.logout_from_auth_method().await {
+        tracing::warn!(error = %e, "failed to destroy previous token");
+    }
+    expiration_time(client).await
+}
+
+/// This function returns the expiration time of the token of the given
+/// client, if it expires.
+async fn expiration_time(client: &Client) -> ConsulResult<Option<OffsetDateTime>> {
+    let acl = client.read_self_token().await?;
+    Ok(acl.expiration_time.map(|time| OffsetDateTime::parse(&time, &Rfc3339)).transpose()?)
+}
+
+/// This function returns the time to wait before renewing a token expiring at
+/// the given time, which is two thirds of its remaining lifetime.
+fn renewal_delay(expires: OffsetDateTime, now: OffsetDateTime) -> Duration {
+    Duration::try_from(expires - now).unwrap_or_default() * 2 / 3
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use http::{Request, Response};
+    use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+    use super::{renewal_delay, TokenRenewer};
+    use crate::{Client, Config, ConsulResult, HttpTransport, LoginPayload};
+
+    #[test]
+    fn test_renewal_delay() {
+        let now = OffsetDateTime::parse("2024-01-01T00:00:00Z", &Rfc3339).unwrap();
+        let expires = OffsetDateTime::parse("2024-01-01T00:15:00Z", &Rfc3339).unwrap();
+        assert_eq!(renewal_delay(expires, now), Duration::from_secs(600));
+        assert_eq!(renewal_delay(now, expires), Duration::ZERO);
+    }
+
+    /// A transport issuing the tokens `s1`, `s2`, ... on every login, where
+    /// `s1` has already expired and the others never expire.
+    #[derive(Debug, Default)]
+    struct AuthMethod {
+        logins: Mutex<usize>,
+        logouts: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl HttpTransport for AuthMethod {
+        async fn execute(&self, request: Request<Bytes>) -> ConsulResult<Response<Bytes>> {
+            let token = request
+                .headers()
+                .get("X-Consul-Token")
+                .map(|token| token.to_str().unwrap().to_owned())
+                .unwrap_or_default();
+            let secret_id = match request.uri().path() {
+                "/v1/acl/login" => {
+                    let mut logins = self.logins.lock().unwrap();
+                    *logins += 1;
+                    format!("s{}", logins)
+                }
+                "/v1/acl/token/self" => token,
+                "/v1/acl/logout" => {
+                    self.logouts.lock().unwrap().push(token);
+                    return Ok(Response::new(Bytes::new()));
+                }
+                path => panic!("unexpected request to {}", path),
+            };
+            let expiration = if secret_id == "s1" { r#""2000-01-01T00:00:00Z""# } else { "null" };
+            let body = format!(
+                r#"{{"AccessorID": "a", "SecretID": "{}", "Description": "", "Policies": [],
+                "Local": true, "CreateTime": "2000-01-01T00:00:00Z", "ExpirationTime": {},
+                "Hash": "", "CreateIndex": 1, "ModifyIndex": 1}}"#,
+                secret_id, expiration
+            );
+            Ok(Response::new(Bytes::from(body)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_token_renewer() {
+        let transport = AuthMethod::default();
+        let logouts = transport.logouts.clone();
+        let config = Config::builder().token("initial").transport(transport).build().unwrap();
+        let mut client = Client::new(config);
+        let payload = LoginPayload {
+            auth_method: String::from("minikube"),
+            bearer_token: String::from("jwt"),
+            meta: None,
+        };
+        let renewer = TokenRenewer::login(&mut client, payload).await.unwrap();
+        // the first token has expired, so it is renewed immediately
+        while renewer.token() != "s2" {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(client.config.shared_token.as_ref().unwrap().get(), "s2");
+        renewer.logout().await.unwrap();
+        assert_eq!(*logouts.lock().unwrap(), ["s1", "s2"]);
+    }
+}
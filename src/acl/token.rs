@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use serde_derive::{Deserialize, Serialize};
 
 use super::{AclServiceIdentity, ConsulAcl, Policy};
-use crate::{Client, ConsulResult};
+use crate::{sealed::Sealed, Client, ConsulResult, WriteMeta, WriteOptions};
 
 /// Request payload for the [AclTokens::create_token] method.
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -58,7 +58,8 @@ pub struct RoleLink {
 
 /// Request payload for the [AclTokens::update_token] method.
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-struct UpdateToken {
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateToken {
     /// Free form human readable description of the token.
     pub description: Option<String>,
     /// The list of policies that should be applied to the token.
@@ -78,14 +79,20 @@ struct UpdateToken {
     pub expiration_ttl: Option<String>,
 }
 
+/// This trait provides methods for interacting with the `/acl/token`
+/// endpoints.
 #[async_trait]
-trait AclTokens {
+pub trait AclTokens: Sealed {
     /// This endpoint creates a new ACL token.
     ///
     /// For more information, see the relevant endpoint's [API documentation].
     ///
     /// [API documentation]: https://www.consul.io/api-docs/acl/tokens#create-a-token
-    async fn create_token(&self, create_token: CreateToken) -> ConsulResult<ConsulAcl>;
+    async fn create_token(
+        &self,
+        create_token: CreateToken,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(ConsulAcl, WriteMeta)>;
 
     /// This method reads an ACL token with the given Accessor ID.
     ///
@@ -114,7 +121,8 @@ trait AclTokens {
         &self,
         accessor_id: S,
         update_token: UpdateToken,
-    ) -> ConsulResult<ConsulAcl>;
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(ConsulAcl, WriteMeta)>;
 
     /// This method clones an existing ACL token.
     ///
@@ -125,7 +133,8 @@ trait AclTokens {
         &self,
         accessor_id: S,
         new_description: Option<S>,
-    ) -> ConsulResult<ConsulAcl>;
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(ConsulAcl, WriteMeta)>;
 
     /// This method deletes an ACL token.
     ///
@@ -135,7 +144,8 @@ trait AclTokens {
     async fn delete_token<S: AsRef<str> + Send + Debug>(
         &self,
         accessor_id: S,
-    ) -> ConsulResult<bool>;
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
 
     /// This method lists all the ACL tokens.
     ///
@@ -148,8 +158,12 @@ trait AclTokens {
 #[async_trait]
 impl AclTokens for Client {
     #[tracing::instrument]
-    async fn create_token(&self, create_token: CreateToken) -> ConsulResult<ConsulAcl> {
-        self.put("/v1/acl/token", create_token, None, None).await
+    async fn create_token(
+        &self,
+        create_token: CreateToken,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(ConsulAcl, WriteMeta)> {
+        self.put("/v1/acl/token", create_token, None, options).await
     }
 
     #[tracing::instrument]
@@ -170,8 +184,10 @@ impl AclTokens for Client {
         &self,
         accessor_id: S,
         update_token: UpdateToken,
-    ) -> ConsulResult<ConsulAcl> {
-        self.put(format!("/v1/acl/token/{}", accessor_id.as_ref()), update_token, None, None).await
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(ConsulAcl, WriteMeta)> {
+        self.put(format!("/v1/acl/token/{}", accessor_id.as_ref()), update_token, None, options)
+            .await
     }
 
     #[tracing::instrument]
@@ -179,7 +195,8 @@ impl AclTokens for Client {
         &self,
         accessor_id: S,
         new_description: Option<S>,
-    ) -> ConsulResult<ConsulAcl> {
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(ConsulAcl, WriteMeta)> {
         #[derive(Serialize, Debug)]
         #[serde(rename_all = "PascalCase")]
         struct Payload {
@@ -190,7 +207,7 @@ impl AclTokens for Client {
             new_description
                 .map(|description| Payload { description: description.as_ref().to_string() }),
             None,
-            None,
+            options,
         )
         .await
     }
@@ -199,8 +216,9 @@ impl AclTokens for Client {
     async fn delete_token<S: AsRef<str> + Send + Debug>(
         &self,
         accessor_id: S,
-    ) -> ConsulResult<bool> {
-        self.delete(format!("/v1/acl/token/{}", accessor_id.as_ref()), None, None).await
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        self.delete(format!("/v1/acl/token/{}", accessor_id.as_ref()), None, options).await
     }
 
     #[tracing::instrument]
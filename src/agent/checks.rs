@@ -2,55 +2,26 @@ use std::collections::HashMap;
 
 use async_trait::async_trait;
 
-use crate::{Client, ConsulResult};
-
-/// A health check run on a service hosted on this node.
-#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
-#[serde(default)]
-pub struct AgentCheck {
-    /// The node the service is running on.
-    #[serde(rename = "Node")]
-    pub node: String,
-    /// The ID of the service within the agent.
-    #[serde(rename = "CheckID")]
-    pub check_id: String,
-    /// The name of the service.
-    #[serde(rename = "Name")]
-    pub name: String,
-    /// The status of the check.
-    #[serde(rename = "Status")]
-    pub status: String,
-    /// Notes attached to this check.
-    #[serde(rename = "Notes")]
-    pub notes: String,
-    /// Output of the check.
-    #[serde(rename = "Output")]
-    pub output: String,
-    /// The ID of the service.
-    #[serde(rename = "ServiceID")]
-    pub service_id: String,
-    /// The name of the service.
-    #[serde(rename = "ServiceName")]
-    pub service_name: String,
-}
+use crate::{CheckStatus, Client, ConsulResult, HealthCheck, QueryOptions, WriteMeta};
 
 /// The request payload for the [`AgentChecks::register_check`] endpoint.
 ///
 /// See the [API Documentation] for more information.
 ///
 /// [API Documentation]: https://www.consul.io/api-docs/agent/check#json-request-body-schema
-#[derive(Serialize, Default, Debug)]
+#[derive(Clone, Serialize, Default, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct RegisterCheckPayload {
     /// Specifies a unique ID for this check on the node. This defaults to the
     /// "Name" parameter, but it may be necessary to provide an ID for
     /// uniqueness. This value will return in the response as "CheckId".
-    #[serde(rename = "ID")]
+    #[serde(rename = "ID", skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
     /// Specifies the name of the check.
     pub name: String,
     /// Specifies arbitrary information for humans. This is not used by Consul
     /// internally.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
     /// Specifies that checks associated with a service should deregister after
     /// this time. This is specified as a time duration with suffix like "10m".
@@ -62,36 +33,44 @@ pub struct RegisterCheckPayload {
     /// deregistration. This should generally be configured with a timeout
     /// that's much, much longer than any expected recoverable outage for the
     /// given service.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub deregister_critical_service_after: Option<String>,
     /// Specifies the ID of the node for an alias check. If no service is
     /// specified, the check will alias the health of the node. If a service is
     /// specified, the check will alias the specified service on this particular
     /// node.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub alias_node: Option<String>,
-    ///  Specifies the ID of a service for an alias check. If the service is not
+    /// Specifies the ID of a service for an alias check. If the service is not
     /// registered with the same agent, AliasNode must also be specified. Note
     /// this is the service ID and not the service name (though they are very
     /// often the same).
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub alias_service: Option<String>,
     /// Specifies the ID of a service to associate the registered check with an
     /// existing service provided by the agent.
+    #[serde(rename = "ServiceID", skip_serializing_if = "Option::is_none")]
     pub service_id: Option<String>,
     /// Specifies the initial status of the health check.
-    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<CheckStatus>,
 
-    /// pecifies the number of consecutive successful results required before
+    /// Specifies the number of consecutive successful results required before
     /// check status transitions to passing. Available for HTTP, TCP, gRPC,
     /// Docker & Monitor checks. Added in Consul 1.7.0.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub success_before_passing: Option<u8>,
     /// Specifies the number of consecutive unsuccessful results required before
     /// check status transitions to warning. Defaults to the same value as
     /// FailuresBeforeCritical. Values higher than FailuresBeforeCritical are
     /// invalid. Available for HTTP, TCP, gRPC, Docker & Monitor checks. Added
     /// in Consul 1.11.0.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub failures_before_warning: Option<u8>,
     /// Specifies the number of consecutive unsuccessful results required before
     /// check status transitions to critical. Available for HTTP, TCP, gRPC,
     /// Docker & Monitor checks. Added in Consul 1.7.0.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub failures_before_critical: Option<u8>,
 
     /// Specifies command arguments to run to update the status of the check.
@@ -100,13 +79,16 @@ pub struct RegisterCheckPayload {
     /// array was added so that checks can be run without a shell. The Script
     /// field is deprecated, and you should include the shell in the Args to run
     /// under a shell, eg. "args": ["sh", "-c", "..."].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub args: Vec<String>,
     /// Specifies that the check is a Docker check, and Consul will evaluate the
     /// script every Interval in the given container using the specified Shell.
     /// Note that Shell is currently only supported for Docker checks.
+    #[serde(rename = "DockerContainerID", skip_serializing_if = "Option::is_none")]
     pub docker_container_id: Option<String>,
     /// Used alongside `docker_container_id` to specify the shell to use when
     /// evaluating the script inside the given container.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shell: Option<String>,
 
     /// Specifies an HTTP check to perform a GET request against the value of
@@ -115,29 +97,41 @@ pub struct RegisterCheckPayload {
     /// the check is warning. Otherwise, the check is critical. HTTP checks also
     /// support SSL. By default, a valid SSL certificate is expected.
     /// Certificate verification can be controlled using the TLSSkipVerify.
+    #[serde(rename = "HTTP", skip_serializing_if = "Option::is_none")]
     pub http: Option<String>,
     /// Specifies a different HTTP method to be used for an HTTP check. When no
     /// value is specified, GET is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub method: Option<String>,
     /// Specifies a set of headers that should be set for HTTP checks. Each
     /// header can have multiple values.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub header: Option<HashMap<String, Vec<String>>>,
     /// Specifies a body that should be sent with `http` checks.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub body: Option<String>,
     /// Specifies whether to disable following HTTP redirects when performing an
     /// `HTTP` check.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
     pub disable_redirects: bool,
     /// Specifies the frequency at which to run this check. This is required for
     /// HTTP and TCP checks.
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub interval: String,
     /// Specifies a timeout for outgoing connections in the case of a Script,
     /// HTTP, TCP, or gRPC check. Can be specified in the form of "10s" or "5m"
     /// (i.e., 10 seconds or 5 minutes, respectively).
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub timeout: String,
 
     /// Specifies if the certificate for an HTTPS check should not be verified.
-    #[serde(rename = "TLSSkipVerify")]
-    pub tlsskip_verify: bool,
+    #[serde(rename = "TLSSkipVerify", skip_serializing_if = "std::ops::Not::not")]
+    pub tls_skip_verify: bool,
+    /// Specifies an optional string used to set the SNI host when connecting
+    /// via TLS. For an HTTP check, this value is set automatically if the URL
+    /// uses a hostname (not an IP address).
+    #[serde(rename = "TLSServerName", skip_serializing_if = "Option::is_none")]
+    pub tls_server_name: Option<String>,
 
     /// Specifies a `gRPC` check's endpoint that supports the standard gRPC
     /// health checking protocol. The state of the check will be updated at
@@ -145,14 +139,14 @@ pub struct RegisterCheckPayload {
     /// service identifier after the `gRPC` check's endpoint in the
     /// following format to check for a specific service instead of the
     /// whole gRPC server `/:service_identifier`.
-    #[serde(rename = "GRPC")]
+    #[serde(rename = "GRPC", skip_serializing_if = "Option::is_none")]
     pub grpc: Option<String>,
     /// Specifies whether to use TLS for this `gRPC` health check. If TLS is
     /// enabled, then by default, a valid TLS certificate is expected.
     /// Certificate verification can be turned off by setting `tls_skip_verify`
     /// to `true`.
-    #[serde(rename = "GRPCUseTLS")]
-    pub gprc_use_tls: Option<bool>,
+    #[serde(rename = "GRPCUseTLS", skip_serializing_if = "Option::is_none")]
+    pub grpc_use_tls: Option<bool>,
 
     /// Specifies an address that uses http2 to run a ping check on. At the
     /// specified Interval, a connection is made to the address, and a ping is
@@ -161,43 +155,69 @@ pub struct RegisterCheckPayload {
     /// default. To disable TLS and use h2c, set `h2_ping_use_tls` to `false`.
     /// If TLS is enabled, a valid SSL certificate is required by default,
     /// but verification can be removed with `tls_skip_verify`.
-    #[serde(rename = "H2Ping")]
+    #[serde(rename = "H2Ping", skip_serializing_if = "Option::is_none")]
     pub h2_ping: Option<String>,
     /// Specifies if TLS should be used for H2PING check. If TLS is enabled, a
     /// valid SSL certificate is required by default, but verification can be
     /// removed with `tls_skip_verify`.
-    #[serde(rename = "H2PingUseTLS")]
+    #[serde(rename = "H2PingUseTLS", skip_serializing_if = "Option::is_none")]
     pub h2_ping_use_tls: Option<bool>,
 
-    ///  Specifies a TCP to connect against the value of TCP (expected to be an
+    /// Specifies a TCP to connect against the value of TCP (expected to be an
     /// IP or hostname plus port combination) every Interval. If the connection
     /// attempt is successful, the check is passing. If the connection attempt
     /// is unsuccessful, the check is critical. In the case of a hostname that
     /// resolves to both IPv4 and IPv6 addresses, an attempt will be made to
     /// both addresses, and the first successful connection attempt will result
     /// in a successful check.
-    #[serde(rename = "TCP")]
+    #[serde(rename = "TCP", skip_serializing_if = "Option::is_none")]
     pub tcp: Option<String>,
+    /// Specifies a UDP IP address or hostname plus port to send datagrams to
+    /// every Interval. If a response or an error other than a timeout is
+    /// received, the check is passing. If the request times out, the check is
+    /// critical.
+    #[serde(rename = "UDP", skip_serializing_if = "Option::is_none")]
+    pub udp: Option<String>,
+
+    /// Specifies the identifier of an OS-level service to check. On Windows,
+    /// this is the name of a service managed by the Service Control Manager,
+    /// and on Linux the name of a systemd unit. The check is passing if the
+    /// service is running.
+    #[serde(rename = "OSService", skip_serializing_if = "Option::is_none")]
+    pub os_service: Option<String>,
 
     /// Specifies this is a TTL check, and the TTL endpoint must be used
     /// periodically to update the state of the check. If the check is not set
     /// to passing within the specified duration, then the check will be set to
     /// the failed state.
+    #[serde(rename = "TTL", skip_serializing_if = "Option::is_none")]
     pub ttl: Option<String>,
 }
 
+/// Request payload for the [AgentChecks::update_check] method.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct UpdateCheckPayload<'a> {
+    status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<&'a str>,
+}
+
 /// This trait provides methods for interacting with the `/agent/check`
 /// endpoints.
 #[async_trait]
 pub trait AgentChecks {
     /// This method returns all checks that are registered with the local
-    /// agent.
+    /// agent. The checks can be filtered with [QueryOptions::filter].
     ///
     /// For more information, see the relevant endpoint's [API
     /// documentation].
     ///
     /// [API documentation]: https://www.consul.io/api/agent/check.html#list-checks
-    async fn list_checks(&self) -> ConsulResult<HashMap<String, AgentCheck>>;
+    async fn list_checks(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<HashMap<String, HealthCheck>>;
 
     /// This method registers a check with the local agent.
     ///
@@ -205,7 +225,7 @@ pub trait AgentChecks {
     /// documentation].
     ///
     /// [API documentation]: https://www.consul.io/api/agent/check.html#register-check
-    async fn register_check(&self, check: RegisterCheckPayload) -> ConsulResult<()>;
+    async fn register_check(&self, check: RegisterCheckPayload) -> ConsulResult<WriteMeta>;
 
     /// This method deregisters a check with the local agent.
     ///
@@ -213,40 +233,189 @@ pub trait AgentChecks {
     /// documentation].
     ///
     /// [API documentation]: https://www.consul.io/api/agent/check.html#deregister-check
-    async fn deregister_check(&self, check_id: &str) -> ConsulResult<()>;
+    async fn deregister_check(&self, check_id: &str) -> ConsulResult<WriteMeta>;
+
+    /// This method marks a TTL check as passing and resets its TTL clock. The
+    /// optional `note` is set as the output of the check.
+    ///
+    /// For more information, see the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent/check#ttl-check-pass
+    async fn check_pass(&self, check_id: &str, note: Option<&str>) -> ConsulResult<WriteMeta>;
+
+    /// This method marks a TTL check as warning and resets its TTL clock. The
+    /// optional `note` is set as the output of the check.
+    ///
+    /// For more information, see the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent/check#ttl-check-warn
+    async fn check_warn(&self, check_id: &str, note: Option<&str>) -> ConsulResult<WriteMeta>;
+
+    /// This method marks a TTL check as critical and resets its TTL clock. The
+    /// optional `note` is set as the output of the check.
+    ///
+    /// For more information, see the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent/check#ttl-check-fail
+    async fn check_fail(&self, check_id: &str, note: Option<&str>) -> ConsulResult<WriteMeta>;
+
+    /// This method sets the status and output of a TTL check and resets its
+    /// TTL clock. Unlike the other TTL methods, the output is sent in the
+    /// request body, so it may be larger.
+    ///
+    /// For more information, see the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent/check#ttl-check-update
+    async fn update_check(
+        &self,
+        check_id: &str,
+        status: CheckStatus,
+        output: Option<&str>,
+    ) -> ConsulResult<WriteMeta>;
 }
 
 #[async_trait]
 impl AgentChecks for Client {
     #[tracing::instrument]
-    async fn list_checks(&self) -> ConsulResult<HashMap<String, AgentCheck>> {
-        self.get("/v1/agent/checks", None).await
+    async fn list_checks(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<HashMap<String, HealthCheck>> {
+        self.get("/v1/agent/checks", options).await
     }
 
     #[tracing::instrument]
-    async fn register_check(&self, check: RegisterCheckPayload) -> ConsulResult<()> {
+    async fn register_check(&self, check: RegisterCheckPayload) -> ConsulResult<WriteMeta> {
         self.put_with_empty("/v1/agent/check/register", check, None, None)
             .await
-            .map(|_: Option<()>| ())
+            .map(|(_, meta): (Option<()>, _)| meta)
     }
 
     #[tracing::instrument]
-    async fn deregister_check(&self, check_id: &str) -> ConsulResult<()> {
+    async fn deregister_check(&self, check_id: &str) -> ConsulResult<WriteMeta> {
         self.put_with_empty(&format!("/v1/agent/check/deregister/{}", check_id), (), None, None)
             .await
-            .map(|_: Option<()>| ())
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn check_pass(&self, check_id: &str, note: Option<&str>) -> ConsulResult<WriteMeta> {
+        let path = format!("/v1/agent/check/pass/{}", check_id);
+        self.put_with_empty(&path, (), Some(ttl_params(note)), None)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn check_warn(&self, check_id: &str, note: Option<&str>) -> ConsulResult<WriteMeta> {
+        let path = format!("/v1/agent/check/warn/{}", check_id);
+        self.put_with_empty(&path, (), Some(ttl_params(note)), None)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn check_fail(&self, check_id: &str, note: Option<&str>) -> ConsulResult<WriteMeta> {
+        let path = format!("/v1/agent/check/fail/{}", check_id);
+        self.put_with_empty(&path, (), Some(ttl_params(note)), None)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn update_check(
+        &self,
+        check_id: &str,
+        status: CheckStatus,
+        output: Option<&str>,
+    ) -> ConsulResult<WriteMeta> {
+        let path = format!("/v1/agent/check/update/{}", check_id);
+        let payload = UpdateCheckPayload { status, output };
+        self.put_with_empty(&path, payload, None, None).await.map(|(_, meta): (Option<()>, _)| meta)
+    }
+}
+
+/// This function returns the query parameters of the TTL check endpoints.
+fn ttl_params(note: Option<&str>) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    if let Some(note) = note {
+        params.insert(String::from("note"), note.to_owned());
     }
+    params
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{AgentChecks, Client, Config};
+    use crate::{
+        request::tests::recording_client, AgentChecks, Client, Config, Filter, QueryOptions,
+        RegisterCheckPayload,
+    };
+
+    #[test]
+    fn test_encode_register_check() {
+        let check = RegisterCheckPayload {
+            name: String::from("api"),
+            interval: String::from("10s"),
+            http: Some(String::from("https://localhost:8443/health")),
+            method: Some(String::from("POST")),
+            tls_skip_verify: true,
+            failures_before_critical: Some(3),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(check).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "Name": "api",
+                "Interval": "10s",
+                "HTTP": "https://localhost:8443/health",
+                "Method": "POST",
+                "TLSSkipVerify": true,
+                "FailuresBeforeCritical": 3,
+            })
+        );
+    }
 
     #[tokio::test]
     async fn test_list_checks() {
         let client = Client::new(Config::default());
-        let result = client.list_checks().await.unwrap();
+        let result = client.list_checks(None).await.unwrap();
         assert_eq!(result.len(), 0);
         println!("{:?}", result);
     }
+
+    #[tokio::test]
+    async fn test_list_checks_filter() {
+        const CHECKS: &str = r#"{
+            "web-http": {
+                "Node": "foobar",
+                "CheckID": "web-http",
+                "Name": "HTTP on web",
+                "Status": "passing",
+                "ServiceID": "web-1",
+                "ServiceName": "web",
+                "ServiceTags": ["v1"],
+                "Type": "http",
+                "Interval": "10s",
+                "Timeout": "5s",
+                "ExposedPort": 21500
+            }
+        }"#;
+        let (client, requests) = recording_client(&[(200, &[], CHECKS)]);
+        let filter = Filter::field("ServiceName").eq("web");
+        let options = QueryOptions { filter: Some(filter.to_string()), ..Default::default() };
+        let checks = client.list_checks(Some(options)).await.unwrap();
+        let check = &checks["web-http"];
+        assert_eq!(check.check_type, "http");
+        assert_eq!(check.service_tags, ["v1"]);
+        assert_eq!(check.exposed_port, 21500);
+        assert_eq!(
+            *requests.lock().unwrap(),
+            ["GET /v1/agent/checks?filter=ServiceName+%3D%3D+%22web%22"]
+        );
+    }
 }
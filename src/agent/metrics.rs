@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+/// A gauge metric, holding the last value set during the interval.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct GaugeValue {
+    /// The name of the metric, such as `consul.runtime.num_goroutines`.
+    pub name: String,
+    /// The value of the gauge.
+    pub value: f32,
+    /// The labels of the metric.
+    pub labels: HashMap<String, String>,
+}
+
+/// A point metric, holding all the values emitted during the interval.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct PointValue {
+    /// The name of the metric.
+    pub name: String,
+    /// The values emitted during the interval.
+    pub points: Vec<f32>,
+}
+
+/// A counter or sample metric, aggregated over the interval.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct SampledValue {
+    /// The name of the metric, such as `consul.rpc.request`.
+    pub name: String,
+    /// The number of values recorded during the interval.
+    pub count: u64,
+    /// The sum of the values recorded during the interval.
+    pub sum: f64,
+    /// The smallest value recorded during the interval.
+    pub min: f64,
+    /// The largest value recorded during the interval.
+    pub max: f64,
+    /// The mean of the values recorded during the interval.
+    pub mean: f64,
+    /// The standard deviation of the values recorded during the interval.
+    pub stddev: f64,
+    /// The rate of the metric per second, over the interval.
+    pub rate: f64,
+    /// The labels of the metric.
+    pub labels: HashMap<String, String>,
+}
+
+/// The metrics of the most recent telemetry interval of the agent, returned
+/// by the [Agent::get_metrics](crate::Agent::get_metrics) method.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AgentMetrics {
+    /// The start of the interval, formatted like `2021-03-01 10:00:00 +0000
+    /// UTC`.
+    pub timestamp: String,
+    /// The gauges of the interval.
+    pub gauges: Vec<GaugeValue>,
+    /// The points of the interval.
+    pub points: Vec<PointValue>,
+    /// The counters of the interval.
+    pub counters: Vec<SampledValue>,
+    /// The samples, such as timings, of the interval.
+    pub samples: Vec<SampledValue>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AgentMetrics;
+
+    #[test]
+    fn test_decode_metrics() {
+        let metrics: AgentMetrics = serde_json::from_str(
+            r#"{
+                "Timestamp": "2021-03-01 10:00:00 +0000 UTC",
+                "Gauges": [{"Name": "consul.runtime.num_goroutines", "Value": 128, "Labels": {}}],
+                "Points": [],
+                "Counters": [
+                    {
+                        "Name": "consul.rpc.request",
+                        "Count": 2,
+                        "Rate": 0.2,
+                        "Sum": 2,
+                        "Min": 1,
+                        "Max": 1,
+                        "Mean": 1,
+                        "Stddev": 0,
+                        "Labels": {"method": "Status.Ping"}
+                    }
+                ],
+                "Samples": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(metrics.gauges[0].value, 128.0);
+        assert_eq!(metrics.counters[0].count, 2);
+        assert_eq!(metrics.counters[0].labels["method"], "Status.Ping");
+    }
+}
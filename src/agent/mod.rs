@@ -1,13 +1,18 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
 use async_trait::async_trait;
+use serde_json::Value;
 
-use crate::{sealed::Sealed, Client, ConsulResult};
+use crate::{sealed::Sealed, Client, ConsulResult, ServiceWeights, TaggedAddress, WriteMeta};
 
 mod checks;
+mod metrics;
+mod monitor;
 mod service;
 
 pub use checks::*;
+pub use metrics::*;
+pub use monitor::*;
 pub use service::*;
 
 /// A member within the cluster gossip pool.
@@ -52,7 +57,7 @@ pub struct AgentMember {
 /// A service hosted on this node.
 ///
 /// For more information, see [Agent::list_members].
-#[derive(Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
 #[serde(default)]
 pub struct AgentService {
     /// The ID of the service in the agent.
@@ -73,12 +78,159 @@ pub struct AgentService {
     /// Whether tags are being overridden.
     #[serde(rename = "EnableTagOverride")]
     pub enable_tag_override: bool,
+    /// The kind of the service, such as `connect-proxy`. Empty for typical
+    /// services.
+    #[serde(rename = "Kind")]
+    pub kind: String,
+    /// The metadata of the service.
+    #[serde(rename = "Meta")]
+    pub meta: HashMap<String, String>,
+    /// The additional addresses of the service, keyed by tag.
+    #[serde(rename = "TaggedAddresses")]
+    pub tagged_addresses: HashMap<String, TaggedAddress>,
+    /// The weights of the service in DNS responses.
+    #[serde(rename = "Weights")]
+    pub weights: ServiceWeights,
+    /// The configuration of the proxy, if the service is one.
+    #[serde(rename = "Proxy", skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<Box<ServiceProxy>>,
+    /// The Connect configuration of the service.
+    #[serde(rename = "Connect", skip_serializing_if = "Option::is_none")]
+    pub connect: Option<AgentServiceConnect>,
+    /// The hash of the service definition, which is only returned by the
+    /// agent endpoints.
+    #[serde(rename = "ContentHash", skip_serializing_if = "String::is_empty")]
+    pub content_hash: String,
     #[serde(rename = "CreateIndex")]
     pub create_index: u64,
     #[serde(rename = "ModifyIndex")]
     pub modify_index: u64,
 }
 
+/// The Connect configuration of an [AgentService].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AgentServiceConnect {
+    /// Whether the service supports Connect natively, without a proxy.
+    pub native: bool,
+}
+
+/// The `Config` section of [AgentSelf], describing the agent's identity.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AgentSelfConfig {
+    /// The datacenter of the agent.
+    pub datacenter: String,
+    /// The primary datacenter of the cluster.
+    pub primary_datacenter: String,
+    /// The name of the agent's node.
+    pub node_name: String,
+    /// The ID of the agent's node.
+    #[serde(rename = "NodeID")]
+    pub node_id: String,
+    /// The git revision the agent was built from.
+    pub revision: String,
+    /// Whether the agent is running in server mode.
+    pub server: bool,
+    /// The version of the agent, such as `1.9.4`.
+    pub version: String,
+    /// Any other fields of the section.
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// The `DebugConfig` section of [AgentSelf], containing the agent's full
+/// runtime configuration.
+///
+/// Only commonly used fields are typed, as the contents of this section are
+/// not covered by Consul's compatibility guarantees.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AgentDebugConfig {
+    /// The datacenter of the agent.
+    pub datacenter: String,
+    /// The primary datacenter of the cluster.
+    pub primary_datacenter: String,
+    /// The name of the agent's node.
+    pub node_name: String,
+    /// Whether the agent is running in server mode.
+    pub server_mode: bool,
+    /// The directory the agent stores its state in.
+    pub data_dir: String,
+    /// Whether ACLs are enabled.
+    #[serde(rename = "ACLsEnabled")]
+    pub acls_enabled: bool,
+    /// Whether Connect is enabled.
+    pub connect_enabled: bool,
+    /// Any other fields of the section.
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// The configuration and member information of the local agent, returned by
+/// the [Agent::agent_self] method.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AgentSelf {
+    /// The agent's identity.
+    pub config: AgentSelfConfig,
+    /// The agent's full runtime configuration.
+    pub debug_config: AgentDebugConfig,
+    /// The agent's own entry in the LAN gossip pool.
+    pub member: AgentMember,
+    /// Runtime statistics of the agent, grouped by subsystem such as `agent`,
+    /// `raft` or `serf_lan`.
+    pub stats: HashMap<String, HashMap<String, String>>,
+    /// The node metadata of the agent.
+    pub meta: HashMap<String, String>,
+    /// Any other sections of the response.
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// The kind of ACL token set by the [Agent::update_agent_token] method.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AgentTokenKind {
+    /// The token used for requests without a token of their own.
+    Default,
+    /// The token used by the agent for its internal operations.
+    Agent,
+    /// The token used for operations on the agent when the ACL servers are
+    /// unavailable.
+    AgentRecovery,
+    /// The token used by secondary datacenters to replicate ACLs.
+    Replication,
+    /// The token used to register services and checks defined in the agent's
+    /// configuration files.
+    ConfigFileServiceRegistration,
+}
+
+impl AgentTokenKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AgentTokenKind::Default => "default",
+            AgentTokenKind::Agent => "agent",
+            AgentTokenKind::AgentRecovery => "agent_recovery",
+            AgentTokenKind::Replication => "replication",
+            AgentTokenKind::ConfigFileServiceRegistration => "config_file_service_registration",
+        }
+    }
+}
+
+/// Request payload for the [Agent::update_agent_token] method.
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct UpdateAgentTokenPayload<'a> {
+    token: &'a str,
+}
+
+impl fmt::Debug for UpdateAgentTokenPayload<'_> {
+    // the token is redacted, since requests are traced
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UpdateAgentTokenPayload").field("token", &"<redacted>").finish()
+    }
+}
+
 /// This trait provides methods for interacting with the local Consul agent.
 ///
 /// These methods are used to interact with the local Consul agent.
@@ -101,26 +253,107 @@ pub trait Agent: Sealed {
     /// documentation].
     ///
     /// [API documentation]: https://www.consul.io/api-docs/agent#list-members.
-    async fn list_members(&self, wan: bool) -> ConsulResult<AgentMember>;
+    async fn list_members(&self, wan: bool) -> ConsulResult<Vec<AgentMember>>;
+
+    /// This method returns the configuration and member information of the
+    /// local agent.
+    ///
+    /// For more information, see the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent#read-configuration
+    async fn agent_self(&self) -> ConsulResult<AgentSelf>;
+
+    /// This method returns the metrics of the agent's most recent telemetry
+    /// interval.
+    ///
+    /// For more information, see the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent#view-metrics
+    async fn get_metrics(&self) -> ConsulResult<AgentMetrics>;
+
+    /// This method returns the metrics of the agent in the Prometheus text
+    /// exposition format. This requires `telemetry.prometheus_retention_time`
+    /// to be set in the agent's configuration.
+    ///
+    /// For more information, see the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent#view-metrics
+    async fn get_prometheus_metrics(&self) -> ConsulResult<String>;
+
+    /// This method streams the logs of the agent at or above the given level,
+    /// one line per item.
+    ///
+    /// The stream never ends on its own. Dropping it closes the underlying
+    /// connection, which stops the agent from streaming logs.
+    ///
+    /// For more information, see the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent#stream-logs
+    async fn monitor(&self, log_level: LogLevel) -> ConsulResult<MonitorStream<String>>;
+
+    /// This method streams the logs of the agent at or above the given level
+    /// as structured entries. The stream behaves like the one returned by
+    /// [Agent::monitor].
+    ///
+    /// For more information, see the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent#stream-logs
+    async fn monitor_json(&self, log_level: LogLevel) -> ConsulResult<MonitorStream<LogEntry>>;
 
     /// This method instructs the agent to reload its configuration.
     ///
     /// For more information, see the relevant endpoint's [API
     /// documentation].
     ///
-    /// [API documentation]: https://www.consul.io/api-docs/agent#reload-agent.
-    async fn reload_agent(&self) -> ConsulResult<()>;
+    /// [API documentation]: https://www.consul.io/api-docs/agent#reload-agent
+    async fn reload_agent(&self) -> ConsulResult<WriteMeta>;
+
+    /// This method places the node into maintenance mode. During maintenance
+    /// mode, the node is marked as unavailable and will not be present in DNS
+    /// or API queries. The optional `reason` is attached to the maintenance
+    /// check.
+    ///
+    /// For more information, see the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent#enable-maintenance-mode
+    async fn enable_node_maintenance(&self, reason: Option<&str>) -> ConsulResult<WriteMeta>;
 
-    /// This method places the agent into "maintenance mode". During maintenance
-    /// mode, the node will be marked as unavailable and will not be present in
-    /// DNS or API queries.
+    /// This method takes the node out of maintenance mode.
     ///
     /// For more information, see the relevant endpoint's [API
     /// documentation].
     ///
-    /// [API documentation]: https://www.consul.io/api-docs/agent#enable-maintenance-mode.
-    async fn enable_maintenance_mode(&self, enable: bool, reason: Option<&str>)
-        -> ConsulResult<()>;
+    /// [API documentation]: https://www.consul.io/api-docs/agent#enable-maintenance-mode
+    async fn disable_node_maintenance(&self) -> ConsulResult<WriteMeta>;
+
+    /// This method places the given service into maintenance mode. During
+    /// maintenance mode, the service is marked as unavailable and will not be
+    /// present in DNS or API queries. The optional `reason` is attached to the
+    /// maintenance check.
+    ///
+    /// For more information, see the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent/service#enable-maintenance-mode
+    async fn enable_service_maintenance(
+        &self,
+        service_id: &str,
+        reason: Option<&str>,
+    ) -> ConsulResult<WriteMeta>;
+
+    /// This method takes the given service out of maintenance mode.
+    ///
+    /// For more information, see the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent/service#enable-maintenance-mode
+    async fn disable_service_maintenance(&self, service_id: &str) -> ConsulResult<WriteMeta>;
 
     /// This method instructs the agent to attempt to connect to a given
     /// address.
@@ -128,8 +361,8 @@ pub trait Agent: Sealed {
     /// For more information, see the relevant endpoint's [API
     /// documentation].
     ///
-    /// [API documentation]: https://www.consul.io/api-docs/agent#join-agent.
-    async fn join_cluster(&self, address: &str, wan: bool) -> ConsulResult<()>;
+    /// [API documentation]: https://www.consul.io/api-docs/agent#join-agent
+    async fn join_cluster(&self, address: &str, wan: bool) -> ConsulResult<WriteMeta>;
 
     /// This endpoint triggers a graceful leave and shutdown of the agent. It is
     /// used to ensure other nodes see the agent as "left" instead of "failed".
@@ -137,69 +370,218 @@ pub trait Agent: Sealed {
     /// For more information, see the relevant endpoint's [API
     /// documentation].
     ///
-    /// [API documentation]: https://www.consul.io/api/agent.html#graceful-leave-and-shutdown.
-    async fn leave_cluster(&self) -> ConsulResult<()>;
+    /// [API documentation]: https://www.consul.io/api-docs/agent#graceful-leave-and-shutdown
+    async fn leave_cluster(&self) -> ConsulResult<WriteMeta>;
 
-    /// This endpoint instructs the agent to force a node into the left state in
-    /// the LAN and WAN gossip pools. If a node fails unexpectedly, then it will
-    /// be in a failed state.
+    /// This endpoint instructs the agent to force the given node into the left
+    /// state in the LAN and WAN gossip pools. If a node fails unexpectedly, it
+    /// remains in the failed state until it is reaped. If `prune` is set, the
+    /// node is removed from the member list entirely.
     ///
     /// For more information, see the relevant endpoint's [API
     /// documentation].
     ///
-    /// [API documentation]:  https://www.consul.io/api-docs/agent#force-leave-and-shutdown.
-    async fn force_leave_cluster(&self) -> ConsulResult<()>;
+    /// [API documentation]: https://www.consul.io/api-docs/agent#force-leave-and-shutdown
+    async fn force_leave_cluster(&self, node: &str, prune: bool) -> ConsulResult<WriteMeta>;
+
+    /// This method updates one of the ACL tokens used by the agent. The token
+    /// takes effect immediately, and is persisted if the agent's
+    /// `acl.enable_token_persistence` option is set.
+    ///
+    /// For more information, see the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent#update-acl-tokens
+    async fn update_agent_token(
+        &self,
+        kind: AgentTokenKind,
+        token: &str,
+    ) -> ConsulResult<WriteMeta>;
 }
 
 #[async_trait]
 impl Agent for Client {
     #[tracing::instrument]
-    async fn list_members(&self, wan: bool) -> ConsulResult<AgentMember> {
+    async fn list_members(&self, wan: bool) -> ConsulResult<Vec<AgentMember>> {
         let mut params = HashMap::new();
         if wan {
             params.insert(String::from("wan"), String::from("1"));
         }
-        self.get("/v1/agent/members", None).await
+        self.get_with_params("/v1/agent/members", Some(params), None).await
     }
 
     #[tracing::instrument]
-    async fn reload_agent(&self) -> ConsulResult<()> {
-        self.put("/v1/agent/reload", (), None, None).await
+    async fn agent_self(&self) -> ConsulResult<AgentSelf> {
+        self.get("/v1/agent/self", None).await
     }
 
     #[tracing::instrument]
-    async fn enable_maintenance_mode(
-        &self,
-        enable: bool,
-        reason: Option<&str>,
-    ) -> ConsulResult<()> {
+    async fn get_metrics(&self) -> ConsulResult<AgentMetrics> {
+        self.get("/v1/agent/metrics", None).await
+    }
+
+    #[tracing::instrument]
+    async fn get_prometheus_metrics(&self) -> ConsulResult<String> {
         let mut params = HashMap::new();
-        let enable_str = if enable { String::from("true") } else { String::from("false") };
-        params.insert(String::from("enabled"), enable_str);
-        if let Some(r) = reason {
-            params.insert(String::from("reason"), r.to_owned());
-        }
-        self.put("/v1/agent/maintenance", (), Some(params), None).await
+        params.insert(String::from("format"), String::from("prometheus"));
+        self.get_text("/v1/agent/metrics", Some(params), None).await
+    }
+
+    #[tracing::instrument]
+    async fn monitor(&self, log_level: LogLevel) -> ConsulResult<MonitorStream<String>> {
+        let mut params = HashMap::new();
+        params.insert(String::from("loglevel"), log_level.to_string());
+        let (stream, _) = self.get_stream("/v1/agent/monitor", Some(params), None).await?;
+        Ok(monitor::split_lines(stream))
     }
 
     #[tracing::instrument]
-    async fn join_cluster(&self, address: &str, wan: bool) -> ConsulResult<()> {
+    async fn monitor_json(&self, log_level: LogLevel) -> ConsulResult<MonitorStream<LogEntry>> {
         let mut params = HashMap::new();
+        params.insert(String::from("loglevel"), log_level.to_string());
+        params.insert(String::from("logjson"), String::from("true"));
+        let (stream, _) = self.get_stream("/v1/agent/monitor", Some(params), None).await?;
+        Ok(monitor::decode_entries(monitor::split_lines(stream)))
+    }
+
+    #[tracing::instrument]
+    async fn reload_agent(&self) -> ConsulResult<WriteMeta> {
+        self.put("/v1/agent/reload", (), None, None).await.map(|((), meta)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn enable_node_maintenance(&self, reason: Option<&str>) -> ConsulResult<WriteMeta> {
+        let params = maintenance_params(true, reason);
+        self.put_with_empty("/v1/agent/maintenance", (), Some(params), None)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn disable_node_maintenance(&self) -> ConsulResult<WriteMeta> {
+        let params = maintenance_params(false, None);
+        self.put_with_empty("/v1/agent/maintenance", (), Some(params), None)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
 
+    #[tracing::instrument]
+    async fn enable_service_maintenance(
+        &self,
+        service_id: &str,
+        reason: Option<&str>,
+    ) -> ConsulResult<WriteMeta> {
+        let params = maintenance_params(true, reason);
+        let path = format!("/v1/agent/service/maintenance/{}", service_id);
+        self.put_with_empty(&path, (), Some(params), None)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn disable_service_maintenance(&self, service_id: &str) -> ConsulResult<WriteMeta> {
+        let params = maintenance_params(false, None);
+        let path = format!("/v1/agent/service/maintenance/{}", service_id);
+        self.put_with_empty(&path, (), Some(params), None)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn join_cluster(&self, address: &str, wan: bool) -> ConsulResult<WriteMeta> {
+        let mut params = HashMap::new();
         if wan {
             params.insert(String::from("wan"), String::from("true"));
         }
         let path = format!("/v1/agent/join/{}", address);
-        self.put(&path, (), Some(params), None).await
+        self.put_with_empty(&path, (), Some(params), None)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
     }
 
     #[tracing::instrument]
-    async fn leave_cluster(&self) -> ConsulResult<()> {
-        self.put("/v1/agent/leave", (), None, None).await
+    async fn leave_cluster(&self) -> ConsulResult<WriteMeta> {
+        self.put_with_empty("/v1/agent/leave", (), None, None)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
     }
 
     #[tracing::instrument]
-    async fn force_leave_cluster(&self) -> ConsulResult<()> {
-        self.put("/v1/agent/force-leave", (), None, None).await
+    async fn force_leave_cluster(&self, node: &str, prune: bool) -> ConsulResult<WriteMeta> {
+        let mut params = HashMap::new();
+        if prune {
+            params.insert(String::from("prune"), String::from("true"));
+        }
+        let path = format!("/v1/agent/force-leave/{}", node);
+        self.put_with_empty(&path, (), Some(params), None)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument(skip(token))]
+    async fn update_agent_token(
+        &self,
+        kind: AgentTokenKind,
+        token: &str,
+    ) -> ConsulResult<WriteMeta> {
+        let path = format!("/v1/agent/token/{}", kind.as_str());
+        let payload = UpdateAgentTokenPayload { token };
+        self.put_with_empty(&path, payload, None, None).await.map(|(_, meta): (Option<()>, _)| meta)
+    }
+}
+
+/// This function returns the query parameters of the maintenance endpoints.
+fn maintenance_params(enable: bool, reason: Option<&str>) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    params.insert(String::from("enable"), enable.to_string());
+    if let Some(reason) = reason {
+        params.insert(String::from("reason"), reason.to_owned());
+    }
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Agent, AgentSelf};
+    use crate::request::tests::recording_client;
+
+    #[test]
+    fn test_decode_agent_self() {
+        let agent: AgentSelf = serde_json::from_str(
+            r#"{
+                "Config": {
+                    "Datacenter": "dc1",
+                    "NodeName": "foobar",
+                    "NodeID": "9d754d17-d864-b1d3-e758-f3fe25a9874f",
+                    "Server": true,
+                    "Version": "1.9.4",
+                    "Revision": "10bb6cb3b"
+                },
+                "DebugConfig": {"Datacenter": "dc1", "ServerMode": true, "DNSPort": 8600},
+                "Coord": {"Vec": [0, 0], "Error": 1.5},
+                "Member": {"Name": "foobar", "Addr": "10.1.10.12", "Port": 8301, "Status": 1},
+                "Stats": {"agent": {"checks": "0", "services": "1"}}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(agent.config.version, "1.9.4");
+        assert!(agent.debug_config.server_mode);
+        assert_eq!(agent.debug_config.extra["DNSPort"], 8600);
+        assert!(agent.extra.contains_key("Coord"));
+        assert_eq!(agent.stats["agent"]["services"], "1");
+    }
+
+    #[tokio::test]
+    async fn test_list_members() {
+        const MEMBERS: &str = r#"[
+            {"Name": "foobar.dc1", "Addr": "10.1.10.12", "Port": 8302, "Tags": {"role": "consul"}, "Status": 1},
+            {"Name": "baz.dc2", "Addr": "10.1.11.12", "Port": 8302, "Tags": {"role": "consul"}, "Status": 1}
+        ]"#;
+        let (client, requests) = recording_client(&[(200, &[], MEMBERS)]);
+        let members = client.list_members(true).await.unwrap();
+        let names: Vec<&str> = members.iter().map(|member| member.name.as_str()).collect();
+        assert_eq!(names, ["foobar.dc1", "baz.dc2"]);
+        assert_eq!(members[0].port, 8302);
+        assert_eq!(*requests.lock().unwrap(), ["GET /v1/agent/members?wan=1"]);
     }
 }
@@ -0,0 +1,139 @@
+use std::{collections::HashMap, fmt, pin::Pin};
+
+use bytes::Bytes;
+use futures::{stream, Stream, StreamExt};
+use serde_json::Value;
+
+use crate::{ConsulError, ConsulResult};
+
+/// A boxed [Stream] of log lines streamed from the agent.
+pub type MonitorStream<T> = Pin<Box<dyn Stream<Item = ConsulResult<T>> + Send>>;
+
+/// The minimum level of the logs streamed by the [Agent::monitor] and
+/// [Agent::monitor_json] methods.
+///
+/// [Agent::monitor]: crate::Agent::monitor
+/// [Agent::monitor_json]: crate::Agent::monitor_json
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        })
+    }
+}
+
+/// A structured log entry, streamed by the [Agent::monitor_json] method.
+///
+/// [Agent::monitor_json]: crate::Agent::monitor_json
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct LogEntry {
+    /// The level of the entry.
+    #[serde(rename = "@level")]
+    pub level: String,
+    /// The message of the entry.
+    #[serde(rename = "@message")]
+    pub message: String,
+    /// The module that logged the entry, such as `agent.server.raft`.
+    #[serde(rename = "@module")]
+    pub module: String,
+    /// The time the entry was logged, in RFC 3339 format.
+    #[serde(rename = "@timestamp")]
+    pub timestamp: String,
+    /// The structured fields of the entry.
+    #[serde(flatten)]
+    pub fields: HashMap<String, Value>,
+}
+
+/// This function splits a stream of chunks into a stream of non-empty lines.
+///
+/// Lines may span several chunks. If the stream yields an error, the error is
+/// forwarded and the stream ends after any buffered line.
+pub(crate) fn split_lines<S>(chunks: S) -> MonitorStream<String>
+where
+    S: Stream<Item = ConsulResult<Bytes>> + Send + 'static,
+{
+    let state = (Box::pin(chunks), Vec::new(), false);
+    Box::pin(stream::unfold(state, |(mut chunks, mut buffer, mut done)| async move {
+        loop {
+            if let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line[..end]).trim_end_matches('\r').to_owned();
+                if line.is_empty() {
+                    continue;
+                }
+                return Some((Ok(line), (chunks, buffer, done)));
+            }
+            if done {
+                if buffer.is_empty() {
+                    return None;
+                }
+                let line = String::from_utf8_lossy(&buffer).into_owned();
+                buffer.clear();
+                return Some((Ok(line), (chunks, buffer, done)));
+            }
+            match chunks.next().await {
+                Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                Some(Err(e)) => return Some((Err(e), (chunks, buffer, true))),
+                None => done = true,
+            }
+        }
+    }))
+}
+
+/// This function decodes a stream of JSON log lines into log entries.
+pub(crate) fn decode_entries(lines: MonitorStream<String>) -> MonitorStream<LogEntry> {
+    Box::pin(lines.map(|line| {
+        line.and_then(|line| serde_json::from_str(&line).map_err(ConsulError::DecodeError))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::{executor::block_on, stream, StreamExt};
+
+    use super::{decode_entries, split_lines};
+    use crate::{ConsulError, ConsulResult};
+
+    #[test]
+    fn test_split_lines() {
+        let chunks: Vec<ConsulResult<Bytes>> = vec![
+            Ok(Bytes::from("2021-03-01 [INFO] agent: sta")),
+            Ok(Bytes::from("rted\r\n\n2021-03-01 [DEBUG] agent: ")),
+            Ok(Bytes::from("synced\n2021-03-01 [WARN]")),
+            Err(ConsulError::EmptyKey),
+        ];
+        let lines: Vec<_> = block_on(split_lines(stream::iter(chunks)).collect());
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].as_ref().unwrap(), "2021-03-01 [INFO] agent: started");
+        assert_eq!(lines[1].as_ref().unwrap(), "2021-03-01 [DEBUG] agent: synced");
+        assert!(lines[2].is_err());
+        assert_eq!(lines[3].as_ref().unwrap(), "2021-03-01 [WARN]");
+    }
+
+    #[test]
+    fn test_decode_entries() {
+        let chunks: Vec<ConsulResult<Bytes>> = vec![Ok(Bytes::from(
+            r#"{"@level":"info","@message":"synced node info","@module":"agent","@timestamp":"2021-03-01T10:00:00.000Z","node":"foobar"}"#,
+        ))];
+        let entries: Vec<_> = block_on(decode_entries(split_lines(stream::iter(chunks))).collect());
+        let entry = entries[0].as_ref().unwrap();
+        assert_eq!(entry.message, "synced node info");
+        assert_eq!(entry.fields["node"], "foobar");
+    }
+}
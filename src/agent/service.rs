@@ -1,57 +1,24 @@
-use std::{collections::HashMap, fmt::Debug};
+use std::collections::HashMap;
 
 use async_trait::async_trait;
+use http::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 
-use crate::{sealed::Sealed, Client, ConsulResult, HealthCheck, ServiceWeights, TaggedAddress};
-
-/// A service registered with the local agent.
-///
-/// This service was either provided through configuration files or added
-/// dynamically using the HTTP API.
-///
-/// See the [List Services] endpoint documentation for more information.
-///
-/// [List Services]: https://www.consul.io/api-docs/agent/service#list-services
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-pub struct Service {
-    /// Identifies the service as a Connect proxy. See Connect
-    /// for details.
-    pub kind: Option<String>,
-    /// Specifies the service ID. If this was not specified
-    /// when the service was created, the value of the name field will be
-    /// used.
-    #[serde(rename = "ID")]
-    pub id: String,
-    /// List of string values that used to add service-level labels.\
-    #[serde(default)]
-    pub tags: Vec<String>,
-    /// Object that defines a map of the max 64 key/value pairs.
-    /// The meta object has the same limitations as the node meta object in the
-    /// node definition.
-    #[serde(default)]
-    pub meta: HashMap<String, String>,
-    /// String value that specifies a service-specific IP address or hostname.
-    pub address: String,
-    /// Additional addresses defined for the service.
-    #[serde(default)]
-    pub tagged_addresses: HashMap<String, TaggedAddress>,
-    /// Specifies a service-specific port number.
-    pub port: u16,
-    /// Determines if the anti-entropy feature for the service is enabled.
-    pub enable_tag_override: Option<bool>,
-    /// Struct that configures the weight of the service in terms of its DNS
-    /// service (SRV) response.
-    pub weights: ServiceWeights,
-}
+use crate::{
+    request::response_error, sealed::Sealed, AgentService, AgentServiceConnect, CheckStatus,
+    Client, ConsulResult, ExposeConfig, HealthCheck, MeshGatewayConfig, QueryMeta, QueryOptions,
+    RegisterCheckPayload, ServiceWeights, TaggedAddress, TransparentProxyConfig, WriteMeta,
+};
 
 /// Response returned by [AgentServices::get_local_service_config]. Identical to
-/// [Service], but with the `content_hash` field.
-#[derive(Deserialize, Debug)]
+/// [AgentService], but with the `content_hash` field always set.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct ServiceConfig {
     /// Identifies the service as a Connect proxy. See Connect
     /// for details.
+    #[serde(default)]
     pub kind: String,
     /// Specifies the service ID. If this was not specified
     /// when the service was created, the value of the name field will be
@@ -69,6 +36,7 @@ pub struct ServiceConfig {
     /// String value that specifies a service-specific IP address or hostname.
     pub address: String,
     /// Additional addresses defined for the service.
+    #[serde(default)]
     pub tagged_addresses: HashMap<String, TaggedAddress>,
     /// Specifies a service-specific port number.
     pub port: u16,
@@ -79,13 +47,33 @@ pub struct ServiceConfig {
     pub weights: ServiceWeights,
     /// Contains the hash-based blocking query hash for the result.
     pub content_hash: String,
-    // TODO: add proxy field
-    // pub proxy: Proxy
+    /// The configuration of the proxy, if the service is one.
+    #[serde(default)]
+    pub proxy: Option<ServiceProxy>,
+    /// The Connect configuration of the service.
+    #[serde(default)]
+    pub connect: Option<AgentServiceConnect>,
+}
+
+/// The health of a service instance registered with the local agent.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AgentServiceHealth {
+    /// The aggregated status of the health checks of the instance.
+    ///
+    /// The agent also reports it with the status code of its response: `200`
+    /// for passing, `429` for warning and `503` for critical or maintenance.
+    pub aggregated_status: CheckStatus,
+    /// The service instance.
+    pub service: AgentService,
+    /// The health checks of the instance.
+    #[serde(default)]
+    pub checks: Vec<HealthCheck>,
 }
 
 /// Defines the configuration of a service to be created. Used by the
 /// [AgentServices::register_service] method.
-#[derive(Serialize, Default, Debug)]
+#[derive(Clone, Serialize, Default, Debug)]
 pub struct ServiceRegistrationPayload {
     #[serde(rename = "Name")]
     /// Specifies the logical name of the service.
@@ -106,7 +94,226 @@ pub struct ServiceRegistrationPayload {
     ///Specifies to disable the anti-entropy feature for this service's tags.
     #[serde(rename = "EnableTagOverride")]
     pub enable_tag_override: bool,
+    /// Specifies the health checks to register alongside the service.
+    #[serde(rename = "Checks", skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<RegisterCheckPayload>,
+    /// Specifies the kind of the service, such as `connect-proxy` when
+    /// registering a proxy directly. Defaults to a typical service.
+    #[serde(rename = "Kind", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Specifies the Connect configuration of the service, such as the sidecar
+    /// proxy to register alongside it.
+    #[serde(rename = "Connect", skip_serializing_if = "Option::is_none")]
+    pub connect: Option<ServiceConnect>,
+    /// Specifies the configuration of the proxy, if the service is one. This
+    /// is also used to configure the proxy of a sidecar service.
+    #[serde(rename = "Proxy", skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ServiceProxy>,
+    /// Specifies to replace the health checks previously registered with the
+    /// service by the checks of this registration, removing any check it
+    /// does not define. By default, existing checks are kept.
+    #[serde(skip)]
+    pub replace_existing_checks: bool,
+}
+
+impl ServiceRegistrationPayload {
+    /// This method returns a builder for a registration of the service with
+    /// the given name. Every other field is optional.
+    ///
+    /// ```
+    /// use consul_oxide::ServiceRegistrationPayload;
+    ///
+    /// let payload = ServiceRegistrationPayload::builder("web").port(8080).tag("v1").build();
+    /// assert_eq!(payload.name, "web");
+    /// assert_eq!(payload.tags, Some(vec![String::from("v1")]));
+    /// ```
+    pub fn builder(name: impl Into<String>) -> ServiceRegistrationPayloadBuilder {
+        ServiceRegistrationPayloadBuilder {
+            payload: ServiceRegistrationPayload { name: name.into(), ..Default::default() },
+        }
+    }
 }
+
+/// A builder for a [ServiceRegistrationPayload], created with
+/// [ServiceRegistrationPayload::builder].
+#[derive(Clone, Debug)]
+pub struct ServiceRegistrationPayloadBuilder {
+    payload: ServiceRegistrationPayload,
+}
+
+impl ServiceRegistrationPayloadBuilder {
+    /// This method sets the ID of the service, which defaults to its name.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.payload.id = Some(id.into());
+        self
+    }
+
+    /// This method adds a tag to the service.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.payload.tags.get_or_insert_with(Vec::new).push(tag.into());
+        self
+    }
+
+    /// This method sets the port on which the service is exposed.
+    pub fn port(mut self, port: u16) -> Self {
+        self.payload.port = port;
+        self
+    }
+
+    /// This method sets the address on which the service is exposed, which
+    /// defaults to the address of the agent.
+    pub fn address(mut self, address: impl Into<String>) -> Self {
+        self.payload.address = Some(address.into());
+        self
+    }
+
+    /// This method sets whether to disable the anti-entropy feature for the
+    /// service's tags.
+    pub fn enable_tag_override(mut self, enable_tag_override: bool) -> Self {
+        self.payload.enable_tag_override = enable_tag_override;
+        self
+    }
+
+    /// This method adds a health check to register alongside the service.
+    pub fn check(mut self, check: RegisterCheckPayload) -> Self {
+        self.payload.checks.push(check);
+        self
+    }
+
+    /// This method sets the kind of the service, such as `connect-proxy`.
+    pub fn kind(mut self, kind: impl Into<String>) -> Self {
+        self.payload.kind = Some(kind.into());
+        self
+    }
+
+    /// This method sets the Connect configuration of the service.
+    pub fn connect(mut self, connect: ServiceConnect) -> Self {
+        self.payload.connect = Some(connect);
+        self
+    }
+
+    /// This method sets the configuration of the proxy, if the service is one.
+    pub fn proxy(mut self, proxy: ServiceProxy) -> Self {
+        self.payload.proxy = Some(proxy);
+        self
+    }
+
+    /// This method sets whether to replace the health checks previously
+    /// registered with the service by the checks of this registration.
+    pub fn replace_existing_checks(mut self, replace_existing_checks: bool) -> Self {
+        self.payload.replace_existing_checks = replace_existing_checks;
+        self
+    }
+
+    /// This method returns the built payload.
+    pub fn build(self) -> ServiceRegistrationPayload {
+        self.payload
+    }
+}
+
+/// The Connect configuration of a service registration.
+///
+/// See the [Connect Structure] documentation for more information.
+///
+/// [Connect Structure]: https://developer.hashicorp.com/consul/api-docs/agent/service#connect-structure
+#[derive(Clone, Serialize, Default, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ServiceConnect {
+    /// Specifies whether the service supports Connect natively, without a
+    /// proxy.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub native: bool,
+    /// Specifies a sidecar proxy to register alongside the service. Its
+    /// fields default to values derived from the service, such as its name,
+    /// an automatically assigned port and a proxy of the service, so an empty
+    /// payload registers a working sidecar.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sidecar_service: Option<Box<ServiceRegistrationPayload>>,
+}
+
+/// The configuration of a Connect proxy, registered with a proxy service or a
+/// sidecar service.
+///
+/// See the [Proxy Configuration] documentation for more information.
+///
+/// [Proxy Configuration]: https://developer.hashicorp.com/consul/docs/connect/proxies/proxy-config-reference
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceProxy {
+    /// Specifies the name of the service the proxy represents. This is
+    /// required for proxies registered directly, and defaults to the parent
+    /// service for sidecar services.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination_service_name: Option<String>,
+    /// Specifies the ID of the service instance the proxy represents.
+    #[serde(rename = "DestinationServiceID", skip_serializing_if = "Option::is_none")]
+    pub destination_service_id: Option<String>,
+    /// Specifies the address the proxy forwards inbound connections to.
+    /// Defaults to `127.0.0.1`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_service_address: Option<String>,
+    /// Specifies the port the proxy forwards inbound connections to. Defaults
+    /// to the port of the parent service for sidecar services.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_service_port: Option<u16>,
+    /// Specifies the proxy mode, either `direct` or `transparent`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// Specifies the transparent proxy configuration of the proxy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transparent_proxy: Option<TransparentProxyConfig>,
+    /// Specifies opaque configuration passed to the proxy, such as the
+    /// `protocol` or Envoy escape hatches.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub config: HashMap<String, Value>,
+    /// Specifies the upstreams the proxy listens for local connections to.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub upstreams: Vec<ProxyUpstream>,
+    /// Specifies the mesh gateway configuration of the proxy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mesh_gateway: Option<MeshGatewayConfig>,
+    /// Specifies the HTTP paths exposed through the proxy without requiring
+    /// mutual TLS.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expose: Option<ExposeConfig>,
+}
+
+/// An upstream of a Connect proxy, which is a service or prepared query the
+/// proxy listens for local connections to.
+///
+/// See the [Upstream Configuration] documentation for more information.
+///
+/// [Upstream Configuration]: https://developer.hashicorp.com/consul/docs/connect/proxies/proxy-config-reference#upstream-configuration-reference
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ProxyUpstream {
+    /// Specifies the type of the destination, either `service` or
+    /// `prepared_query`. Defaults to `service`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination_type: Option<String>,
+    /// Specifies the name of the service or prepared query to connect to.
+    pub destination_name: String,
+    /// Specifies the namespace of the destination service.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination_namespace: Option<String>,
+    /// Specifies the datacenter of the destination. Defaults to the local
+    /// datacenter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datacenter: Option<String>,
+    /// Specifies the address the proxy listens on for the upstream. Defaults
+    /// to `127.0.0.1`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_bind_address: Option<String>,
+    /// Specifies the port the proxy listens on for the upstream.
+    pub local_bind_port: u16,
+    /// Specifies opaque configuration of the upstream passed to the proxy.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub config: HashMap<String, Value>,
+    /// Specifies the mesh gateway configuration of the upstream.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mesh_gateway: Option<MeshGatewayConfig>,
+}
+
 /// This trait provides methods for interacting with the `/agent/service`
 /// endpoints.
 ///
@@ -116,90 +323,372 @@ pub struct ServiceRegistrationPayload {
 pub trait AgentServices: Sealed {
     /// This method returns all the services that are registered with the
     /// local agent. These services were either provided through configuration
-    /// files or added dynamically using the HTTP API.
+    /// files or added dynamically using the HTTP API. The services can be
+    /// filtered with [QueryOptions::filter], such as by `Kind` or
+    /// `Proxy.DestinationServiceName`.
     ///
     /// For more information, see the relevant endpoint's [API documentation].
     ///
     /// [API documentation]: https://www.consul.io/api-docs/agent/service#list-services
-    async fn list_local_services(&self) -> ConsulResult<Vec<Service>>;
+    async fn list_local_services(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Vec<AgentService>>;
 
     /// This method returns the full service definition for a single service
-    /// instance registered on the local agent.
+    /// instance registered on the local agent, or [None] if no service with
+    /// the given ID is registered.
+    ///
+    /// This endpoint supports hash-based blocking queries: passing the
+    /// [QueryMeta::last_content_hash] of a previous result as
+    /// [QueryOptions::wait_hash] blocks until the definition changes, which
+    /// allows detecting drift of the local configuration.
     ///
     /// For more information, see the relevant endpoint's [API documentation].
     ///
     /// [API Documentation]: https://www.consul.io/api-docs/agent/service#get-service-configuration
-    async fn get_local_service_config<S: AsRef<str> + Send + Debug>(
+    async fn get_local_service_config(
         &self,
-        id: S,
-    ) -> ConsulResult<ServiceConfig>;
+        id: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<ServiceConfig>, QueryMeta)>;
 
-    /// This method retrieves an aggregated state of service(s) on the local
-    /// agent by name.
+    /// This method retrieves the health of the instances of a service
+    /// registered with the local agent by name, alongside their aggregated
+    /// status. Unhealthy services are not reported as errors, and a service
+    /// without instances is critical.
+    ///
+    /// This is cheaper than querying the catalog, so it is suited to
+    /// liveness checks of local services.
     ///
     /// For more information, see the relevant endpoint's [API documentation].
     ///
     /// [API documentation]: https://www.consul.io/api-docs/agent/service#get-local-service-health
-    async fn get_local_service_health<S: AsRef<str> + Send + Debug>(
+    async fn get_local_service_health(
         &self,
-        name: S,
-    ) -> ConsulResult<HealthCheck>;
+        name: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(CheckStatus, Vec<AgentServiceHealth>)>;
 
-    /// This method retrieves the health state of a specific service on the
-    /// local agent by ID.
-    async fn get_local_service_health_by_id<S: AsRef<str> + Send + Debug>(
+    /// This method retrieves the health of a service instance registered with
+    /// the local agent by ID, or [None] if no instance with the given ID is
+    /// registered. Unhealthy instances are not reported as errors.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent/service#get-local-service-health-by-its-id
+    async fn get_local_service_health_by_id(
         &self,
-        id: S,
-    ) -> ConsulResult<HealthCheck>;
+        id: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Option<AgentServiceHealth>>;
 
     /// This endpoint adds a new service, with optional health checks, to the
-    /// local agent.
+    /// local agent. Registering a service with the ID of an existing one
+    /// updates it, replacing its health checks if the payload's
+    /// `replace_existing_checks` is set.
     ///
     /// For more information, see the relevant endpoint's [API documentation].
     ///
     /// [API documentation]: https://www.consul.io/api-docs/agent/service#register-service
-    async fn register_service(&self, payload: ServiceRegistrationPayload) -> ConsulResult<()>;
+    async fn register_service(
+        &self,
+        payload: ServiceRegistrationPayload,
+    ) -> ConsulResult<WriteMeta>;
+
+    /// This endpoint removes a service, and its associated health checks, from
+    /// the local agent.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent/service#deregister-service
+    async fn deregister_service(&self, id: &str) -> ConsulResult<WriteMeta>;
 }
 
 #[async_trait]
 impl AgentServices for Client {
     #[tracing::instrument]
-    async fn list_local_services(&self) -> ConsulResult<Vec<Service>> {
-        let services: HashMap<String, Service> = match self.get("/v1/agent/services", None).await {
-            Ok(services) => services,
-            Err(e) => return Err(e),
-        };
+    async fn list_local_services(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Vec<AgentService>> {
+        let services: HashMap<String, AgentService> =
+            self.get("/v1/agent/services", options).await?;
         Ok(services.into_values().collect())
     }
 
     #[tracing::instrument]
-    async fn get_local_service_config<S: AsRef<str> + Send + Debug>(
+    async fn get_local_service_config(
         &self,
-        name: S,
-    ) -> ConsulResult<ServiceConfig> {
-        self.get(format!("/v1/agent/services/{}", name.as_ref()), None).await
+        id: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<ServiceConfig>, QueryMeta)> {
+        self.get_with_meta(format!("/v1/agent/service/{}", id), None, options).await
     }
 
     #[tracing::instrument]
-    async fn get_local_service_health<S: AsRef<str> + Send + Debug>(
+    async fn get_local_service_health(
         &self,
-        name: S,
-    ) -> ConsulResult<HealthCheck> {
-        self.get(format!("/v1/agent/health/service/{}", name.as_ref()), None).await
+        name: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(CheckStatus, Vec<AgentServiceHealth>)> {
+        let path = format!("/v1/agent/health/service/name/{}", name);
+        let health = local_service_health(self, path, options).await?;
+        Ok(health.unwrap_or((CheckStatus::Critical, Vec::new())))
     }
 
     #[tracing::instrument]
-    async fn get_local_service_health_by_id<S: AsRef<str> + Send + Debug>(
+    async fn get_local_service_health_by_id(
         &self,
-        id: S,
-    ) -> ConsulResult<HealthCheck> {
-        self.get(format!("/v1/agent/health/service/id/{}", id.as_ref()), None).await
+        id: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Option<AgentServiceHealth>> {
+        let path = format!("/v1/agent/health/service/id/{}", id);
+        let health = local_service_health(self, path, options).await?;
+        Ok(health.map(|(_, health)| health))
     }
 
     #[tracing::instrument]
-    async fn register_service(&self, payload: ServiceRegistrationPayload) -> ConsulResult<()> {
-        self.put_with_empty("/v1/agent/service/register", payload, None, None)
+    async fn register_service(
+        &self,
+        payload: ServiceRegistrationPayload,
+    ) -> ConsulResult<WriteMeta> {
+        let mut params = HashMap::new();
+        if payload.replace_existing_checks {
+            params.insert(String::from("replace-existing-checks"), String::from("true"));
+        }
+        self.put_with_empty("/v1/agent/service/register", payload, Some(params), None)
             .await
-            .map(|_: Option<()>| ())
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn deregister_service(&self, id: &str) -> ConsulResult<WriteMeta> {
+        self.put_with_empty(&format!("/v1/agent/service/deregister/{}", id), (), None, None)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+}
+
+/// This function requests the health of local services at the given path,
+/// returning the aggregated status reported by the status code of the response
+/// alongside its body, or [None] if the service is not registered.
+async fn local_service_health<T: DeserializeOwned>(
+    client: &Client,
+    path: String,
+    options: Option<QueryOptions>,
+) -> ConsulResult<Option<(CheckStatus, T)>> {
+    let builder = client.build_request(Method::GET, path, None, None::<()>, options);
+    let response = client.execute(builder).await?;
+    let status = match response.status() {
+        StatusCode::OK => CheckStatus::Passing,
+        StatusCode::TOO_MANY_REQUESTS => CheckStatus::Warning,
+        StatusCode::SERVICE_UNAVAILABLE => CheckStatus::Critical,
+        StatusCode::NOT_FOUND => return Ok(None),
+        _ => return Err(response_error(response).await),
+    };
+    let body: T = response.json().await?;
+    Ok(Some((status, body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        AgentServices, CheckStatus, ProxyUpstream, ServiceConnect, ServiceProxy,
+        ServiceRegistrationPayload,
+    };
+    use crate::{request::tests::recording_client, QueryOptions, RegisterCheckPayload};
+
+    #[test]
+    fn test_builder() {
+        let check = RegisterCheckPayload { name: String::from("web-ttl"), ..Default::default() };
+        let payload = ServiceRegistrationPayload::builder("web")
+            .id("web-1")
+            .tag("primary")
+            .tag("v1")
+            .port(8080)
+            .check(check)
+            .build();
+        let value = serde_json::to_value(&payload).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "Name": "web",
+                "ID": "web-1",
+                "Tags": ["primary", "v1"],
+                "Port": 8080,
+                "Address": null,
+                "EnableTagOverride": false,
+                "Checks": [{"Name": "web-ttl"}],
+            })
+        );
+    }
+
+    #[test]
+    fn test_sidecar_service() {
+        let upstream = ProxyUpstream {
+            destination_type: Some(String::from("prepared_query")),
+            destination_name: String::from("db"),
+            local_bind_port: 9191,
+            ..Default::default()
+        };
+        let sidecar = ServiceRegistrationPayload {
+            proxy: Some(ServiceProxy { upstreams: vec![upstream], ..Default::default() }),
+            ..Default::default()
+        };
+        let payload = ServiceRegistrationPayload {
+            name: String::from("web"),
+            port: 8080,
+            connect: Some(ServiceConnect {
+                sidecar_service: Some(Box::new(sidecar)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&payload).unwrap();
+        assert_eq!(
+            value["Connect"],
+            json!({
+                "SidecarService": {
+                    "Name": "",
+                    "ID": null,
+                    "Tags": null,
+                    "Port": 0,
+                    "Address": null,
+                    "EnableTagOverride": false,
+                    "Proxy": {
+                        "Upstreams": [{
+                            "DestinationType": "prepared_query",
+                            "DestinationName": "db",
+                            "LocalBindPort": 9191,
+                        }],
+                    },
+                },
+            })
+        );
+        assert!(value.get("Proxy").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_register_service_replace_existing_checks() {
+        let (client, requests) = recording_client(&[(200, &[], ""), (200, &[], "")]);
+        let payload = ServiceRegistrationPayload::builder("web").build();
+        client.register_service(payload).await.unwrap();
+        let payload =
+            ServiceRegistrationPayload::builder("web").replace_existing_checks(true).build();
+        assert!(serde_json::to_value(&payload).unwrap().get("ReplaceExistingChecks").is_none());
+        client.register_service(payload).await.unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "PUT /v1/agent/service/register",
+                "PUT /v1/agent/service/register?replace-existing-checks=true",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_local_service_config() {
+        const SERVICE: &str = r#"{
+            "ID": "web-1",
+            "Service": "web",
+            "Tags": ["v1"],
+            "Meta": {},
+            "Port": 8080,
+            "Address": "",
+            "EnableTagOverride": false,
+            "Weights": {"Passing": 1, "Warning": 1},
+            "ContentHash": "4ecd29c7bc647ca8"
+        }"#;
+        let (client, requests) = recording_client(&[
+            (200, &[("X-Consul-ContentHash", "4ecd29c7bc647ca8")], SERVICE),
+            (404, &[], "unknown service ID: web-2"),
+        ]);
+        let (service, meta) = client.get_local_service_config("web-1", None).await.unwrap();
+        let service = service.unwrap();
+        assert_eq!(service.content_hash, "4ecd29c7bc647ca8");
+        assert_eq!(service.kind, "");
+        let options = QueryOptions { wait_hash: meta.last_content_hash, ..Default::default() };
+        let (service, _) = client.get_local_service_config("web-2", Some(options)).await.unwrap();
+        assert!(service.is_none());
+        assert_eq!(
+            *requests.lock().unwrap(),
+            ["GET /v1/agent/service/web-1", "GET /v1/agent/service/web-2?hash=4ecd29c7bc647ca8"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_local_service_health() {
+        const SERVICE: &str = r#"{
+            "AggregatedStatus": "warning",
+            "Service": {"ID": "web-1", "Service": "web", "Port": 8080},
+            "Checks": [{"CheckID": "web-http", "Status": "warning", "ServiceID": "web-1"}]
+        }"#;
+        const SERVICES: &str = r#"[{
+            "AggregatedStatus": "critical",
+            "Service": {"ID": "web-1", "Service": "web", "Port": 8080},
+            "Checks": []
+        }]"#;
+        let (client, requests) = recording_client(&[
+            (429, &[], SERVICE),
+            (404, &[], "ServiceId web-2 not found"),
+            (503, &[], SERVICES),
+            (404, &[], "ServiceName db Not Found"),
+        ]);
+        let health = client.get_local_service_health_by_id("web-1", None).await.unwrap().unwrap();
+        assert_eq!(health.aggregated_status, CheckStatus::Warning);
+        assert_eq!(health.service.port, 8080);
+        assert_eq!(health.checks[0].check_id, "web-http");
+        assert!(client.get_local_service_health_by_id("web-2", None).await.unwrap().is_none());
+        let (status, instances) = client.get_local_service_health("web", None).await.unwrap();
+        assert_eq!(status, CheckStatus::Critical);
+        assert_eq!(instances.len(), 1);
+        let (status, instances) = client.get_local_service_health("db", None).await.unwrap();
+        assert_eq!(status, CheckStatus::Critical);
+        assert!(instances.is_empty());
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "GET /v1/agent/health/service/id/web-1",
+                "GET /v1/agent/health/service/id/web-2",
+                "GET /v1/agent/health/service/name/web",
+                "GET /v1/agent/health/service/name/db",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_local_services() {
+        const SERVICES: &str = r#"{
+            "web-sidecar-proxy": {
+                "Kind": "connect-proxy",
+                "ID": "web-sidecar-proxy",
+                "Service": "web-sidecar-proxy",
+                "Port": 21000,
+                "Weights": {"Passing": 1, "Warning": 1},
+                "Proxy": {
+                    "DestinationServiceName": "web",
+                    "DestinationServiceID": "web-1",
+                    "LocalServicePort": 8080,
+                    "Upstreams": [{"DestinationName": "db", "LocalBindPort": 9191}]
+                },
+                "ContentHash": "b1c9f4e0d6a3e6b2"
+            },
+            "api": {"ID": "api", "Service": "api", "Port": 9090, "Connect": {"Native": true}}
+        }"#;
+        let (client, requests) = recording_client(&[(200, &[], SERVICES)]);
+        let options =
+            QueryOptions { filter: Some(String::from("Kind != \"\"")), ..Default::default() };
+        let mut services = client.list_local_services(Some(options)).await.unwrap();
+        services.sort_by(|a, b| a.id.cmp(&b.id));
+        assert!(services[0].connect.as_ref().unwrap().native);
+        let proxy = services[1].proxy.as_ref().unwrap();
+        assert_eq!(proxy.destination_service_name.as_deref(), Some("web"));
+        assert_eq!(proxy.upstreams[0].local_bind_port, 9191);
+        assert_eq!(services[1].weights.passing, 1);
+        assert_eq!(services[1].content_hash, "b1c9f4e0d6a3e6b2");
+        assert_eq!(*requests.lock().unwrap(), ["GET /v1/agent/services?filter=Kind+%21%3D+%22%22"]);
     }
 }
@@ -0,0 +1,215 @@
+//! A blocking Consul client, for programs that cannot easily adopt async, such
+//! as command line tools and build scripts.
+//!
+//! The [Client] of this module wraps the asynchronous [crate::Client], and
+//! drives its requests to completion on an internal runtime. Its methods
+//! mirror the most commonly used endpoints, and any other endpoint can be
+//! called through [Client::block_on].
+//!
+//! ```no_run
+//! use consul_oxide::{blocking::Client, Catalog, Config};
+//!
+//! let client = Client::new(Config::new_from_env()).unwrap();
+//! let leader = client.get_leader(None).unwrap();
+//! let (nodes, _) = client.block_on(client.async_client().list_datacenter_nodes(None)).unwrap();
+//! ```
+//!
+//! The blocking client must not be used from within an asynchronous runtime,
+//! as it would block the runtime's threads.
+
+#[cfg(feature = "catalog")]
+use std::collections::HashMap;
+use std::{future::Future, sync::Arc};
+
+use tokio::runtime::{Builder, Runtime};
+
+#[cfg(any(feature = "catalog", feature = "health", feature = "kv"))]
+use crate::QueryMeta;
+#[cfg(any(feature = "catalog", feature = "health", feature = "kv", feature = "status"))]
+use crate::QueryOptions;
+#[cfg(feature = "status")]
+use crate::Status;
+#[cfg(any(feature = "agent", feature = "kv"))]
+use crate::WriteMeta;
+#[cfg(feature = "agent")]
+use crate::{AgentServices, ServiceRegistrationPayload};
+#[cfg(feature = "catalog")]
+use crate::{Catalog, CatalogService};
+use crate::{Config, ConsulResult};
+#[cfg(feature = "health")]
+use crate::{Health, ServiceEntry};
+#[cfg(feature = "kv")]
+use crate::{KVPair, KVPutParams, WriteOptions, KV};
+
+/// A blocking Consul client. Clones of the client share its runtime.
+///
+/// # Panics
+/// Every method of this client panics if it is called from within an
+/// asynchronous runtime.
+#[derive(Clone, Debug)]
+pub struct Client {
+    inner: crate::Client,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    /// This method creates a new blocking Consul client, along with the
+    /// runtime driving its requests.
+    pub fn new(config: Config) -> ConsulResult<Self> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        Ok(Client { inner: crate::Client::new(config), runtime: Arc::new(runtime) })
+    }
+
+    /// This method returns the asynchronous client wrapped by this client.
+    pub fn async_client(&self) -> &crate::Client {
+        &self.inner
+    }
+
+    /// This method runs the given future to completion on the runtime of the
+    /// client, such as a request of the asynchronous client for an endpoint
+    /// this client has no method for.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// This method returns the Raft leader of the datacenter of the agent.
+    #[cfg(feature = "status")]
+    pub fn get_leader(&self, options: Option<QueryOptions>) -> ConsulResult<String> {
+        self.block_on(self.inner.get_leader(options))
+    }
+
+    /// This method returns the Raft peers of the datacenter of the agent.
+    #[cfg(feature = "status")]
+    pub fn list_peers(&self, options: Option<QueryOptions>) -> ConsulResult<Vec<String>> {
+        self.block_on(self.inner.list_peers(options))
+    }
+
+    /// This method returns the entries of the given key.
+    #[cfg(feature = "kv")]
+    pub fn get_entry(
+        &self,
+        key: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<KVPair>, QueryMeta)> {
+        self.block_on(self.inner.get_entry(key, options))
+    }
+
+    /// This method returns the entries sharing the given prefix.
+    #[cfg(feature = "kv")]
+    pub fn list_entries(
+        &self,
+        prefix: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<KVPair>, QueryMeta)> {
+        self.block_on(self.inner.list_entries(prefix, options))
+    }
+
+    /// This method returns the keys sharing the given prefix, up to the given
+    /// separator.
+    #[cfg(feature = "kv")]
+    pub fn list_keys(
+        &self,
+        prefix: &str,
+        separator: Option<&str>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<String>, QueryMeta)> {
+        self.block_on(self.inner.list_keys(prefix, separator, options))
+    }
+
+    /// This method updates or creates the given entry.
+    #[cfg(feature = "kv")]
+    pub fn put_entry(
+        &self,
+        pair: &KVPair,
+        params: Option<KVPutParams>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        self.block_on(self.inner.put_entry(pair, params, options))
+    }
+
+    /// This method deletes the given key, if its modify index matches the given
+    /// check-and-set index.
+    #[cfg(feature = "kv")]
+    pub fn delete_entry(
+        &self,
+        key: &str,
+        cas: Option<u64>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        self.block_on(self.inner.delete_entry(key, cas, options))
+    }
+
+    /// This method returns the known datacenters.
+    #[cfg(feature = "catalog")]
+    pub fn list_datacenters(&self) -> ConsulResult<Vec<String>> {
+        self.block_on(self.inner.list_datacenters())
+    }
+
+    /// This method returns the services registered in the datacenter, along
+    /// with their tags.
+    #[cfg(feature = "catalog")]
+    pub fn list_datacenter_services(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(HashMap<String, Vec<String>>, QueryMeta)> {
+        self.block_on(self.inner.list_datacenter_services(options))
+    }
+
+    /// This method returns the nodes providing the given service, optionally
+    /// only those with the given tag.
+    #[cfg(feature = "catalog")]
+    pub fn list_nodes_for_service(
+        &self,
+        service: &str,
+        tag: Option<&str>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<CatalogService>, QueryMeta)> {
+        self.block_on(self.inner.list_nodes_for_service(service, tag, options))
+    }
+
+    /// This method returns the instances of the given service with all the
+    /// given tags, optionally only those whose checks are passing.
+    #[cfg(feature = "health")]
+    pub fn list_service_instances(
+        &self,
+        service: &str,
+        tags: &[&str],
+        passing_only: bool,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<ServiceEntry>, QueryMeta)> {
+        self.block_on(self.inner.list_service_instances(service, tags, passing_only, options))
+    }
+
+    /// This method registers the given service with the local agent.
+    #[cfg(feature = "agent")]
+    pub fn register_service(&self, payload: ServiceRegistrationPayload) -> ConsulResult<WriteMeta> {
+        self.block_on(self.inner.register_service(payload))
+    }
+
+    /// This method deregisters the service with the given ID from the local
+    /// agent.
+    #[cfg(feature = "agent")]
+    pub fn deregister_service(&self, id: &str) -> ConsulResult<WriteMeta> {
+        self.block_on(self.inner.deregister_service(id))
+    }
+}
+
+#[cfg(all(test, feature = "reqwest", feature = "status"))]
+mod tests {
+    use super::Client;
+    use crate::{
+        request::tests::{serve, LEADER},
+        Config,
+    };
+
+    #[test]
+    fn test_get_leader() {
+        // the blocking client cannot run within a runtime, so the server is run
+        // on its own
+        let server_runtime = tokio::runtime::Runtime::new().unwrap();
+        let (address, server) = server_runtime.block_on(serve(&[("200 OK", "", LEADER)]));
+        let client = Client::new(Config::builder().address(address).build().unwrap()).unwrap();
+        assert_eq!(client.get_leader(None).unwrap(), "10.0.0.1:8300");
+        server_runtime.block_on(server).unwrap();
+    }
+}
@@ -0,0 +1,246 @@
+//! Utilities for writing custom watches with [blocking queries].
+//!
+//! The watches of this crate, such as [KV::watch_key], are built on
+//! [loop_fn], which implements the index handling recommended by the Consul
+//! documentation:
+//!
+//! * a response with an unchanged index is a query that timed out without
+//!   changes, and is not yielded;
+//! * an index going backwards, such as after a snapshot restore, resets the
+//!   index so that the next query returns immediately;
+//! * an index of zero is treated as one, so that the next query still blocks
+//!   instead of returning immediately in a busy loop.
+//!
+//! [blocking queries]: https://www.consul.io/api-docs/features/blocking
+//! [KV::watch_key]: crate::KV::watch_key
+
+use std::{cmp, future::Future, time::Duration};
+
+use futures::stream;
+use rand::Rng;
+
+use crate::{watch::WatchStream, ConsulResult, QueryMeta, QueryOptions};
+
+/// The initial delay before retrying a failed blocking query.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+/// The maximum delay before retrying a failed blocking query.
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// The state of a running loop of blocking queries.
+struct LoopState<F> {
+    fetch: F,
+    options: QueryOptions,
+    index: u64,
+    failures: u32,
+}
+
+/// This function returns a [WatchStream] that repeatedly calls `fetch` with
+/// blocking query options, yielding a result every time the returned index
+/// changes.
+///
+/// The options passed to `fetch` are the given options with the
+/// [QueryOptions::wait_index] of the next query, which is [None] for the
+/// first one. Errors are
+/// yielded as they occur, after which the query is retried with a jittered
+/// exponential backoff.
+///
+/// ```no_run
+/// use consul_oxide::{blocking_query, Client, Config, KV};
+/// use futures::StreamExt;
+///
+/// # async fn run() {
+/// let client = Client::new(Config::default());
+/// let mut keys = blocking_query::loop_fn(None, move |options| {
+///     let client = client.clone();
+///     async move { client.list_keys("config/", None, Some(options)).await }
+/// });
+/// while let Some(keys) = keys.next().await {
+///     println!("{:?}", keys);
+/// }
+/// # }
+/// ```
+pub fn loop_fn<T, F, Fut>(options: Option<QueryOptions>, fetch: F) -> WatchStream<ConsulResult<T>>
+where
+    T: Send + 'static,
+    F: FnMut(QueryOptions) -> Fut + Send + 'static,
+    Fut: Future<Output = ConsulResult<(T, QueryMeta)>> + Send,
+{
+    loop_fn_from(0, options, fetch)
+}
+
+/// This function returns the same stream as [loop_fn], with the first query
+/// waiting on the given index instead, which is that of a result that was
+/// already read. An index of zero makes the first query return immediately.
+pub(crate) fn loop_fn_from<T, F, Fut>(
+    index: u64,
+    options: Option<QueryOptions>,
+    fetch: F,
+) -> WatchStream<ConsulResult<T>>
+where
+    T: Send + 'static,
+    F: FnMut(QueryOptions) -> Fut + Send + 'static,
+    Fut: Future<Output = ConsulResult<(T, QueryMeta)>> + Send,
+{
+    let state = LoopState { fetch, options: options.unwrap_or_default(), index, failures: 0 };
+    Box::pin(stream::unfold(state, |mut state| async move {
+        loop {
+            if state.failures > 0 {
+                tokio::time::sleep(retry_delay(state.failures)).await;
+            }
+            let options = QueryOptions {
+                wait_index: if state.index > 0 { Some(state.index) } else { None },
+                ..state.options.clone()
+            };
+            let (value, meta) = match (state.fetch)(options).await {
+                Ok(result) => result,
+                Err(e) => {
+                    state.failures = state.failures.saturating_add(1);
+                    return Some((Err(e), state));
+                }
+            };
+            state.failures = 0;
+            let previous = state.index;
+            // an index of zero is treated as one before comparing it, so that
+            // an agent always returning zero does not look like an index
+            // going backwards on every other query
+            let index = cmp::max(meta.last_index.unwrap_or(0), 1);
+            if !is_changed(previous, index) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(index, "blocking query timed out without changes");
+                continue;
+            }
+            #[cfg(feature = "tracing")]
+            tracing::debug!(previous, index, "blocking query woke up");
+            state.index = next_index(previous, index);
+            return Some((Ok(value), state));
+        }
+    }))
+}
+
+/// This function returns whether the index returned by a blocking query
+/// signals a change, given the index the query waited on. An unchanged index
+/// means the query timed out without changes. The first query, which waits
+/// on no index, always signals a change.
+pub fn is_changed(previous: u64, index: u64) -> bool {
+    previous == 0 || index != previous
+}
+
+/// This function returns the index to wait on in the next blocking query,
+/// given the previous index and the index returned by the agent.
+///
+/// As recommended by the Consul documentation, the index is reset to zero if
+/// it goes backwards, and an index of zero is treated as one to prevent the
+/// next query returning immediately.
+pub fn next_index(previous: u64, index: u64) -> u64 {
+    if index < previous {
+        0
+    } else {
+        cmp::max(index, 1)
+    }
+}
+
+/// This function returns the delay before retrying after the given number of
+/// consecutive failures.
+pub(crate) fn retry_delay(failures: u32) -> Duration {
+    let delay = RETRY_BASE_DELAY.saturating_mul(2u32.saturating_pow(failures - 1));
+    let delay = cmp::min(delay, RETRY_MAX_DELAY);
+    // add up to 50% jitter to avoid many watchers retrying in lockstep
+    let jitter = rand::thread_rng().gen_range(0..=delay.as_millis() as u64 / 2);
+    delay + Duration::from_millis(jitter)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use futures::StreamExt;
+
+    use super::{is_changed, loop_fn, loop_fn_from, next_index, retry_delay, RETRY_MAX_DELAY};
+    use crate::QueryMeta;
+
+    #[test]
+    fn test_next_index() {
+        assert_eq!(next_index(0, 10), 10);
+        assert_eq!(next_index(10, 12), 12);
+        // index went backwards, so reset
+        assert_eq!(next_index(12, 5), 0);
+        // index should never be zero
+        assert_eq!(next_index(0, 0), 1);
+    }
+
+    #[test]
+    fn test_is_changed() {
+        assert!(is_changed(0, 10));
+        assert!(is_changed(10, 12));
+        assert!(is_changed(12, 5));
+        assert!(!is_changed(12, 12));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert!(retry_delay(1) >= super::RETRY_BASE_DELAY);
+        assert!(retry_delay(100) <= RETRY_MAX_DELAY + RETRY_MAX_DELAY / 2);
+    }
+
+    #[tokio::test]
+    async fn test_loop_fn() {
+        // the indexes returned by the agent to each query, in order
+        const INDEXES: [u64; 5] = [5, 5, 7, 3, 0];
+        let waits = Arc::new(Mutex::new(Vec::new()));
+        let stream = loop_fn(None, {
+            let waits = waits.clone();
+            move |options| {
+                let mut waits = waits.lock().unwrap();
+                let index = INDEXES[waits.len()];
+                waits.push(options.wait_index);
+                let meta = QueryMeta { last_index: Some(index), ..Default::default() };
+                async move { Ok((index, meta)) }
+            }
+        });
+        // the unchanged index is skipped
+        let values: Vec<_> = stream.take(4).map(Result::unwrap).collect().await;
+        assert_eq!(values, [5, 7, 3, 0]);
+        // the index is reset after going backwards
+        assert_eq!(*waits.lock().unwrap(), [None, Some(5), Some(5), Some(7), None]);
+    }
+
+    #[tokio::test]
+    async fn test_loop_fn_zero_index() {
+        const INDEXES: [u64; 4] = [0, 0, 0, 2];
+        let waits = Arc::new(Mutex::new(Vec::new()));
+        let stream = loop_fn(None, {
+            let waits = waits.clone();
+            move |options| {
+                let mut waits = waits.lock().unwrap();
+                let index = INDEXES[waits.len()];
+                waits.push(options.wait_index);
+                let meta = QueryMeta { last_index: Some(index), ..Default::default() };
+                async move { Ok((index, meta)) }
+            }
+        });
+        // repeated zero indexes block on one instead of being yielded again
+        let values: Vec<_> = stream.take(2).map(Result::unwrap).collect().await;
+        assert_eq!(values, [0, 2]);
+        assert_eq!(*waits.lock().unwrap(), [None, Some(1), Some(1), Some(1)]);
+    }
+
+    #[tokio::test]
+    async fn test_loop_fn_from() {
+        const INDEXES: [u64; 2] = [5, 7];
+        let waits = Arc::new(Mutex::new(Vec::new()));
+        let stream = loop_fn_from(5, None, {
+            let waits = waits.clone();
+            move |options| {
+                let mut waits = waits.lock().unwrap();
+                let index = INDEXES[waits.len()];
+                waits.push(options.wait_index);
+                let meta = QueryMeta { last_index: Some(index), ..Default::default() };
+                async move { Ok((index, meta)) }
+            }
+        });
+        // the first query blocks on the given index, and times out unchanged
+        let values: Vec<_> = stream.take(1).map(Result::unwrap).collect().await;
+        assert_eq!(values, [7]);
+        assert_eq!(*waits.lock().unwrap(), [Some(5), Some(5)]);
+    }
+}
@@ -0,0 +1,349 @@
+use std::{
+    cmp,
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::future::BoxFuture;
+use tokio::task::JoinHandle;
+
+use crate::{
+    blocking_query, Catalog, CatalogService, Client, ConsulResult, Health, QueryMeta, QueryOptions,
+    ServiceEntry,
+};
+
+/// The minimum wait time of the blocking queries refreshing a cache, which
+/// prevents a short maximum age from turning them into a busy loop.
+const MIN_REFRESH_WAIT: Duration = Duration::from_secs(1);
+
+/// A function fetching the cached resource with the given options.
+type Fetch<T> =
+    Arc<dyn Fn(QueryOptions) -> BoxFuture<'static, ConsulResult<(T, QueryMeta)>> + Send + Sync>;
+
+/// The key of a cached service health lookup: the service, its tags and
+/// whether only passing instances are returned.
+type HealthKey = (String, Vec<String>, bool);
+
+/// The key of a cached catalog lookup: the service and its tag.
+type CatalogKey = (String, Option<String>);
+
+/// The options of a [CachedHealth] or [CachedCatalog].
+#[derive(Clone, Debug)]
+pub struct CacheOptions {
+    /// The time a result is fresh for after it was last confirmed by the
+    /// agent. Results are refreshed by blocking queries waiting for half of
+    /// this time, so they stay fresh while the agent is reachable. Defaults to
+    /// one minute.
+    pub max_age: Duration,
+    /// The time a result is still served for once it is stale, while the
+    /// background refresh keeps retrying. After that, lookups wait for a
+    /// fresh result from the agent. Defaults to five minutes.
+    pub stale_while_revalidate: Duration,
+    /// The options of the queries made by the cache, such as their datacenter
+    /// or consistency mode. Their wait index and time are ignored.
+    pub query_options: QueryOptions,
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        CacheOptions {
+            max_age: Duration::from_secs(60),
+            stale_while_revalidate: Duration::from_secs(300),
+            query_options: QueryOptions::default(),
+        }
+    }
+}
+
+/// A cached result, alongside the time it was last confirmed by the agent.
+#[derive(Debug)]
+struct Cached<T> {
+    value: Arc<T>,
+    refreshed: Instant,
+}
+
+/// A cache entry, kept up to date by a background refresh.
+#[derive(Debug)]
+struct Entry<T> {
+    cached: Arc<Mutex<Cached<T>>>,
+    refresh: JoinHandle<()>,
+}
+
+/// A cache of resources refreshed by blocking queries in the background.
+struct Cache<K, T> {
+    options: CacheOptions,
+    entries: Mutex<HashMap<K, Entry<T>>>,
+}
+
+impl<K, T: fmt::Debug> fmt::Debug for Cache<K, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = self.entries.lock().unwrap().len();
+        f.debug_struct("Cache").field("options", &self.options).field("entries", &entries).finish()
+    }
+}
+
+impl<K: Eq + Hash, T: Send + Sync + 'static> Cache<K, T> {
+    /// This method creates an empty cache with the given options.
+    fn new(options: CacheOptions) -> Self {
+        Cache { options, entries: Mutex::default() }
+    }
+
+    /// This method returns the cached resource with the given key. If it is
+    /// not cached, or too stale to be served, it is fetched with the function
+    /// returned by `fetch`, and refreshed in the background from then on.
+    async fn get(&self, key: K, fetch: impl FnOnce() -> Fetch<T>) -> ConsulResult<Arc<T>> {
+        let cached = self.entries.lock().unwrap().get(&key).map(|entry| entry.cached.clone());
+        if let Some(cached) = &cached {
+            let cached = cached.lock().unwrap();
+            let max_age = self.options.max_age + self.options.stale_while_revalidate;
+            if cached.refreshed.elapsed() <= max_age {
+                return Ok(cached.value.clone());
+            }
+        }
+        let fetch = fetch();
+        let (value, meta) = fetch(self.options.query_options.clone()).await?;
+        let value = Arc::new(value);
+        let fresh = Cached { value: value.clone(), refreshed: Instant::now() };
+        match cached {
+            Some(cached) => *cached.lock().unwrap() = fresh,
+            None => {
+                let cached = Arc::new(Mutex::new(fresh));
+                let index =
+                    blocking_query::next_index(0, cmp::max(meta.last_index.unwrap_or(0), 1));
+                let options = QueryOptions {
+                    wait_time: Some(cmp::max(self.options.max_age / 2, MIN_REFRESH_WAIT)),
+                    ..self.options.query_options.clone()
+                };
+                let refresh = tokio::spawn(refresh(cached.clone(), fetch, options, index));
+                // a concurrent lookup may have inserted the entry first
+                let entry = Entry { cached, refresh };
+                if let Some(previous) = self.entries.lock().unwrap().insert(key, entry) {
+                    previous.refresh.abort();
+                }
+            }
+        }
+        Ok(value)
+    }
+}
+
+impl<K, T> Drop for Cache<K, T> {
+    fn drop(&mut self) {
+        for entry in self.entries.get_mut().unwrap().values() {
+            entry.refresh.abort();
+        }
+    }
+}
+
+/// This function refreshes the given cached resource with blocking queries,
+/// starting from the given index, until it is aborted.
+///
+/// Every response confirms the cached result, even if the query timed out
+/// without changes. Failed queries are retried with a jittered exponential
+/// backoff, while the cached result becomes stale.
+async fn refresh<T>(
+    cached: Arc<Mutex<Cached<T>>>,
+    fetch: Fetch<T>,
+    options: QueryOptions,
+    mut index: u64,
+) {
+    let mut failures = 0;
+    loop {
+        if failures > 0 {
+            tokio::time::sleep(blocking_query::retry_delay(failures)).await;
+        }
+        let options = QueryOptions {
+            wait_index: if index > 0 { Some(index) } else { None },
+            ..options.clone()
+        };
+        match fetch(options).await {
+            Ok((value, meta)) => {
+                failures = 0;
+                let next = cmp::max(meta.last_index.unwrap_or(0), 1);
+                let mut cached = cached.lock().unwrap();
+                cached.refreshed = Instant::now();
+                if blocking_query::is_changed(index, next) {
+                    cached.value = Arc::new(value);
+                }
+                index = blocking_query::next_index(index, next);
+            }
+            Err(e) => {
+                tracing::debug!(error = %e, failures, "failed to refresh cache");
+                failures += 1;
+            }
+        }
+    }
+}
+
+/// A cache of service instances and their health, serving lookups from memory.
+///
+/// The first lookup of a service queries the agent, after which its instances
+/// are refreshed by blocking queries in the background, so later lookups
+/// return immediately. If the agent becomes unreachable, the last known
+/// instances are served until they are older than the
+/// [CacheOptions::stale_while_revalidate] window. Refreshes run on the current
+/// Tokio runtime, and stop when the cache and all its clones are dropped.
+///
+/// ```no_run
+/// # async fn run() -> consul_oxide::ConsulResult<()> {
+/// use consul_oxide::{CacheOptions, CachedHealth, Client, Config};
+///
+/// let client = Client::new(Config::default());
+/// let health = CachedHealth::new(client, CacheOptions::default());
+/// let instances = health.service_instances("web", &[], true).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CachedHealth {
+    client: Client,
+    cache: Arc<Cache<HealthKey, Vec<ServiceEntry>>>,
+}
+
+impl CachedHealth {
+    /// This method creates an empty cache of the services known to the given
+    /// client.
+    pub fn new(client: Client, options: CacheOptions) -> Self {
+        CachedHealth { client, cache: Arc::new(Cache::new(options)) }
+    }
+
+    /// This method returns the instances of the given service, as returned by
+    /// [Health::list_service_instances].
+    #[tracing::instrument]
+    pub async fn service_instances(
+        &self,
+        service: &str,
+        tags: &[&str],
+        passing_only: bool,
+    ) -> ConsulResult<Arc<Vec<ServiceEntry>>> {
+        let service = service.to_owned();
+        let tags: Vec<String> = tags.iter().map(|tag| String::from(*tag)).collect();
+        let key = (service.clone(), tags.clone(), passing_only);
+        let client = self.client.clone();
+        let fetch = move || -> Fetch<Vec<ServiceEntry>> {
+            Arc::new(move |options| {
+                let client = client.clone();
+                let service = service.clone();
+                let tags = tags.clone();
+                Box::pin(async move {
+                    let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+                    client
+                        .list_service_instances(&service, &tags, passing_only, Some(options))
+                        .await
+                })
+            })
+        };
+        self.cache.get(key, fetch).await
+    }
+}
+
+/// A cache of the nodes providing services, serving lookups from memory.
+///
+/// Lookups are cached and refreshed in the same way as by [CachedHealth].
+#[derive(Clone, Debug)]
+pub struct CachedCatalog {
+    client: Client,
+    cache: Arc<Cache<CatalogKey, Vec<CatalogService>>>,
+}
+
+impl CachedCatalog {
+    /// This method creates an empty cache of the services known to the given
+    /// client.
+    pub fn new(client: Client, options: CacheOptions) -> Self {
+        CachedCatalog { client, cache: Arc::new(Cache::new(options)) }
+    }
+
+    /// This method returns the nodes providing the given service, as returned
+    /// by [Catalog::list_nodes_for_service].
+    #[tracing::instrument]
+    pub async fn nodes_for_service(
+        &self,
+        service: &str,
+        tag: Option<&str>,
+    ) -> ConsulResult<Arc<Vec<CatalogService>>> {
+        let service = service.to_owned();
+        let tag = tag.map(String::from);
+        let key = (service.clone(), tag.clone());
+        let client = self.client.clone();
+        let fetch = move || -> Fetch<Vec<CatalogService>> {
+            Arc::new(move |options| {
+                let client = client.clone();
+                let service = service.clone();
+                let tag = tag.clone();
+                Box::pin(async move {
+                    client.list_nodes_for_service(&service, tag.as_deref(), Some(options)).await
+                })
+            })
+        };
+        self.cache.get(key, fetch).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use super::{Cache, CacheOptions, Fetch};
+    use crate::QueryMeta;
+
+    /// This function returns a fetch that counts the queries without a wait
+    /// index in `fetches`, and answers blocking queries once with `blocking`.
+    fn fetch(fetches: Arc<AtomicU64>, blocking: Option<u64>) -> Fetch<u64> {
+        Arc::new(move |options| {
+            let fetches = fetches.clone();
+            Box::pin(async move {
+                match (options.wait_index, blocking) {
+                    (None, _) => {
+                        let count = fetches.fetch_add(1, Ordering::SeqCst) + 1;
+                        Ok((count, QueryMeta { last_index: Some(1), ..Default::default() }))
+                    }
+                    (Some(1), Some(value)) => {
+                        Ok((value, QueryMeta { last_index: Some(2), ..Default::default() }))
+                    }
+                    (Some(_), _) => futures::future::pending().await,
+                }
+            })
+        })
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit() {
+        let cache = Cache::new(CacheOptions::default());
+        let fetches = Arc::new(AtomicU64::default());
+        assert_eq!(*cache.get("a", || fetch(fetches.clone(), None)).await.unwrap(), 1);
+        assert_eq!(*cache.get("a", || fetch(fetches.clone(), None)).await.unwrap(), 1);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(*cache.get("b", || fetch(fetches.clone(), None)).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cache_refresh() {
+        let cache = Cache::new(CacheOptions::default());
+        let fetches = Arc::new(AtomicU64::default());
+        assert_eq!(*cache.get("a", || fetch(fetches.clone(), Some(42))).await.unwrap(), 1);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(*cache.get("a", || fetch(fetches.clone(), Some(42))).await.unwrap(), 42);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cache_expired() {
+        let options = CacheOptions {
+            max_age: Duration::ZERO,
+            stale_while_revalidate: Duration::ZERO,
+            ..Default::default()
+        };
+        let cache = Cache::new(options);
+        let fetches = Arc::new(AtomicU64::default());
+        assert_eq!(*cache.get("a", || fetch(fetches.clone(), None)).await.unwrap(), 1);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(*cache.get("a", || fetch(fetches.clone(), None)).await.unwrap(), 2);
+    }
+}
@@ -3,126 +3,313 @@ use std::collections::HashMap;
 use async_trait::async_trait;
 
 use crate::{
-    agent::AgentService, sealed::Sealed, AgentCheck, Client, ConsulResult, QueryOptions,
-    ServiceWeights,
+    agent::AgentService, sealed::Sealed, CheckStatus, Client, ConsulResult, Node, QueryMeta,
+    QueryOptions, ServiceWeights, TaggedAddress, WriteMeta, WriteOptions,
 };
 
-/// A node within the cluster gossip pool.
-#[derive(Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
-#[serde(default, rename_all = "PascalCase")]
-pub struct Node {
-    /// The ID of the node.
-    #[serde(rename = "ID")]
-    id: String,
-    /// The name of the node.
-    node: String,
-    /// The address of the node.
-    address: String,
-    /// The datacenter of the node.
-    datacenter: String,
-    /// The tags of the node.
-    tagged_addresses: HashMap<String, String>,
-    /// The meta data of the node.
-    meta: HashMap<String, String>,
-    create_index: u64,
-    modify_index: u64,
-}
-
 /// A service defined within the Agent catalog.
-#[derive(Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct CatalogService {
     /// The ID of the service.
     #[serde(rename = "ID")]
-    id: String,
+    pub id: String,
     /// The node the service is associated with.
-    node: String,
+    pub node: String,
     /// The address of the node.
-    address: String,
+    pub address: String,
     /// The datacenter of the node running the service.
-    datacenter: String,
+    pub datacenter: String,
     /// A map of addresses tagged to the node hosting the service.
-    tagged_addresses: HashMap<String, String>,
+    pub tagged_addresses: HashMap<String, String>,
     /// Metadata attached to the node this service is hosted on.
-    node_meta: HashMap<String, String>,
+    pub node_meta: HashMap<String, String>,
     /// The ID of the service.
     #[serde(rename = "ServiceID")]
-    service_id: String,
+    pub service_id: String,
     /// The name of the service.
-    service_name: String,
+    pub service_name: String,
     /// The address of the service.
-    service_address: String,
+    pub service_address: String,
     /// Tags assigned to the service.
-    service_tags: Vec<String>,
+    pub service_tags: Vec<String>,
     /// Metadata assigned to the service.
-    service_meta: HashMap<String, String>,
+    pub service_meta: HashMap<String, String>,
     /// The port of the service.
-    service_port: u32,
-    service_weights: ServiceWeights,
-    service_enable_tag_override: bool,
-    create_index: u64,
-    modify_index: u64,
+    pub service_port: u32,
+    /// The weights of the service in DNS responses.
+    pub service_weights: ServiceWeights,
+    /// Whether the tags of the service can be modified by the catalog.
+    pub service_enable_tag_override: bool,
+    /// The index at which the service was created.
+    pub create_index: u64,
+    /// The index at which the service was last modified.
+    pub modify_index: u64,
 }
 
 /// A response datatype containing a [Node] and its services.
-#[derive(Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct CatalogNode {
     /// The node stored in the catalog.
-    node: Option<Node>,
+    pub node: Option<Node>,
+    /// The services associated with the node.
+    pub services: HashMap<String, AgentService>,
+}
+
+/// A response datatype containing a [Node] and a list of its services, as
+/// returned by [Catalog::list_node_services].
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CatalogNodeServiceList {
+    /// The node stored in the catalog.
+    pub node: Node,
     /// The services associated with the node.
-    services: HashMap<String, AgentService>,
+    pub services: Vec<AgentService>,
 }
 
 /// Datatype containing payload data for the [crate::Catalog::register] method.
 ///
 /// For more information, see the [API documentation](https://www.consul.io/api-docs/catalog#json-request-body-schema).
-#[derive(Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct CatalogRegistrationPayload {
     /// An optional UUID to assign to the node. This must be a 36-character
     /// UUID-formatted string.
-    #[serde(rename = "Node")]
-    id: String,
+    #[serde(rename = "ID", skip_serializing_if = "String::is_empty")]
+    pub id: String,
     /// Specifies the node ID to register.
-    node: String,
+    pub node: String,
     /// Specifies the address to register.
-    address: String,
-    /// Specifies the tagged addresses.
-    tagged_addresses: HashMap<String, String>,
+    pub address: String,
+    /// Specifies the tagged addresses of the node, such as `lan` and `wan`.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub tagged_addresses: HashMap<String, String>,
     /// Specifies arbitrary KV metadata pairs for filtering purposes.
-    node_meta: HashMap<String, String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub node_meta: HashMap<String, String>,
     /// Specifies the datacenter, which defaults to the agent's datacenter if
     /// not provided.
-    datacenter: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub datacenter: String,
     /// Specifies to register a service. If `id` is not provided, it will be
     /// defaulted to the value of the Service.Service property. Only one service
     /// with a given ID may be present per node.
-    service: Option<AgentService>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service: Option<CatalogServiceRegistration>,
     /// Specifies to register a check.
-    check: Option<AgentCheck>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check: Option<CatalogCheckRegistration>,
+    /// Specifies to register several checks, alongside `check` if it is also
+    /// set.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<CatalogCheckRegistration>,
     /// Specifies whether to skip updating the node's information in the
-    /// registration.
-    skip_node_update: bool,
+    /// registration, such as its address and metadata. This is useful when
+    /// registering a service or check on an existing node.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub skip_node_update: bool,
+}
+
+impl CatalogRegistrationPayload {
+    /// This method returns a builder for a registration of the node with the
+    /// given name and address. Every other field is optional.
+    ///
+    /// ```
+    /// use consul_oxide::{CatalogRegistrationPayload, CatalogServiceRegistration};
+    ///
+    /// let service = CatalogServiceRegistration {
+    ///     service: String::from("search"),
+    ///     port: Some(80),
+    ///     ..Default::default()
+    /// };
+    /// let payload =
+    ///     CatalogRegistrationPayload::builder("google", "www.google.com").service(service).build();
+    /// assert_eq!(payload.node, "google");
+    /// ```
+    pub fn builder(
+        node: impl Into<String>,
+        address: impl Into<String>,
+    ) -> CatalogRegistrationPayloadBuilder {
+        CatalogRegistrationPayloadBuilder {
+            payload: CatalogRegistrationPayload {
+                node: node.into(),
+                address: address.into(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// A builder for a [CatalogRegistrationPayload], created with
+/// [CatalogRegistrationPayload::builder].
+#[derive(Clone, Debug)]
+pub struct CatalogRegistrationPayloadBuilder {
+    payload: CatalogRegistrationPayload,
+}
+
+impl CatalogRegistrationPayloadBuilder {
+    /// This method sets the UUID of the node, which must be a 36-character
+    /// UUID-formatted string.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.payload.id = id.into();
+        self
+    }
+
+    /// This method adds a tagged address to the node, such as `lan` or `wan`.
+    pub fn tagged_address(mut self, tag: impl Into<String>, address: impl Into<String>) -> Self {
+        self.payload.tagged_addresses.insert(tag.into(), address.into());
+        self
+    }
+
+    /// This method adds a metadata pair to the node.
+    pub fn node_meta(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.payload.node_meta.insert(key.into(), value.into());
+        self
+    }
+
+    /// This method sets the datacenter, which defaults to the agent's
+    /// datacenter.
+    pub fn datacenter(mut self, datacenter: impl Into<String>) -> Self {
+        self.payload.datacenter = datacenter.into();
+        self
+    }
+
+    /// This method sets the service to register on the node.
+    pub fn service(mut self, service: CatalogServiceRegistration) -> Self {
+        self.payload.service = Some(service);
+        self
+    }
+
+    /// This method adds a check to register on the node.
+    pub fn check(mut self, check: CatalogCheckRegistration) -> Self {
+        self.payload.checks.push(check);
+        self
+    }
+
+    /// This method sets whether to skip updating the node's information, such
+    /// as its address and metadata.
+    pub fn skip_node_update(mut self, skip_node_update: bool) -> Self {
+        self.payload.skip_node_update = skip_node_update;
+        self
+    }
+
+    /// This method returns the built payload.
+    pub fn build(self) -> CatalogRegistrationPayload {
+        self.payload
+    }
+}
+
+/// A service registered with the [crate::Catalog::register] method.
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CatalogServiceRegistration {
+    /// Specifies the ID of the service, which defaults to its name.
+    #[serde(rename = "ID", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Specifies the name of the service.
+    pub service: String,
+    /// Specifies the tags of the service.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Specifies the address of the service, which defaults to the address of
+    /// the node.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Specifies the tagged addresses of the service, such as `lan` and
+    /// `wan`, each with its own port.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub tagged_addresses: HashMap<String, TaggedAddress>,
+    /// Specifies arbitrary KV metadata pairs of the service.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub meta: HashMap<String, String>,
+    /// Specifies the port of the service.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Specifies the weights of the service in DNS responses. The passing
+    /// weight must be at least one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weights: Option<ServiceWeights>,
+    /// Specifies to disable the anti-entropy feature for the service's tags.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub enable_tag_override: bool,
+}
+
+/// A check registered with the [crate::Catalog::register] method.
+///
+/// Checks registered through the catalog are not run by any agent, so their
+/// status must be updated by further registrations, unless they are run by an
+/// external health checker such as [consul-esm].
+///
+/// [consul-esm]: https://github.com/hashicorp/consul-esm
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CatalogCheckRegistration {
+    /// Specifies the node of the check, which defaults to the registered node.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// Specifies the ID of the check, which defaults to its name.
+    #[serde(rename = "CheckID", skip_serializing_if = "Option::is_none")]
+    pub check_id: Option<String>,
+    /// Specifies the name of the check.
+    pub name: String,
+    /// Specifies the status of the check.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<CheckStatus>,
+    /// Specifies arbitrary information for humans.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Specifies the ID of the service the check is associated with.
+    #[serde(rename = "ServiceID", skip_serializing_if = "Option::is_none")]
+    pub service_id: Option<String>,
+    /// Specifies how the check is run by an external health checker.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub definition: Option<CatalogCheckDefinition>,
+}
+
+/// The definition of a [CatalogCheckRegistration], used by external health
+/// checkers. Durations are specified with a suffix, such as `10s`.
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CatalogCheckDefinition {
+    /// Specifies the URL of an HTTP check.
+    #[serde(rename = "HTTP", skip_serializing_if = "Option::is_none")]
+    pub http: Option<String>,
+    /// Specifies the address of a TCP check, such as `10.0.0.1:8080`.
+    #[serde(rename = "TCP", skip_serializing_if = "Option::is_none")]
+    pub tcp: Option<String>,
+    /// Specifies the interval between runs of the check.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<String>,
+    /// Specifies the timeout of a run of the check.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+    /// Specifies to skip verifying the certificate of an HTTPS check.
+    #[serde(rename = "TLSSkipVerify", skip_serializing_if = "std::ops::Not::not")]
+    pub tls_skip_verify: bool,
+    /// Specifies to deregister the service of the check after it has been
+    /// critical for this long.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deregister_critical_service_after: Option<String>,
 }
 
 /// Request payload datatype for the [crate::Catalog::deregister] method.
-#[derive(Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct CatalogDeregistrationPayload {
     /// Specifies the node ID to deregister.
-    node: String,
+    pub node: String,
     /// The address of the node.
-    address: String,
+    pub address: String,
     /// Specifies the datacenter, which defaults to the agent's datacenter if
     /// not provided.
-    datacenter: String,
+    pub datacenter: String,
     /// Specifies the service ID to deregister.
     #[serde(rename = "ServiceID")]
-    service_id: String,
+    pub service_id: String,
     /// Specifies the check ID to deregister.
     #[serde(rename = "CheckID")]
-    check_id: String,
+    pub check_id: String,
 }
 
 /// This trait provides methods for interacting with the Agent catalogue.
@@ -137,8 +324,8 @@ pub trait Catalog: Sealed {
     async fn register(
         &self,
         reg: CatalogRegistrationPayload,
-        q: Option<QueryOptions>,
-    ) -> ConsulResult<()>;
+        q: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>;
 
     /// This method is a low-level mechanism for directly removing entries from
     /// the Catalog. It is usually preferable to instead use methods defined
@@ -149,8 +336,8 @@ pub trait Catalog: Sealed {
     async fn deregister(
         &self,
         payload: CatalogDeregistrationPayload,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<()>;
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>;
 
     /// This method returns the list of all known datacenters. The datacenters
     /// will be sorted in ascending order based on the estimated median round
@@ -160,12 +347,13 @@ pub trait Catalog: Sealed {
     async fn list_datacenters(&self) -> ConsulResult<Vec<String>>;
 
     /// This endpoint and returns the nodes registered in a given datacenter.
+    /// The nodes can be sorted using [QueryOptions::near].
     ///
     /// For more information, see the [API documentation](https://www.consul.io/api/catalog.html#list-nodes).
     async fn list_datacenter_nodes(
         &self,
         q: Option<QueryOptions>,
-    ) -> ConsulResult<HashMap<String, Vec<Node>>>;
+    ) -> ConsulResult<(Vec<Node>, QueryMeta)>;
 
     /// This endpoint returns the services registered in a given datacenter.
     ///
@@ -173,7 +361,39 @@ pub trait Catalog: Sealed {
     async fn list_datacenter_services(
         &self,
         q: Option<QueryOptions>,
-    ) -> ConsulResult<HashMap<String, Vec<String>>>;
+    ) -> ConsulResult<(HashMap<String, Vec<String>>, QueryMeta)>;
+
+    /// This method returns the nodes providing the given service, optionally
+    /// only those with the given tag. The results can be further filtered
+    /// using [QueryOptions::filter], and sorted using [QueryOptions::near].
+    ///
+    /// For more information, see the [API documentation](https://www.consul.io/api-docs/catalog#list-nodes-for-service).
+    async fn list_nodes_for_service(
+        &self,
+        service: &str,
+        tag: Option<&str>,
+        q: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<CatalogService>, QueryMeta)>;
+
+    /// This method returns the node with the given name and a map of its
+    /// services, or [None] if the node does not exist.
+    ///
+    /// For more information, see the [API documentation](https://www.consul.io/api-docs/catalog#retrieve-map-of-services-for-a-node).
+    async fn get_node(
+        &self,
+        node: &str,
+        q: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<CatalogNode>, QueryMeta)>;
+
+    /// This method returns the node with the given name and a list of its
+    /// services, or [None] if the node does not exist.
+    ///
+    /// For more information, see the [API documentation](https://www.consul.io/api-docs/catalog#list-services-for-node).
+    async fn list_node_services(
+        &self,
+        node: &str,
+        q: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<CatalogNodeServiceList>, QueryMeta)>;
 }
 
 #[async_trait]
@@ -182,18 +402,22 @@ impl Catalog for Client {
     async fn register(
         &self,
         payload: CatalogRegistrationPayload,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<()> {
-        self.put("/v1/session/create", payload, None, options).await
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta> {
+        // the endpoint responds with `true`, which carries no information
+        let (_, meta): (bool, _) = self.put("/v1/catalog/register", payload, None, options).await?;
+        Ok(meta)
     }
 
     #[tracing::instrument]
     async fn deregister(
         &self,
         payload: CatalogDeregistrationPayload,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<()> {
-        self.put("/v1/catalog/deregister", payload, None, options).await
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta> {
+        let (_, meta): (bool, _) =
+            self.put("/v1/catalog/deregister", payload, None, options).await?;
+        Ok(meta)
     }
 
     #[tracing::instrument]
@@ -205,22 +429,157 @@ impl Catalog for Client {
     async fn list_datacenter_nodes(
         &self,
         q: Option<QueryOptions>,
-    ) -> ConsulResult<HashMap<String, Vec<Node>>> {
-        self.get("/v1/catalog/nodes", q).await
+    ) -> ConsulResult<(Vec<Node>, QueryMeta)> {
+        self.get_with_meta("/v1/catalog/nodes", None, q)
+            .await
+            .map(|(r, meta): (Option<Vec<Node>>, _)| (r.unwrap_or_default(), meta))
     }
 
     #[tracing::instrument]
     async fn list_datacenter_services(
         &self,
         options: Option<QueryOptions>,
-    ) -> ConsulResult<HashMap<String, Vec<String>>> {
-        self.get("/v1/catalog/services", options).await
+    ) -> ConsulResult<(HashMap<String, Vec<String>>, QueryMeta)> {
+        self.get_with_meta("/v1/catalog/services", None, options).await.map(
+            |(r, meta): (Option<HashMap<String, Vec<String>>>, _)| (r.unwrap_or_default(), meta),
+        )
+    }
+
+    #[tracing::instrument]
+    async fn list_nodes_for_service(
+        &self,
+        service: &str,
+        tag: Option<&str>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<CatalogService>, QueryMeta)> {
+        let mut params = HashMap::new();
+        if let Some(tag) = tag {
+            params.insert(String::from("tag"), tag.to_owned());
+        }
+        let path = format!("/v1/catalog/service/{}", service);
+        self.get_with_meta(&path, Some(params), options)
+            .await
+            .map(|(r, meta): (Option<Vec<CatalogService>>, _)| (r.unwrap_or_default(), meta))
+    }
+
+    #[tracing::instrument]
+    async fn get_node(
+        &self,
+        node: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<CatalogNode>, QueryMeta)> {
+        let path = format!("/v1/catalog/node/{}", node);
+        // consul returns null if the node does not exist
+        self.get_with_meta(&path, None, options)
+            .await
+            .map(|(r, meta): (Option<Option<CatalogNode>>, _)| (r.flatten(), meta))
+    }
+
+    #[tracing::instrument]
+    async fn list_node_services(
+        &self,
+        node: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<CatalogNodeServiceList>, QueryMeta)> {
+        let path = format!("/v1/catalog/node-services/{}", node);
+        self.get_with_meta(&path, None, options)
+            .await
+            .map(|(r, meta): (Option<Option<CatalogNodeServiceList>>, _)| (r.flatten(), meta))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Catalog, Client, Config};
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use crate::{
+        Catalog, CatalogCheckDefinition, CatalogCheckRegistration, CatalogRegistrationPayload,
+        CatalogService, CatalogServiceRegistration, Client, Config, ServiceWeights, TaggedAddress,
+    };
+
+    #[test]
+    fn test_registration_builder() {
+        let check = CatalogCheckRegistration { name: String::from("ping"), ..Default::default() };
+        let payload = CatalogRegistrationPayload::builder("external", "10.0.0.1")
+            .node_meta("env", "prod")
+            .check(check.clone())
+            .skip_node_update(true)
+            .build();
+        assert_eq!(payload.node, "external");
+        assert_eq!(payload.address, "10.0.0.1");
+        assert_eq!(payload.node_meta["env"], "prod");
+        assert_eq!(payload.checks, [check]);
+        assert!(payload.skip_node_update);
+        assert!(payload.service.is_none());
+    }
+
+    #[test]
+    fn test_catalog_service() {
+        let json = json!({
+            "ID": "40e4a748-2192-161a-0510-9bf59fe950b5",
+            "Node": "foobar",
+            "Address": "192.168.10.10",
+            "ServiceID": "redis1",
+            "ServiceName": "redis",
+            "ServiceTags": ["primary"],
+            "ServicePort": 8000,
+            "ServiceWeights": {"Passing": 10, "Warning": 1},
+        });
+        let service: CatalogService = serde_json::from_value(json).unwrap();
+        assert_eq!(service.node, "foobar");
+        assert_eq!(service.service_id, "redis1");
+        assert_eq!(service.service_port, 8000);
+        assert_eq!(service.service_weights, ServiceWeights { passing: 10, warning: 1 });
+        let reencoded = serde_json::to_value(&service).unwrap();
+        assert_eq!(serde_json::from_value::<CatalogService>(reencoded).unwrap(), service);
+    }
+
+    #[test]
+    fn test_registration_payload() {
+        let wan = TaggedAddress { address: String::from("198.51.100.1"), port: 80 };
+        let payload = CatalogRegistrationPayload {
+            node: String::from("external"),
+            address: String::from("10.0.0.1"),
+            service: Some(CatalogServiceRegistration {
+                service: String::from("web"),
+                port: Some(8080),
+                tagged_addresses: HashMap::from([(String::from("wan"), wan)]),
+                weights: Some(ServiceWeights { passing: 10, warning: 1 }),
+                ..Default::default()
+            }),
+            checks: vec![CatalogCheckRegistration {
+                name: String::from("web-http"),
+                definition: Some(CatalogCheckDefinition {
+                    http: Some(String::from("http://10.0.0.1:8080/health")),
+                    interval: Some(String::from("10s")),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            skip_node_update: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            json!({
+                "Node": "external",
+                "Address": "10.0.0.1",
+                "Service": {
+                    "Service": "web",
+                    "TaggedAddresses": {"wan": {"Address": "198.51.100.1", "Port": 80}},
+                    "Port": 8080,
+                    "Weights": {"Passing": 10, "Warning": 1},
+                },
+                "Checks": [{
+                    "Name": "web-http",
+                    "Definition": {"HTTP": "http://10.0.0.1:8080/health", "Interval": "10s"},
+                }],
+                "SkipNodeUpdate": true,
+            })
+        );
+    }
 
     #[tokio::test]
     async fn test_list_datacenters() {
@@ -234,11 +593,19 @@ mod tests {
     async fn test_list_datacenter_services() {
         let config = Config::default();
         let client = Client::new(config);
-        let r = client.list_datacenter_services(None).await.unwrap();
+        let (r, _) = client.list_datacenter_services(None).await.unwrap();
         assert_ne!(r.len(), 0);
         match r.get("consul") {
             None => panic!("Should have a Consul service"),
             Some(val) => assert_eq!(val.len(), 0), // consul has no tags
         }
     }
+
+    #[tokio::test]
+    async fn test_list_nodes_for_service() {
+        let client = Client::new(Config::default());
+        let (r, meta) = client.list_nodes_for_service("consul", None, None).await.unwrap();
+        assert_eq!(r.len(), 1);
+        assert!(meta.last_index.is_some());
+    }
 }
@@ -1,16 +1,245 @@
-/// A service's weights, comonly used in response payloads from Consul.
-#[derive(Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
-#[serde(default)]
+use std::{collections::HashMap, fmt};
+
+/// A node within the cluster gossip pool. Returned by both the catalog and
+/// health endpoints.
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct Node {
+    /// The ID of the node.
+    #[serde(rename = "ID")]
+    pub id: String,
+    /// The name of the node.
+    pub node: String,
+    /// The address of the node.
+    pub address: String,
+    /// The datacenter of the node.
+    pub datacenter: String,
+    /// The tags of the node.
+    pub tagged_addresses: HashMap<String, String>,
+    /// The meta data of the node.
+    pub meta: HashMap<String, String>,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+/// A service's weights, commonly used in response payloads from Consul.
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
 pub struct ServiceWeights {
-    passing: u32,
-    warning: u32,
+    /// The weight of the service in DNS responses while its checks pass.
+    pub passing: u32,
+    /// The weight of the service in DNS responses while its checks warn.
+    pub warning: u32,
 }
 
 /// A tagged address, commonly used in response payloads from Consul.
-#[derive(Deserialize, Debug)]
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
 pub struct TaggedAddress {
     /// The tagged address.
     pub address: String,
     /// The port included with this address.
     pub port: u16,
 }
+
+/// The mesh gateway configuration of a service or proxy.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct MeshGatewayConfig {
+    /// The mesh gateway mode, one of `none`, `local` or `remote`.
+    pub mode: String,
+}
+
+/// An HTTP path to expose through a proxy without requiring mutual TLS.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ExposePath {
+    /// The HTTP path to expose.
+    pub path: String,
+    /// The port where the local service is listening.
+    pub local_path_port: u16,
+    /// The port where the proxy will listen for connections.
+    pub listener_port: u16,
+    /// The protocol of the listener, either `http` or `http2`.
+    pub protocol: String,
+}
+
+/// The expose configuration of a service or proxy.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ExposeConfig {
+    /// Whether to expose the HTTP and gRPC checks of the service through the
+    /// proxy.
+    pub checks: bool,
+    /// The paths to expose through the proxy.
+    pub paths: Vec<ExposePath>,
+}
+
+/// The transparent proxy configuration of a service or proxy.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct TransparentProxyConfig {
+    /// The port the proxy listens on for outbound traffic.
+    pub outbound_listener_port: u16,
+    /// Whether the proxy should only dial upstreams through their virtual IP.
+    pub dialed_directly: bool,
+}
+
+/// The ID of the check registered while a node is in maintenance mode.
+const NODE_MAINTENANCE_CHECK_ID: &str = "_node_maintenance";
+/// The prefix of the IDs of the checks registered while a service is in
+/// maintenance mode, which are followed by the ID of the service.
+const SERVICE_MAINTENANCE_CHECK_ID_PREFIX: &str = "_service_maintenance:";
+
+/// The status of a health check, or the aggregated status of a set of checks.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Passing,
+    Warning,
+    /// The initial status of a check, unless registered with another one.
+    #[default]
+    Critical,
+    /// The node or service is in maintenance mode. Consul reports this with a
+    /// critical check, so this status is only returned by aggregations, and
+    /// cannot be set on a check.
+    Maintenance,
+}
+
+impl CheckStatus {
+    /// This method returns the status as sent to and returned by Consul, such
+    /// as `passing`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckStatus::Passing => "passing",
+            CheckStatus::Warning => "warning",
+            CheckStatus::Critical => "critical",
+            CheckStatus::Maintenance => "maintenance",
+        }
+    }
+
+    /// This method returns whether the status is passing or warning. As in
+    /// DNS queries, instances with warning checks are still healthy enough to
+    /// receive traffic.
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, CheckStatus::Passing | CheckStatus::Warning)
+    }
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How a [HealthCheck] is run by the agent. Only the fields of the check's
+/// own type are set. Durations are formatted with a suffix, such as `10s`.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct HealthCheckDefinition {
+    /// The URL of an HTTP check.
+    #[serde(rename = "HTTP")]
+    pub http: String,
+    /// The headers sent by an HTTP check.
+    pub header: HashMap<String, Vec<String>>,
+    /// The method used by an HTTP check.
+    pub method: String,
+    /// The body sent by an HTTP check.
+    pub body: String,
+    /// The SNI sent by an HTTPS check.
+    #[serde(rename = "TLSServerName")]
+    pub tls_server_name: String,
+    /// Whether an HTTPS check skips verifying the certificate.
+    #[serde(rename = "TLSSkipVerify")]
+    pub tls_skip_verify: bool,
+    /// The address of a TCP check.
+    #[serde(rename = "TCP")]
+    pub tcp: String,
+    /// The address of a UDP check.
+    #[serde(rename = "UDP")]
+    pub udp: String,
+    /// The address and service of a gRPC check.
+    #[serde(rename = "GRPC")]
+    pub grpc: String,
+    /// Whether a gRPC check uses TLS.
+    #[serde(rename = "GRPCUseTLS")]
+    pub grpc_use_tls: bool,
+    /// The interval between runs of the check.
+    pub interval: String,
+    /// The timeout of a run of the check.
+    pub timeout: String,
+    /// How long the check can be critical before its service is
+    /// deregistered.
+    pub deregister_critical_service_after: String,
+}
+
+/// A health check, as returned by the agent, health and catalog endpoints.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct HealthCheck {
+    /// The name of the node the check is registered on.
+    pub node: String,
+    /// The ID of the check, which is unique on its node.
+    #[serde(rename = "CheckID")]
+    pub check_id: String,
+    /// The name of the check.
+    pub name: String,
+    /// The current status of the check.
+    pub status: CheckStatus,
+    /// Notes attached to the check.
+    pub notes: String,
+    /// The output of the last run of the check.
+    pub output: String,
+    /// The ID of the service the check is associated with, or empty for a
+    /// node check.
+    #[serde(rename = "ServiceID")]
+    pub service_id: String,
+    /// The name of the service the check is associated with.
+    pub service_name: String,
+    /// The tags of the service the check is associated with.
+    pub service_tags: Vec<String>,
+    /// The type of the check, such as `http`, `tcp` or `ttl`. Empty for
+    /// checks registered through the catalog.
+    #[serde(rename = "Type")]
+    pub check_type: String,
+    /// The interval the check is run at, such as `10s`. Only returned by the
+    /// agent endpoints.
+    pub interval: String,
+    /// The timeout of the check, such as `5s`. Only returned by the agent
+    /// endpoints.
+    pub timeout: String,
+    /// The port the check is exposed on through a Connect proxy, or zero.
+    pub exposed_port: u16,
+    /// How the check is run.
+    pub definition: HealthCheckDefinition,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+impl HealthCheck {
+    /// This method returns whether the check was registered by Consul to put
+    /// its node or service in maintenance mode.
+    pub fn is_maintenance(&self) -> bool {
+        self.check_id == NODE_MAINTENANCE_CHECK_ID
+            || self.check_id.starts_with(SERVICE_MAINTENANCE_CHECK_ID_PREFIX)
+    }
+}
+
+/// This function returns the aggregated status of the given checks, which is
+/// computed as by Consul itself: any maintenance check puts the set in
+/// maintenance, and otherwise the worst status of the checks wins. An empty
+/// set of checks is passing.
+pub fn aggregated_status<'a>(checks: impl IntoIterator<Item = &'a HealthCheck>) -> CheckStatus {
+    let mut status = CheckStatus::Passing;
+    for check in checks {
+        if check.is_maintenance() {
+            return CheckStatus::Maintenance;
+        }
+        status = match (status, check.status) {
+            (CheckStatus::Critical, _) | (_, CheckStatus::Critical) => CheckStatus::Critical,
+            (CheckStatus::Warning, _) | (_, CheckStatus::Warning) => CheckStatus::Warning,
+            _ => CheckStatus::Passing,
+        };
+    }
+    status
+}
@@ -0,0 +1,459 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+#[cfg(feature = "reqwest")]
+use reqwest::{Client as HttpClient, ClientBuilder};
+use thiserror::Error;
+use url::Url;
+
+#[cfg(feature = "reqwest")]
+use crate::TlsConfig;
+use crate::{
+    pool::DEFAULT_COOLDOWN, AddressPool, Config, HttpTransport, MetricsSink, Middleware,
+    RetryPolicy, RotationStrategy, TokenFile,
+};
+
+/// Enumeration of errors caused by an invalid [Config].
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// The address of the agent was not a valid URL.
+    #[error("invalid address {0}")]
+    InvalidAddress(String),
+    /// The TLS configuration was invalid.
+    #[error("invalid tls configuration, {0}")]
+    InvalidTls(String),
+    /// The HTTP client could not be built.
+    #[cfg(feature = "reqwest")]
+    #[error("failed to build http client")]
+    HttpClient(#[from] reqwest::Error),
+    /// No transport was set, which is required without the `reqwest` feature.
+    #[error("missing transport")]
+    MissingTransport,
+    /// The token file could not be read.
+    #[error("failed to read token file")]
+    TokenFile(#[source] std::io::Error),
+}
+
+/// A builder for [Config], created by [Config::builder].
+///
+/// Unlike the constructors of [Config], the builder validates its settings and
+/// returns an error instead of panicking.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use consul_oxide::{Client, Config};
+///
+/// let config = Config::builder()
+///     .address("http://127.0.0.1:8500/")
+///     .datacenter("dc1")
+///     .wait_time(Duration::from_secs(60))
+///     .build()
+///     .unwrap();
+/// assert_eq!(config.address, "http://127.0.0.1:8500");
+/// let client = Client::new(config);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ConfigBuilder {
+    address: Option<String>,
+    addresses: Vec<String>,
+    rotation_strategy: RotationStrategy,
+    cooldown: Option<Duration>,
+    datacenter: Option<String>,
+    #[cfg(feature = "enterprise")]
+    namespace: Option<String>,
+    #[cfg(feature = "enterprise")]
+    partition: Option<String>,
+    token: Option<String>,
+    token_file: Option<PathBuf>,
+    wait_time: Option<Duration>,
+    #[cfg(feature = "reqwest")]
+    timeout: Option<Duration>,
+    #[cfg(feature = "reqwest")]
+    connect_timeout: Option<Duration>,
+    #[cfg(feature = "reqwest")]
+    pool_idle_timeout: Option<Option<Duration>>,
+    #[cfg(feature = "reqwest")]
+    pool_max_idle_per_host: Option<usize>,
+    #[cfg(feature = "reqwest")]
+    tcp_keepalive: Option<Duration>,
+    #[cfg(feature = "reqwest")]
+    http2_prior_knowledge: bool,
+    #[cfg(feature = "compression")]
+    compression: Option<bool>,
+    #[cfg(feature = "reqwest")]
+    tls: Option<TlsConfig>,
+    retry_policy: Option<RetryPolicy>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    metrics_sink: Option<Arc<dyn MetricsSink>>,
+    transport: Option<Arc<dyn HttpTransport>>,
+}
+
+impl Config {
+    /// This method returns a builder for a configuration.
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+}
+
+impl ConfigBuilder {
+    /// This method sets the address of the agent, which defaults to
+    /// `http://127.0.0.1:8500`. If the address has no scheme, HTTP is used.
+    /// On Unix platforms, this can also be the path of a Unix domain socket,
+    /// such as `unix:///var/run/consul.sock`.
+    pub fn address(mut self, address: impl Into<String>) -> Self {
+        self.address = Some(address.into());
+        self
+    }
+
+    /// This method sets the addresses of several agents, such as all servers
+    /// of a cluster without a load balancer, overriding the address set with
+    /// [ConfigBuilder::address]. Requests are sent to one address until it
+    /// fails to connect, then rotated to the next one, see [AddressPool].
+    /// Unix domain sockets are not supported.
+    pub fn addresses<Address: Into<String>>(
+        mut self,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> Self {
+        self.addresses = addresses.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// This method sets the strategy used to choose the next address when one
+    /// of the addresses set with [ConfigBuilder::addresses] fails. Defaults to
+    /// [RotationStrategy::RoundRobin].
+    pub fn rotation_strategy(mut self, rotation_strategy: RotationStrategy) -> Self {
+        self.rotation_strategy = rotation_strategy;
+        self
+    }
+
+    /// This method sets the time an address that failed to connect is skipped
+    /// for. Defaults to 30 seconds.
+    pub fn dead_address_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = Some(cooldown);
+        self
+    }
+
+    /// This method sets the default datacenter of requests.
+    pub fn datacenter(mut self, datacenter: impl Into<String>) -> Self {
+        self.datacenter = Some(datacenter.into());
+        self
+    }
+
+    /// This method sets the default namespace of requests.
+    #[cfg(feature = "enterprise")]
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// This method sets the default admin partition of requests.
+    #[cfg(feature = "enterprise")]
+    pub fn partition(mut self, partition: impl Into<String>) -> Self {
+        self.partition = Some(partition.into());
+        self
+    }
+
+    /// This method sets the ACL token of requests.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// This method sets the file the ACL token of requests is read from, if
+    /// no token is set with [ConfigBuilder::token]. The file is read again
+    /// whenever it is modified, see [TokenFile].
+    pub fn token_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.token_file = Some(path.into());
+        self
+    }
+
+    /// This method sets the default maximum time to wait for blocking
+    /// queries.
+    pub fn wait_time(mut self, wait_time: Duration) -> Self {
+        self.wait_time = Some(wait_time);
+        self
+    }
+
+    /// This method sets the timeout of requests, from connecting to the agent
+    /// until the response has been read. Blocking queries are not subject to
+    /// it, since they can take up to their wait time to complete, and instead
+    /// time out a little after their wait time.
+    #[cfg(feature = "reqwest")]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// This method sets the timeout for connecting to the agent.
+    #[cfg(feature = "reqwest")]
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// This method sets the time idle connections to the agent are kept open
+    /// for, or keeps them open indefinitely if [None]. Defaults to 90 seconds.
+    #[cfg(feature = "reqwest")]
+    pub fn pool_idle_timeout(mut self, pool_idle_timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = Some(pool_idle_timeout);
+        self
+    }
+
+    /// This method sets the maximum number of idle connections kept open to
+    /// each address. By default, it is unlimited.
+    #[cfg(feature = "reqwest")]
+    pub fn pool_max_idle_per_host(mut self, max_idle: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max_idle);
+        self
+    }
+
+    /// This method sets the interval of TCP keepalive probes sent on
+    /// connections to the agent. By default, keepalive is disabled.
+    #[cfg(feature = "reqwest")]
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// This method makes requests use HTTP/2 without negotiating it first,
+    /// which requires an agent serving HTTP/2 over cleartext or TLS.
+    #[cfg(feature = "reqwest")]
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// This method sets whether responses may be compressed. When enabled,
+    /// requests are sent with `Accept-Encoding: gzip, deflate` and compressed
+    /// responses are decompressed transparently. Defaults to enabled.
+    #[cfg(feature = "compression")]
+    pub fn compression(mut self, compression: bool) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// This method sets the TLS configuration used to connect to the agent,
+    /// which switches the address to HTTPS.
+    #[cfg(feature = "reqwest")]
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// This method sets the policy used to retry requests that failed due to
+    /// transient errors. By default, requests are not retried.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// This method registers middleware called around every request. It is
+    /// called after any previously registered middleware.
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// This method sets the sink receiving an observation of every request.
+    pub fn metrics_sink(mut self, metrics_sink: impl MetricsSink + 'static) -> Self {
+        self.metrics_sink = Some(Arc::new(metrics_sink));
+        self
+    }
+
+    /// This method sets the transport sending requests, replacing the `reqwest`
+    /// client built from the other settings. Timeouts and TLS settings are then
+    /// up to the transport.
+    pub fn transport(mut self, transport: impl HttpTransport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// This method validates the settings and builds the configuration.
+    pub fn build(self) -> Result<Config, ConfigError> {
+        let mut addresses = Vec::with_capacity(self.addresses.len());
+        for address in &self.addresses {
+            let address = normalize_address(address)?;
+            if address.starts_with("unix://") {
+                return Err(ConfigError::InvalidAddress(address));
+            }
+            // addresses are compared to request urls when redirecting them
+            let url = Url::parse(&address).map_err(|_| ConfigError::InvalidAddress(address))?;
+            addresses.push(url.as_str().trim_end_matches('/').to_owned());
+        }
+        let address = match addresses.first() {
+            Some(first) => first.clone(),
+            None => normalize_address(self.address.as_deref().unwrap_or("http://127.0.0.1:8500"))?,
+        };
+        #[cfg(feature = "reqwest")]
+        let (http_client, address) = self.http_client(address, &mut addresses)?;
+        // unix domain sockets are only supported by the reqwest client
+        #[cfg(not(feature = "reqwest"))]
+        if address.starts_with("unix://") {
+            return Err(ConfigError::InvalidAddress(address));
+        }
+        #[cfg(not(feature = "reqwest"))]
+        if self.transport.is_none() {
+            return Err(ConfigError::MissingTransport);
+        }
+        let address_pool = (!addresses.is_empty()).then(|| {
+            let cooldown = self.cooldown.unwrap_or(DEFAULT_COOLDOWN);
+            AddressPool::new(addresses, self.rotation_strategy, cooldown)
+        });
+        Ok(Config {
+            address,
+            datacenter: self.datacenter,
+            #[cfg(feature = "enterprise")]
+            namespace: self.namespace,
+            #[cfg(feature = "enterprise")]
+            partition: self.partition,
+            #[cfg(feature = "reqwest")]
+            http_client,
+            token: self.token,
+            token_file: self
+                .token_file
+                .map(TokenFile::new)
+                .transpose()
+                .map_err(ConfigError::TokenFile)?,
+            shared_token: None,
+            wait_time: self.wait_time,
+            retry_policy: self.retry_policy,
+            middleware: self.middleware,
+            metrics_sink: self.metrics_sink,
+            address_pool,
+            transport: self.transport,
+        })
+    }
+
+    /// This method builds the `reqwest` client of the configuration, returning
+    /// it alongside the address requests are sent to, which is changed to
+    /// HTTPS by the TLS configuration and to the Host header of requests sent
+    /// over a Unix domain socket.
+    #[cfg(feature = "reqwest")]
+    fn http_client(
+        &self,
+        mut address: String,
+        addresses: &mut [String],
+    ) -> Result<(HttpClient, String), ConfigError> {
+        let mut builder = ClientBuilder::new();
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(pool_idle_timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(pool_idle_timeout);
+        }
+        if let Some(max_idle) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        #[cfg(feature = "compression")]
+        if let Some(compression) = self.compression {
+            builder = builder.gzip(compression).deflate(compression);
+        }
+        #[cfg(unix)]
+        if let Some(path) = address.strip_prefix("unix://") {
+            builder = builder.unix_socket(path);
+            address = String::from("http://localhost");
+        }
+        if let Some(tls) = &self.tls {
+            if addresses.len() > 1 && tls.server_name.is_some() {
+                return Err(ConfigError::InvalidTls(String::from(
+                    "server name cannot be set with multiple addresses",
+                )));
+            }
+            (builder, address) = tls.apply(builder, &address)?;
+            for address in addresses.iter_mut().skip(1) {
+                (_, *address) = tls.apply(ClientBuilder::new(), address)?;
+            }
+            if let Some(first) = addresses.first_mut() {
+                *first = address.clone();
+            }
+        }
+        Ok((builder.build()?, address))
+    }
+}
+
+/// This function validates the given address, adding the HTTP scheme if it has
+/// none and removing trailing slashes.
+fn normalize_address(address: &str) -> Result<String, ConfigError> {
+    if let Some(path) = address.strip_prefix("unix://") {
+        if path.is_empty() || cfg!(not(unix)) {
+            return Err(ConfigError::InvalidAddress(address.to_owned()));
+        }
+        return Ok(address.to_owned());
+    }
+    let address =
+        if address.contains("://") { address.to_owned() } else { format!("http://{}", address) };
+    let url = Url::parse(&address).map_err(|_| ConfigError::InvalidAddress(address.clone()))?;
+    if !matches!(url.scheme(), "http" | "https") || !url.has_host() {
+        return Err(ConfigError::InvalidAddress(address));
+    }
+    Ok(address.trim_end_matches('/').to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "reqwest")]
+    use std::time::Duration;
+
+    use super::normalize_address;
+    use crate::{Config, ConfigError};
+
+    #[test]
+    fn test_normalize_address() {
+        assert_eq!(normalize_address("127.0.0.1:8500").unwrap(), "http://127.0.0.1:8500");
+        assert_eq!(normalize_address("https://consul/api//").unwrap(), "https://consul/api");
+        assert!(matches!(normalize_address("ftp://consul"), Err(ConfigError::InvalidAddress(_))));
+        assert!(matches!(normalize_address("http://"), Err(ConfigError::InvalidAddress(_))));
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_builder_addresses() {
+        let config = Config::builder()
+            .address("unix:///var/run/consul.sock")
+            .addresses(["10.0.0.1:8500", "http://10.0.0.2:8500/"])
+            .build()
+            .unwrap();
+        assert_eq!(config.address, "http://10.0.0.1:8500");
+        let pool = config.address_pool.unwrap();
+        assert_eq!(pool.addresses(), ["http://10.0.0.1:8500", "http://10.0.0.2:8500"]);
+        assert!(Config::builder().addresses(["unix:///var/run/consul.sock"]).build().is_err());
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_builder() {
+        let config = Config::builder().token("secret").build().unwrap();
+        assert_eq!(config.address, "http://127.0.0.1:8500");
+        assert_eq!(config.token.as_deref(), Some("secret"));
+        assert!(Config::builder().address("not a url").build().is_err());
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_builder_connection_settings() {
+        let config = Config::builder()
+            .timeout(Duration::from_secs(30))
+            .connect_timeout(Duration::from_secs(5))
+            .pool_idle_timeout(None)
+            .pool_max_idle_per_host(4)
+            .tcp_keepalive(Duration::from_secs(60))
+            .http2_prior_knowledge()
+            .build();
+        assert!(config.is_ok());
+    }
+
+    #[cfg(not(feature = "reqwest"))]
+    #[test]
+    fn test_builder_missing_transport() {
+        assert!(matches!(Config::builder().build(), Err(ConfigError::MissingTransport)));
+    }
+}
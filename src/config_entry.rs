@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::{
+    sealed::Sealed, Client, ConsulResult, ExposeConfig, MeshGatewayConfig, QueryMeta, QueryOptions,
+    TransparentProxyConfig, WriteMeta, WriteOptions,
+};
+
+/// A `service-defaults` config entry, configuring the defaults for all
+/// instances of a service.
+///
+/// For more information, see the [documentation](https://www.consul.io/docs/connect/config-entries/service-defaults).
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceDefaultsConfigEntry {
+    /// The name of the service.
+    pub name: String,
+    /// The protocol of the service, such as `tcp`, `http`, `http2` or `grpc`.
+    pub protocol: String,
+    /// The proxy mode of the service, either `direct` or `transparent`.
+    pub mode: String,
+    /// The transparent proxy configuration of the service.
+    pub transparent_proxy: TransparentProxyConfig,
+    /// The mesh gateway configuration of the service.
+    pub mesh_gateway: MeshGatewayConfig,
+    /// The expose configuration of the service.
+    pub expose: ExposeConfig,
+    /// The SNI server name to use when dialing the service, if it is external
+    /// to the mesh.
+    #[serde(rename = "ExternalSNI")]
+    pub external_sni: String,
+    /// The maximum number of concurrent inbound connections to each instance.
+    pub max_inbound_connections: u64,
+    /// The timeout for establishing connections to the local application.
+    pub local_connect_timeout_ms: u64,
+    /// The timeout for HTTP requests to the local application.
+    pub local_request_timeout_ms: u64,
+    /// Arbitrary metadata attached to the config entry.
+    pub meta: HashMap<String, String>,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+/// A `proxy-defaults` config entry, configuring global defaults for all
+/// proxies. The name of this entry must be `global`.
+///
+/// For more information, see the [documentation](https://www.consul.io/docs/connect/config-entries/proxy-defaults).
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ProxyDefaultsConfigEntry {
+    /// The name of the config entry, which must be `global`.
+    pub name: String,
+    /// Opaque configuration passed to the proxies.
+    pub config: HashMap<String, Value>,
+    /// The default proxy mode, either `direct` or `transparent`.
+    pub mode: String,
+    /// The default transparent proxy configuration.
+    pub transparent_proxy: TransparentProxyConfig,
+    /// The default mesh gateway configuration.
+    pub mesh_gateway: MeshGatewayConfig,
+    /// The default expose configuration.
+    pub expose: ExposeConfig,
+    /// Arbitrary metadata attached to the config entry.
+    pub meta: HashMap<String, String>,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+/// Criteria matching a request header or query parameter in a
+/// [ServiceRouteHttpMatch].
+///
+/// At most one of `present`, `exact`, `prefix`, `suffix` or `regex` may be
+/// configured.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceRouteHttpMatchHeader {
+    /// The name of the header or query parameter to match.
+    pub name: String,
+    /// Match if the header or query parameter is present with any value.
+    pub present: bool,
+    /// Match if the value is exactly this value.
+    pub exact: String,
+    /// Match if the value has this prefix. Not supported for query
+    /// parameters.
+    pub prefix: String,
+    /// Match if the value has this suffix. Not supported for query
+    /// parameters.
+    pub suffix: String,
+    /// Match if the value matches this regular expression.
+    pub regex: String,
+    /// Inverts the logic of the match. Not supported for query parameters.
+    pub invert: bool,
+}
+
+/// HTTP-specific criteria for matching a request in a [ServiceRoute].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceRouteHttpMatch {
+    /// Exact path to match on the HTTP request path.
+    pub path_exact: String,
+    /// Path prefix to match on the HTTP request path.
+    pub path_prefix: String,
+    /// Regular expression to match on the HTTP request path.
+    pub path_regex: String,
+    /// Criteria matching the HTTP request headers.
+    pub header: Vec<ServiceRouteHttpMatchHeader>,
+    /// Criteria matching the HTTP request query parameters.
+    pub query_param: Vec<ServiceRouteHttpMatchHeader>,
+    /// The HTTP methods to match. If empty, all methods are matched.
+    pub methods: Vec<String>,
+}
+
+/// The criteria for matching a request in a [ServiceRoute].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceRouteMatch {
+    /// HTTP-specific match criteria.
+    #[serde(rename = "HTTP")]
+    pub http: Option<ServiceRouteHttpMatch>,
+}
+
+/// The destination of a matched request in a [ServiceRoute].
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceRouteDestination {
+    /// The service to route the request to. Defaults to the router's service.
+    pub service: String,
+    /// The subset of the service to route the request to.
+    pub service_subset: String,
+    /// The namespace of the service to route the request to.
+    pub namespace: String,
+    /// A value to replace the matched path prefix with.
+    pub prefix_rewrite: String,
+    /// The total timeout for the request, such as "10s".
+    pub request_timeout: String,
+    /// The number of times to retry the request.
+    pub num_retries: u32,
+    /// Whether to retry the request on connection failure.
+    pub retry_on_connect_failure: bool,
+    /// The HTTP status codes to retry the request on.
+    pub retry_on_status_codes: Vec<u16>,
+}
+
+/// A single route in a [ServiceRouterConfigEntry].
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceRoute {
+    /// The criteria for matching a request to this route.
+    #[serde(rename = "Match")]
+    pub route_match: Option<ServiceRouteMatch>,
+    /// The destination of requests matching this route.
+    pub destination: Option<ServiceRouteDestination>,
+}
+
+/// A `service-router` config entry, routing L7 traffic for a service based on
+/// request properties.
+///
+/// For more information, see the [documentation](https://www.consul.io/docs/connect/config-entries/service-router).
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceRouterConfigEntry {
+    /// The name of the service the router applies to.
+    pub name: String,
+    /// The routes, evaluated in order. The first matching route is used.
+    pub routes: Vec<ServiceRoute>,
+    /// Arbitrary metadata attached to the config entry.
+    pub meta: HashMap<String, String>,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+/// A single split in a [ServiceSplitterConfigEntry].
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceSplit {
+    /// The percentage of traffic sent to this split, between 0 and 100.
+    pub weight: f32,
+    /// The service to send the traffic to. Defaults to the splitter's service.
+    pub service: String,
+    /// The subset of the service to send the traffic to.
+    pub service_subset: String,
+    /// The namespace of the service to send the traffic to.
+    pub namespace: String,
+}
+
+/// A `service-splitter` config entry, splitting L7 traffic for a service
+/// between multiple services or subsets.
+///
+/// For more information, see the [documentation](https://www.consul.io/docs/connect/config-entries/service-splitter).
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceSplitterConfigEntry {
+    /// The name of the service the splitter applies to.
+    pub name: String,
+    /// The splits, whose weights must sum to 100.
+    pub splits: Vec<ServiceSplit>,
+    /// Arbitrary metadata attached to the config entry.
+    pub meta: HashMap<String, String>,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+/// A named subset of a service's instances in a [ServiceResolverConfigEntry].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceResolverSubset {
+    /// A filter expression selecting the instances in the subset.
+    pub filter: String,
+    /// Whether to only include instances with all checks passing.
+    pub only_passing: bool,
+}
+
+/// A redirect to another service in a [ServiceResolverConfigEntry].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceResolverRedirect {
+    /// The service to redirect to.
+    pub service: String,
+    /// The subset of the service to redirect to.
+    pub service_subset: String,
+    /// The namespace of the service to redirect to.
+    pub namespace: String,
+    /// The datacenter to redirect to.
+    pub datacenter: String,
+}
+
+/// A failover policy in a [ServiceResolverConfigEntry].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceResolverFailover {
+    /// The service to fail over to.
+    pub service: String,
+    /// The subset of the service to fail over to.
+    pub service_subset: String,
+    /// The namespace of the service to fail over to.
+    pub namespace: String,
+    /// The datacenters to fail over to, tried in order.
+    pub datacenters: Vec<String>,
+}
+
+/// A `service-resolver` config entry, defining which instances of a service
+/// satisfy discovery requests.
+///
+/// For more information, see the [documentation](https://www.consul.io/docs/connect/config-entries/service-resolver).
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceResolverConfigEntry {
+    /// The name of the service the resolver applies to.
+    pub name: String,
+    /// The subset to use when no subset is requested.
+    pub default_subset: String,
+    /// The named subsets of the service.
+    pub subsets: HashMap<String, ServiceResolverSubset>,
+    /// A redirect to another service. If set, all other fields are ignored.
+    pub redirect: Option<ServiceResolverRedirect>,
+    /// The failover policies, keyed by subset name or `*` for all subsets.
+    pub failover: HashMap<String, ServiceResolverFailover>,
+    /// The timeout for establishing connections to the service, such as "5s".
+    pub connect_timeout: String,
+    /// The load balancing policy configuration.
+    pub load_balancer: Option<Value>,
+    /// Arbitrary metadata attached to the config entry.
+    pub meta: HashMap<String, String>,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+/// A config entry, tagged by its kind.
+///
+/// For more information, see the [API documentation].
+///
+/// [API documentation]: https://www.consul.io/api-docs/config
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(tag = "Kind", rename_all = "kebab-case")]
+pub enum ConfigEntry {
+    ServiceDefaults(ServiceDefaultsConfigEntry),
+    ProxyDefaults(ProxyDefaultsConfigEntry),
+    ServiceRouter(ServiceRouterConfigEntry),
+    ServiceSplitter(ServiceSplitterConfigEntry),
+    ServiceResolver(ServiceResolverConfigEntry),
+}
+
+impl ConfigEntry {
+    /// This method returns the kind of the config entry, as used by the API.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ConfigEntry::ServiceDefaults(_) => "service-defaults",
+            ConfigEntry::ProxyDefaults(_) => "proxy-defaults",
+            ConfigEntry::ServiceRouter(_) => "service-router",
+            ConfigEntry::ServiceSplitter(_) => "service-splitter",
+            ConfigEntry::ServiceResolver(_) => "service-resolver",
+        }
+    }
+
+    /// This method returns the name of the config entry.
+    pub fn name(&self) -> &str {
+        match self {
+            ConfigEntry::ServiceDefaults(entry) => &entry.name,
+            ConfigEntry::ProxyDefaults(entry) => &entry.name,
+            ConfigEntry::ServiceRouter(entry) => &entry.name,
+            ConfigEntry::ServiceSplitter(entry) => &entry.name,
+            ConfigEntry::ServiceResolver(entry) => &entry.name,
+        }
+    }
+}
+
+/// This trait provides methods for interacting with the `/config` endpoints.
+#[async_trait]
+pub trait ConfigEntries: Sealed {
+    /// This method creates or updates the given config entry.
+    ///
+    /// If `cas` is provided, the update only succeeds if the entry's current
+    /// modify index matches, and `false` is returned otherwise. A `cas` of 0
+    /// only creates the entry if it does not already exist.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/config#apply-configuration
+    async fn apply_config_entry(
+        &self,
+        entry: ConfigEntry,
+        cas: Option<u64>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+
+    /// This method returns the config entry with the given kind and name, or
+    /// [None] if it does not exist.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/config#get-configuration
+    async fn get_config_entry(
+        &self,
+        kind: &str,
+        name: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<ConfigEntry>, QueryMeta)>;
+
+    /// This method returns all the config entries of the given kind.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/config#list-configurations
+    async fn list_config_entries(
+        &self,
+        kind: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<ConfigEntry>, QueryMeta)>;
+
+    /// This method deletes the config entry with the given kind and name.
+    ///
+    /// If `cas` is provided, the deletion only succeeds if the entry's current
+    /// modify index matches, and `false` is returned otherwise.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/config#delete-configuration
+    async fn delete_config_entry(
+        &self,
+        kind: &str,
+        name: &str,
+        cas: Option<u64>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+}
+
+#[async_trait]
+impl ConfigEntries for Client {
+    #[tracing::instrument]
+    async fn apply_config_entry(
+        &self,
+        entry: ConfigEntry,
+        cas: Option<u64>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        let mut params = HashMap::new();
+        if let Some(cas) = cas {
+            params.insert(String::from("cas"), cas.to_string());
+        }
+        self.put("/v1/config", entry, Some(params), options).await
+    }
+
+    #[tracing::instrument]
+    async fn get_config_entry(
+        &self,
+        kind: &str,
+        name: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<ConfigEntry>, QueryMeta)> {
+        let path = format!("/v1/config/{}/{}", kind, name);
+        self.get_with_meta(&path, None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn list_config_entries(
+        &self,
+        kind: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<ConfigEntry>, QueryMeta)> {
+        let path = format!("/v1/config/{}", kind);
+        self.get_with_meta(&path, None, options)
+            .await
+            .map(|(r, meta): (Option<Vec<ConfigEntry>>, _)| (r.unwrap_or_default(), meta))
+    }
+
+    #[tracing::instrument]
+    async fn delete_config_entry(
+        &self,
+        kind: &str,
+        name: &str,
+        cas: Option<u64>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        let mut params = HashMap::new();
+        if let Some(cas) = cas {
+            params.insert(String::from("cas"), cas.to_string());
+        }
+        let path = format!("/v1/config/{}/{}", kind, name);
+        // consul only returns a body when performing a check-and-set
+        self.delete_with_empty(&path, Some(params), options)
+            .await
+            .map(|(r, meta): (Option<bool>, _)| (r.unwrap_or(true), meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigEntry, ServiceSplit, ServiceSplitterConfigEntry};
+
+    #[test]
+    fn test_config_entry_kind() {
+        let entry: ConfigEntry = serde_json::from_str(
+            r#"{
+                "Kind": "service-splitter",
+                "Name": "web",
+                "Splits": [
+                    {"Weight": 90, "ServiceSubset": "v1"},
+                    {"Weight": 10, "ServiceSubset": "v2"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(entry.kind(), "service-splitter");
+        assert_eq!(entry.name(), "web");
+        match entry {
+            ConfigEntry::ServiceSplitter(ServiceSplitterConfigEntry { splits, .. }) => {
+                assert_eq!(splits.len(), 2);
+                assert_eq!(
+                    splits[1],
+                    ServiceSplit {
+                        weight: 10.0,
+                        service_subset: String::from("v2"),
+                        ..Default::default()
+                    }
+                );
+            }
+            _ => panic!("expected a service-splitter entry"),
+        }
+    }
+}
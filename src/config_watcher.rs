@@ -0,0 +1,329 @@
+use std::{fmt, sync::Arc};
+
+use arc_swap::ArcSwap;
+use futures::{stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use tokio::{sync::watch, task::JoinHandle};
+
+use crate::{
+    blocking_query, watch::WatchStream, Client, ConsulError, ConsulResult, KVPair, QueryMeta,
+    QueryOptions, KV,
+};
+
+/// Where the configuration watched by a [ConfigWatcher] is stored in the KV
+/// store.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum ConfigSource {
+    /// Each key under the prefix holds a field of the configuration, with
+    /// nested folders mapped to nested structs. For example, the keys
+    /// `app/port` and `app/db/host` under the prefix `app/` are decoded as
+    /// `{"port": ..., "db": {"host": ...}}`.
+    ///
+    /// Values that are valid JSON, such as `8080` or `true`, are decoded as
+    /// JSON, and other values as strings, so string fields holding numbers
+    /// must be quoted.
+    Prefix(String),
+    /// A single key holds the whole configuration, encoded as JSON.
+    Json(String),
+    /// A single key holds the whole configuration, encoded as YAML.
+    #[cfg(feature = "yaml")]
+    Yaml(String),
+}
+
+impl ConfigSource {
+    /// This method reads the entries holding the configuration.
+    async fn fetch(
+        &self,
+        client: &Client,
+        options: QueryOptions,
+    ) -> ConsulResult<(Vec<KVPair>, QueryMeta)> {
+        match self {
+            ConfigSource::Prefix(prefix) => client.list_entries(prefix, Some(options)).await,
+            ConfigSource::Json(key) => client.get_entry(key, Some(options)).await,
+            #[cfg(feature = "yaml")]
+            ConfigSource::Yaml(key) => client.get_entry(key, Some(options)).await,
+        }
+    }
+
+    /// This method decodes the configuration from the given entries.
+    fn decode<T: DeserializeOwned>(&self, pairs: &[KVPair]) -> ConsulResult<T> {
+        let key = match self {
+            ConfigSource::Prefix(prefix) => return decode_prefix(prefix, pairs),
+            ConfigSource::Json(key) => key,
+            #[cfg(feature = "yaml")]
+            ConfigSource::Yaml(key) => key,
+        };
+        let pair = pairs.first().ok_or_else(|| ConsulError::MissingParameter(key.clone()))?;
+        match self {
+            #[cfg(feature = "yaml")]
+            ConfigSource::Yaml(_) => Ok(serde_yaml::from_slice(&pair.value)?),
+            _ => Ok(serde_json::from_slice(&pair.value)?),
+        }
+    }
+}
+
+/// A configuration struct loaded from the KV store, and reloaded in the
+/// background every time it changes.
+///
+/// Changes are watched with blocking queries. A change that fails to decode
+/// is logged and skipped, so the last valid configuration stays current.
+/// Dropping the watcher stops watching.
+///
+/// ```no_run
+/// # async fn run() -> consul_oxide::ConsulResult<()> {
+/// use consul_oxide::{Client, Config, ConfigSource, ConfigWatcher};
+/// use futures::StreamExt;
+///
+/// #[derive(serde::Deserialize)]
+/// struct AppConfig {
+///     port: u16,
+///     debug: bool,
+/// }
+///
+/// let client = Client::new(Config::default());
+/// let source = ConfigSource::Prefix(String::from("config/app/"));
+/// let watcher: ConfigWatcher<AppConfig> = ConfigWatcher::start(&client, source, None).await?;
+/// println!("listening on {}", watcher.current().port);
+/// let mut changes = watcher.changes();
+/// while let Some(config) = changes.next().await {
+///     println!("debug is now {}", config.debug);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct ConfigWatcher<T> {
+    current: Arc<ArcSwap<T>>,
+    changes: watch::Receiver<Arc<T>>,
+    task: JoinHandle<()>,
+}
+
+impl<T: DeserializeOwned + Send + Sync + 'static> ConfigWatcher<T> {
+    /// This method loads the configuration from the given source, and starts
+    /// watching it for changes. Loading fails if the configuration cannot be
+    /// read or decoded, or if its key does not exist.
+    #[tracing::instrument(skip(client))]
+    pub async fn start(
+        client: &Client,
+        source: ConfigSource,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Self> {
+        let options = options.unwrap_or_default();
+        let (pairs, meta) = source.fetch(client, options.clone()).await?;
+        let initial = Arc::new(source.decode::<T>(&pairs)?);
+        let current = Arc::new(ArcSwap::new(initial.clone()));
+        let (sender, changes) = watch::channel(initial);
+        // watching starts from the index of the initial load
+        let index = meta.last_index.unwrap_or(0);
+        let task = tokio::spawn(reload(
+            client.clone(),
+            source,
+            options,
+            index,
+            pairs,
+            current.clone(),
+            sender,
+        ));
+        Ok(ConfigWatcher { current, changes, task })
+    }
+
+    /// This method returns the current configuration.
+    pub fn current(&self) -> Arc<T> {
+        self.current.load_full()
+    }
+
+    /// This method returns a [WatchStream] yielding the configuration every
+    /// time it changes from now on. A consumer that falls behind only
+    /// receives the latest configuration.
+    pub fn changes(&self) -> WatchStream<Arc<T>> {
+        let mut changes = self.changes.clone();
+        changes.borrow_and_update();
+        Box::pin(stream::unfold(changes, |mut changes| async move {
+            changes.changed().await.ok()?;
+            let config = changes.borrow_and_update().clone();
+            Some((config, changes))
+        }))
+    }
+}
+
+impl<T> Drop for ConfigWatcher<T> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl<T> fmt::Debug for ConfigWatcher<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigWatcher").finish_non_exhaustive()
+    }
+}
+
+/// This function reloads the configuration every time its entries change
+/// from the given ones, read at the given index, for as long as the task is
+/// not aborted.
+async fn reload<T: DeserializeOwned + Send + Sync + 'static>(
+    client: Client,
+    source: ConfigSource,
+    options: QueryOptions,
+    index: u64,
+    mut pairs: Vec<KVPair>,
+    current: Arc<ArcSwap<T>>,
+    sender: watch::Sender<Arc<T>>,
+) {
+    let source = Arc::new(source);
+    let fetch = {
+        let source = source.clone();
+        move |options| {
+            let client = client.clone();
+            let source = source.clone();
+            async move { source.fetch(&client, options).await }
+        }
+    };
+    // changes to other keys under a prefix wake up the query too
+    let mut changes = blocking_query::loop_fn_from(index, Some(options), fetch);
+    while let Some(result) = changes.next().await {
+        let changed = match result {
+            Ok(changed) => changed,
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to watch configuration");
+                continue;
+            }
+        };
+        if changed == pairs {
+            continue;
+        }
+        pairs = changed;
+        match source.decode::<T>(&pairs) {
+            Ok(config) => {
+                let config = Arc::new(config);
+                current.store(config.clone());
+                sender.send_replace(config);
+            }
+            Err(e) => tracing::warn!(error = %e, "failed to decode configuration"),
+        }
+    }
+}
+
+/// This function decodes the entries under the given prefix as a struct,
+/// with each key mapped to a field.
+fn decode_prefix<T: DeserializeOwned>(prefix: &str, pairs: &[KVPair]) -> ConsulResult<T> {
+    let mut root = Map::new();
+    for pair in pairs {
+        let path: Vec<&str> = pair
+            .key
+            .strip_prefix(prefix)
+            .unwrap_or(&pair.key)
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        let (field, folders) = match path.split_last() {
+            Some(path) => path,
+            // folders are stored as keys ending with a slash, without a value
+            None => continue,
+        };
+        if pair.key.ends_with('/') {
+            continue;
+        }
+        let mut object = &mut root;
+        for folder in folders {
+            let entry = object.entry(*folder).or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            object = entry.as_object_mut().unwrap();
+        }
+        let value = serde_json::from_slice(&pair.value)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&pair.value).into_owned()));
+        object.insert(String::from(*field), value);
+    }
+    Ok(serde_json::from_value(Value::Object(root))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::{decode_prefix, ConfigSource, ConfigWatcher};
+    use crate::{request::tests::recording_client, KVPair};
+
+    #[derive(Deserialize, Eq, PartialEq, Debug)]
+    struct AppConfig {
+        port: u16,
+        debug: bool,
+        db: DbConfig,
+    }
+
+    #[derive(Deserialize, Eq, PartialEq, Debug)]
+    struct DbConfig {
+        host: String,
+        port: String,
+    }
+
+    fn pair(key: &str, value: &str) -> KVPair {
+        KVPair { key: String::from(key), value: value.as_bytes().to_vec(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_decode_prefix() {
+        let pairs = [
+            pair("app/", ""),
+            pair("app/port", "8080"),
+            pair("app/debug", "true"),
+            pair("app/db/", ""),
+            pair("app/db/host", "db.local"),
+            pair("app/db/port", "\"5432\""),
+        ];
+        let config: AppConfig = decode_prefix("app/", &pairs).unwrap();
+        assert_eq!(
+            config,
+            AppConfig {
+                port: 8080,
+                debug: true,
+                db: DbConfig { host: String::from("db.local"), port: String::from("5432") }
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_config_watcher() {
+        const CONFIG: &str = r#"[
+            {"Key": "app/port", "Value": "ODA4MA=="},
+            {"Key": "app/debug", "Value": "ZmFsc2U="}
+        ]"#;
+        const INVALID_CONFIG: &str = r#"[
+            {"Key": "app/port", "Value": "ZGIubG9jYWw="},
+            {"Key": "app/debug", "Value": "ZmFsc2U="}
+        ]"#;
+        const NEW_CONFIG: &str = r#"[
+            {"Key": "app/port", "Value": "OTA5MA=="},
+            {"Key": "app/debug", "Value": "dHJ1ZQ=="}
+        ]"#;
+
+        #[derive(Deserialize, Debug)]
+        struct AppConfig {
+            port: u16,
+            debug: bool,
+        }
+
+        let (client, requests) = recording_client(&[
+            (200, &[("X-Consul-Index", "10")], CONFIG),
+            // the first blocking query waits on the index of the initial load
+            (200, &[("X-Consul-Index", "10")], CONFIG),
+            // a configuration that fails to decode is skipped
+            (200, &[("X-Consul-Index", "11")], INVALID_CONFIG),
+            (200, &[("X-Consul-Index", "12")], NEW_CONFIG),
+        ]);
+        let source = ConfigSource::Prefix(String::from("app/"));
+        let watcher: ConfigWatcher<AppConfig> =
+            ConfigWatcher::start(&client, source, None).await.unwrap();
+        assert_eq!(watcher.current().port, 8080);
+        let config = watcher.changes().next().await.unwrap();
+        assert_eq!(config.port, 9090);
+        assert!(config.debug);
+        assert_eq!(watcher.current().port, 9090);
+        // the watcher keeps blocking on the next change
+        let requests = requests.lock().unwrap();
+        assert!(requests[1].contains("index=10"));
+        assert_eq!(requests.len(), 5);
+    }
+}
@@ -0,0 +1,153 @@
+use async_trait::async_trait;
+
+use crate::{
+    sealed::Sealed,
+    watch::{self, WatchStream},
+    Client, ConsulResult, QueryMeta, QueryOptions,
+};
+
+/// A leaf certificate issued by the Connect CA for a service, returned by the
+/// [AgentConnect::get_leaf_cert] method.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct LeafCert {
+    /// The serial number of the certificate, as colon-separated hex bytes.
+    pub serial_number: String,
+    /// The PEM-encoded certificate.
+    #[serde(rename = "CertPEM")]
+    pub cert_pem: String,
+    /// The PEM-encoded private key of the certificate.
+    #[serde(rename = "PrivateKeyPEM")]
+    pub private_key_pem: String,
+    /// The name of the service the certificate was issued for.
+    pub service: String,
+    /// The SPIFFE URI identifying the service, which is set as the URI SAN of
+    /// the certificate.
+    #[serde(rename = "ServiceURI")]
+    pub service_uri: String,
+    /// The time from which the certificate is valid, in RFC 3339 format.
+    pub valid_after: String,
+    /// The time until which the certificate is valid, in RFC 3339 format.
+    pub valid_before: String,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+/// The result of an authorization check, returned by the
+/// [AgentConnect::connect_authorize] method.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ConnectAuthorization {
+    /// Whether the connection is authorized.
+    pub authorized: bool,
+    /// A human readable explanation of the decision, such as the intention
+    /// that matched.
+    pub reason: String,
+}
+
+/// Request payload for the [AgentConnect::connect_authorize] method.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct ConnectAuthorizePayload<'a> {
+    target: &'a str,
+    #[serde(rename = "ClientCertURI")]
+    client_cert_uri: &'a str,
+    client_cert_serial: &'a str,
+}
+
+/// This trait provides implementations of the agent's `/agent/connect`
+/// endpoints, which are used by Connect-native applications and proxies.
+///
+/// For more information, see the [API documentation](https://www.consul.io/api-docs/agent/connect).
+#[async_trait]
+pub trait AgentConnect: Sealed {
+    /// This method returns a leaf certificate for the given service, signed by
+    /// the active Connect CA root.
+    ///
+    /// The agent caches the certificate and renews it ahead of expiry. When
+    /// used as a blocking query, this method returns once a new certificate
+    /// has been issued, either because of a renewal or a root rotation.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent/connect#service-leaf-certificate
+    async fn get_leaf_cert(
+        &self,
+        service: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(LeafCert, QueryMeta)>;
+
+    /// This method returns a stream yielding the leaf certificate of the given
+    /// service, and a new certificate every time it is renewed or reissued.
+    ///
+    /// The stream is driven by blocking queries against the [service leaf
+    /// certificate] endpoint. Errors are yielded as they occur, after which
+    /// the watch is retried with a jittered backoff.
+    ///
+    /// [service leaf certificate]: https://www.consul.io/api-docs/agent/connect#service-leaf-certificate
+    fn watch_leaf_cert(
+        &self,
+        service: &str,
+        options: Option<QueryOptions>,
+    ) -> WatchStream<ConsulResult<LeafCert>>;
+
+    /// This method checks whether a client presenting a certificate with the
+    /// given SPIFFE URI and serial number may connect to the target service,
+    /// according to the current intentions.
+    ///
+    /// Connect-native servers should call this method for every inbound
+    /// connection, after verifying the client certificate against the CA
+    /// roots. The agent caches the result, so this is cheap to call.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/agent/connect#authorize
+    async fn connect_authorize(
+        &self,
+        target: &str,
+        client_cert_uri: &str,
+        client_cert_serial: &str,
+    ) -> ConsulResult<ConnectAuthorization>;
+}
+
+#[async_trait]
+impl AgentConnect for Client {
+    #[tracing::instrument]
+    async fn get_leaf_cert(
+        &self,
+        service: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(LeafCert, QueryMeta)> {
+        let path = format!("/v1/agent/connect/ca/leaf/{}", service);
+        self.get_with_meta(&path, None, options)
+            .await
+            .map(|(r, meta): (Option<LeafCert>, _)| (r.unwrap_or_default(), meta))
+    }
+
+    fn watch_leaf_cert(
+        &self,
+        service: &str,
+        options: Option<QueryOptions>,
+    ) -> WatchStream<ConsulResult<LeafCert>> {
+        let client = self.clone();
+        let service = service.to_owned();
+        watch::watch(options, move |options| {
+            let client = client.clone();
+            let service = service.clone();
+            async move { client.get_leaf_cert(&service, Some(options)).await }
+        })
+    }
+
+    #[tracing::instrument]
+    async fn connect_authorize(
+        &self,
+        target: &str,
+        client_cert_uri: &str,
+        client_cert_serial: &str,
+    ) -> ConsulResult<ConnectAuthorization> {
+        let payload = ConnectAuthorizePayload { target, client_cert_uri, client_cert_serial };
+        self.post("/v1/agent/connect/authorize", payload, None, None).await.map(|(r, _)| r)
+    }
+}
@@ -1,54 +1,93 @@
 use async_trait::async_trait;
 use serde_json::Value;
 
-use crate::{sealed::Sealed, Client, ConsulResult, QueryOptions};
+use crate::{
+    sealed::Sealed, Client, ConsulResult, QueryMeta, QueryOptions, WriteMeta, WriteOptions,
+};
 
-/// Response payload for the [ConnectCA::get_ca_config] method.
-#[derive(Default, Serialize, Deserialize, Debug)]
-#[serde(default)]
+/// The CA configuration, used by the [ConnectCA::get_ca_config] and
+/// [ConnectCA::update_ca_config] methods.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
 #[allow(clippy::upper_case_acronyms)]
 pub struct CAConfig {
-    #[serde(rename = "Provider")]
-    provider: String,
-    #[serde(rename = "Config")]
-    config: Value,
-    #[serde(rename = "CreateIndex")]
-    create_index: u64,
-    #[serde(rename = "ModifyIndex")]
-    modify_index: u64,
+    /// The CA provider, such as `consul` or `vault`.
+    pub provider: String,
+    /// The provider-specific configuration.
+    pub config: Value,
+    /// Whether to skip cross-signing the new root with the old one when
+    /// changing providers. Only used when updating the configuration.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub force_without_cross_signing: bool,
+    #[serde(skip_serializing)]
+    pub create_index: u64,
+    #[serde(skip_serializing)]
+    pub modify_index: u64,
 }
 
 /// Response payload for the [ConnectCA::list_ca_root_certs] method.
-#[derive(Default, Serialize, Deserialize, Debug)]
-#[serde(default)]
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
 #[allow(clippy::upper_case_acronyms)]
 pub struct CARootList {
+    /// The ID of the root certificate currently used to sign leaf
+    /// certificates.
     #[serde(rename = "ActiveRootID")]
-    active_root_id: String,
-    #[serde(rename = "TrustDomain")]
-    trust_domain: String,
-    #[serde(rename = "Roots")]
-    roots: Vec<CARoot>,
+    pub active_root_id: String,
+    /// The trust domain of the cluster, used in SPIFFE IDs.
+    pub trust_domain: String,
+    /// The root certificates trusted by the cluster. During a rotation this
+    /// includes both the old and the new root.
+    pub roots: Vec<CARoot>,
+}
+
+impl CARootList {
+    /// This method returns the root certificate currently used to sign leaf
+    /// certificates, if present in the list.
+    pub fn active_root(&self) -> Option<&CARoot> {
+        self.roots.iter().find(|root| root.id == self.active_root_id)
+    }
 }
 
 /// Entry in the root certificate list. Returned by the
 /// [ConnectCA::list_ca_root_certs] method.
-#[derive(Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
-#[serde(default)]
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
 #[allow(clippy::upper_case_acronyms)]
 pub struct CARoot {
+    /// The unique ID of the root, which is the fingerprint of its public key.
     #[serde(rename = "ID")]
-    id: String,
-    #[serde(rename = "Name")]
-    name: String,
-    #[serde(rename = "RootCert")]
-    root_cert: String,
-    #[serde(rename = "Active")]
-    active: bool,
-    #[serde(rename = "CreateIndex")]
-    create_index: u64,
-    #[serde(rename = "ModifyIndex")]
-    modify_index: u64,
+    pub id: String,
+    /// A human readable name for the root.
+    pub name: String,
+    /// The serial number of the root certificate.
+    pub serial_number: u64,
+    /// The key ID of the root's signing key.
+    #[serde(rename = "SigningKeyID")]
+    pub signing_key_id: String,
+    /// An external trust domain, such as that of the Vault provider, used
+    /// while the root was created.
+    pub external_trust_domain: String,
+    /// The time from which the root certificate is valid, in RFC 3339 format.
+    pub not_before: String,
+    /// The time until which the root certificate is valid, in RFC 3339 format.
+    pub not_after: String,
+    /// The PEM-encoded root certificate.
+    pub root_cert: String,
+    /// Any PEM-encoded intermediate certificates needed to chain leaf
+    /// certificates to the root.
+    pub intermediate_certs: Vec<String>,
+    /// Whether this root is currently used to sign leaf certificates.
+    pub active: bool,
+    /// The time at which the root was rotated out, in RFC 3339 format. This is
+    /// only set for roots that are no longer active but are still trusted.
+    pub rotated_out_at: String,
+    /// The type of the root's private key, such as `ec` or `rsa`.
+    pub private_key_type: String,
+    /// The size in bits of the root's private key.
+    pub private_key_bits: u32,
+    pub create_index: u64,
+    pub modify_index: u64,
 }
 
 /// This trait provides implementations of the Consul `/connect/ca` endpoint.
@@ -60,37 +99,47 @@ pub struct CARoot {
 #[async_trait]
 pub trait ConnectCA: Sealed {
     /// This method returns the current list of trusted CA root certificates in
-    /// the cluster.
+    /// the cluster. This endpoint supports blocking queries, which can be used
+    /// to be notified of root rotations.
     ///
-    /// Fore more information, see the relevant endpoint's [API documentation].
+    /// For more information, see the relevant endpoint's [API documentation].
     ///
-    /// [API documentation]: https://www.consul.io/api/connect/ca.html#list-ca-root-certificates
-    async fn list_ca_root_certs(&self, options: Option<QueryOptions>) -> ConsulResult<CARootList>;
+    /// [API documentation]: https://www.consul.io/api-docs/connect/ca#list-ca-root-certificates
+    async fn list_ca_root_certs(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(CARootList, QueryMeta)>;
 
     /// This method returns the current CA configuration.
     ///
     /// For more information, see the relevant endpoint's [API documentation].
     ///
-    /// [API documentation]: https://www.consul.io/api/connect/ca.html#get-ca-configuration
+    /// [API documentation]: https://www.consul.io/api-docs/connect/ca#get-ca-configuration
     async fn get_ca_config(&self, options: Option<QueryOptions>) -> ConsulResult<CAConfig>;
 
-    /// This method updates the configuration for the CA.
+    /// This method updates the configuration for the CA. Changing the
+    /// provider or its keys triggers a root rotation.
     ///
     /// For more information, see the relevant endpoint's [API documentation].
     ///
-    /// [API documentation]: https://www.consul.io/api/connect/ca.html#update-ca-configuration
+    /// [API documentation]: https://www.consul.io/api-docs/connect/ca#update-ca-configuration
     async fn update_ca_config(
         &self,
         conf: CAConfig,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<()>;
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>;
 }
 
 #[async_trait]
 impl ConnectCA for Client {
     #[tracing::instrument]
-    async fn list_ca_root_certs(&self, options: Option<QueryOptions>) -> ConsulResult<CARootList> {
-        self.get("/v1/connect/ca/roots", options).await
+    async fn list_ca_root_certs(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(CARootList, QueryMeta)> {
+        self.get_with_meta("/v1/connect/ca/roots", None, options)
+            .await
+            .map(|(r, meta): (Option<CARootList>, _)| (r.unwrap_or_default(), meta))
     }
 
     #[tracing::instrument]
@@ -102,8 +151,33 @@ impl ConnectCA for Client {
     async fn update_ca_config(
         &self,
         payload: CAConfig,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<()> {
-        self.put("/v1/connect/ca/configuration", payload, None, options).await
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta> {
+        self.put_with_empty("/v1/connect/ca/configuration", payload, None, options)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CARootList;
+
+    #[test]
+    fn test_active_root() {
+        let roots: CARootList = serde_json::from_str(
+            r#"{
+                "ActiveRootID": "c7:bd:55",
+                "TrustDomain": "7f42f496-fbc7-8692-05ed-334aa5340c1e.consul",
+                "Roots": [
+                    {"ID": "a1:b2:c3", "Active": false, "RotatedOutAt": "2021-03-01T10:00:00Z"},
+                    {"ID": "c7:bd:55", "Active": true, "NotAfter": "2031-03-01T10:00:00Z"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let active = roots.active_root().unwrap();
+        assert!(active.active);
+        assert_eq!(active.not_after, "2031-03-01T10:00:00Z");
     }
 }
@@ -2,80 +2,85 @@ use std::collections::HashMap;
 
 use async_trait::async_trait;
 
-use crate::ConsulResult;
+use crate::{
+    sealed::Sealed, Client, ConsulResult, QueryMeta, QueryOptions, WriteMeta, WriteOptions,
+};
 
 /// Request payload for the [ConnectIntentions::upsert_intention_by_name]
 /// method.
-#[derive(Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Default)]
+#[serde(rename_all = "PascalCase")]
 pub struct UpsertIntentionPayload {
     /// The type for the SourceName value. This can be only "consul" today to
     /// represent a Consul service. If not provided, this will be defaulted to
     /// "consul".
-    #[serde(rename = "SourceType")]
-    pub soucre_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_type: Option<String>,
     /// For an L4 intention this is required, and should be set to one of
     /// "allow" or "deny" for the action that should be taken if this intention
-    /// matches a request.
-    #[serde(rename = "Action")]
-    pub action: IntentionAction,
-    /// The name of the source service. This is required for an L4 intention.
-    #[serde(rename = "Permissions")]
-    pub permissions: Vec<IntentionPermision>,
+    /// matches a request. This must be omitted for an L7 intention.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<IntentionAction>,
+    /// The list of all additional L7 attributes that extend the intention
+    /// match criteria. This must be omitted for an L4 intention.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub permissions: Vec<IntentionPermission>,
     /// Description for the intention. This is not used by Consul, but is
     /// presented in API responses to assist tooling.
-    #[serde(rename = "Description")]
-    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Arbitrary metadata attached to the intention.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub meta: HashMap<String, String>,
 }
 
 /// Used to specify action type in [UpsertIntentionPayload].
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub enum IntentionAction {
     #[serde(rename = "allow")]
     Allow,
     #[serde(rename = "deny")]
+    #[default]
     Deny,
 }
 
-impl Default for IntentionAction {
-    fn default() -> Self {
-        IntentionAction::Deny
-    }
-}
-
 /// L7 attributes datatype.
 ///
 /// This is used to specify the attributes for a L7 intention, as used by the
 /// [ConnectIntentions::upsert_intention_by_name] method.
-#[derive(Debug, Serialize)]
-pub struct IntentionPermision {
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct IntentionPermission {
     /// This is one of "allow" or "deny" for the action that should be taken if
     /// this permission matches a request.
     pub action: IntentionAction,
     /// A set of HTTP-specific authorization criteria
-    pub http: IntentionHttpPermission,
+    #[serde(rename = "HTTP")]
+    pub http: Option<IntentionHttpPermission>,
 }
 
-/// HTTP-specific authorization criteria, as used in [IntentionPermision].
+/// HTTP-specific authorization criteria, as used in [IntentionPermission].
 ///
 /// At most only one of PathExact, PathPrefix, or PathRegex may be configured.
-#[derive(Debug, Serialize, Default)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
 pub struct IntentionHttpPermission {
     /// Exact path to match on the HTTP request path.
-    #[serde(rename = "PathExact")]
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub path_exact: String,
     /// Path prefix to match on the HTTP request path.
-    #[serde(rename = "PathPrefix")]
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub path_prefix: String,
     /// Regular expression to match on the HTTP request path.
-    #[serde(rename = "PathRegex")]
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub path_regex: String,
     /// A list of HTTP methods for which this match applies. If unspecified all
     /// HTTP methods are matched. If provided the names must be a valid method.
-    #[serde(rename = "Methods")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub methods: Vec<String>,
     /// A set of criteria that can match on HTTP request headers. If more than
     /// one is configured all must match for the overall match to apply.
-    #[serde(rename = "Header")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub header: Vec<IntentionHttpHeaderPermission>,
 }
 
@@ -84,60 +89,105 @@ pub struct IntentionHttpPermission {
 ///
 /// At most only one of `Exact`, `Prefix`, `Suffix`, `Regex`, or `Present` may
 /// be configured.
-#[derive(Debug, Serialize, Default)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
 pub struct IntentionHttpHeaderPermission {
     /// Name of the header to match.
-    #[serde(rename = "Name")]
     pub name: String,
     /// Match if the header with the given name is present with any value.
-    #[serde(rename = "Present")]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
     pub present: bool,
     /// Match if the header with the given name is this value.
-    #[serde(rename = "Exact")]
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub exact: String,
     /// Match if the header with the given name has this prefix.
-    #[serde(rename = "Prefix")]
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub prefix: String,
-    /// Match if the header with the given name has this suffix.\
-    #[serde(rename = "Suffix")]
+    /// Match if the header with the given name has this suffix.
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub suffix: String,
     /// Match if the header with the given name matches this pattern.
-    #[serde(rename = "Regex")]
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub regex: String,
     /// Inverts the logic of the match.
-    #[serde(rename = "Invert")]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
     pub invert: bool,
 }
 
-/// Response payload for the [ConnectIntentions::read_intention_by_name].
-#[derive(Debug, Deserialize)]
-pub struct ReadIntentionByNameResponse {
-    #[serde(rename = "Description")]
+/// A service intention, as returned by the [ConnectIntentions] methods and
+/// used as the payload of [ConnectIntentions::create_intention].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct Intention {
+    /// The unique ID of the intention. This is assigned by Consul on creation.
+    #[serde(rename = "ID", skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    /// Description for the intention.
     pub description: String,
     #[cfg(feature = "enterprise")]
     #[serde(rename = "SourceNS")]
     pub source_ns: String,
-    #[serde(rename = "SourceName")]
+    /// The name of the source service.
     pub source_name: String,
     #[cfg(feature = "enterprise")]
     #[serde(rename = "DestinationNS")]
     pub destination_ns: String,
-    #[serde(rename = "DestinationName")]
+    /// The name of the destination service.
     pub destination_name: String,
-    #[serde(rename = "SourceType")]
+    /// The type for the source name value, which is always "consul" today.
     pub source_type: String,
-    #[serde(rename = "Action")]
-    pub action: IntentionAction,
-    #[serde(rename = "Meta")]
+    /// The action of an L4 intention. This is [None] for L7 intentions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<IntentionAction>,
+    /// The L7 permissions of the intention, evaluated in order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub permissions: Vec<IntentionPermission>,
+    /// Arbitrary metadata attached to the intention.
     pub meta: HashMap<String, String>,
-    #[serde(rename = "Precedence")]
+    /// The precedence of the intention, computed by Consul from the
+    /// specificity of the source and destination.
+    #[serde(skip_serializing)]
     pub precedence: u64,
-    #[serde(rename = "CreateIndex")]
+    #[serde(skip_serializing)]
     pub create_index: u64,
-    #[serde(rename = "ModifyIndex")]
+    #[serde(skip_serializing)]
     pub modify_index: u64,
 }
 
+/// Which side of an intention to match against, used by the
+/// [ConnectIntentions::match_intentions] method.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IntentionMatchBy {
+    /// Match intentions with the given service as their source.
+    Source,
+    /// Match intentions with the given service as their destination.
+    Destination,
+}
+
+impl IntentionMatchBy {
+    fn as_str(&self) -> &'static str {
+        match self {
+            IntentionMatchBy::Source => "source",
+            IntentionMatchBy::Destination => "destination",
+        }
+    }
+}
+
+/// Response payload for the create intention endpoint.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CreateIntentionResponse {
+    #[serde(rename = "ID")]
+    id: String,
+}
+
+/// Response payload for the check intention endpoint.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CheckIntentionResponse {
+    allowed: bool,
+}
+
 /// This trait provides implementations for the  `/connect/intentions` endpoint,
 /// which provides tools for managing intentions.
 ///
@@ -145,42 +195,69 @@ pub struct ReadIntentionByNameResponse {
 ///
 /// [API documentation]: https://www.consul.io/api-docs/connect/intentions
 #[async_trait]
-pub trait ConnectIntentions {
-    /// This method creates a new intention and returns true if it was created
-    /// successfully.
+pub trait ConnectIntentions: Sealed {
+    /// This method creates a new intention and returns its ID. The source and
+    /// destination pair must be unique.
     ///
-    /// The name and destination pair must be unique. If another intention
-    /// matches the name and destination, the creation will replace the previous
-    /// intention.
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/connect/intentions#create-intention-with-id
+    async fn create_intention(
+        &self,
+        intention: Intention,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(String, WriteMeta)>;
+
+    /// This method creates a new intention, or replaces the existing intention
+    /// with the same source and destination, and returns true if it was
+    /// written successfully.
     ///
     /// For more information, see the relevant endpoint's [API documentation].
     ///
     /// [API documentation]: https://www.consul.io/api-docs/connect/intentions#upsert-intention-by-name
-    async fn upsert_intention_by_name<S: ToString>(
-        source: S,
-        desintation: S,
+    async fn upsert_intention_by_name(
+        &self,
+        source: &str,
+        destination: &str,
         payload: UpsertIntentionPayload,
-    ) -> ConsulResult<bool>;
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
 
     /// This method reads a specific intention by its unique source and
-    /// destination.
+    /// destination, returning [None] if it does not exist.
     ///
     /// For more information, see the relevant endpoint's [API documentation].
     ///
-    /// [API documentation]: https://www.consul.io/api-docs/connect/intentions##read-specific-intention-by-name
+    /// [API documentation]: https://www.consul.io/api-docs/connect/intentions#read-specific-intention-by-name
     async fn read_intention_by_name(
-        source: String,
-        destination: String,
-    ) -> ConsulResult<ReadIntentionByNameResponse>;
+        &self,
+        source: &str,
+        destination: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Option<Intention>>;
 
-    /// This method lists all intentions.
+    /// This method reads a specific intention by its ID, returning [None] if
+    /// it does not exist.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/connect/intentions#read-specific-intention-by-id
+    async fn read_intention(
+        &self,
+        id: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Option<Intention>>;
+
+    /// This method lists all intentions. The results may be filtered using
+    /// [QueryOptions::filter].
     ///
     /// For more information, see the relevant endpoint's [API documentation].
     ///
     /// [API documentation]: https://www.consul.io/api-docs/connect/intentions#list-intentions
     async fn list_intentions(
-        filter: Option<String>,
-    ) -> ConsulResult<Vec<ReadIntentionByNameResponse>>;
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<Intention>, QueryMeta)>;
 
     /// This method deletes a specific intention by its unique source and
     /// destination.
@@ -188,5 +265,211 @@ pub trait ConnectIntentions {
     /// For more information, see the relevant endpoint's [API documentation].
     ///
     /// [API documentation]: https://www.consul.io/api-docs/connect/intentions#delete-intention-by-name
-    async fn delete_intention_by_name<S: ToString>(source: S, destination: S) -> ConsulResult<()>;
+    async fn delete_intention_by_name(
+        &self,
+        source: &str,
+        destination: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+
+    /// This method deletes a specific intention by its ID.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/connect/intentions#delete-intention-by-id
+    async fn delete_intention(
+        &self,
+        id: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+
+    /// This method returns whether a connection from the source service to
+    /// the destination service would be authorized by the current set of
+    /// intentions. L7 permissions are not considered.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/connect/intentions#check-intention-result
+    async fn check_intention(
+        &self,
+        source: &str,
+        destination: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<bool>;
+
+    /// This method returns the intentions matching the given service as
+    /// either source or destination, ordered by precedence.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/connect/intentions#list-matching-intentions
+    async fn match_intentions(
+        &self,
+        by: IntentionMatchBy,
+        name: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<Intention>, QueryMeta)>;
+}
+
+#[async_trait]
+impl ConnectIntentions for Client {
+    #[tracing::instrument]
+    async fn create_intention(
+        &self,
+        intention: Intention,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(String, WriteMeta)> {
+        self.post("/v1/connect/intentions", intention, None, options)
+            .await
+            .map(|(r, meta): (CreateIntentionResponse, _)| (r.id, meta))
+    }
+
+    #[tracing::instrument]
+    async fn upsert_intention_by_name(
+        &self,
+        source: &str,
+        destination: &str,
+        payload: UpsertIntentionPayload,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        let params = exact_params(source, destination);
+        self.put("/v1/connect/intentions/exact", payload, Some(params), options).await
+    }
+
+    #[tracing::instrument]
+    async fn read_intention_by_name(
+        &self,
+        source: &str,
+        destination: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Option<Intention>> {
+        let params = exact_params(source, destination);
+        self.get_with_meta("/v1/connect/intentions/exact", Some(params), options)
+            .await
+            .map(|(r, _)| r)
+    }
+
+    #[tracing::instrument]
+    async fn read_intention(
+        &self,
+        id: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Option<Intention>> {
+        let path = format!("/v1/connect/intentions/{}", id);
+        self.get_with_meta(&path, None, options).await.map(|(r, _)| r)
+    }
+
+    #[tracing::instrument]
+    async fn list_intentions(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<Intention>, QueryMeta)> {
+        self.get_with_meta("/v1/connect/intentions", None, options)
+            .await
+            .map(|(r, meta): (Option<Vec<Intention>>, _)| (r.unwrap_or_default(), meta))
+    }
+
+    #[tracing::instrument]
+    async fn delete_intention_by_name(
+        &self,
+        source: &str,
+        destination: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        let params = exact_params(source, destination);
+        self.delete("/v1/connect/intentions/exact", Some(params), options).await
+    }
+
+    #[tracing::instrument]
+    async fn delete_intention(
+        &self,
+        id: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        let path = format!("/v1/connect/intentions/{}", id);
+        self.delete(&path, None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn check_intention(
+        &self,
+        source: &str,
+        destination: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<bool> {
+        let params = exact_params(source, destination);
+        self.get_with_params("/v1/connect/intentions/check", Some(params), options)
+            .await
+            .map(|r: CheckIntentionResponse| r.allowed)
+    }
+
+    #[tracing::instrument]
+    async fn match_intentions(
+        &self,
+        by: IntentionMatchBy,
+        name: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<Intention>, QueryMeta)> {
+        let mut params = HashMap::new();
+        params.insert(String::from("by"), by.as_str().to_owned());
+        params.insert(String::from("name"), name.to_owned());
+        // consul returns a map of the requested names to their intentions
+        self.get_with_meta("/v1/connect/intentions/match", Some(params), options).await.map(
+            |(r, meta): (Option<HashMap<String, Vec<Intention>>>, _)| {
+                (r.and_then(|mut r| r.remove(name)).unwrap_or_default(), meta)
+            },
+        )
+    }
+}
+
+/// This function builds the parameters identifying an intention by its source
+/// and destination.
+fn exact_params(source: &str, destination: &str) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    params.insert(String::from("source"), source.to_owned());
+    params.insert(String::from("destination"), destination.to_owned());
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Intention, IntentionAction, UpsertIntentionPayload};
+
+    #[test]
+    fn test_decode_l7_intention() {
+        let intention: Intention = serde_json::from_str(
+            r#"{
+                "ID": "e9ebc19f-d481-42b1-4871-4d298d3acd5c",
+                "SourceName": "web",
+                "DestinationName": "db",
+                "SourceType": "consul",
+                "Permissions": [
+                    {
+                        "Action": "allow",
+                        "HTTP": {
+                            "PathPrefix": "/v1",
+                            "Methods": ["GET"],
+                            "Header": [{"Name": "X-Debug", "Present": true}]
+                        }
+                    }
+                ],
+                "Precedence": 9,
+                "CreateIndex": 11,
+                "ModifyIndex": 11
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(intention.action, None);
+        assert_eq!(intention.permissions[0].action, IntentionAction::Allow);
+        let http = intention.permissions[0].http.as_ref().unwrap();
+        assert_eq!(http.path_prefix, "/v1");
+        assert!(http.header[0].present);
+    }
+
+    #[test]
+    fn test_encode_l4_payload() {
+        let payload =
+            UpsertIntentionPayload { action: Some(IntentionAction::Deny), ..Default::default() };
+        assert_eq!(serde_json::to_string(&payload).unwrap(), r#"{"Action":"deny"}"#);
+    }
 }
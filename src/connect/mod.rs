@@ -1,5 +1,11 @@
+mod agent;
 mod ca;
 mod intentions;
+#[cfg(feature = "connect-native")]
+mod native;
 
+pub use agent::*;
 pub use ca::*;
 pub use intentions::*;
+#[cfg(feature = "connect-native")]
+pub use native::*;
@@ -0,0 +1,110 @@
+use std::{fmt, sync::Arc};
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_rustls::{
+    rustls::{server::AllowAnyAuthenticatedClient, ServerConfig},
+    server::TlsStream,
+    TlsAcceptor,
+};
+
+use super::{load_leaf, load_roots, peer_identity, RotatingConfig};
+use crate::{AgentConnect, CARootList, Client, ConsulError, ConsulResult, LeafCert};
+
+/// A TLS acceptor for a Connect-native service.
+///
+/// The acceptor presents the service's leaf certificate, requires clients to
+/// present a certificate signed by the Connect CA, and authorizes every
+/// connection against the intentions. Certificates are rotated in the
+/// background for as long as the acceptor is alive.
+///
+/// ```no_run
+/// # async fn run() -> consul_oxide::ConsulResult<()> {
+/// use consul_oxide::{Client, Config, ConnectAcceptor};
+/// use tokio::net::TcpListener;
+///
+/// let client = Client::new(Config::default());
+/// let acceptor = ConnectAcceptor::new(&client, "web").await?;
+/// let listener = TcpListener::bind("127.0.0.1:8443").await?;
+/// loop {
+///     let (stream, _) = listener.accept().await?;
+///     match acceptor.accept(stream).await {
+///         Ok(stream) => { /* serve the connection */ }
+///         Err(e) => eprintln!("rejected connection: {}", e),
+///     }
+/// }
+/// # }
+/// ```
+pub struct ConnectAcceptor {
+    client: Client,
+    service: String,
+    config: RotatingConfig<ServerConfig>,
+}
+
+impl ConnectAcceptor {
+    /// This method creates a new acceptor for the given service, fetching its
+    /// leaf certificate and the CA roots from the agent.
+    pub async fn new(client: &Client, service: &str) -> ConsulResult<Self> {
+        let config = RotatingConfig::new(client, service, server_config).await?;
+        Ok(ConnectAcceptor { client: client.clone(), service: service.to_owned(), config })
+    }
+
+    /// This method returns the current TLS configuration of the acceptor, for
+    /// use with other TLS servers. Connections accepted using this
+    /// configuration are not authorized against the intentions.
+    pub fn server_config(&self) -> Arc<ServerConfig> {
+        self.config.current()
+    }
+
+    /// This method performs the TLS handshake on the given stream, then
+    /// authorizes the client certificate against the intentions of the
+    /// service.
+    ///
+    /// If the connection is denied, a [ConsulError::Unauthorized] error is
+    /// returned and the stream is dropped.
+    #[tracing::instrument(skip(stream))]
+    pub async fn accept<IO>(&self, stream: IO) -> ConsulResult<TlsStream<IO>>
+    where
+        IO: AsyncRead + AsyncWrite + Unpin,
+    {
+        let stream = TlsAcceptor::from(self.config.current()).accept(stream).await?;
+        let cert =
+            stream.get_ref().1.peer_certificates().and_then(|certs| certs.first()).ok_or_else(
+                || ConsulError::InvalidCertificate(String::from("missing client cert")),
+            )?;
+        let identity = peer_identity(cert)?;
+        let authorization =
+            self.client.connect_authorize(&self.service, &identity.uri, &identity.serial).await?;
+        if !authorization.authorized {
+            return Err(ConsulError::Unauthorized(authorization.reason));
+        }
+        Ok(stream)
+    }
+}
+
+impl fmt::Debug for ConnectAcceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectAcceptor").field("service", &self.service).finish()
+    }
+}
+
+/// This function builds a server configuration presenting the leaf
+/// certificate and requiring client certificates signed by the CA roots.
+fn server_config(leaf: &LeafCert, roots: &CARootList) -> ConsulResult<ServerConfig> {
+    let (certs, key) = load_leaf(leaf)?;
+    let verifier = AllowAnyAuthenticatedClient::new(load_roots(roots)?).boxed();
+    Ok(ServerConfig::builder()
+        .with_safe_defaults()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(certs, key)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::server_config;
+    use crate::connect::native::tests::{leaf, roots};
+
+    #[test]
+    fn test_server_config() {
+        assert!(server_config(&leaf(), &roots()).is_ok());
+    }
+}
@@ -0,0 +1,225 @@
+use std::{fmt, sync::Arc, time::SystemTime};
+
+use rand::seq::SliceRandom;
+use tokio::net::TcpStream;
+use tokio_rustls::{
+    client::TlsStream,
+    rustls::{
+        client::{
+            verify_server_cert_signed_by_trust_anchor, ServerCertVerified, ServerCertVerifier,
+        },
+        server::ParsedCertificate,
+        Certificate, CertificateError, ClientConfig, Error, PrivateKey, RootCertStore, ServerName,
+    },
+    TlsConnector,
+};
+
+use super::{load_leaf, load_roots, peer_identity, RotatingConfig, SpiffeId};
+use crate::{CARootList, Client, ConsulError, ConsulResult, Health, LeafCert, ServiceEntry};
+
+/// A TLS connector for the upstreams of a Connect-native service.
+///
+/// The connector resolves healthy instances of an upstream using the health
+/// API, presents the service's leaf certificate, and verifies that the
+/// upstream presents a certificate signed by the Connect CA for the expected
+/// service. Certificates are rotated in the background for as long as the
+/// connector is alive.
+///
+/// ```no_run
+/// # async fn run() -> consul_oxide::ConsulResult<()> {
+/// use consul_oxide::{Client, Config, ConnectConnector};
+///
+/// let client = Client::new(Config::default());
+/// let connector = ConnectConnector::new(&client, "web").await?;
+/// let stream = connector.connect("db").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ConnectConnector {
+    client: Client,
+    service: String,
+    identity: RotatingConfig<ClientIdentity>,
+}
+
+impl ConnectConnector {
+    /// This method creates a new connector for the given service, fetching its
+    /// leaf certificate and the CA roots from the agent.
+    pub async fn new(client: &Client, service: &str) -> ConsulResult<Self> {
+        let identity = RotatingConfig::new(client, service, ClientIdentity::new).await?;
+        Ok(ConnectConnector { client: client.clone(), service: service.to_owned(), identity })
+    }
+
+    /// This method returns a TLS configuration presenting the current leaf
+    /// certificate, which only accepts servers identifying as the given
+    /// upstream service.
+    pub fn client_config(&self, upstream: &str) -> ConsulResult<Arc<ClientConfig>> {
+        self.identity.current().client_config(upstream).map(Arc::new)
+    }
+
+    /// This method connects to a random healthy instance of the given upstream
+    /// service, returning the established TLS stream.
+    ///
+    /// Instances are resolved using [Health::list_connect_service_instances],
+    /// so both Connect-native instances and sidecar proxies are considered.
+    #[tracing::instrument]
+    pub async fn connect(&self, upstream: &str) -> ConsulResult<TlsStream<TcpStream>> {
+        let (instances, _) =
+            self.client.list_connect_service_instances(upstream, &[], true, None).await?;
+        let instance = instances
+            .choose(&mut rand::thread_rng())
+            .ok_or_else(|| ConsulError::NoInstances(upstream.to_owned()))?;
+        self.connect_to(upstream, instance).await
+    }
+
+    /// This method connects to the given instance of the upstream service,
+    /// returning the established TLS stream.
+    #[tracing::instrument]
+    pub async fn connect_to(
+        &self,
+        upstream: &str,
+        instance: &ServiceEntry,
+    ) -> ConsulResult<TlsStream<TcpStream>> {
+        let identity = self.identity.current();
+        let address = if instance.service.address.is_empty() {
+            &instance.node.address
+        } else {
+            &instance.service.address
+        };
+        let stream = TcpStream::connect((address.as_str(), instance.service.port)).await?;
+        let sni = service_sni(upstream, &instance.node.datacenter, &identity.trust_domain);
+        let server_name = ServerName::try_from(sni.as_str())
+            .map_err(|_| ConsulError::InvalidCertificate(format!("invalid server name {}", sni)))?;
+        let config = Arc::new(identity.client_config(upstream)?);
+        Ok(TlsConnector::from(config).connect(server_name, stream).await?)
+    }
+}
+
+impl fmt::Debug for ConnectConnector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectConnector").field("service", &self.service).finish()
+    }
+}
+
+/// The certificates used by a [ConnectConnector] to build client
+/// configurations.
+struct ClientIdentity {
+    certs: Vec<Certificate>,
+    key: PrivateKey,
+    roots: Arc<RootCertStore>,
+    trust_domain: String,
+}
+
+impl ClientIdentity {
+    fn new(leaf: &LeafCert, roots: &CARootList) -> ConsulResult<Self> {
+        let (certs, key) = load_leaf(leaf)?;
+        Ok(ClientIdentity {
+            certs,
+            key,
+            roots: Arc::new(load_roots(roots)?),
+            trust_domain: roots.trust_domain.clone(),
+        })
+    }
+
+    /// This method builds a client configuration which only accepts servers
+    /// identifying as the given upstream service.
+    fn client_config(&self, upstream: &str) -> ConsulResult<ClientConfig> {
+        let verifier = SpiffeVerifier {
+            roots: self.roots.clone(),
+            trust_domain: self.trust_domain.clone(),
+            service: upstream.to_owned(),
+        };
+        Ok(ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+            .with_client_auth_cert(self.certs.clone(), self.key.clone())?)
+    }
+}
+
+/// A server certificate verifier checking that the certificate is signed by
+/// the Connect CA, and that its SPIFFE ID matches the expected service.
+///
+/// As with the official Connect clients, the datacenter of the SPIFFE ID is
+/// not checked, so that failover to other datacenters is possible.
+struct SpiffeVerifier {
+    roots: Arc<RootCertStore>,
+    trust_domain: String,
+    service: String,
+}
+
+impl ServerCertVerifier for SpiffeVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        _: &ServerName,
+        _: &mut dyn Iterator<Item = &[u8]>,
+        _: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, Error> {
+        let cert = ParsedCertificate::try_from(end_entity)?;
+        verify_server_cert_signed_by_trust_anchor(&cert, &self.roots, intermediates, now)?;
+        let id: SpiffeId = peer_identity(end_entity)
+            .and_then(|identity| identity.uri.parse())
+            .map_err(|e| Error::InvalidCertificate(CertificateError::Other(Arc::new(e))))?;
+        if !id.trust_domain.eq_ignore_ascii_case(&self.trust_domain) || id.service != self.service {
+            return Err(Error::InvalidCertificate(CertificateError::NotValidForName));
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+/// This function returns the SNI used by Connect to identify the given service
+/// in the default namespace.
+fn service_sni(service: &str, datacenter: &str, trust_domain: &str) -> String {
+    format!("{}.default.{}.internal.{}", service, datacenter, trust_domain)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use tokio_rustls::rustls::{
+        client::{ServerCertVerifier, ServerName},
+        CertificateError, Error,
+    };
+
+    use super::{service_sni, ClientIdentity, SpiffeVerifier};
+    use crate::connect::native::{
+        parse_certs,
+        tests::{leaf, roots},
+    };
+
+    fn verify(service: &str) -> Result<(), Error> {
+        let identity = ClientIdentity::new(&leaf(), &roots()).unwrap();
+        let verifier = SpiffeVerifier {
+            roots: identity.roots.clone(),
+            trust_domain: identity.trust_domain.clone(),
+            service: service.to_owned(),
+        };
+        let cert = parse_certs(&leaf().cert_pem).unwrap().remove(0);
+        let name = ServerName::try_from("web.default.dc1.internal.consul").unwrap();
+        verifier
+            .verify_server_cert(&cert, &[], &name, &mut std::iter::empty(), &[], SystemTime::now())
+            .map(|_| ())
+    }
+
+    #[test]
+    fn test_verify_server_cert() {
+        assert!(verify("web").is_ok());
+        assert!(matches!(
+            verify("db"),
+            Err(Error::InvalidCertificate(CertificateError::NotValidForName))
+        ));
+    }
+
+    #[test]
+    fn test_client_config() {
+        let identity = ClientIdentity::new(&leaf(), &roots()).unwrap();
+        assert!(identity.client_config("db").is_ok());
+    }
+
+    #[test]
+    fn test_service_sni() {
+        assert_eq!(service_sni("db", "dc1", "abc.consul"), "db.default.dc1.internal.abc.consul");
+    }
+}
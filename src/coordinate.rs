@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    sealed::Sealed, Client, ConsulResult, QueryMeta, QueryOptions, WriteMeta, WriteOptions,
+};
+
+/// A network coordinate in the Vivaldi coordinate system used by Consul's
+/// network tomography.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct Coordinate {
+    /// The Euclidean portion of the coordinate.
+    pub vec: Vec<f64>,
+    /// The confidence in the coordinate's value.
+    pub error: f64,
+    /// A distance offset computed from the node's latency to its neighbours.
+    pub adjustment: f64,
+    /// A distance offset accounting for access links, such as a node's
+    /// connection to its router.
+    pub height: f64,
+}
+
+impl Coordinate {
+    /// This method estimates the round trip time between this coordinate and
+    /// another.
+    ///
+    /// This uses the same calculation as Consul itself, and is only
+    /// meaningful for coordinates in the same segment and area.
+    pub fn distance_to(&self, other: &Coordinate) -> Duration {
+        let magnitude = self
+            .vec
+            .iter()
+            .zip(other.vec.iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f64>()
+            .sqrt();
+        let distance = magnitude + self.height + other.height;
+        let adjusted = distance + self.adjustment + other.adjustment;
+        let distance = if adjusted > 0.0 { adjusted } else { distance };
+        Duration::from_secs_f64(distance.max(0.0))
+    }
+}
+
+/// The network coordinate of a node.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CoordinateEntry {
+    /// The name of the node.
+    pub node: String,
+    /// The network segment the node belongs to.
+    pub segment: String,
+    /// The coordinate of the node.
+    pub coord: Coordinate,
+}
+
+impl CoordinateEntry {
+    /// This method estimates the round trip time between this node and
+    /// another. See [Coordinate::distance_to] for more information.
+    pub fn rtt_to(&self, other: &CoordinateEntry) -> Duration {
+        self.coord.distance_to(&other.coord)
+    }
+}
+
+/// The WAN coordinates of the servers in a datacenter.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CoordinateDatacenterMap {
+    /// The name of the datacenter.
+    pub datacenter: String,
+    /// The network area the coordinates belong to.
+    #[serde(rename = "AreaID")]
+    pub area_id: String,
+    /// The coordinates of the servers in the datacenter.
+    pub coordinates: Vec<CoordinateEntry>,
+}
+
+/// This trait provides methods for interacting with the `/coordinate`
+/// endpoints.
+#[async_trait]
+pub trait Coordinates: Sealed {
+    /// This method returns the WAN network coordinates for all Consul servers,
+    /// organized by datacenter.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/coordinate#read-wan-coordinates
+    async fn list_datacenter_coordinates(&self) -> ConsulResult<Vec<CoordinateDatacenterMap>>;
+
+    /// This method returns the LAN network coordinates for all nodes in a
+    /// given datacenter.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/coordinate#read-lan-coordinates-for-all-nodes
+    async fn list_node_coordinates(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<CoordinateEntry>, QueryMeta)>;
+
+    /// This method returns the LAN network coordinates for the given node.
+    /// A node may have a coordinate for each network segment it belongs to.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/coordinate#read-lan-coordinates-for-a-node
+    async fn get_node_coordinates(
+        &self,
+        node: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<CoordinateEntry>, QueryMeta)>;
+
+    /// This method updates the LAN network coordinates for a node in a given
+    /// datacenter.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/coordinate#update-lan-coordinates-for-a-node
+    async fn update_node_coordinates(
+        &self,
+        entry: CoordinateEntry,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>;
+}
+
+#[async_trait]
+impl Coordinates for Client {
+    #[tracing::instrument]
+    async fn list_datacenter_coordinates(&self) -> ConsulResult<Vec<CoordinateDatacenterMap>> {
+        self.get("/v1/coordinate/datacenters", None).await
+    }
+
+    #[tracing::instrument]
+    async fn list_node_coordinates(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<CoordinateEntry>, QueryMeta)> {
+        self.get_with_meta("/v1/coordinate/nodes", None, options)
+            .await
+            .map(|(r, meta): (Option<Vec<CoordinateEntry>>, _)| (r.unwrap_or_default(), meta))
+    }
+
+    #[tracing::instrument]
+    async fn get_node_coordinates(
+        &self,
+        node: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<CoordinateEntry>, QueryMeta)> {
+        let path = format!("/v1/coordinate/node/{}", node);
+        self.get_with_meta(&path, None, options)
+            .await
+            .map(|(r, meta): (Option<Vec<CoordinateEntry>>, _)| (r.unwrap_or_default(), meta))
+    }
+
+    #[tracing::instrument]
+    async fn update_node_coordinates(
+        &self,
+        entry: CoordinateEntry,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta> {
+        self.put_with_empty("/v1/coordinate/update", entry, None, options)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Coordinate;
+
+    #[test]
+    fn test_distance_to() {
+        let a = Coordinate { vec: vec![0.0, 0.0], height: 0.001, ..Default::default() };
+        let b = Coordinate { vec: vec![0.003, 0.004], height: 0.001, ..Default::default() };
+        assert_eq!(a.distance_to(&b), Duration::from_secs_f64(0.007));
+        // negative adjustments are ignored if they would make the distance
+        // negative
+        let c = Coordinate { adjustment: -1.0, ..b.clone() };
+        assert_eq!(a.distance_to(&c), Duration::from_secs_f64(0.007));
+        let d = Coordinate { adjustment: 0.002, ..b };
+        assert_eq!(a.distance_to(&d), Duration::from_secs_f64(0.009));
+    }
+}
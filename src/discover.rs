@@ -0,0 +1,155 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{ready, Stream};
+use tower::discover::Change;
+
+use crate::{watch::WatchStream, Client, ConsulResult, Health, ServiceEntry};
+
+/// A [Discover] of the healthy instances of a service, for load balancing
+/// requests between them with [tower::balance].
+///
+/// The instances are watched with [Health::watch_service], and every change is
+/// yielded as a [Change::Insert] or [Change::Remove] of the service built for
+/// the instance. Instances are keyed by their node and service ID, and are
+/// removed and inserted again if their address or port changes. Failed watch
+/// queries are retried, so the stream never yields an error.
+///
+/// ```no_run
+/// use consul_oxide::{Client, Config, ConsulDiscover};
+///
+/// let client = Client::new(Config::default());
+/// let discover = ConsulDiscover::new(&client, "api", &[], |instance| {
+///     format!("http://{}:{}", instance.address(), instance.service.port)
+/// });
+/// ```
+///
+/// [Discover]: tower::discover::Discover
+/// [tower::balance]: https://docs.rs/tower/latest/tower/balance/index.html
+pub struct ConsulDiscover<S, F> {
+    watch: WatchStream<ConsulResult<Vec<ServiceEntry>>>,
+    make_service: F,
+    /// The address and port of every inserted instance, by key.
+    instances: HashMap<String, (String, u16)>,
+    pending: VecDeque<Change<String, S>>,
+}
+
+impl<S, F: FnMut(&ServiceEntry) -> S> ConsulDiscover<S, F> {
+    /// This method creates a discover of the passing instances of the given
+    /// service with all the given tags, building a service for every instance
+    /// with `make_service`.
+    pub fn new(client: &Client, service: &str, tags: &[&str], make_service: F) -> Self {
+        ConsulDiscover::from_watch(client.watch_service(service, tags, true, None), make_service)
+    }
+
+    /// This method creates a discover of the instances yielded by the given
+    /// watch.
+    fn from_watch(watch: WatchStream<ConsulResult<Vec<ServiceEntry>>>, make_service: F) -> Self {
+        ConsulDiscover { watch, make_service, instances: HashMap::new(), pending: VecDeque::new() }
+    }
+
+    /// This method queues the changes between the inserted instances and the
+    /// given ones.
+    fn update(&mut self, entries: Vec<ServiceEntry>) {
+        let mut instances = HashMap::with_capacity(entries.len());
+        for entry in &entries {
+            let key = format!("{}/{}", entry.node.node, entry.service.id);
+            let endpoint = (entry.address().to_owned(), entry.service.port);
+            match self.instances.remove(&key) {
+                Some(previous) if previous == endpoint => {}
+                Some(_) => {
+                    self.pending.push_back(Change::Remove(key.clone()));
+                    self.pending.push_back(Change::Insert(key.clone(), (self.make_service)(entry)));
+                }
+                None => {
+                    self.pending.push_back(Change::Insert(key.clone(), (self.make_service)(entry)));
+                }
+            }
+            instances.insert(key, endpoint);
+        }
+        // the remaining instances are no longer passing or were deregistered
+        for key in self.instances.drain().map(|(key, _)| key) {
+            self.pending.push_back(Change::Remove(key));
+        }
+        self.instances = instances;
+    }
+}
+
+// the pending services are never pinned, and the watch is already boxed
+impl<S, F> Unpin for ConsulDiscover<S, F> {}
+
+impl<S, F: FnMut(&ServiceEntry) -> S> Stream for ConsulDiscover<S, F> {
+    type Item = Result<Change<String, S>, Infallible>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(change) = this.pending.pop_front() {
+                return Poll::Ready(Some(Ok(change)));
+            }
+            match ready!(this.watch.as_mut().poll_next(cx)) {
+                Some(Ok(entries)) => this.update(entries),
+                Some(Err(e)) => tracing::warn!(error = %e, "failed to watch service instances"),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<S, F> fmt::Debug for ConsulDiscover<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConsulDiscover").field("instances", &self.instances).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{stream, StreamExt};
+    use tower::discover::Change;
+
+    use super::ConsulDiscover;
+    use crate::{ConsulError, ServiceEntry};
+
+    fn entry(node: &str, address: &str, port: u16) -> ServiceEntry {
+        let mut entry = ServiceEntry::default();
+        entry.node.node = node.to_owned();
+        entry.node.address = address.to_owned();
+        entry.service.id = String::from("api");
+        entry.service.port = port;
+        entry
+    }
+
+    #[tokio::test]
+    async fn test_discover() {
+        let watch = stream::iter(vec![
+            Ok(vec![entry("a", "10.0.0.1", 80), entry("b", "10.0.0.2", 80)]),
+            Err(ConsulError::MissingParameter(String::from("test"))),
+            Ok(vec![entry("a", "10.0.0.1", 8080)]),
+        ]);
+        let discover = ConsulDiscover::from_watch(Box::pin(watch), |entry: &ServiceEntry| {
+            format!("{}:{}", entry.address(), entry.service.port)
+        });
+        let changes: Vec<_> = discover
+            .map(|change| match change.unwrap() {
+                Change::Insert(key, service) => format!("+{} {}", key, service),
+                Change::Remove(key) => format!("-{}", key),
+            })
+            .collect()
+            .await;
+        assert_eq!(
+            changes,
+            [
+                "+a/api 10.0.0.1:80",
+                "+b/api 10.0.0.2:80",
+                "-a/api",
+                "+a/api 10.0.0.1:8080",
+                "-b/api"
+            ]
+        );
+    }
+}
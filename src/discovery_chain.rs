@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use http::Method;
+use serde_json::Value;
+
+use crate::{
+    sealed::Sealed, Client, ConsulResult, MeshGatewayConfig, QueryMeta, QueryOptions,
+    ServiceResolverSubset, ServiceRoute, ServiceSplit,
+};
+
+/// The type of a node in a [CompiledDiscoveryChain].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiscoveryGraphNodeType {
+    /// A node routing requests by their L7 properties.
+    Router,
+    /// A node splitting traffic between multiple nodes.
+    Splitter,
+    /// A node resolving requests to a target.
+    #[default]
+    Resolver,
+}
+
+/// A route of a router node in a [CompiledDiscoveryChain].
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct DiscoveryRoute {
+    /// The route as defined in the `service-router` config entry.
+    pub definition: Option<ServiceRoute>,
+    /// The name of the node requests matching the route are sent to.
+    pub next_node: String,
+}
+
+/// A split of a splitter node in a [CompiledDiscoveryChain].
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct DiscoverySplit {
+    /// The split as defined in the `service-splitter` config entry.
+    pub definition: Option<ServiceSplit>,
+    /// The percentage of traffic sent to this split.
+    pub weight: f32,
+    /// The name of the node the traffic is sent to.
+    pub next_node: String,
+}
+
+/// The failover policy of a resolver node in a [CompiledDiscoveryChain].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct DiscoveryFailover {
+    /// The IDs of the targets to fail over to, tried in order.
+    pub targets: Vec<String>,
+}
+
+/// The resolver of a resolver node in a [CompiledDiscoveryChain].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct DiscoveryResolver {
+    /// Whether the resolver was synthesized, because no `service-resolver`
+    /// config entry exists for the service.
+    pub default: bool,
+    /// The timeout for establishing connections to the target, such as "5s".
+    pub connect_timeout: String,
+    /// The ID of the target requests are resolved to.
+    pub target: String,
+    /// The failover policy of the resolver.
+    pub failover: Option<DiscoveryFailover>,
+}
+
+/// A node in a [CompiledDiscoveryChain].
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct DiscoveryGraphNode {
+    /// The type of the node, which determines which of the other fields are
+    /// set.
+    #[serde(rename = "Type")]
+    pub kind: DiscoveryGraphNodeType,
+    /// The name of the node.
+    pub name: String,
+    /// The routes of a router node.
+    pub routes: Vec<DiscoveryRoute>,
+    /// The splits of a splitter node.
+    pub splits: Vec<DiscoverySplit>,
+    /// The resolver of a resolver node.
+    pub resolver: Option<DiscoveryResolver>,
+    /// The load balancing policy of the node.
+    pub load_balancer: Option<Value>,
+}
+
+/// A target of a [CompiledDiscoveryChain], identifying a set of service
+/// instances that requests may be sent to.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct DiscoveryTarget {
+    /// The unique ID of the target, such as `v1.web.default.dc1`.
+    #[serde(rename = "ID")]
+    pub id: String,
+    /// The name of the service.
+    pub service: String,
+    /// The name of the service subset.
+    pub service_subset: String,
+    /// The namespace of the service.
+    pub namespace: String,
+    /// The datacenter of the service.
+    pub datacenter: String,
+    /// The mesh gateway configuration used to reach the target.
+    pub mesh_gateway: MeshGatewayConfig,
+    /// The subset definition selecting the target's instances.
+    pub subset: ServiceResolverSubset,
+    /// Whether the target is outside the mesh.
+    pub external: bool,
+    /// The SNI used to connect to the target.
+    #[serde(rename = "SNI")]
+    pub sni: String,
+    /// The name of the target, as used in Envoy clusters.
+    pub name: String,
+}
+
+/// The compiled discovery chain of a service, describing how requests to the
+/// service are routed, split and resolved.
+///
+/// The chain is a graph starting at `start_node`, whose nodes reference each
+/// other by name and ultimately resolve to `targets`.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct CompiledDiscoveryChain {
+    /// The name of the service the chain was compiled for.
+    pub service_name: String,
+    /// The namespace of the service.
+    pub namespace: String,
+    /// The datacenter the chain was compiled for.
+    pub datacenter: String,
+    /// A hash of the overrides used to compile the chain, if any.
+    pub customization_hash: String,
+    /// Whether the chain was synthesized because no config entries exist for
+    /// the service.
+    pub default: bool,
+    /// The protocol of the service, such as `tcp` or `http`.
+    pub protocol: String,
+    /// The metadata of the service, from its `service-defaults` entry.
+    pub service_meta: HashMap<String, String>,
+    /// The name of the first node of the chain.
+    pub start_node: String,
+    /// The nodes of the chain, keyed by name.
+    pub nodes: HashMap<String, DiscoveryGraphNode>,
+    /// The targets of the chain, keyed by ID.
+    pub targets: HashMap<String, DiscoveryTarget>,
+}
+
+impl CompiledDiscoveryChain {
+    /// This method returns the first node of the chain.
+    pub fn start(&self) -> Option<&DiscoveryGraphNode> {
+        self.nodes.get(&self.start_node)
+    }
+}
+
+/// Overrides used when compiling a discovery chain, used by the
+/// [DiscoveryChain::get_discovery_chain] method.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct DiscoveryChainOverrides {
+    /// Overrides the mesh gateway configuration of the chain's targets.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_mesh_gateway: Option<MeshGatewayConfig>,
+    /// Overrides the protocol of the service.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_protocol: Option<String>,
+    /// Overrides the connect timeout of the chain's resolvers, such as "5s".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_connect_timeout: Option<String>,
+}
+
+/// Response payload of the discovery chain endpoint.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DiscoveryChainResponse {
+    chain: CompiledDiscoveryChain,
+}
+
+/// This trait provides methods for interacting with the `/discovery-chain`
+/// endpoints.
+#[async_trait]
+pub trait DiscoveryChain: Sealed {
+    /// This method returns the compiled discovery chain of the given service.
+    ///
+    /// If `compile_dc` is provided, the chain is compiled as if it were
+    /// requested from that datacenter. If `overrides` are provided, the chain
+    /// is compiled with them applied.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/discovery-chain#read-compiled-discovery-chain
+    async fn get_discovery_chain(
+        &self,
+        service: &str,
+        compile_dc: Option<&str>,
+        overrides: Option<DiscoveryChainOverrides>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(CompiledDiscoveryChain, QueryMeta)>;
+}
+
+#[async_trait]
+impl DiscoveryChain for Client {
+    #[tracing::instrument]
+    async fn get_discovery_chain(
+        &self,
+        service: &str,
+        compile_dc: Option<&str>,
+        overrides: Option<DiscoveryChainOverrides>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(CompiledDiscoveryChain, QueryMeta)> {
+        let mut params = HashMap::new();
+        if let Some(compile_dc) = compile_dc {
+            params.insert(String::from("compile-dc"), compile_dc.to_owned());
+        }
+        // overrides can only be sent in the body of a POST request
+        let method = if overrides.is_some() { Method::POST } else { Method::GET };
+        let path = format!("/v1/discovery-chain/{}", service);
+        self.send_with_meta(method, &path, Some(params), overrides, options).await.map(
+            |(r, meta): (Option<DiscoveryChainResponse>, _)| {
+                (r.map(|r| r.chain).unwrap_or_default(), meta)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CompiledDiscoveryChain, DiscoveryGraphNodeType};
+
+    #[test]
+    fn test_decode_chain() {
+        let chain: CompiledDiscoveryChain = serde_json::from_str(
+            r#"{
+                "ServiceName": "web",
+                "Namespace": "default",
+                "Datacenter": "dc1",
+                "Protocol": "http",
+                "StartNode": "splitter:web.default",
+                "Nodes": {
+                    "splitter:web.default": {
+                        "Type": "splitter",
+                        "Name": "web.default",
+                        "Splits": [
+                            {"Weight": 90, "NextNode": "resolver:v1.web.default.dc1"},
+                            {"Weight": 10, "NextNode": "resolver:v2.web.default.dc1"}
+                        ]
+                    },
+                    "resolver:v1.web.default.dc1": {
+                        "Type": "resolver",
+                        "Name": "v1.web.default.dc1",
+                        "Resolver": {"ConnectTimeout": "5s", "Target": "v1.web.default.dc1"}
+                    }
+                },
+                "Targets": {
+                    "v1.web.default.dc1": {
+                        "ID": "v1.web.default.dc1",
+                        "Service": "web",
+                        "ServiceSubset": "v1",
+                        "Subset": {"Filter": "Service.Meta.version == v1"},
+                        "MeshGateway": {},
+                        "SNI": "v1.web.default.dc1.internal.11111111.consul"
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let start = chain.start().unwrap();
+        assert_eq!(start.kind, DiscoveryGraphNodeType::Splitter);
+        assert_eq!(start.splits[1].weight, 10.0);
+        let resolver = chain.nodes[&start.splits[0].next_node].resolver.as_ref().unwrap();
+        assert_eq!(chain.targets[&resolver.target].service_subset, "v1");
+    }
+}
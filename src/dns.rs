@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+
+use hickory_resolver::{
+    config::{NameServerConfig, ResolverConfig, ResolverOpts},
+    lookup::Lookup,
+    net::{runtime::TokioRuntimeProvider, NetError},
+    proto::rr::{Name, RData},
+    TokioResolver,
+};
+
+use crate::{AgentService, ConsulError, ConsulResult, Node, ServiceEntry};
+
+/// A resolver of services through the [DNS interface] of a Consul agent.
+///
+/// This can be used as a fallback discovery path when the HTTP API is
+/// unavailable. Services are resolved from SRV records, and returned in the
+/// same shape as [Health::list_service_instances]. Since DNS only returns
+/// healthy instances and no metadata, only the node name and address and the
+/// service name, address and port of the entries are set.
+///
+/// Queries are sent over UDP, and sent again over TCP when the response is
+/// truncated, such as for services with many instances.
+///
+/// ```no_run
+/// # async fn run() -> consul_oxide::ConsulResult<()> {
+/// use consul_oxide::DnsResolver;
+///
+/// let resolver = DnsResolver::default().with_datacenter("dc1");
+/// for instance in resolver.resolve_service("web", Some("primary")).await? {
+///     println!("{}:{}", instance.address(), instance.service.port);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [DNS interface]: https://developer.hashicorp.com/consul/docs/services/discovery/dns-overview
+/// [Health::list_service_instances]: crate::Health::list_service_instances
+#[derive(Clone, Debug)]
+pub struct DnsResolver {
+    address: SocketAddr,
+    domain: String,
+    datacenter: Option<String>,
+    timeout: Duration,
+}
+
+impl Default for DnsResolver {
+    fn default() -> Self {
+        DnsResolver::new(SocketAddr::from((Ipv4Addr::LOCALHOST, 8600)))
+    }
+}
+
+impl DnsResolver {
+    /// This method creates a resolver querying the DNS interface at the given
+    /// address, which defaults to `127.0.0.1:8600`.
+    pub fn new(address: SocketAddr) -> Self {
+        DnsResolver {
+            address,
+            domain: String::from("consul"),
+            datacenter: None,
+            timeout: Duration::from_secs(2),
+        }
+    }
+
+    /// This method sets the domain of the DNS interface, which defaults to
+    /// `consul`.
+    pub fn with_domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = domain.into();
+        self
+    }
+
+    /// This method sets the datacenter services are resolved in, which
+    /// defaults to the datacenter of the agent.
+    pub fn with_datacenter(mut self, datacenter: impl Into<String>) -> Self {
+        self.datacenter = Some(datacenter.into());
+        self
+    }
+
+    /// This method sets the time to wait for a response, which defaults to
+    /// two seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// This method returns the healthy instances of the given service,
+    /// optionally only those with the given tag. A service with no healthy
+    /// instances returns an empty list.
+    #[tracing::instrument]
+    pub async fn resolve_service(
+        &self,
+        service: &str,
+        tag: Option<&str>,
+    ) -> ConsulResult<Vec<ServiceEntry>> {
+        let name = self.service_name(service, tag);
+        let lookup = match self.resolver()?.srv_lookup(name.as_str()).await {
+            Ok(lookup) => lookup,
+            // the name does not exist, so the service has no healthy instances
+            Err(e) if e.is_no_records_found() => return Ok(Vec::new()),
+            Err(e) => return Err(dns_error(&name, e)),
+        };
+        Ok(Records::from_lookup(&lookup).into_entries(service, self.datacenter.as_deref()))
+    }
+
+    /// This method returns a resolver querying the DNS interface of the agent
+    /// over UDP, falling back to TCP for truncated responses.
+    fn resolver(&self) -> ConsulResult<TokioResolver> {
+        let mut name_server = NameServerConfig::udp_and_tcp(self.address.ip());
+        for connection in &mut name_server.connections {
+            connection.port = self.address.port();
+        }
+        let config = ResolverConfig::from_name_servers(vec![name_server]);
+        let mut options = ResolverOpts::default();
+        options.timeout = self.timeout;
+        options.attempts = 1;
+        // the agent may return a different instance on every query
+        options.cache_size = 0;
+        TokioResolver::builder_with_config(config, TokioRuntimeProvider::default())
+            .with_options(options)
+            .build()
+            .map_err(|e| ConsulError::DnsError(e.to_string()))
+    }
+
+    /// This method returns the DNS name of the given service.
+    fn service_name(&self, service: &str, tag: Option<&str>) -> String {
+        let mut name = String::new();
+        if let Some(tag) = tag {
+            name.push_str(tag);
+            name.push('.');
+        }
+        name.push_str(service);
+        name.push_str(".service.");
+        if let Some(datacenter) = &self.datacenter {
+            name.push_str(datacenter);
+            name.push('.');
+        }
+        name.push_str(&self.domain);
+        // the name is fully qualified, so that no search domain is appended
+        name.push('.');
+        name
+    }
+}
+
+/// An SRV record.
+#[derive(Debug, Eq, PartialEq)]
+struct SrvRecord {
+    port: u16,
+    target: String,
+}
+
+/// The records of a DNS response.
+#[derive(Debug, Default)]
+struct Records {
+    srv: Vec<SrvRecord>,
+    /// The addresses of the targets of the SRV records, by name.
+    addresses: HashMap<String, IpAddr>,
+}
+
+impl Records {
+    /// This method collects the SRV records of the answers of a lookup, and
+    /// the addresses of their targets from its additional records.
+    fn from_lookup(lookup: &Lookup) -> Self {
+        let mut records = Records::default();
+        for record in lookup.answers() {
+            if let RData::SRV(srv) = &record.data {
+                records.srv.push(SrvRecord { port: srv.port, target: name_to_string(&srv.target) });
+            }
+        }
+        for record in lookup.additionals() {
+            let address = match &record.data {
+                RData::A(a) => IpAddr::V4(a.0),
+                RData::AAAA(aaaa) => IpAddr::V6(aaaa.0),
+                _ => continue,
+            };
+            records.addresses.insert(name_to_string(&record.name), address);
+        }
+        records
+    }
+
+    /// This method converts the records into service entries. Targets whose
+    /// address is not included in the response are skipped.
+    fn into_entries(self, service: &str, datacenter: Option<&str>) -> Vec<ServiceEntry> {
+        let mut entries = Vec::with_capacity(self.srv.len());
+        for srv in self.srv {
+            let address = match self.addresses.get(&srv.target) {
+                Some(address) => address.to_string(),
+                None => continue,
+            };
+            // targets are named `<node>.node.<datacenter>.<domain>`, or
+            // `<hex address>.addr.<datacenter>.<domain>` for services
+            // registered with their own address
+            let mut labels = srv.target.split('.');
+            let node = match (labels.next(), labels.next()) {
+                (Some(node), Some("node")) => node.to_owned(),
+                _ => String::new(),
+            };
+            entries.push(ServiceEntry {
+                node: Node {
+                    node,
+                    address: address.clone(),
+                    datacenter: datacenter
+                        .map(String::from)
+                        .or_else(|| labels.next().map(String::from))
+                        .unwrap_or_default(),
+                    ..Default::default()
+                },
+                service: AgentService {
+                    service: service.to_owned(),
+                    address,
+                    port: srv.port,
+                    ..Default::default()
+                },
+                checks: Vec::new(),
+            });
+        }
+        entries
+    }
+}
+
+/// This function returns the given name without its trailing dot.
+fn name_to_string(name: &Name) -> String {
+    name.to_utf8().trim_end_matches('.').to_owned()
+}
+
+/// This function converts an error of the query for the given name.
+fn dns_error(name: &str, e: NetError) -> ConsulError {
+    ConsulError::DnsError(format!("query for {} failed, {}", name, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    use hickory_resolver::proto::{
+        op::{Message, OpCode},
+        rr::{rdata::SRV, Name, RData, Record},
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, UdpSocket},
+    };
+
+    use super::DnsResolver;
+
+    #[test]
+    fn test_service_name() {
+        let resolver = DnsResolver::default();
+        assert_eq!(resolver.service_name("web", None), "web.service.consul.");
+        let resolver = resolver.with_datacenter("dc1").with_domain("example");
+        assert_eq!(resolver.service_name("web", Some("v1")), "v1.web.service.dc1.example.");
+    }
+
+    /// This function returns the response of the agent to the given query,
+    /// with the answers only if it is not truncated.
+    fn response(query: &[u8], truncated: bool) -> Vec<u8> {
+        let query = Message::from_vec(query).unwrap();
+        let mut response = Message::response(query.metadata.id, OpCode::Query);
+        response.metadata.truncation = truncated;
+        response.add_query(query.queries[0].clone());
+        if !truncated {
+            let name = query.queries[0].name().clone();
+            for node in ["node1", "node2"] {
+                let target = Name::from_ascii(format!("{}.node.dc1.consul.", node)).unwrap();
+                let srv = SRV::new(1, 1, 8080, target);
+                response.add_answer(Record::from_rdata(name.clone(), 0, RData::SRV(srv)));
+            }
+            // the address of the second target is missing from the response
+            let target = Name::from_ascii("node1.node.dc1.consul.").unwrap();
+            let address = RData::A(Ipv4Addr::new(10, 0, 0, 1).into());
+            response.add_additional(Record::from_rdata(target, 0, address));
+        }
+        response.to_vec().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_service_truncated() {
+        let udp = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let address: SocketAddr = udp.local_addr().unwrap();
+        let tcp = TcpListener::bind(address).await.unwrap();
+        tokio::spawn(async move {
+            let mut query = vec![0; 512];
+            let (len, peer) = udp.recv_from(&mut query).await.unwrap();
+            udp.send_to(&response(&query[..len], true), peer).await.unwrap();
+        });
+        tokio::spawn(async move {
+            let (mut stream, _) = tcp.accept().await.unwrap();
+            let mut query = vec![0; stream.read_u16().await.unwrap() as usize];
+            stream.read_exact(&mut query).await.unwrap();
+            let response = response(&query, false);
+            stream.write_u16(response.len() as u16).await.unwrap();
+            stream.write_all(&response).await.unwrap();
+        });
+
+        let entries = DnsResolver::new(address).resolve_service("web", None).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].node.node, "node1");
+        assert_eq!(entries[0].node.datacenter, "dc1");
+        assert_eq!(entries[0].address(), "10.0.0.1");
+        assert_eq!(entries[0].service.port, 8080);
+    }
+}
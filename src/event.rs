@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    sealed::Sealed, Client, ConsulResult, QueryMeta, QueryOptions, WriteMeta, WriteOptions,
+};
+
+/// A user event fired through the agent.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct UserEvent {
+    /// The unique ID of the event.
+    #[serde(rename = "ID")]
+    pub id: String,
+    /// The name of the event.
+    pub name: String,
+    /// The payload of the event, decoded from base64.
+    #[serde(serialize_with = "serialize_payload", deserialize_with = "deserialize_payload")]
+    pub payload: Option<Vec<u8>>,
+    /// A regular expression to filter the nodes receiving the event by name.
+    pub node_filter: String,
+    /// A regular expression to filter the nodes receiving the event by the
+    /// services they host.
+    pub service_filter: String,
+    /// A regular expression to filter the nodes receiving the event by service
+    /// tag. Requires `service_filter` to be set.
+    pub tag_filter: String,
+    pub version: u64,
+    /// The Lamport time of the event.
+    #[serde(rename = "LTime")]
+    pub ltime: u64,
+}
+
+/// Filters restricting the nodes an event is delivered to, used by the
+/// [Event::fire_event] and [Event::list_events] methods.
+#[derive(Clone, Default, Debug)]
+pub struct EventFilter {
+    /// A regular expression to filter by node name.
+    pub node: Option<String>,
+    /// A regular expression to filter by service name.
+    pub service: Option<String>,
+    /// A regular expression to filter by service tag. This must be used
+    /// alongside `service`.
+    pub tag: Option<String>,
+}
+
+impl EventFilter {
+    /// This method converts the filter into request parameters.
+    fn into_params(self) -> HashMap<String, String> {
+        let mut params = HashMap::new();
+        if let Some(node) = self.node {
+            params.insert(String::from("node"), node);
+        }
+        if let Some(service) = self.service {
+            params.insert(String::from("service"), service);
+        }
+        if let Some(tag) = self.tag {
+            params.insert(String::from("tag"), tag);
+        }
+        params
+    }
+}
+
+/// This function encodes the payload of an event as base64, as returned by
+/// Consul.
+fn serialize_payload<S>(payload: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    payload.as_ref().map(base64::encode).serialize(serializer)
+}
+
+/// This function decodes the base64 encoded payload of an event.
+fn deserialize_payload<'de, D>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|payload| base64::decode(payload).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// This trait provides methods for interacting with the `/event` endpoints.
+#[async_trait]
+pub trait Event: Sealed {
+    /// This method triggers a new user event, with an optional payload. The
+    /// event is delivered to all nodes matching the given filter.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/event#fire-event
+    async fn fire_event(
+        &self,
+        name: &str,
+        payload: Option<Vec<u8>>,
+        filter: Option<EventFilter>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(UserEvent, WriteMeta)>;
+
+    /// This method returns the most recent events known by the agent,
+    /// optionally only those with the given name or matching the given
+    /// filter.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/event#list-events
+    async fn list_events(
+        &self,
+        name: Option<&str>,
+        filter: Option<EventFilter>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<UserEvent>, QueryMeta)>;
+}
+
+#[async_trait]
+impl Event for Client {
+    #[tracing::instrument]
+    async fn fire_event(
+        &self,
+        name: &str,
+        payload: Option<Vec<u8>>,
+        filter: Option<EventFilter>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(UserEvent, WriteMeta)> {
+        let params = filter.unwrap_or_default().into_params();
+        let path = format!("/v1/event/fire/{}", name);
+        self.put_raw(&path, payload.unwrap_or_default(), Some(params), options).await
+    }
+
+    #[tracing::instrument]
+    async fn list_events(
+        &self,
+        name: Option<&str>,
+        filter: Option<EventFilter>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<UserEvent>, QueryMeta)> {
+        let mut params = filter.unwrap_or_default().into_params();
+        if let Some(name) = name {
+            params.insert(String::from("name"), name.to_owned());
+        }
+        self.get_with_meta("/v1/event/list", Some(params), options)
+            .await
+            .map(|(r, meta): (Option<Vec<UserEvent>>, _)| (r.unwrap_or_default(), meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UserEvent;
+
+    #[test]
+    fn test_decode_payload() {
+        let event: UserEvent =
+            serde_json::from_str(r#"{"ID":"b54fe110","Name":"deploy","Payload":"MTYwOQ=="}"#)
+                .unwrap();
+        assert_eq!(event.payload.as_deref(), Some("1609".as_bytes()));
+        let event: UserEvent =
+            serde_json::from_str(r#"{"ID":"b54fe110","Name":"deploy","Payload":null}"#).unwrap();
+        assert_eq!(event.payload, None);
+    }
+
+    #[test]
+    fn test_encode_payload() {
+        let event = UserEvent {
+            id: String::from("b54fe110"),
+            payload: Some(b"1609".to_vec()),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["Payload"], "MTYwOQ==");
+        assert_eq!(serde_json::from_value::<UserEvent>(json).unwrap(), event);
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{Client, Config, ConsulError, ConsulResult, Status};
+
+/// The default interval between checks of the primary's health while reads are
+/// failed over to a secondary.
+const DEFAULT_RECOVERY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A client failing reads over between several Consul agents or datacenters.
+///
+/// Reads are sent to the primary first. If it cannot be reached, or responds
+/// that its datacenter has no known leader, the read is retried against the
+/// secondaries in order, and the first one to succeed serves subsequent reads.
+/// While failed over, the health of the primary is checked at most once per
+/// recovery interval, and reads return to it once it has a leader again.
+///
+/// Writes are not failed over, as they may not be safe to repeat against
+/// another datacenter, and should be sent to [FailoverClient::primary].
+///
+/// ```no_run
+/// # async fn run() -> consul_oxide::ConsulResult<()> {
+/// use consul_oxide::{Config, FailoverClient, KV};
+///
+/// let primary = Config::builder().address("http://consul.dc1:8500").build().unwrap();
+/// let secondary = Config::builder().address("http://consul.dc2:8500").build().unwrap();
+/// let client = FailoverClient::new(primary, vec![secondary]);
+/// let (keys, _) = client.read(|client| async move { client.list_keys("config", None, None).await }).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct FailoverClient {
+    clients: Arc<[Client]>,
+    recovery_interval: Duration,
+    state: Arc<Mutex<FailoverState>>,
+}
+
+/// The state of a [FailoverClient], shared between its clones.
+#[derive(Debug, Default)]
+struct FailoverState {
+    /// The index of the client serving reads.
+    active: usize,
+    /// The time the primary was last failed over from or checked.
+    last_check: Option<Instant>,
+}
+
+impl FailoverClient {
+    /// This method creates a new failover client, sending reads to the primary
+    /// configuration and failing over to the secondaries in order.
+    pub fn new(primary: Config, secondaries: Vec<Config>) -> Self {
+        let clients = std::iter::once(primary).chain(secondaries).map(Client::new).collect();
+        FailoverClient {
+            clients,
+            recovery_interval: DEFAULT_RECOVERY_INTERVAL,
+            state: Arc::default(),
+        }
+    }
+
+    /// This method sets the interval between checks of the primary's health
+    /// while reads are failed over. Defaults to 30 seconds.
+    pub fn with_recovery_interval(mut self, recovery_interval: Duration) -> Self {
+        self.recovery_interval = recovery_interval;
+        self
+    }
+
+    /// This method returns the client of the primary configuration.
+    pub fn primary(&self) -> &Client {
+        &self.clients[0]
+    }
+
+    /// This method returns the client currently serving reads.
+    pub fn active(&self) -> &Client {
+        &self.clients[self.state.lock().unwrap().active]
+    }
+
+    /// This method returns whether reads are currently failed over to a
+    /// secondary.
+    pub fn is_failed_over(&self) -> bool {
+        self.state.lock().unwrap().active != 0
+    }
+
+    /// This method performs the given read against the active client, failing
+    /// over to the other clients if it cannot be served. If every client
+    /// fails, the error of the last one is returned.
+    #[tracing::instrument(skip(read))]
+    pub async fn read<T, F, Fut>(&self, read: F) -> ConsulResult<T>
+    where
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = ConsulResult<T>>,
+    {
+        self.recover().await;
+        let active = self.state.lock().unwrap().active;
+        let order = std::iter::once(active).chain((0..self.clients.len()).filter(|&i| i != active));
+        let mut last_error = None;
+        for index in order {
+            match read(self.clients[index].clone()).await {
+                Err(e) if should_fail_over(&e) => last_error = Some(e),
+                result => {
+                    if index != active {
+                        let mut state = self.state.lock().unwrap();
+                        state.active = index;
+                        state.last_check = Some(Instant::now());
+                    }
+                    return result;
+                }
+            }
+        }
+        Err(last_error.expect("a failover client has at least one client"))
+    }
+
+    /// This method returns reads to the primary if they are failed over, the
+    /// recovery interval has passed and the primary has a leader again.
+    async fn recover(&self) {
+        {
+            let mut state = self.state.lock().unwrap();
+            let due = state.last_check.is_none_or(|last| last.elapsed() >= self.recovery_interval);
+            if state.active == 0 || !due {
+                return;
+            }
+            state.last_check = Some(Instant::now());
+        }
+        if let Ok(leader) = self.primary().get_leader(None).await {
+            if !leader.is_empty() {
+                self.state.lock().unwrap().active = 0;
+            }
+        }
+    }
+}
+
+/// This function returns whether a read failing with the given error should be
+/// retried against another client.
+fn should_fail_over(error: &ConsulError) -> bool {
+    match error {
+        error if error.is_connect() || error.is_timeout() => true,
+        ConsulError::RequestFailed(response) => {
+            let message = response.message.to_lowercase();
+            message.contains("no cluster leader") || message.contains("no known leader")
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::should_fail_over;
+    #[cfg(feature = "reqwest")]
+    use super::FailoverClient;
+    #[cfg(feature = "reqwest")]
+    use crate::{
+        request::tests::{serve, LEADER},
+        Config, Status,
+    };
+    use crate::{ConsulError, ErrorResponse};
+
+    #[test]
+    fn test_should_fail_over() {
+        let error = |message: &str| ErrorResponse {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            url: String::from("http://localhost:8500/v1/kv/a"),
+            message: message.to_owned(),
+        };
+        assert!(should_fail_over(&ConsulError::RequestFailed(error("No cluster leader"))));
+        assert!(!should_fail_over(&ConsulError::RequestFailed(error("internal error"))));
+        assert!(!should_fail_over(&ConsulError::NotFound(error("No cluster leader"))));
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_failover() {
+        // bind and drop a listener to find a port nothing is listening on
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let primary = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let (secondary, server) = serve(&[
+            ("200 OK", "", LEADER),
+            ("500 Internal Server Error", "", "No cluster leader"),
+        ])
+        .await;
+
+        let config = |address: &str| Config::builder().address(address).build().unwrap();
+        let client = FailoverClient::new(config(&primary), vec![config(&secondary)]);
+        let leader = client.read(|client| async move { client.get_leader(None).await }).await;
+        assert_eq!(leader.unwrap(), "10.0.0.1:8300");
+        assert!(client.is_failed_over());
+
+        let error = client.read(|client| async move { client.get_leader(None).await }).await;
+        assert!(error.unwrap_err().is_connect());
+        server.await.unwrap();
+    }
+}
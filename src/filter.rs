@@ -0,0 +1,172 @@
+use std::{fmt, ops::Not};
+
+/// A [filter expression], used to filter the results of list endpoints with
+/// [QueryOptions::filter].
+///
+/// Filters are built from the selectors of the fields to filter on, and can
+/// be combined with [Filter::and], [Filter::or] and negated with `!`. Values
+/// are quoted and escaped, so they may contain arbitrary characters.
+///
+/// ```
+/// use consul_oxide::{Filter, QueryOptions};
+///
+/// let filter = Filter::field("ServiceMeta.version")
+///     .eq("2")
+///     .and(!Filter::field("ServiceTags").contains("canary"));
+/// assert_eq!(
+///     filter.to_string(),
+///     r#"(ServiceMeta.version == "2") and (not ("canary" in ServiceTags))"#
+/// );
+/// let options = QueryOptions { filter: Some(filter.into()), ..Default::default() };
+/// ```
+///
+/// [filter expression]: https://www.consul.io/api-docs/features/filtering
+/// [QueryOptions::filter]: crate::QueryOptions::filter
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Filter(String);
+
+impl Filter {
+    /// This method starts a filter on the field with the given selector, such
+    /// as `Service.Meta.version`.
+    pub fn field(selector: &str) -> FilterField {
+        FilterField { selector: selector.to_owned() }
+    }
+
+    /// This method creates a filter from a raw expression, which is used as
+    /// is.
+    pub fn raw(expression: &str) -> Self {
+        Filter(expression.to_owned())
+    }
+
+    /// This method returns a filter matching when both this filter and the
+    /// given one match.
+    pub fn and(self, other: Filter) -> Self {
+        Filter(format!("({}) and ({})", self.0, other.0))
+    }
+
+    /// This method returns a filter matching when either this filter or the
+    /// given one match.
+    pub fn or(self, other: Filter) -> Self {
+        Filter(format!("({}) or ({})", self.0, other.0))
+    }
+}
+
+impl Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Self::Output {
+        Filter(format!("not ({})", self.0))
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<Filter> for String {
+    fn from(filter: Filter) -> Self {
+        filter.0
+    }
+}
+
+/// A field of a [Filter], created by [Filter::field].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FilterField {
+    selector: String,
+}
+
+impl FilterField {
+    /// This method returns a filter matching when the field equals the given
+    /// value.
+    pub fn eq(self, value: impl AsRef<str>) -> Filter {
+        self.binary("==", value.as_ref())
+    }
+
+    /// This method returns a filter matching when the field does not equal the
+    /// given value.
+    pub fn ne(self, value: impl AsRef<str>) -> Filter {
+        self.binary("!=", value.as_ref())
+    }
+
+    /// This method returns a filter matching when the field, which must be a
+    /// string, list or map, is empty.
+    pub fn is_empty(self) -> Filter {
+        Filter(format!("{} is empty", self.selector))
+    }
+
+    /// This method returns a filter matching when the field, which must be a
+    /// string, list or map, is not empty.
+    pub fn is_not_empty(self) -> Filter {
+        Filter(format!("{} is not empty", self.selector))
+    }
+
+    /// This method returns a filter matching when the field contains the given
+    /// value. For strings, this matches substrings, for lists their elements
+    /// and for maps their keys.
+    pub fn contains(self, value: impl AsRef<str>) -> Filter {
+        Filter(format!("{} in {}", quote(value.as_ref()), self.selector))
+    }
+
+    /// This method returns a filter matching when the field does not contain
+    /// the given value.
+    pub fn not_contains(self, value: impl AsRef<str>) -> Filter {
+        Filter(format!("{} not in {}", quote(value.as_ref()), self.selector))
+    }
+
+    /// This method returns a filter matching when the field, which must be a
+    /// string, matches the given regular expression.
+    pub fn matches(self, pattern: impl AsRef<str>) -> Filter {
+        self.binary("matches", pattern.as_ref())
+    }
+
+    /// This method returns a filter matching when the field, which must be a
+    /// string, does not match the given regular expression.
+    pub fn not_matches(self, pattern: impl AsRef<str>) -> Filter {
+        self.binary("not matches", pattern.as_ref())
+    }
+
+    fn binary(self, operator: &str, value: &str) -> Filter {
+        Filter(format!("{} {} {}", self.selector, operator, quote(value)))
+    }
+}
+
+/// This function quotes the given value as a filter string literal.
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Filter;
+
+    #[test]
+    fn test_filter() {
+        let filter = Filter::field("Service.Meta.version")
+            .eq("2")
+            .or(Filter::field("Service.Tags").is_empty())
+            .and(!Filter::field("Node.Node").matches(r#"^web-"\d+$"#));
+        assert_eq!(
+            filter.to_string(),
+            r#"((Service.Meta.version == "2") or (Service.Tags is empty)) and (not (Node.Node matches "^web-\"\\d+$"))"#
+        );
+    }
+
+    #[test]
+    fn test_filter_contains() {
+        assert_eq!(
+            Filter::field("ServiceTags").not_contains("a b").to_string(),
+            r#""a b" not in ServiceTags"#
+        );
+    }
+}
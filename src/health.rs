@@ -1,43 +1,18 @@
 use std::collections::HashMap;
 
 use async_trait::async_trait;
+use url::form_urlencoded;
 
-use crate::{sealed::Sealed, AgentService, Client, ConsulResult, QueryOptions};
-
-/// A registered service health check. Returned with its associated
-/// [ServiceEntry] instance by [Health::list_service_instances].
-#[derive(Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
-#[serde(default, rename_all = "PascalCase")]
-pub struct HealthCheck {
-    pub node: String,
-    #[serde(rename = "CheckID")]
-    pub check_id: String,
-    pub name: String,
-    pub status: String,
-    pub notes: String,
-    pub output: String,
-    #[serde(rename = "ServiceID")]
-    pub service_id: String,
-    pub servicename: String,
-    pub servicetags: Option<Vec<String>>,
-}
-
-#[derive(Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
-#[serde(default, rename_all = "PascalCase")]
-pub struct Node {
-    #[serde(rename = "ID")]
-    pub id: String,
-    pub node: String,
-    pub address: String,
-    pub datacenter: Option<String>,
-    pub taggedaddresses: Option<HashMap<String, String>>,
-    pub meta: Option<HashMap<String, String>>,
-    pub createindex: u64,
-    pub modifyindex: u64,
-}
+use crate::{
+    aggregated_status,
+    sealed::Sealed,
+    watch::{self, WatchStream},
+    AgentService, CheckStatus, Client, ConsulResult, HealthCheck, Node, QueryMeta, QueryOptions,
+    ResolveOptions, ResolvedService,
+};
 
 /// An [AgentService] with its associated [HealthCheck]s.
-#[derive(Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[derive(Clone, Eq, Default, PartialEq, Serialize, Deserialize, Debug)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct ServiceEntry {
     /// The node the service is associated with.
@@ -48,11 +23,35 @@ pub struct ServiceEntry {
     pub checks: Vec<HealthCheck>,
 }
 
+impl ServiceEntry {
+    /// This method returns the address of the service instance, which is the
+    /// address of its node unless the service registered its own.
+    pub fn address(&self) -> &str {
+        if self.service.address.is_empty() {
+            &self.node.address
+        } else {
+            &self.service.address
+        }
+    }
+
+    /// This method returns the aggregated status of the instance, computed
+    /// from the checks of both the service and its node with
+    /// [aggregated_status].
+    pub fn aggregated_status(&self) -> CheckStatus {
+        aggregated_status(&self.checks)
+    }
+}
+
 /// This trait provides methods for interacting with the `/health` endpoints.
 #[async_trait]
 pub trait Health: Sealed {
-    /// This endpoint returns the checks associated with the service provided on
-    /// the path.
+    /// This endpoint returns the instances of the given service, alongside
+    /// their nodes and health checks.
+    ///
+    /// Only instances with all the given `tags` are returned. If
+    /// `passing_only` is set, only instances whose checks are all passing are
+    /// returned. The results can be further filtered with
+    /// [QueryOptions::filter], and sorted with [QueryOptions::near].
     ///
     /// For more information, see the relevant endpoint's [API documentation].
     ///
@@ -60,10 +59,87 @@ pub trait Health: Sealed {
     async fn list_service_instances(
         &self,
         service: &str,
-        tag: Option<&str>,
+        tags: &[&str],
         passing_only: bool,
         options: Option<QueryOptions>,
-    ) -> ConsulResult<Vec<ServiceEntry>>;
+    ) -> ConsulResult<(Vec<ServiceEntry>, QueryMeta)>;
+
+    /// This method returns the Connect-capable instances of the given service,
+    /// which are either Connect-native services or the proxies of the
+    /// service. The results are filtered as with
+    /// [Health::list_service_instances].
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/health#list-nodes-for-connect-capable-service
+    async fn list_connect_service_instances(
+        &self,
+        service: &str,
+        tags: &[&str],
+        passing_only: bool,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<ServiceEntry>, QueryMeta)>;
+
+    /// This method returns the checks registered on the given node.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/health#list-checks-for-node
+    async fn node_checks(
+        &self,
+        node: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<HealthCheck>, QueryMeta)>;
+
+    /// This method returns the checks associated with the given service.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/health#list-checks-for-service
+    async fn service_checks(
+        &self,
+        service: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<HealthCheck>, QueryMeta)>;
+
+    /// This method returns the checks in the given state, or all checks if
+    /// `state` is [None]. Checks in the [CheckStatus::Maintenance] state are
+    /// the critical checks which put their node or service in maintenance
+    /// mode.
+    ///
+    /// For more information, see the relevant endpoint's [API documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/health#list-checks-in-state
+    async fn checks_in_state(
+        &self,
+        state: Option<CheckStatus>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<HealthCheck>, QueryMeta)>;
+
+    /// This method returns a [WatchStream] that yields the instances of the
+    /// given service every time they change.
+    ///
+    /// The stream is driven by blocking queries against the same endpoint as
+    /// [Health::list_service_instances]. Results are only yielded when the
+    /// returned index changes, so identical results are not repeated.
+    fn watch_service(
+        &self,
+        service: &str,
+        tags: &[&str],
+        passing_only: bool,
+        options: Option<QueryOptions>,
+    ) -> WatchStream<ConsulResult<Vec<ServiceEntry>>>;
+
+    /// This method returns the healthy instances of the given service, from
+    /// which a single instance can be selected with [ResolvedService::select].
+    ///
+    /// As in the responses of the Consul DNS interface, instances with warning
+    /// checks are healthy, but instances with critical checks are not.
+    async fn resolve_service(
+        &self,
+        service: &str,
+        options: ResolveOptions,
+    ) -> ConsulResult<ResolvedService>;
 }
 
 #[async_trait]
@@ -72,45 +148,276 @@ impl Health for Client {
     async fn list_service_instances(
         &self,
         service: &str,
-        tag: Option<&str>,
+        tags: &[&str],
+        passing_only: bool,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<ServiceEntry>, QueryMeta)> {
+        let path = service_path("/v1/health/service", service, tags);
+        self.get_with_meta(&path, Some(service_params(passing_only)), options)
+            .await
+            .map(|(r, meta): (Option<Vec<ServiceEntry>>, _)| (r.unwrap_or_default(), meta))
+    }
+
+    #[tracing::instrument]
+    async fn list_connect_service_instances(
+        &self,
+        service: &str,
+        tags: &[&str],
         passing_only: bool,
         options: Option<QueryOptions>,
-    ) -> ConsulResult<Vec<ServiceEntry>> {
-        let mut params = HashMap::new();
-        let path = format!("/v1/health/service/{}", service);
-        if passing_only {
-            params.insert(String::from("passing"), String::from("1"));
+    ) -> ConsulResult<(Vec<ServiceEntry>, QueryMeta)> {
+        let path = service_path("/v1/health/connect", service, tags);
+        self.get_with_meta(&path, Some(service_params(passing_only)), options)
+            .await
+            .map(|(r, meta): (Option<Vec<ServiceEntry>>, _)| (r.unwrap_or_default(), meta))
+    }
+
+    #[tracing::instrument]
+    async fn node_checks(
+        &self,
+        node: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<HealthCheck>, QueryMeta)> {
+        let path = format!("/v1/health/node/{}", node);
+        self.get_with_meta(&path, None, options)
+            .await
+            .map(|(r, meta): (Option<Vec<HealthCheck>>, _)| (r.unwrap_or_default(), meta))
+    }
+
+    #[tracing::instrument]
+    async fn service_checks(
+        &self,
+        service: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<HealthCheck>, QueryMeta)> {
+        let path = format!("/v1/health/checks/{}", service);
+        self.get_with_meta(&path, None, options)
+            .await
+            .map(|(r, meta): (Option<Vec<HealthCheck>>, _)| (r.unwrap_or_default(), meta))
+    }
+
+    #[tracing::instrument]
+    async fn checks_in_state(
+        &self,
+        state: Option<CheckStatus>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<HealthCheck>, QueryMeta)> {
+        let maintenance = state == Some(CheckStatus::Maintenance);
+        let state = match state {
+            Some(CheckStatus::Maintenance) => CheckStatus::Critical.as_str(),
+            Some(state) => state.as_str(),
+            None => "any",
+        };
+        let path = format!("/v1/health/state/{}", state);
+        let (checks, meta) = self
+            .get_with_meta(&path, None, options)
+            .await
+            .map(|(r, meta): (Option<Vec<HealthCheck>>, _)| (r.unwrap_or_default(), meta))?;
+        if maintenance {
+            return Ok((checks.into_iter().filter(HealthCheck::is_maintenance).collect(), meta));
         }
-        if let Some(tag) = tag {
-            params.insert(String::from("tag"), tag.to_owned());
+        Ok((checks, meta))
+    }
+
+    fn watch_service(
+        &self,
+        service: &str,
+        tags: &[&str],
+        passing_only: bool,
+        options: Option<QueryOptions>,
+    ) -> WatchStream<ConsulResult<Vec<ServiceEntry>>> {
+        let client = self.clone();
+        let service = service.to_owned();
+        let tags: Vec<String> = tags.iter().map(|tag| String::from(*tag)).collect();
+        watch::watch(options, move |options| {
+            let client = client.clone();
+            let service = service.clone();
+            let tags = tags.clone();
+            async move {
+                let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+                client.list_service_instances(&service, &tags, passing_only, Some(options)).await
+            }
+        })
+    }
+
+    #[tracing::instrument]
+    async fn resolve_service(
+        &self,
+        service: &str,
+        options: ResolveOptions,
+    ) -> ConsulResult<ResolvedService> {
+        let tags: Vec<&str> = options.tags.iter().map(String::as_str).collect();
+        let query_options = Some(options.query_options());
+        let (entries, _) =
+            self.list_service_instances(service, &tags, false, query_options).await?;
+        Ok(ResolvedService::new(entries, options.strategy))
+    }
+}
+
+/// This function builds the path of the service health endpoints.
+///
+/// The `tag` parameter is repeated for each tag, which cannot be expressed by
+/// the parameter map, so it is encoded into the path instead.
+fn service_path(prefix: &str, service: &str, tags: &[&str]) -> String {
+    let mut path = format!("{}/{}", prefix, service);
+    if !tags.is_empty() {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        for tag in tags {
+            query.append_pair("tag", tag);
         }
-        self.get(&path, options).await
+        path.push('?');
+        path.push_str(&query.finish());
+    }
+    path
+}
+
+/// This function builds the parameters shared by the service health
+/// endpoints.
+fn service_params(passing_only: bool) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    if passing_only {
+        params.insert(String::from("passing"), String::from("1"));
     }
+    params
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Client, Config, Health};
+    use http::Method;
+
+    use super::{service_params, service_path};
+    use crate::{
+        request::tests::recording_client, CheckStatus, Client, Config, Health, HealthCheck, Near,
+        QueryOptions, ResolveOptions, ServiceEntry,
+    };
 
     #[tokio::test]
     async fn test_list_service_instances() {
         let config = Config::default();
         let client = Client::new(config);
         // An existing service for a agent in dev mode
-        let snodes = client
-            .list_service_instances("consul", Option::None, true, Option::None)
-            .await
-            .unwrap();
+        let (snodes, _) =
+            client.list_service_instances("consul", &[], true, Option::None).await.unwrap();
         {
             assert!(!snodes.is_empty(), "should have at least one Service Node");
         }
         // A non existing, should be empty
-        let snodes = client
-            .list_service_instances("non-existing-service", Option::None, true, Option::None)
+        let (snodes, _) = client
+            .list_service_instances("non-existing-service", &[], true, Option::None)
             .await
             .unwrap();
         {
             assert_eq!(snodes.len(), 0);
         }
     }
+
+    #[tokio::test]
+    async fn test_resolve_service() {
+        let client = Client::new(Config::default());
+        let resolved = client.resolve_service("consul", ResolveOptions::default()).await.unwrap();
+        assert_eq!(resolved.select().unwrap().service.service, "consul");
+    }
+
+    #[test]
+    fn test_service_path() {
+        let client = Client::new(Config::default());
+        let path = service_path("/v1/health/service", "web", &["v1", "eu west"]);
+        let options = QueryOptions { near: Some(Near::Agent), ..Default::default() };
+        let request = client.build_request(
+            Method::GET,
+            path,
+            Some(service_params(true)),
+            None::<()>,
+            Some(options),
+        );
+        assert_eq!(request.uri().path(), "/v1/health/service/web");
+        let query = request.uri().query().unwrap_or_default();
+        let mut pairs: Vec<_> =
+            url::form_urlencoded::parse(query.as_bytes()).into_owned().collect();
+        pairs.sort();
+        assert_eq!(
+            pairs,
+            [("near", "_agent"), ("passing", "1"), ("tag", "eu west"), ("tag", "v1")]
+                .map(|(k, v)| (k.to_owned(), v.to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_checks_in_state() {
+        let client = Client::new(Config::default());
+        let (checks, _) = client.checks_in_state(None, None).await.unwrap();
+        // the serf health check of the dev agent is always registered
+        assert!(checks.iter().any(|check| check.check_id == "serfHealth"));
+    }
+
+    #[tokio::test]
+    async fn test_service_checks() {
+        const CHECKS: &str = r#"[{
+            "Node": "foobar",
+            "CheckID": "service:redis",
+            "Name": "Service 'redis' check",
+            "Status": "warning",
+            "Notes": "",
+            "Output": "HTTP GET http://10.1.10.12:6379/health: 429 Too Many Requests",
+            "ServiceID": "redis",
+            "ServiceName": "redis",
+            "ServiceTags": ["primary"],
+            "Type": "http",
+            "ExposedPort": 0,
+            "Definition": {
+                "HTTP": "http://10.1.10.12:6379/health",
+                "Header": {"Accept": ["application/json"]},
+                "Interval": "10s",
+                "Timeout": "5s",
+                "DeregisterCriticalServiceAfter": "0s"
+            },
+            "CreateIndex": 10,
+            "ModifyIndex": 12
+        }]"#;
+        let (client, requests) = recording_client(&[(200, &[], CHECKS)]);
+        let (checks, _) = client.service_checks("redis", None).await.unwrap();
+        assert_eq!(checks[0].status, CheckStatus::Warning);
+        assert_eq!(checks[0].service_name, "redis");
+        assert_eq!(checks[0].service_tags, ["primary"]);
+        assert_eq!(checks[0].check_type, "http");
+        assert_eq!(checks[0].definition.http, "http://10.1.10.12:6379/health");
+        assert_eq!(checks[0].definition.header["Accept"], ["application/json"]);
+        assert_eq!(checks[0].definition.interval, "10s");
+        assert_eq!(checks[0].modify_index, 12);
+        assert_eq!(*requests.lock().unwrap(), ["GET /v1/health/checks/redis"]);
+    }
+
+    #[test]
+    fn test_aggregated_status() {
+        let check = |check_id: &str, status| HealthCheck {
+            check_id: check_id.to_owned(),
+            status,
+            ..Default::default()
+        };
+        let mut entry = ServiceEntry::default();
+        assert_eq!(entry.aggregated_status(), CheckStatus::Passing);
+        entry.checks.push(check("serfHealth", CheckStatus::Passing));
+        entry.checks.push(check("service:web", CheckStatus::Warning));
+        assert_eq!(entry.aggregated_status(), CheckStatus::Warning);
+        entry.checks.push(check("service:web-tcp", CheckStatus::Critical));
+        entry.checks.push(check("service:web-ttl", CheckStatus::Warning));
+        assert_eq!(entry.aggregated_status(), CheckStatus::Critical);
+        entry.checks.push(check("_service_maintenance:web", CheckStatus::Critical));
+        assert_eq!(entry.aggregated_status(), CheckStatus::Maintenance);
+    }
+
+    #[tokio::test]
+    async fn test_checks_in_maintenance() {
+        const CHECKS: &str = r#"[
+            {"Node": "foobar", "CheckID": "_node_maintenance", "Status": "critical"},
+            {"Node": "foobar", "CheckID": "_service_maintenance:redis", "Status": "critical"},
+            {"Node": "foobar", "CheckID": "service:redis", "Status": "critical"}
+        ]"#;
+        let (client, requests) = recording_client(&[(200, &[], CHECKS)]);
+        let (checks, _) =
+            client.checks_in_state(Some(CheckStatus::Maintenance), None).await.unwrap();
+        assert_eq!(checks.len(), 2);
+        assert!(checks.iter().all(|check| !check.status.is_healthy()));
+        assert_eq!(*requests.lock().unwrap(), ["GET /v1/health/state/critical"]);
+    }
 }
@@ -1,9 +1,29 @@
 use std::collections::HashMap;
+#[cfg(feature = "txn")]
+use std::pin::Pin;
 
 use async_trait::async_trait;
-use reqwest::Method;
+use bytes::Bytes;
+#[cfg(feature = "txn")]
+use futures::{stream, Stream, TryStreamExt};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{sealed::Sealed, Client, ConsulError, ConsulResult, QueryOptions};
+use crate::{
+    sealed::Sealed,
+    watch::{self, WatchStream},
+    Client, ConsulError, ConsulResult, QueryMeta, QueryOptions, WriteMeta, WriteOptions,
+};
+#[cfg(feature = "txn")]
+use crate::{KVTxnOp, Txn, TxnOp, TxnResult, MAX_TXN_OPS};
+
+/// The maximum number of times [KV::update] attempts its read-modify-write
+/// cycle before giving up.
+pub const MAX_CAS_ATTEMPTS: usize = 10;
+
+/// A boxed [Stream] of the entries of a KV tree, as returned by
+/// [KV::stream_entries].
+#[cfg(feature = "txn")]
+pub type KVStream = Pin<Box<dyn Stream<Item = ConsulResult<KVPair>> + Send>>;
 
 /// A key-value pair within the Consul KV store.
 #[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
@@ -21,53 +41,288 @@ pub struct KVPair {
     pub lockindex: Option<u64>,
     #[serde(rename = "Flags")]
     pub flags: Option<u64>,
-    /// The value of the key-value pair.
-    #[serde(rename = "Value")]
-    pub value: String,
+    /// The value of the key-value pair. This is empty if the key has no value.
+    ///
+    /// Values are arbitrary bytes, and are encoded as base64 when the pair is
+    /// serialized, as in the responses of the Consul API.
+    #[serde(
+        rename = "Value",
+        serialize_with = "serialize_value",
+        deserialize_with = "deserialize_value"
+    )]
+    pub value: Vec<u8>,
     #[serde(rename = "Session")]
     pub session: Option<String>,
 }
 
+/// This function encodes the value of a key as base64.
+fn serialize_value<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&base64::encode(value))
+}
+
+/// This function decodes the value of a key from base64, which is null if the
+/// key has no value.
+fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?.unwrap_or_default();
+    base64::decode(value).map_err(serde::de::Error::custom)
+}
+
+/// An entry of a KV tree, in the format used by `consul kv export` and
+/// `consul kv import`.
+///
+/// A list of entries returned by [KV::export_tree] can be serialized with
+/// `serde_json` to produce the same output as `consul kv export`, and vice
+/// versa for [KV::import_tree].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct KVExportEntry {
+    /// The key of the entry.
+    pub key: String,
+    /// The flags of the entry.
+    pub flags: u64,
+    /// The value of the entry, encoded as base64.
+    pub value: String,
+}
+
+/// Parameters of a [KV::put_entry] request, controlling how the write is
+/// applied. The flags of the write are taken from the [KVPair].
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct KVPutParams {
+    /// Turns the write into a check-and-set operation, which only succeeds if
+    /// the `ModifyIndex` of the key matches this index. An index of 0 only
+    /// succeeds if the key does not exist yet.
+    pub cas: Option<u64>,
+    /// The ID of a session used to acquire a lock on the key. The write only
+    /// succeeds if the lock is not held by another session.
+    pub acquire: Option<String>,
+    /// The ID of the session holding the lock on the key, which is released by
+    /// the write. The write only succeeds if the lock is held by this session.
+    pub release: Option<String>,
+}
+
 /// This trait provides methods for interacting with the Consul KV store.
 #[async_trait]
 pub trait KV: Sealed {
     // TODO: deprecate
-    async fn acquire_entry(&self, _: &KVPair, _: Option<QueryOptions>) -> ConsulResult<bool>;
+    async fn acquire_entry(
+        &self,
+        _: &KVPair,
+        _: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
 
-    /// This method deletes a single key or all keys sharing a prefix.
+    /// This method deletes a single key.
+    ///
+    /// If `cas` is provided, the key is only deleted if its `ModifyIndex`
+    /// matches this index, and `false` is returned otherwise.
     ///
     /// For more information, consult the relevant endpoint's [API
     /// documentation].
     ///
     /// [API documentation]: https://www.consul.io/api-docs/kv#delete-key
-    async fn delete_entry(&self, _: &str, _: Option<QueryOptions>) -> ConsulResult<bool>;
+    async fn delete_entry(
+        &self,
+        _: &str,
+        _: Option<u64>,
+        _: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+
+    /// This method deletes all keys sharing the given prefix.
+    ///
+    /// The method makes use of the `recurse` parameter used by the [delete
+    /// key] endpoint. Recursive deletes cannot be check-and-set, so use a
+    /// transaction to delete a tree conditionally.
+    ///
+    /// [delete key]: https://www.consul.io/api-docs/kv#delete-key
+    async fn delete_tree(
+        &self,
+        _: &str,
+        _: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+
+    /// This method exports all keys sharing the given prefix, in the format
+    /// used by `consul kv export`.
+    async fn export_tree(
+        &self,
+        _: &str,
+        _: Option<QueryOptions>,
+    ) -> ConsulResult<Vec<KVExportEntry>>;
 
     /// This method returns the specified key. If no key exists at the given
     /// path, an empty [Vec] is returned.
     ///
+    /// The returned [QueryMeta] can be used to perform a blocking query for
+    /// changes to the key.
+    ///
     /// For more information, consult the relevant endpoint's [API
     /// documentation].
     ///
     /// [API documentation]: https://www.consul.io/api-docs/kv#read-key
-    async fn get_entry(&self, _: &str, _: Option<QueryOptions>) -> ConsulResult<Vec<KVPair>>;
+    async fn get_entry(
+        &self,
+        _: &str,
+        _: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<KVPair>, QueryMeta)>;
+
+    /// This method returns the specified keys, with [None] for the keys that
+    /// do not exist.
+    ///
+    /// The keys are read using transactions of at most [MAX_TXN_OPS] `get`
+    /// operations, so many keys can be read in a few round trips. Each
+    /// transaction reads a consistent snapshot of its keys, but keys read by
+    /// different transactions may be from different points in time.
+    #[cfg(feature = "txn")]
+    async fn get_many(
+        &self,
+        _: &[&str],
+        _: Option<QueryOptions>,
+    ) -> ConsulResult<HashMap<String, Option<KVPair>>>;
+
+    /// This method returns the value of the specified key, or [None] if no
+    /// key exists at the given path.
+    ///
+    /// The value is read with the `raw` parameter of the [read key] endpoint,
+    /// so it is returned as-is instead of being encoded as base64 in a JSON
+    /// response, which is cheaper for large values. The returned [QueryMeta]
+    /// can be used to perform a blocking query for changes to the key.
+    ///
+    /// [read key]: https://www.consul.io/api-docs/kv#read-key
+    async fn get_raw(
+        &self,
+        _: &str,
+        _: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<Bytes>, QueryMeta)>;
+
+    /// This method imports the given entries, in the format used by `consul kv
+    /// import`. If `prefix` is provided, it is prepended to the key of each
+    /// entry.
+    ///
+    /// The entries are written using transactions of at most [MAX_TXN_OPS]
+    /// operations. Each transaction is atomic, but if one fails, the entries
+    /// written by the previous transactions are kept.
+    #[cfg(feature = "txn")]
+    async fn import_tree(
+        &self,
+        _: Vec<KVExportEntry>,
+        _: Option<&str>,
+        _: Option<WriteOptions>,
+    ) -> ConsulResult<()>;
 
     /// This method returns a [Vec] of [KVPair]s for all keys sharing the given
     /// prefix.
     ///
     /// The method makes use of the `recurse` parameter used by the [read key](https://www.consul.io/api-docs/kv#read-key) endpoint.
-    async fn list_entries(&self, _: &str, _: Option<QueryOptions>) -> ConsulResult<Vec<KVPair>>;
+    async fn list_entries(
+        &self,
+        _: &str,
+        _: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<KVPair>, QueryMeta)>;
+
+    /// This method returns the keys sharing the given prefix, without their
+    /// values.
+    ///
+    /// If `separator` is provided, only keys up to the first occurrence of the
+    /// separator after the prefix are returned, so that `/` lists a single
+    /// level of a directory-like hierarchy.
+    ///
+    /// The method makes use of the `keys` parameter used by the [read key](https://www.consul.io/api-docs/kv#read-key) endpoint.
+    async fn list_keys(
+        &self,
+        _: &str,
+        _: Option<&str>,
+        _: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<String>, QueryMeta)>;
 
     /// This method updates the value of the specified key. If no key exists
     /// at the given path, the key will be created.
     ///
+    /// The given [KVPutParams] can make the write conditional, in which case
+    /// `false` is returned if the condition does not hold and the key was not
+    /// updated.
+    ///
     /// For more information, consult the relevant endpoint's [API
     /// documentation].
     ///
     /// [API documentation]: https://www.consul.io/api-docs/kv#create-update-key
-    async fn put_entry(&self, _: &KVPair, _: Option<QueryOptions>) -> ConsulResult<bool>;
+    async fn put_entry(
+        &self,
+        _: &KVPair,
+        _: Option<KVPutParams>,
+        _: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+
+    /// This method writes the given value to the specified key, locked by the
+    /// given session, so that the key is deleted once the session is
+    /// invalidated if the session has the [delete behavior].
+    ///
+    /// As with any lock, `false` is returned if the key is locked by another
+    /// session, in which case the value is not written. A [SessionHandle]
+    /// keeps such a session alive for as long as the key should exist.
+    ///
+    /// [delete behavior]: crate::SessionBehavior::Delete
+    /// [SessionHandle]: crate::SessionHandle
+    async fn put_ephemeral(
+        &self,
+        _: &str,
+        _: &[u8],
+        _: &str,
+        _: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
 
     // TODO: deprecate
-    async fn release_entry(&self, _: &KVPair, _: Option<QueryOptions>) -> ConsulResult<bool>;
+    async fn release_entry(
+        &self,
+        _: &KVPair,
+        _: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+
+    /// This method returns a [KVStream] of the entries sharing the given
+    /// prefix, for trees too large to be read at once by
+    /// [KV::list_entries].
+    ///
+    /// The tree is traversed one folder at a time by listing keys with a `/`
+    /// separator, and the values of the keys are read using transactions of
+    /// at most [MAX_TXN_OPS] `get` operations. Only the keys of the folders
+    /// being traversed and a single batch of values are held in memory at a
+    /// time. Entries are yielded in key order, and keys deleted during the
+    /// traversal are skipped.
+    ///
+    /// Unlike [KV::list_entries], the entries are not read from a single
+    /// snapshot of the tree.
+    #[cfg(feature = "txn")]
+    fn stream_entries(&self, _: &str, _: Option<QueryOptions>) -> KVStream;
+
+    /// This method reads the value of the specified key, passes it to the
+    /// given closure, and writes the value returned by the closure back to
+    /// the key. The closure receives [None] if the key does not exist yet.
+    ///
+    /// The write is a check-and-set against the `ModifyIndex` of the read,
+    /// so if the key is changed concurrently the cycle is retried with the
+    /// new value, up to [MAX_CAS_ATTEMPTS] times before failing with
+    /// [ConsulError::CasConflict]. The closure must therefore be prepared to
+    /// run more than once. The flags of the key are preserved, and the
+    /// written value is returned.
+    async fn update<F>(
+        &self,
+        _: &str,
+        _: F,
+        _: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<u8>, WriteMeta)>
+    where
+        F: FnMut(Option<Vec<u8>>) -> Vec<u8> + Send;
+
+    /// This method returns a [WatchStream] that yields the specified key every
+    /// time it changes, or [None] if the key does not exist.
+    ///
+    /// The stream is driven by blocking queries against the [read key]
+    /// endpoint. Errors are yielded as they occur, after which the watch is
+    /// retried with a jittered backoff.
+    ///
+    /// [read key]: https://www.consul.io/api-docs/kv#read-key
+    fn watch_key(
+        &self,
+        _: &str,
+        _: Option<QueryOptions>,
+    ) -> WatchStream<ConsulResult<Option<KVPair>>>;
 }
 
 #[async_trait]
@@ -76,27 +331,58 @@ impl KV for Client {
     async fn acquire_entry(
         &self,
         pair: &KVPair,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<bool> {
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        let session = pair
+            .session
+            .clone()
+            .ok_or_else(|| ConsulError::MissingParameter("session_flag".to_owned()))?;
+        let params = KVPutParams { acquire: Some(session), ..Default::default() };
+        self.put_entry(pair, Some(params), options).await
+    }
+
+    #[tracing::instrument]
+    async fn delete_entry(
+        &self,
+        key: &str,
+        cas: Option<u64>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
         let mut params = HashMap::new();
-        if let Some(i) = pair.flags {
-            if i != 0 {
-                params.insert(String::from("flags"), i.to_string());
-            }
-        }
-        if let Some(ref session) = pair.session {
-            params.insert(String::from("acquire"), session.to_owned());
-            let path = format!("/v1/kv/{}", pair.key);
-            self.put(&path, &pair.value, Some(params), options).await
-        } else {
-            Err(ConsulError::MissingParameter("session_flag".to_owned()))
+        if let Some(cas) = cas {
+            params.insert(String::from("cas"), cas.to_string());
         }
+        let path = format!("/v1/kv/{}", key);
+        self.delete(&path, Some(params), options).await
     }
 
     #[tracing::instrument]
-    async fn delete_entry(&self, key: &str, options: Option<QueryOptions>) -> ConsulResult<bool> {
-        let path = format!("/v1/kv/{}", key);
-        self.delete(&path, None, options).await
+    async fn delete_tree(
+        &self,
+        prefix: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        let mut params = HashMap::new();
+        params.insert(String::from("recurse"), String::new());
+        let path = format!("/v1/kv/{}", prefix);
+        self.delete(&path, Some(params), options).await
+    }
+
+    #[tracing::instrument]
+    async fn export_tree(
+        &self,
+        prefix: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Vec<KVExportEntry>> {
+        let (pairs, _) = self.list_entries(prefix, options).await?;
+        Ok(pairs
+            .into_iter()
+            .map(|pair| KVExportEntry {
+                key: pair.key,
+                flags: pair.flags.unwrap_or_default(),
+                value: base64::encode(pair.value),
+            })
+            .collect())
     }
 
     #[tracing::instrument]
@@ -104,9 +390,97 @@ impl KV for Client {
         &self,
         key: &str,
         options: Option<QueryOptions>,
-    ) -> ConsulResult<Vec<KVPair>> {
+    ) -> ConsulResult<(Vec<KVPair>, QueryMeta)> {
+        let path = format!("/v1/kv/{}", key);
+        // consul returns a 404 if the key does not exist
+        self.get_with_meta(&path, None, options)
+            .await
+            .map(|(r, meta): (Option<Vec<KVPair>>, _)| (r.unwrap_or_default(), meta))
+    }
+
+    #[cfg(feature = "txn")]
+    #[tracing::instrument]
+    async fn get_many(
+        &self,
+        keys: &[&str],
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<HashMap<String, Option<KVPair>>> {
+        let options = options.as_ref().map(write_options);
+        let mut entries = HashMap::with_capacity(keys.len());
+        for chunk in keys.chunks(MAX_TXN_OPS) {
+            let mut chunk = chunk.to_vec();
+            while !chunk.is_empty() {
+                let ops = chunk
+                    .iter()
+                    .map(|key| TxnOp::Kv(KVTxnOp::Get { key: String::from(*key) }))
+                    .collect();
+                let errors = match self.txn(ops, options.clone()).await {
+                    Ok((results, _)) => {
+                        for result in results {
+                            if let TxnResult::Kv(pair) = result {
+                                entries.insert(pair.key.clone(), Some(pair));
+                            }
+                        }
+                        break;
+                    }
+                    Err(ConsulError::TxnFailed(errors)) => errors,
+                    Err(e) => return Err(e),
+                };
+                // a get of a missing key rolls back the transaction, so the
+                // missing keys are recorded and the rest are read again
+                if errors.iter().any(|error| !error.what.contains("doesn't exist")) {
+                    return Err(ConsulError::TxnFailed(errors));
+                }
+                let mut index = 0;
+                chunk.retain(|key| {
+                    let missing = errors.iter().any(|error| error.op_index == index);
+                    if missing {
+                        entries.insert(String::from(*key), None);
+                    }
+                    index += 1;
+                    !missing
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    #[tracing::instrument]
+    async fn get_raw(
+        &self,
+        key: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<Bytes>, QueryMeta)> {
+        let mut params = HashMap::new();
+        params.insert(String::from("raw"), String::new());
         let path = format!("/v1/kv/{}", key);
-        self.get(&path, options).await
+        // consul returns a 404 if the key does not exist
+        self.get_bytes(&path, Some(params), options).await
+    }
+
+    #[cfg(feature = "txn")]
+    #[tracing::instrument]
+    async fn import_tree(
+        &self,
+        entries: Vec<KVExportEntry>,
+        prefix: Option<&str>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<()> {
+        let prefix = prefix.unwrap_or_default();
+        let ops = entries
+            .into_iter()
+            .map(|entry| {
+                Ok(TxnOp::Kv(KVTxnOp::Set {
+                    key: format!("{}{}", prefix, entry.key),
+                    value: base64::decode(entry.value)?,
+                    flags: entry.flags,
+                }))
+            })
+            .collect::<ConsulResult<Vec<_>>>()?;
+        for chunk in ops.chunks(MAX_TXN_OPS) {
+            self.txn(chunk.to_vec(), options.clone()).await?;
+        }
+        Ok(())
     }
 
     #[tracing::instrument]
@@ -114,43 +488,488 @@ impl KV for Client {
         &self,
         prefix: &str,
         o: Option<QueryOptions>,
-    ) -> ConsulResult<Vec<KVPair>> {
+    ) -> ConsulResult<(Vec<KVPair>, QueryMeta)> {
         let mut params = HashMap::new();
         // enable key mode
         params.insert(String::from("recurse"), String::from(""));
         let path = format!("/v1/kv/{}", prefix);
-        // use send with empty as consul returns invalid json
-        self.send_with_empty(Method::GET, path, Some(params), None as Option<()>, o)
+        // use get with meta as consul returns invalid json
+        self.get_with_meta(path, Some(params), o)
             .await
-            .map(|r: Option<Vec<KVPair>>| r.unwrap_or_default())
+            .map(|(r, meta): (Option<Vec<KVPair>>, _)| (r.unwrap_or_default(), meta))
     }
 
     #[tracing::instrument]
-    async fn put_entry(&self, pair: &KVPair, o: Option<QueryOptions>) -> ConsulResult<bool> {
+    async fn list_keys(
+        &self,
+        prefix: &str,
+        separator: Option<&str>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<String>, QueryMeta)> {
         let mut params = HashMap::new();
-        if let Some(i) = pair.flags {
-            if i != 0 {
-                params.insert(String::from("flags"), i.to_string());
-            }
+        params.insert(String::from("keys"), String::new());
+        if let Some(separator) = separator {
+            params.insert(String::from("separator"), separator.to_owned());
         }
+        let path = format!("/v1/kv/{}", prefix);
+        // consul returns a 404 if no keys share the prefix
+        self.get_with_meta(&path, Some(params), options)
+            .await
+            .map(|(r, meta): (Option<Vec<String>>, _)| (r.unwrap_or_default(), meta))
+    }
+
+    #[tracing::instrument]
+    async fn put_entry(
+        &self,
+        pair: &KVPair,
+        params: Option<KVPutParams>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
         let path = format!("/v1/kv/{}", pair.key);
-        self.put(&path, &pair.value, None, o).await
+        let params = put_params(pair.flags, params.unwrap_or_default());
+        self.put_raw(&path, pair.value.clone(), Some(params), options).await
+    }
+
+    #[tracing::instrument(skip(value))]
+    async fn put_ephemeral(
+        &self,
+        key: &str,
+        value: &[u8],
+        session: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        let path = format!("/v1/kv/{}", key);
+        let params = put_params(
+            None,
+            KVPutParams { acquire: Some(session.to_owned()), ..Default::default() },
+        );
+        self.put_raw(&path, value.to_vec(), Some(params), options).await
     }
 
     #[tracing::instrument]
-    async fn release_entry(&self, pair: &KVPair, o: Option<QueryOptions>) -> ConsulResult<bool> {
-        let mut params = HashMap::new();
-        if let Some(i) = pair.flags {
-            if i != 0 {
-                params.insert(String::from("flags"), i.to_string());
+    async fn release_entry(
+        &self,
+        pair: &KVPair,
+        o: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        let session = pair
+            .session
+            .clone()
+            .ok_or_else(|| ConsulError::MissingParameter("session_flag".to_owned()))?;
+        let params = KVPutParams { release: Some(session), ..Default::default() };
+        self.put_entry(pair, Some(params), o).await
+    }
+
+    #[cfg(feature = "txn")]
+    fn stream_entries(&self, prefix: &str, options: Option<QueryOptions>) -> KVStream {
+        // the keys still to be read, in reverse order, along with whether
+        // each is a folder to be listed
+        let pending = vec![(prefix.to_owned(), true)];
+        let entries = stream::try_unfold(
+            (self.clone(), options, pending),
+            |(client, options, mut pending)| async move {
+                while let Some((key, folder)) = pending.pop() {
+                    if folder {
+                        let (keys, _) = client.list_keys(&key, Some("/"), options.clone()).await?;
+                        // a key named like its folder is listed in the folder
+                        let keys = keys.into_iter().rev().map(|child| {
+                            let folder = child.ends_with('/') && child != key;
+                            (child, folder)
+                        });
+                        pending.extend(keys);
+                        continue;
+                    }
+                    let mut batch = vec![key];
+                    while batch.len() < MAX_TXN_OPS {
+                        match pending.pop() {
+                            Some((key, false)) => batch.push(key),
+                            Some(folder) => {
+                                pending.push(folder);
+                                break;
+                            }
+                            None => break,
+                        }
+                    }
+                    let keys: Vec<&str> = batch.iter().map(String::as_str).collect();
+                    let mut entries = client.get_many(&keys, options.clone()).await?;
+                    let pairs: Vec<KVPair> =
+                        batch.iter().filter_map(|key| entries.remove(key).flatten()).collect();
+                    return Ok(Some((pairs, (client, options, pending))));
+                }
+                ConsulResult::Ok(None)
+            },
+        );
+        Box::pin(entries.map_ok(|pairs| stream::iter(pairs.into_iter().map(Ok))).try_flatten())
+    }
+
+    #[tracing::instrument(skip(update))]
+    async fn update<F>(
+        &self,
+        key: &str,
+        mut update: F,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<u8>, WriteMeta)>
+    where
+        F: FnMut(Option<Vec<u8>>) -> Vec<u8> + Send,
+    {
+        let options = options.unwrap_or_default();
+        let write_options = write_options(&options);
+        let path = format!("/v1/kv/{}", key);
+        for _ in 0..MAX_CAS_ATTEMPTS {
+            let (pairs, _) = self.get_entry(key, Some(options.clone())).await?;
+            let pair = pairs.into_iter().next();
+            // an index of 0 only writes the key if it still does not exist
+            let cas = pair.as_ref().and_then(|pair| pair.modifyindex).unwrap_or_default();
+            let flags = pair.as_ref().and_then(|pair| pair.flags);
+            let value = update(pair.map(|pair| pair.value));
+            let params = put_params(flags, KVPutParams { cas: Some(cas), ..Default::default() });
+            let (updated, meta) = self
+                .put_raw(&path, value.clone(), Some(params), Some(write_options.clone()))
+                .await?;
+            if updated {
+                return Ok((value, meta));
             }
         }
-        if let Some(ref session) = pair.session {
-            params.insert(String::from("release"), session.to_owned());
-            let path = format!("/v1/kv/{}", pair.key);
-            self.put(&path, &pair.value, Some(params), o).await
-        } else {
-            Err(ConsulError::MissingParameter("session_flag".to_owned()))
+        Err(ConsulError::CasConflict(MAX_CAS_ATTEMPTS))
+    }
+
+    fn watch_key(
+        &self,
+        key: &str,
+        options: Option<QueryOptions>,
+    ) -> WatchStream<ConsulResult<Option<KVPair>>> {
+        let client = self.clone();
+        let key = key.to_owned();
+        watch::watch(options, move |options| {
+            let client = client.clone();
+            let key = key.clone();
+            async move {
+                let (pairs, meta) = client.get_entry(&key, Some(options)).await?;
+                Ok((pairs.into_iter().next(), meta))
+            }
+        })
+    }
+}
+
+/// This trait provides methods for storing serializable values in the Consul
+/// KV store, encoded as JSON or, with the `yaml` feature, YAML.
+///
+/// Values are read from the base64 encoded `Value` of the key and written as
+/// the raw body of the request, so they can be shared with other Consul
+/// clients.
+#[async_trait]
+pub trait TypedKV: KV {
+    /// This method returns the value of the specified key decoded from JSON,
+    /// or [None] if the key does not exist.
+    async fn get_json<T: DeserializeOwned + Send>(
+        &self,
+        key: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<T>, QueryMeta)>;
+
+    /// This method encodes the given value as JSON and writes it to the
+    /// specified key.
+    async fn put_json<T: Serialize + Sync>(
+        &self,
+        key: &str,
+        value: &T,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+
+    /// This method returns the value of the specified key decoded from YAML,
+    /// or [None] if the key does not exist.
+    #[cfg(feature = "yaml")]
+    async fn get_yaml<T: DeserializeOwned + Send>(
+        &self,
+        key: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<T>, QueryMeta)>;
+
+    /// This method encodes the given value as YAML and writes it to the
+    /// specified key.
+    #[cfg(feature = "yaml")]
+    async fn put_yaml<T: Serialize + Sync>(
+        &self,
+        key: &str,
+        value: &T,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+}
+
+#[async_trait]
+impl TypedKV for Client {
+    #[tracing::instrument]
+    async fn get_json<T: DeserializeOwned + Send>(
+        &self,
+        key: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<T>, QueryMeta)> {
+        let (value, meta) = get_value(self, key, options).await?;
+        let value = value.map(|value| serde_json::from_slice(&value)).transpose()?;
+        Ok((value, meta))
+    }
+
+    #[tracing::instrument(skip(value))]
+    async fn put_json<T: Serialize + Sync>(
+        &self,
+        key: &str,
+        value: &T,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        let value = serde_json::to_vec(value).map_err(ConsulError::EncodeError)?;
+        put_value(self, key, value, options).await
+    }
+
+    #[cfg(feature = "yaml")]
+    #[tracing::instrument]
+    async fn get_yaml<T: DeserializeOwned + Send>(
+        &self,
+        key: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<T>, QueryMeta)> {
+        let (value, meta) = get_value(self, key, options).await?;
+        let value = value.map(|value| serde_yaml::from_slice(&value)).transpose()?;
+        Ok((value, meta))
+    }
+
+    #[cfg(feature = "yaml")]
+    #[tracing::instrument(skip(value))]
+    async fn put_yaml<T: Serialize + Sync>(
+        &self,
+        key: &str,
+        value: &T,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        let value = serde_yaml::to_string(value)?;
+        put_value(self, key, value, options).await
+    }
+}
+
+/// This function reads the value of a key, decoded from base64.
+async fn get_value(
+    client: &Client,
+    key: &str,
+    options: Option<QueryOptions>,
+) -> ConsulResult<(Option<Vec<u8>>, QueryMeta)> {
+    let (pairs, meta) = client.get_entry(key, options).await?;
+    Ok((pairs.into_iter().next().map(|pair| pair.value), meta))
+}
+
+/// This function writes the given raw value to a key.
+async fn put_value<Value: Into<Bytes>>(
+    client: &Client,
+    key: &str,
+    value: Value,
+    options: Option<WriteOptions>,
+) -> ConsulResult<(bool, WriteMeta)> {
+    let path = format!("/v1/kv/{}", key);
+    client.put_raw(&path, value, None, options).await
+}
+
+/// This function builds the query parameters of a put request.
+fn put_params(flags: Option<u64>, params: KVPutParams) -> HashMap<String, String> {
+    let mut query = HashMap::new();
+    if let Some(flags) = flags.filter(|flags| *flags != 0) {
+        query.insert(String::from("flags"), flags.to_string());
+    }
+    if let Some(cas) = params.cas {
+        query.insert(String::from("cas"), cas.to_string());
+    }
+    if let Some(session) = params.acquire {
+        query.insert(String::from("acquire"), session);
+    }
+    if let Some(session) = params.release {
+        query.insert(String::from("release"), session);
+    }
+    query
+}
+
+/// This function returns the options of the writes made on behalf of a read,
+/// such as the transactions of [KV::get_many] or the writes of [KV::update],
+/// which target the same datacenter with the same token.
+fn write_options(options: &QueryOptions) -> WriteOptions {
+    WriteOptions {
+        datacenter: options.datacenter.clone(),
+        #[cfg(feature = "enterprise")]
+        namespace: options.namespace.clone(),
+        #[cfg(feature = "enterprise")]
+        partition: options.partition.clone(),
+        token: options.token.clone(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{put_params, KVExportEntry, KVPair, KVPutParams, KV, MAX_CAS_ATTEMPTS};
+    use crate::{request::tests::recording_client, ConsulError};
+
+    #[test]
+    fn test_put_params() {
+        assert!(put_params(Some(0), KVPutParams::default()).is_empty());
+        let params = KVPutParams {
+            cas: Some(0),
+            acquire: Some(String::from("session")),
+            ..Default::default()
+        };
+        let params = put_params(Some(42), params);
+        assert_eq!(params.len(), 3);
+        assert_eq!(params["flags"], "42");
+        assert_eq!(params["cas"], "0");
+        assert_eq!(params["acquire"], "session");
+    }
+
+    #[test]
+    fn test_decode_null_value() {
+        let pair: KVPair = serde_json::from_str(r#"{"Key": "dir/", "Value": null}"#).unwrap();
+        assert!(pair.value.is_empty());
+    }
+
+    #[test]
+    fn test_decode_export() {
+        let entries: Vec<KVExportEntry> =
+            serde_json::from_str(r#"[{"key": "app/a", "flags": 0, "value": "MQ=="}]"#).unwrap();
+        assert_eq!(entries[0].key, "app/a");
+        assert_eq!(entries[0].value, "MQ==");
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        // "1" and "2", encoded as base64
+        const ENTRY: &str =
+            r#"[{"Key": "counter", "Value": "MQ==", "Flags": 7, "ModifyIndex": 5}]"#;
+        const NEW_ENTRY: &str =
+            r#"[{"Key": "counter", "Value": "Mg==", "Flags": 7, "ModifyIndex": 6}]"#;
+        let (client, requests) = recording_client(&[
+            (200, &[], ENTRY),
+            // the key was changed concurrently, so the cycle is retried
+            (200, &[], "false"),
+            (200, &[], NEW_ENTRY),
+            (200, &[], "true"),
+        ]);
+        let mut seen = Vec::new();
+        let (value, _) = client
+            .update(
+                "counter",
+                |old| {
+                    let old = String::from_utf8(old.unwrap()).unwrap();
+                    seen.push(old.clone());
+                    (old.parse::<u64>().unwrap() + 1).to_string().into_bytes()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(value, b"3");
+        assert_eq!(seen, ["1", "2"]);
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[1].starts_with("PUT /v1/kv/counter?"));
+        assert!(requests[1].contains("cas=5") && requests[1].contains("flags=7"));
+        assert!(requests[3].contains("cas=6"));
+    }
+
+    #[tokio::test]
+    async fn test_update_conflict() {
+        let mut responses = Vec::new();
+        for _ in 0..MAX_CAS_ATTEMPTS {
+            responses.push((404, &[][..], ""));
+            responses.push((200, &[][..], "false"));
         }
+        let (client, requests) = recording_client(&responses);
+        let result = client.update("missing", |_| b"1".to_vec(), None).await;
+        assert!(matches!(result, Err(ConsulError::CasConflict(MAX_CAS_ATTEMPTS))));
+        assert_eq!(requests.lock().unwrap()[1], "PUT /v1/kv/missing?cas=0");
+    }
+
+    #[cfg(feature = "txn")]
+    #[tokio::test]
+    async fn test_get_many() {
+        const MISSING: &str =
+            r#"{"Errors": [{"OpIndex": 1, "What": "key \"app/b\" doesn't exist"}]}"#;
+        const RESULTS: &str = r#"{"Results": [
+            {"KV": {"Key": "app/a", "Value": "MQ==", "ModifyIndex": 5}},
+            {"KV": {"Key": "app/c", "Value": "Mw==", "ModifyIndex": 7}}
+        ]}"#;
+        let (client, requests) = recording_client(&[(409, &[], MISSING), (200, &[], RESULTS)]);
+        let entries = client.get_many(&["app/a", "app/b", "app/c"], None).await.unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries["app/a"].as_ref().unwrap().value, b"1");
+        assert_eq!(entries["app/b"], None);
+        assert_eq!(entries["app/c"].as_ref().unwrap().modifyindex, Some(7));
+        assert_eq!(*requests.lock().unwrap(), ["PUT /v1/txn", "PUT /v1/txn"]);
+    }
+
+    #[cfg(feature = "txn")]
+    #[tokio::test]
+    async fn test_import_tree() {
+        let (client, requests) = recording_client(&[(200, &[], r#"{"Results": []}"#)]);
+        let entries =
+            vec![KVExportEntry { key: String::from("a"), flags: 0, value: String::from("MQ==") }];
+        let options =
+            crate::WriteOptions { datacenter: Some(String::from("dc2")), ..Default::default() };
+        client.import_tree(entries, Some("app/"), Some(options)).await.unwrap();
+        assert_eq!(*requests.lock().unwrap(), ["PUT /v1/txn?dc=dc2"]);
+    }
+
+    #[tokio::test]
+    async fn test_binary_values() {
+        const ENTRY: &str = r#"[{"Key": "blob", "Value": "AP8Q", "ModifyIndex": 5}]"#;
+        let (client, requests) = recording_client(&[
+            (200, &[], "true"),
+            (200, &[], ENTRY),
+            (200, &[("X-Consul-Index", "6")], "not encoded"),
+            (404, &[], ""),
+        ]);
+        let pair =
+            KVPair { key: String::from("blob"), value: vec![0, 255, 16], ..Default::default() };
+        assert!(client.put_entry(&pair, None, None).await.unwrap().0);
+        let (pairs, _) = client.get_entry("blob", None).await.unwrap();
+        assert_eq!(pairs[0].value, [0, 255, 16]);
+        let (value, meta) = client.get_raw("blob", None).await.unwrap();
+        assert_eq!(meta.last_index, Some(6));
+        assert_eq!(value.unwrap().as_ref(), b"not encoded");
+        assert!(client.get_raw("missing", None).await.unwrap().0.is_none());
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "PUT /v1/kv/blob",
+                "GET /v1/kv/blob",
+                "GET /v1/kv/blob?raw=",
+                "GET /v1/kv/missing?raw="
+            ]
+        );
+    }
+
+    #[cfg(feature = "txn")]
+    #[tokio::test]
+    async fn test_stream_entries() {
+        use futures::TryStreamExt;
+
+        const KEYS: &str = r#"["app/", "app/a", "app/db/", "app/z"]"#;
+        const DB_KEYS: &str = r#"["app/db/host"]"#;
+        const RESULTS: &str = r#"{"Results": [
+            {"KV": {"Key": "app/", "Value": null}},
+            {"KV": {"Key": "app/a", "Value": "MQ=="}}
+        ]}"#;
+        const DB_RESULTS: &str = r#"{"Results": [
+            {"KV": {"Key": "app/db/host", "Value": "MQ=="}},
+            {"KV": {"Key": "app/z", "Value": "MQ=="}}
+        ]}"#;
+        let (client, requests) = recording_client(&[
+            (200, &[], KEYS),
+            (200, &[], RESULTS),
+            (200, &[], DB_KEYS),
+            (200, &[], DB_RESULTS),
+        ]);
+        let pairs: Vec<KVPair> = client.stream_entries("app/", None).try_collect().await.unwrap();
+        let keys: Vec<&str> = pairs.iter().map(|pair| pair.key.as_str()).collect();
+        assert_eq!(keys, ["app/", "app/a", "app/db/host", "app/z"]);
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("GET /v1/kv/app/?"));
+        assert_eq!(requests[1], "PUT /v1/txn");
+        assert!(requests[2].starts_with("GET /v1/kv/app/db/?"));
+        assert_eq!(requests[3], "PUT /v1/txn");
+        assert_eq!(requests.len(), 4);
     }
 }
@@ -55,40 +55,153 @@ use thiserror::Error;
 #[macro_use]
 extern crate serde_derive;
 
-use std::{env, time::Duration};
+use std::{env, fmt, sync::Arc, time::Duration};
 
+use http::StatusCode;
+#[cfg(feature = "reqwest")]
 use reqwest::{Client as HttpClient, ClientBuilder};
 
 mod common;
+mod config;
+mod filter;
+mod metrics;
+mod middleware;
+mod pool;
+// the helpers used depend on the endpoints enabled by the features
+#[allow(dead_code)]
 mod request;
+mod retry;
+#[cfg(feature = "reqwest")]
+mod tls;
+mod token;
+mod transport;
 
 mod acl;
 #[cfg(feature = "agent")]
 mod agent;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod blocking_query;
+#[cfg(feature = "cache")]
+mod cache;
 #[cfg(feature = "catalog")]
 mod catalog;
+#[cfg(feature = "config-entry")]
+mod config_entry;
+#[cfg(feature = "config-watcher")]
+mod config_watcher;
 #[cfg(feature = "connect")]
 mod connect;
+#[cfg(feature = "coordinate")]
+mod coordinate;
+#[cfg(feature = "tower")]
+mod discover;
+#[cfg(feature = "discovery-chain")]
+mod discovery_chain;
+#[cfg(feature = "dns")]
+mod dns;
+#[cfg(feature = "event")]
+mod event;
+#[cfg(feature = "failover")]
+mod failover;
 #[cfg(feature = "health")]
 mod health;
 #[cfg(feature = "kv")]
 mod kv;
+#[cfg(feature = "enterprise")]
+mod namespace;
+#[cfg(feature = "operator")]
+mod operator;
+#[cfg(feature = "prepared-query")]
+mod prepared_query;
+#[cfg(feature = "presence")]
+mod presence;
+#[cfg(feature = "queue")]
+pub mod queue;
+#[cfg(feature = "registrar")]
+mod registrar;
+#[cfg(feature = "health")]
+mod resolver;
+#[cfg(feature = "semaphore")]
+mod semaphore;
 #[cfg(feature = "session")]
 mod session;
+#[cfg(feature = "session-handle")]
+mod session_handle;
+#[cfg(feature = "snapshot")]
+mod snapshot;
+#[cfg(feature = "status")]
+mod status;
+#[cfg(any(feature = "testcontainers", feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "txn")]
+mod txn;
+pub mod watch;
 
+pub use acl::*;
 #[cfg(feature = "agent")]
 pub use agent::*;
+#[cfg(feature = "cache")]
+pub use cache::*;
 #[cfg(feature = "catalog")]
 pub use catalog::*;
 pub use common::*;
+pub use config::*;
+#[cfg(feature = "config-entry")]
+pub use config_entry::*;
+#[cfg(feature = "config-watcher")]
+pub use config_watcher::*;
 #[cfg(feature = "connect")]
 pub use connect::*;
+#[cfg(feature = "coordinate")]
+pub use coordinate::*;
+#[cfg(feature = "tower")]
+pub use discover::*;
+#[cfg(feature = "discovery-chain")]
+pub use discovery_chain::*;
+#[cfg(feature = "dns")]
+pub use dns::*;
+#[cfg(feature = "event")]
+pub use event::*;
+#[cfg(feature = "failover")]
+pub use failover::*;
+pub use filter::*;
 #[cfg(feature = "health")]
 pub use health::*;
 #[cfg(feature = "kv")]
 pub use kv::*;
+pub use metrics::*;
+pub use middleware::*;
+#[cfg(feature = "enterprise")]
+pub use namespace::*;
+#[cfg(feature = "operator")]
+pub use operator::*;
+pub use pool::*;
+#[cfg(feature = "prepared-query")]
+pub use prepared_query::*;
+#[cfg(feature = "presence")]
+pub use presence::*;
+#[cfg(feature = "registrar")]
+pub use registrar::*;
+#[cfg(feature = "health")]
+pub use resolver::*;
+pub use retry::*;
+#[cfg(feature = "semaphore")]
+pub use semaphore::*;
 #[cfg(feature = "session")]
 pub use session::*;
+#[cfg(feature = "session-handle")]
+pub use session_handle::*;
+#[cfg(feature = "snapshot")]
+pub use snapshot::*;
+#[cfg(feature = "status")]
+pub use status::*;
+#[cfg(feature = "reqwest")]
+pub use tls::*;
+pub use token::*;
+pub use transport::*;
+#[cfg(feature = "txn")]
+pub use txn::*;
 
 /// The Consul client. This struct implements the various traits providing the
 /// various Consul endpoints, and is responsible for making requests to the
@@ -100,37 +213,123 @@ pub struct Client {
 
 impl Client {
     /// This method creates a new Consul client.
+    ///
+    /// If the address of the configuration is a Unix domain socket, such as
+    /// `unix:///var/run/consul.sock`, its HTTP client is replaced by one
+    /// connecting to the socket.
+    ///
+    /// # Panics
+    /// Panics if `request::Client` construction fails.
     pub fn new(config: Config) -> Self {
+        // requests are sent over the socket, so the host of their url is
+        // only used for the Host header
+        #[cfg(all(unix, feature = "reqwest"))]
+        let config = match config.address.strip_prefix("unix://").map(str::to_owned) {
+            Some(path) => Config {
+                address: String::from("http://localhost"),
+                http_client: ClientBuilder::new().unix_socket(path).build().unwrap(),
+                ..config
+            },
+            None => config,
+        };
         Client { config }
     }
+
+    /// This method returns a clone of the client whose requests default to
+    /// the given datacenter. Options passed to a request still take
+    /// precedence.
+    pub fn with_datacenter(&self, datacenter: impl Into<String>) -> Self {
+        let mut client = self.clone();
+        client.config.datacenter = Some(datacenter.into());
+        client
+    }
+
+    /// This method returns a clone of the client whose requests default to
+    /// the given namespace. Options passed to a request still take
+    /// precedence.
+    #[cfg(feature = "enterprise")]
+    pub fn with_namespace(&self, namespace: impl Into<String>) -> Self {
+        let mut client = self.clone();
+        client.config.namespace = Some(namespace.into());
+        client
+    }
+
+    /// This method returns a clone of the client whose requests default to
+    /// the given admin partition. Options passed to a request still take
+    /// precedence.
+    #[cfg(feature = "enterprise")]
+    pub fn with_partition(&self, partition: impl Into<String>) -> Self {
+        let mut client = self.clone();
+        client.config.partition = Some(partition.into());
+        client
+    }
 }
 
 /// The configuration for the Consul client.
+///
+/// Configurations should be created using [Config::builder], which validates
+/// them. The fields remain public for compatibility, but constructing the
+/// struct directly is deprecated.
 #[derive(Clone, Debug)]
 pub struct Config {
-    /// The address of the Consul agent.
+    /// The address of the Consul agent, such as `http://127.0.0.1:8500`. On
+    /// Unix platforms, this can also be the path of a Unix domain socket, such
+    /// as `unix:///var/run/consul.sock`.
     pub address: String,
     /// The name of the datacenter this agent is hosted in.
     pub datacenter: Option<String>,
+    /// The default namespace to use for requests.
+    #[cfg(feature = "enterprise")]
+    pub namespace: Option<String>,
+    /// The default admin partition to use for requests.
+    #[cfg(feature = "enterprise")]
+    pub partition: Option<String>,
     /// The dedicated HTTP client to use for requests. Most users should not
     /// need to modify this unless authentication is necessary.
+    #[cfg(feature = "reqwest")]
     pub http_client: HttpClient,
     /// The Consul agent's access token.
     pub token: Option<String>,
-    /// The timeout for requests.
+    /// The file the access token is read from, if `token` is not set.
+    pub token_file: Option<TokenFile>,
+    /// The access token shared with the clones of the client, such as a token
+    /// renewed in the background, used if `token` is not set. It takes
+    /// precedence over `token_file`.
+    pub shared_token: Option<SharedToken>,
+    /// The default maximum time to wait for blocking queries.
     pub wait_time: Option<Duration>,
+    /// The policy used to retry requests that failed due to transient errors.
+    /// Requests are not retried if this is [None].
+    pub retry_policy: Option<RetryPolicy>,
+    /// The middleware called around every request, in order.
+    pub middleware: Vec<Arc<dyn Middleware>>,
+    /// The sink receiving an observation of every request.
+    pub metrics_sink: Option<Arc<dyn MetricsSink>>,
+    /// The pool of agent addresses requests are rotated between. If this is
+    /// set, `address` is its first address.
+    pub address_pool: Option<AddressPool>,
+    /// The transport sending requests. Requests are sent with `http_client` if
+    /// this is [None].
+    pub transport: Option<Arc<dyn HttpTransport>>,
 }
 
 impl Config {
     /// This method creates a new default configuration for the Consul client
     /// from the current environment.
     ///
+    /// If `CONSUL_HTTP_SSL` is true or the address uses HTTPS, the TLS
+    /// configuration is read from the environment as well, see
+    /// [TlsConfig::from_env]. If `CONSUL_HTTP_TOKEN_FILE` is set, the token is
+    /// read from that file unless `CONSUL_HTTP_TOKEN` is also set.
+    ///
     /// # Panics
-    /// Panics if `request::Client` construction fails.
+    /// Panics if `request::Client` construction fails, or if the TLS
+    /// configuration is invalid. If the token file cannot be read, a warning
+    /// is logged and it is read again by the next request.
     pub fn new_from_env() -> Config {
         let consul_addr = match env::var("CONSUL_HTTP_ADDR") {
             Ok(val) => {
-                if val.starts_with("http") {
+                if val.starts_with("http") || val.starts_with("unix://") {
                     val
                 } else {
                     format!("http://{}", val)
@@ -139,41 +338,88 @@ impl Config {
             Err(_e) => String::from("http://127.0.0.1:8500"),
         };
         let consul_token = env::var("CONSUL_HTTP_TOKEN").ok();
-        let client = ClientBuilder::new().build().unwrap();
-        Config {
+        #[cfg(feature = "reqwest")]
+        let tls =
+            tls::env_bool("CONSUL_HTTP_SSL") == Some(true) || consul_addr.starts_with("https");
+        let config = Config {
             address: consul_addr,
             datacenter: None,
-            http_client: client,
+            #[cfg(feature = "enterprise")]
+            namespace: env::var("CONSUL_NAMESPACE").ok(),
+            #[cfg(feature = "enterprise")]
+            partition: env::var("CONSUL_PARTITION").ok(),
+            #[cfg(feature = "reqwest")]
+            http_client: ClientBuilder::new().build().unwrap(),
             token: consul_token,
+            token_file: env::var("CONSUL_HTTP_TOKEN_FILE").ok().map(|path| {
+                TokenFile::new(&path).unwrap_or_else(|e| {
+                    tracing::warn!(path = %path, "failed to read token file: {}", e);
+                    TokenFile::unread(path)
+                })
+            }),
+            shared_token: None,
             wait_time: None,
+            retry_policy: None,
+            middleware: Vec::new(),
+            metrics_sink: None,
+            address_pool: None,
+            transport: None,
+        };
+        #[cfg(feature = "reqwest")]
+        if tls {
+            return config.with_tls(TlsConfig::from_env()).unwrap();
         }
+        config
     }
 
     /// This method creates a new `Config` with the given address.
     ///
     /// # Panics
     /// Panics if `request::Client` construction fails.
+    #[deprecated(note = "use `Config::builder` instead")]
     pub fn new_from_consul_host(host: &str, port: Option<u16>, token: Option<String>) -> Config {
-        let client = ClientBuilder::new().build().unwrap();
         Config {
             address: format!("{}:{}", host, port.unwrap_or(8500)),
             datacenter: None,
-            http_client: client,
+            #[cfg(feature = "enterprise")]
+            namespace: None,
+            #[cfg(feature = "enterprise")]
+            partition: None,
+            #[cfg(feature = "reqwest")]
+            http_client: ClientBuilder::new().build().unwrap(),
             token,
+            token_file: None,
+            shared_token: None,
             wait_time: None,
+            retry_policy: None,
+            middleware: Vec::new(),
+            metrics_sink: None,
+            address_pool: None,
+            transport: None,
         }
     }
 }
 
 impl Default for Config {
     fn default() -> Self {
-        let client = ClientBuilder::new().build().unwrap();
         Config {
             address: String::from("http://127.0.0.1:8500"),
             datacenter: None,
-            http_client: client,
+            #[cfg(feature = "enterprise")]
+            namespace: None,
+            #[cfg(feature = "enterprise")]
+            partition: None,
+            #[cfg(feature = "reqwest")]
+            http_client: ClientBuilder::new().build().unwrap(),
             token: None,
+            token_file: None,
+            shared_token: None,
             wait_time: None,
+            retry_policy: None,
+            middleware: Vec::new(),
+            metrics_sink: None,
+            address_pool: None,
+            transport: None,
         }
     }
 }
@@ -182,10 +428,39 @@ impl Default for Config {
 #[derive(Debug, Error)]
 pub enum ConsulError {
     /// The Consul API returned an error.
+    #[cfg(feature = "reqwest")]
     #[error("http request failed")]
     HttpError(#[from] reqwest::Error),
-    #[error("request failed with code {0}")]
-    RequestFailed(reqwest::StatusCode),
+    /// An [HttpTransport] failed to send a request or receive its response, or
+    /// the stream of a request body failed.
+    #[error("transport failed")]
+    TransportError(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// A [Middleware] failed to process a request or its response.
+    #[error("middleware failed")]
+    MiddlewareError(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The Consul API responded with an unexpected error.
+    #[error("request failed, {0}")]
+    RequestFailed(ErrorResponse),
+    /// The request's token lacks the permissions required by the request, or
+    /// does not exist.
+    #[error("permission denied, {0}")]
+    AclPermissionDenied(ErrorResponse),
+    /// The requested resource does not exist.
+    #[error("not found, {0}")]
+    NotFound(ErrorResponse),
+    /// The session used by the request has expired or does not exist.
+    #[error("session expired, {0}")]
+    SessionExpired(ErrorResponse),
+    /// The check used by the request is not registered with the agent.
+    #[error("check not found, {0}")]
+    CheckNotFound(ErrorResponse),
+    /// The request was rejected as invalid by the Consul API.
+    #[error("invalid request, {0}")]
+    InvalidRequest(ErrorResponse),
+    /// The request was rejected by the agent's rate limit. If the agent
+    /// requested a delay before retrying, it is returned as `retry_after`.
+    #[error("request was rate limited")]
+    RateLimited { retry_after: Option<Duration> },
     /// A parameter was not provided.
     #[error("missing parameter, {0}")]
     MissingParameter(String),
@@ -193,6 +468,130 @@ pub enum ConsulError {
     EmptyKey,
     #[error("failed to decode response body")]
     DecodeError(#[from] serde_json::Error),
+    /// The value of a key could not be decoded from base64.
+    #[cfg(feature = "kv")]
+    #[error("failed to decode base64 value")]
+    Base64Error(#[from] base64::DecodeError),
+    /// A value could not be encoded as JSON before being written to a key.
+    #[cfg(feature = "kv")]
+    #[error("failed to encode value")]
+    EncodeError(serde_json::Error),
+    /// A check-and-set write kept conflicting with concurrent writes.
+    #[cfg(feature = "kv")]
+    #[error("check-and-set failed after {0} attempts")]
+    CasConflict(usize),
+    /// A value could not be encoded as or decoded from YAML.
+    #[cfg(feature = "yaml")]
+    #[error("failed to encode or decode yaml value")]
+    YamlError(#[from] serde_yaml::Error),
+    /// A semaphore was acquired with a limit different from the limit of its
+    /// existing holders.
+    #[cfg(feature = "semaphore")]
+    #[error("semaphore exists with a limit of {0}")]
+    SemaphoreLimitConflict(usize),
+    /// The contender key of a semaphore could not be locked.
+    #[cfg(feature = "semaphore")]
+    #[error("failed to lock the contender key of the semaphore")]
+    SemaphoreContenderConflict,
+    /// A member joined a presence set with the ID of a member already present.
+    #[cfg(feature = "presence")]
+    #[error("member {0} is already present")]
+    MemberConflict(String),
+    /// The lease of a queue item was lost, because the session holding it
+    /// was invalidated.
+    #[cfg(feature = "queue")]
+    #[error("lease of item {0} was lost")]
+    LeaseLost(String),
+    /// A transaction was rolled back due to the failure of one or more of its
+    /// operations.
+    #[cfg(feature = "txn")]
+    #[error("transaction rolled back with {} errors", .0.len())]
+    TxnFailed(Vec<TxnError>),
+    /// An I/O error occurred on a Connect-native connection, or the runtime
+    /// of a blocking client could not be started.
+    #[cfg(any(feature = "blocking", feature = "connect-native"))]
+    #[error("i/o error")]
+    IoError(#[from] std::io::Error),
+    /// A TLS configuration could not be built from the certificates issued by
+    /// the Connect CA.
+    #[cfg(feature = "connect-native")]
+    #[error("tls error")]
+    TlsError(#[from] tokio_rustls::rustls::Error),
+    /// A certificate issued by the Connect CA or presented by a peer was
+    /// invalid.
+    #[cfg(feature = "connect-native")]
+    #[error("invalid certificate, {0}")]
+    InvalidCertificate(String),
+    /// A Connect-native connection was denied by the intentions.
+    #[cfg(feature = "connect-native")]
+    #[error("connection unauthorized, {0}")]
+    Unauthorized(String),
+    /// The DNS interface of the agent returned a failed or malformed
+    /// response.
+    #[cfg(feature = "dns")]
+    #[error("dns query failed, {0}")]
+    DnsError(String),
+    /// No healthy instances of an upstream service were found.
+    #[cfg(feature = "connect-native")]
+    #[error("no healthy instances of {0}")]
+    NoInstances(String),
+    /// The expiration time of an ACL token could not be parsed.
+    #[cfg(feature = "token-renewal")]
+    #[error("invalid token expiration time")]
+    ExpirationTimeError(#[from] time::error::Parse),
+}
+
+/// The consistency mode of a read, used by [QueryOptions::consistency].
+///
+/// For more information, see the [API documentation].
+///
+/// [API documentation]: https://www.consul.io/api-docs/features/consistency
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum ConsistencyMode {
+    /// Reads are served by the leader, which may return stale values in the
+    /// rare case of a leadership change. This is the default.
+    #[default]
+    Default,
+    /// Reads are served by any server, without contacting the leader. This
+    /// allows reads to succeed during leader elections, at the cost of
+    /// possibly stale values. [QueryMeta::last_contact] reports how stale the
+    /// values may be.
+    Stale,
+    /// Reads are served by the leader after confirming its leadership with a
+    /// quorum of servers, at the cost of an additional round trip.
+    Consistent,
+    /// Reads are served from the agent's cache, on endpoints which support
+    /// it.
+    Cached {
+        /// The maximum age of a cached result. Older results are refetched.
+        max_age: Option<Duration>,
+        /// How long a cached result may be served past its `max_age` if
+        /// refetching it fails.
+        stale_if_error: Option<Duration>,
+    },
+}
+
+/// The origin used to sort results by estimated round trip time, used by
+/// [QueryOptions::near].
+///
+/// Round trip times are estimated using [network coordinates].
+///
+/// [network coordinates]: https://www.consul.io/docs/architecture/coordinates
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Near {
+    /// Sort by distance from the agent handling the request.
+    Agent,
+    /// Sort by distance from the node with the given name.
+    Node(String),
+}
+
+impl Near {
+    fn as_str(&self) -> &str {
+        match self {
+            Near::Agent => "_agent",
+            Near::Node(node) => node,
+        }
+    }
 }
 
 /// Query options to fine tune requests made to the agent.
@@ -200,17 +599,154 @@ pub enum ConsulError {
 pub struct QueryOptions {
     /// The datacenter to query.
     pub datacenter: Option<String>,
+    /// The namespace to query. If not set, the client's `namespace` is used,
+    /// and otherwise the namespace of the request's token.
+    #[cfg(feature = "enterprise")]
+    pub namespace: Option<String>,
+    /// The admin partition to query. If not set, the client's `partition` is
+    /// used, and otherwise the partition of the request's token.
+    #[cfg(feature = "enterprise")]
+    pub partition: Option<String>,
+    /// The ACL token used for the request, instead of the client's `token`.
+    pub token: Option<String>,
+    /// The index to block on. If set, the query becomes a [blocking query],
+    /// and will not return until the index of the result has changed or
+    /// `wait_time` has elapsed.
+    ///
+    /// [blocking query]: https://www.consul.io/api-docs/features/blocking
     pub wait_index: Option<u64>,
-    /// The maximum time to wait for a query to complete.
+    /// The maximum time to wait for a blocking query to complete. If not set,
+    /// the client's `wait_time` is used, and otherwise the agent's default of
+    /// 5 minutes.
     pub wait_time: Option<Duration>,
+    /// The content hash to block on, on the endpoints supporting [hash-based
+    /// blocking queries] instead of an index, such as the configuration of a
+    /// local service. It is the [QueryMeta::last_content_hash] of a previous
+    /// result.
+    ///
+    /// [hash-based blocking queries]: https://www.consul.io/api-docs/features/blocking#hash-based-blocking-queries
+    pub wait_hash: Option<String>,
+    /// A [filter expression] used to filter the results of list endpoints. It
+    /// can be built using a [Filter].
+    ///
+    /// [filter expression]: https://www.consul.io/api-docs/features/filtering
+    pub filter: Option<String>,
+    /// The node to sort results by estimated round trip time from, on the
+    /// catalog and health endpoints listing nodes and service instances.
+    pub near: Option<Near>,
+    /// The consistency mode of the read.
+    pub consistency: ConsistencyMode,
+}
+
+/// Write options to fine tune requests modifying the state of the cluster.
+#[derive(Clone, Debug, Default)]
+pub struct WriteOptions {
+    /// The datacenter to write to.
+    pub datacenter: Option<String>,
+    /// The namespace to write to. If not set, the client's `namespace` is
+    /// used, and otherwise the namespace of the request's token.
+    #[cfg(feature = "enterprise")]
+    pub namespace: Option<String>,
+    /// The admin partition to write to. If not set, the client's `partition`
+    /// is used, and otherwise the partition of the request's token.
+    #[cfg(feature = "enterprise")]
+    pub partition: Option<String>,
+    /// The ACL token used for the request, instead of the client's `token`.
+    pub token: Option<String>,
+    /// The number of additional nodes relaying the messages of gossip-based
+    /// writes, such as keyring operations, to improve their delivery. This
+    /// must be between 0 and 5.
+    pub relay_factor: Option<u8>,
+}
+
+/// Metadata returned by the agent alongside the result of a query.
+#[derive(Clone, Debug, Default)]
+pub struct QueryMeta {
+    /// The value of the `X-Consul-Index` header. This can be passed as
+    /// [QueryOptions::wait_index] to perform a blocking query for changes to
+    /// the result.
+    pub last_index: Option<u64>,
+    /// The time taken to complete the request.
+    pub request_time: Duration,
+    /// The value of the `X-Consul-KnownLeader` header, indicating whether the
+    /// server handling the request knew of a leader.
+    pub known_leader: bool,
+    /// The value of the `X-Consul-LastContact` header, which is the time
+    /// elapsed since the server handling the request last contacted the
+    /// leader. This is zero if the server is the leader.
+    pub last_contact: Duration,
+    /// Whether the result was served from the agent's cache, as reported by
+    /// the `X-Cache` header.
+    pub cache_hit: bool,
+    /// The value of the `Age` header, which is the age of a cached result.
+    pub cache_age: Option<Duration>,
+    /// The value of the `X-Consul-Translate-Addresses` header, indicating
+    /// whether the agent translates addresses to their WAN addresses when
+    /// queried from another datacenter.
+    pub address_translation_enabled: bool,
+    /// The value of the `X-Consul-ContentHash` header, returned by the
+    /// endpoints supporting hash-based blocking queries. This can be passed
+    /// as [QueryOptions::wait_hash] to block for changes to the result.
+    pub last_content_hash: Option<String>,
+}
+
+/// Metadata returned by the agent alongside the result of a write.
+#[derive(Clone, Debug, Default)]
+pub struct WriteMeta {
+    /// The time taken to complete the request.
+    pub request_time: Duration,
+}
+
+/// The details of an unsuccessful response of the Consul API, returned in
+/// [ConsulError].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ErrorResponse {
+    /// The status code of the response.
+    pub status: StatusCode,
+    /// The URL of the request.
+    pub url: String,
+    /// The error message of the response, truncated if it was too long.
+    pub message: String,
+}
+
+impl fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} from {}", self.status, self.url)?;
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        Ok(())
+    }
 }
 
 /// Type alias for `Result<T, ConsulError>`.
 pub type ConsulResult<T> = Result<T, ConsulError>;
 
+impl ConsulError {
+    /// This method returns whether the error is a failure to connect to the
+    /// agent, in which case the request was never received.
+    pub(crate) fn is_connect(&self) -> bool {
+        match self {
+            #[cfg(feature = "reqwest")]
+            ConsulError::HttpError(e) => e.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// This method returns whether the error is a request timing out.
+    #[cfg(feature = "failover")]
+    pub(crate) fn is_timeout(&self) -> bool {
+        match self {
+            #[cfg(feature = "reqwest")]
+            ConsulError::HttpError(e) => e.is_timeout(),
+            _ => false,
+        }
+    }
+}
+
 pub(crate) mod sealed {
-    ///! Internal module to prevent re-implementation of strictly
-    /// client-related traits.
+    //! Internal module to prevent re-implementation of strictly
+    //! client-related traits.
     use crate::Client;
 
     pub trait Sealed {}
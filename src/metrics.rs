@@ -0,0 +1,64 @@
+use std::{fmt::Debug, time::Duration};
+
+use bytes::Bytes;
+use http::{Method, Request, StatusCode};
+
+use crate::{transport::ClientResponse, ConsulResult};
+
+/// A receiver of observations of every request made to the Consul API,
+/// registered with [ConfigBuilder::metrics_sink].
+///
+/// This can be used to export request rates, latencies and errors to a
+/// metrics system such as Prometheus. The sink is called once per request,
+/// after any retries, so it should not block.
+///
+/// [ConfigBuilder::metrics_sink]: crate::ConfigBuilder::metrics_sink
+pub trait MetricsSink: Debug + Send + Sync {
+    /// This method records the observation of a completed request.
+    fn observe(&self, observation: &RequestObservation);
+}
+
+/// An observation of a completed request, passed to a [MetricsSink].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RequestObservation {
+    /// The method of the request.
+    pub method: Method,
+    /// The path of the request, such as `/v1/kv/config`. Paths may contain
+    /// keys and IDs, so they should be normalized before being used as a
+    /// metric label.
+    pub path: String,
+    /// The datacenter of the request, if one was set.
+    pub datacenter: Option<String>,
+    /// The status code of the response, or [None] if no response was
+    /// received.
+    pub status: Option<StatusCode>,
+    /// The time from sending the request until its response was received,
+    /// including any retries.
+    pub latency: Duration,
+}
+
+impl RequestObservation {
+    /// This method returns whether the request received a successful
+    /// response.
+    pub fn is_success(&self) -> bool {
+        self.status.is_some_and(|status| status.is_success())
+    }
+
+    /// This method creates an observation of the given request, before it is
+    /// sent.
+    pub(crate) fn new(request: &Request<Bytes>, datacenter: Option<String>) -> Self {
+        RequestObservation {
+            method: request.method().clone(),
+            path: request.uri().path().to_owned(),
+            datacenter,
+            status: None,
+            latency: Duration::ZERO,
+        }
+    }
+
+    /// This method completes the observation with the result of the request.
+    pub(crate) fn finish(&mut self, result: &ConsulResult<ClientResponse>, latency: Duration) {
+        self.status = result.as_ref().ok().map(|response| response.status());
+        self.latency = latency;
+    }
+}
@@ -0,0 +1,52 @@
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::{response::Parts, Request};
+
+use crate::ConsulResult;
+
+/// A hook into every request made to the Consul API, registered with
+/// [ConfigBuilder::middleware].
+///
+/// Middleware can be used to add custom headers, sign requests or log them for
+/// auditing. Registered middleware is called in order of registration, once
+/// per attempt if the request is retried. Requests and responses are types of
+/// the `http` crate, whatever the transport sending them.
+///
+/// ```
+/// use async_trait::async_trait;
+/// use bytes::Bytes;
+/// use consul_oxide::{Config, ConsulResult, Middleware};
+/// use http::{header::HeaderValue, Request};
+///
+/// #[derive(Debug)]
+/// struct RequestId;
+///
+/// #[async_trait]
+/// impl Middleware for RequestId {
+///     async fn before_request(&self, request: &mut Request<Bytes>) -> ConsulResult<()> {
+///         request.headers_mut().insert("X-Request-Id", HeaderValue::from_static("42"));
+///         Ok(())
+///     }
+/// }
+///
+/// let config = Config::builder().middleware(RequestId).build().unwrap();
+/// ```
+///
+/// [ConfigBuilder::middleware]: crate::ConfigBuilder::middleware
+#[async_trait]
+pub trait Middleware: Debug + Send + Sync {
+    /// This method is called before the given request is sent. Returning an
+    /// error aborts the request.
+    async fn before_request(&self, _request: &mut Request<Bytes>) -> ConsulResult<()> {
+        Ok(())
+    }
+
+    /// This method is called with the status code and headers of a response
+    /// once it has been received, before its body is read. Returning an error
+    /// fails the request.
+    async fn after_response(&self, _response: &Parts) -> ConsulResult<()> {
+        Ok(())
+    }
+}
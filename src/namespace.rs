@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::{
+    sealed::Sealed, Client, ConsulResult, QueryMeta, QueryOptions, WriteMeta, WriteOptions,
+};
+
+/// A link to an ACL policy or role, used in [NamespaceAcls].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct NamespaceAclLink {
+    /// The ID of the policy or role. Either this or `name` must be set.
+    #[serde(rename = "ID", skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    /// The name of the policy or role.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub name: String,
+}
+
+/// The default ACL policies and roles of a [Namespace].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct NamespaceAcls {
+    /// The policies applied to all tokens in the namespace.
+    pub policy_defaults: Vec<NamespaceAclLink>,
+    /// The roles applied to all tokens in the namespace.
+    pub role_defaults: Vec<NamespaceAclLink>,
+}
+
+/// A Consul Enterprise namespace.
+///
+/// This type is used as both the request payload and the response of the
+/// namespace endpoints. For more information, see the [API documentation].
+///
+/// [API documentation]: https://www.consul.io/api-docs/namespaces
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct Namespace {
+    /// The name of the namespace.
+    pub name: String,
+    /// Free form human readable description of the namespace.
+    pub description: String,
+    /// The default ACL policies and roles of the namespace.
+    #[serde(rename = "ACLs")]
+    pub acls: Option<NamespaceAcls>,
+    /// Arbitrary metadata attached to the namespace.
+    pub meta: HashMap<String, String>,
+    /// The time at which the namespace was marked for deletion, in RFC 3339
+    /// format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+    #[serde(skip_serializing)]
+    pub create_index: u64,
+    #[serde(skip_serializing)]
+    pub modify_index: u64,
+}
+
+/// This trait provides methods for interacting with the `/namespace`
+/// endpoints, which are only available in Consul Enterprise.
+#[async_trait]
+pub trait Namespaces: Sealed {
+    /// This method creates a new namespace.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/namespaces#create-a-namespace
+    async fn create_namespace(
+        &self,
+        namespace: Namespace,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Namespace, WriteMeta)>;
+
+    /// This method returns the namespace with the given name, or [None] if it
+    /// does not exist.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/namespaces#read-a-namespace
+    async fn read_namespace(
+        &self,
+        name: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<Namespace>, QueryMeta)>;
+
+    /// This method updates an existing namespace, identified by its `name`
+    /// field.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/namespaces#update-a-namespace
+    async fn update_namespace(
+        &self,
+        namespace: Namespace,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Namespace, WriteMeta)>;
+
+    /// This method marks a namespace for deletion. The namespace and the
+    /// resources it contains are then deleted in the background.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/namespaces#delete-a-namespace
+    async fn delete_namespace(
+        &self,
+        name: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+
+    /// This method lists all the namespaces the request's token has access
+    /// to.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/namespaces#list-all-namespaces
+    async fn list_namespaces(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<Namespace>, QueryMeta)>;
+}
+
+#[async_trait]
+impl Namespaces for Client {
+    #[tracing::instrument]
+    async fn create_namespace(
+        &self,
+        namespace: Namespace,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Namespace, WriteMeta)> {
+        self.put("/v1/namespace", namespace, None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn read_namespace(
+        &self,
+        name: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<Namespace>, QueryMeta)> {
+        let path = format!("/v1/namespace/{}", name);
+        self.get_with_meta(&path, None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn update_namespace(
+        &self,
+        namespace: Namespace,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Namespace, WriteMeta)> {
+        let path = format!("/v1/namespace/{}", namespace.name);
+        self.put(&path, namespace, None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn delete_namespace(
+        &self,
+        name: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        let path = format!("/v1/namespace/{}", name);
+        self.delete_with_empty(&path, None, options)
+            .await
+            .map(|(r, meta): (Option<bool>, _)| (r.unwrap_or(true), meta))
+    }
+
+    #[tracing::instrument]
+    async fn list_namespaces(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<Namespace>, QueryMeta)> {
+        self.get_with_meta("/v1/namespaces", None, options)
+            .await
+            .map(|(r, meta): (Option<Vec<Namespace>>, _)| (r.unwrap_or_default(), meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::Method;
+
+    use crate::{Client, Config, QueryOptions};
+
+    #[test]
+    fn test_namespace_param() {
+        let config = Config { namespace: Some(String::from("team")), ..Config::default() };
+        let client = Client::new(config);
+        let url = |options| {
+            let request = client.build_request(Method::GET, "/v1/kv/a", None, None::<()>, options);
+            request.uri().query().unwrap_or_default().to_owned()
+        };
+        assert_eq!(url(None), "ns=team");
+        let options = QueryOptions { namespace: Some(String::from("other")), ..Default::default() };
+        assert_eq!(url(Some(options)), "ns=other");
+    }
+
+    #[test]
+    fn test_scoped_client() {
+        let client = Client::new(Config::default()).with_namespace("team").with_partition("eu");
+        let request =
+            client.build_request(Method::GET, "/v1/kv/a", None, None::<()>, None::<QueryOptions>);
+        let query = request.uri().query().unwrap_or_default();
+        let mut params: Vec<_> =
+            url::form_urlencoded::parse(query.as_bytes()).into_owned().collect();
+        params.sort();
+        assert_eq!(params, [("ns".into(), "team".into()), ("partition".into(), "eu".into())]);
+    }
+}
@@ -0,0 +1,325 @@
+use async_trait::async_trait;
+
+use crate::{sealed::Sealed, Client, ConsulResult, QueryOptions, WriteMeta, WriteOptions};
+
+/// A network area, which joins the servers of two datacenters without a
+/// shared WAN gossip pool.
+///
+/// This type is used as both the request payload and the response of the
+/// area endpoints. For more information, see the [API documentation].
+///
+/// [API documentation]: https://www.consul.io/api-docs/operator/area
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct Area {
+    /// The ID of the area. This is assigned by Consul on creation.
+    #[serde(rename = "ID", skip_serializing)]
+    pub id: String,
+    /// The name of the datacenter on the other side of the area.
+    pub peer_datacenter: String,
+    /// The addresses of servers in the peer datacenter that are joined
+    /// automatically, with optional ports, such as "10.1.2.3:8300".
+    pub retry_join: Vec<String>,
+    /// Whether gossip over the area is encrypted with TLS.
+    #[serde(rename = "UseTLS")]
+    pub use_tls: bool,
+}
+
+/// The result of joining a single address, as returned by
+/// [OperatorAreas::join_area].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AreaJoinResponse {
+    /// The address that was joined.
+    pub address: String,
+    /// Whether the join succeeded.
+    pub joined: bool,
+    /// The error encountered if the join failed.
+    pub error: String,
+}
+
+/// A server in a network area, as returned by
+/// [OperatorAreas::list_area_members].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AreaMember {
+    /// The node ID of the server.
+    #[serde(rename = "ID")]
+    pub id: String,
+    /// The node name of the server, suffixed with its datacenter.
+    pub name: String,
+    /// The IP address of the server.
+    pub addr: String,
+    /// The server RPC port of the server.
+    pub port: u16,
+    /// The datacenter of the server.
+    pub datacenter: String,
+    /// The role of the server, which is always `server`.
+    pub role: String,
+    /// The Consul version of the server.
+    pub build: String,
+    /// The protocol version of the server.
+    pub protocol: u64,
+    /// The health of the server, such as `alive`, `leaving`, `left` or
+    /// `failed`.
+    pub status: String,
+    /// The estimated round trip time to the server, in nanoseconds.
+    #[serde(rename = "RTT")]
+    pub rtt: u64,
+}
+
+/// This trait provides methods for interacting with the `/operator/area`
+/// endpoints, which are only available in Consul Enterprise.
+#[async_trait]
+pub trait OperatorAreas: Sealed {
+    /// This method creates a new network area, returning its ID.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/area#create-network-area
+    async fn create_area(
+        &self,
+        area: Area,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(String, WriteMeta)>;
+
+    /// This method lists the network areas of the datacenter.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/area#list-network-areas
+    async fn list_areas(&self, options: Option<QueryOptions>) -> ConsulResult<Vec<Area>>;
+
+    /// This method returns the network area with the given ID, or [None] if
+    /// it does not exist.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/area#list-specific-network-area
+    async fn get_area(&self, id: &str, options: Option<QueryOptions>)
+        -> ConsulResult<Option<Area>>;
+
+    /// This method updates the network area with the given ID. Only the
+    /// `use_tls` field can be changed after creation.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/area#update-network-area
+    async fn update_area(
+        &self,
+        id: &str,
+        area: Area,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>;
+
+    /// This method deletes the network area with the given ID.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/area#delete-network-area
+    async fn delete_area(&self, id: &str, options: Option<WriteOptions>)
+        -> ConsulResult<WriteMeta>;
+
+    /// This method joins the servers at the given addresses into the network
+    /// area, returning the result of each join.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/area#join-network-area
+    async fn join_area(
+        &self,
+        id: &str,
+        addresses: Vec<String>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Vec<AreaJoinResponse>, WriteMeta)>;
+
+    /// This method lists the servers in the network area with the given ID.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/area#list-network-area-members
+    async fn list_area_members(
+        &self,
+        id: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Vec<AreaMember>>;
+}
+
+#[async_trait]
+impl OperatorAreas for Client {
+    #[tracing::instrument]
+    async fn create_area(
+        &self,
+        area: Area,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(String, WriteMeta)> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Response {
+            #[serde(rename = "ID")]
+            id: String,
+        }
+        self.post("/v1/operator/area", area, None, options)
+            .await
+            .map(|(r, meta): (Response, _)| (r.id, meta))
+    }
+
+    #[tracing::instrument]
+    async fn list_areas(&self, options: Option<QueryOptions>) -> ConsulResult<Vec<Area>> {
+        self.get("/v1/operator/area", options).await
+    }
+
+    #[tracing::instrument]
+    async fn get_area(
+        &self,
+        id: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Option<Area>> {
+        let path = format!("/v1/operator/area/{}", id);
+        self.get_with_meta(&path, None, options)
+            .await
+            .map(|(r, _): (Option<Vec<Area>>, _)| r.unwrap_or_default().into_iter().next())
+    }
+
+    #[tracing::instrument]
+    async fn update_area(
+        &self,
+        id: &str,
+        area: Area,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta> {
+        let path = format!("/v1/operator/area/{}", id);
+        self.put_with_empty(&path, area, None, options)
+            .await
+            .map(|(_, meta): (Option<serde_json::Value>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn delete_area(
+        &self,
+        id: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta> {
+        let path = format!("/v1/operator/area/{}", id);
+        self.delete_with_empty(&path, None, options).await.map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn join_area(
+        &self,
+        id: &str,
+        addresses: Vec<String>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Vec<AreaJoinResponse>, WriteMeta)> {
+        let path = format!("/v1/operator/area/{}/join", id);
+        self.put(&path, addresses, None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn list_area_members(
+        &self,
+        id: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Vec<AreaMember>> {
+        self.get(format!("/v1/operator/area/{}/members", id), options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Area, OperatorAreas};
+    use crate::{operator::OperatorSegments, request::tests::recording_client};
+
+    const AREA_ID: &str = "8f246b77-f3e1-ff88-5b48-8ec93abf3e05";
+
+    #[tokio::test]
+    async fn test_areas() {
+        const ID: &str = r#"{"ID": "8f246b77-f3e1-ff88-5b48-8ec93abf3e05"}"#;
+        const AREAS: &str = r#"[{
+            "ID": "8f246b77-f3e1-ff88-5b48-8ec93abf3e05",
+            "PeerDatacenter": "dc2",
+            "RetryJoin": ["10.1.2.3"],
+            "UseTLS": true
+        }]"#;
+        const JOINED: &str = r#"[
+            {"Address": "10.1.2.3", "Joined": true, "Error": ""},
+            {"Address": "10.1.2.4", "Joined": false, "Error": "dial tcp: i/o timeout"}
+        ]"#;
+        const MEMBERS: &str = r#"[{
+            "ID": "afc5d95c-1eee-4b46-b85b-0efe4c76dd48",
+            "Name": "node-2.dc2",
+            "Addr": "10.1.2.3",
+            "Port": 8300,
+            "Datacenter": "dc2",
+            "Role": "server",
+            "Build": "1.9.0",
+            "Protocol": 2,
+            "Status": "alive",
+            "RTT": 256478
+        }]"#;
+        let (client, requests) = recording_client(&[
+            (200, &[], ID),
+            (200, &[], AREAS),
+            (404, &[], ""),
+            (200, &[], ID),
+            (200, &[], JOINED),
+            (200, &[], MEMBERS),
+            (200, &[], ""),
+            (200, &[], r#"["", "alpha"]"#),
+        ]);
+        let area = Area {
+            peer_datacenter: String::from("dc2"),
+            retry_join: vec![String::from("10.1.2.3")],
+            ..Default::default()
+        };
+        let (id, _) = client.create_area(area.clone(), None).await.unwrap();
+        assert_eq!(id, AREA_ID);
+        let read = client.get_area(AREA_ID, None).await.unwrap().unwrap();
+        assert_eq!(read.peer_datacenter, "dc2");
+        assert!(read.use_tls);
+        assert!(client.get_area("missing", None).await.unwrap().is_none());
+        client.update_area(AREA_ID, Area { use_tls: true, ..area }, None).await.unwrap();
+        let addresses = vec![String::from("10.1.2.3"), String::from("10.1.2.4")];
+        let (joined, _) = client.join_area(AREA_ID, addresses, None).await.unwrap();
+        assert!(joined[0].joined);
+        assert_eq!(joined[1].error, "dial tcp: i/o timeout");
+        let members = client.list_area_members(AREA_ID, None).await.unwrap();
+        assert_eq!(members[0].rtt, 256478);
+        client.delete_area(AREA_ID, None).await.unwrap();
+        assert_eq!(client.list_segments(None).await.unwrap(), ["", "alpha"]);
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "POST /v1/operator/area",
+                format!("GET /v1/operator/area/{}", AREA_ID).as_str(),
+                "GET /v1/operator/area/missing",
+                format!("PUT /v1/operator/area/{}", AREA_ID).as_str(),
+                format!("PUT /v1/operator/area/{}/join", AREA_ID).as_str(),
+                format!("GET /v1/operator/area/{}/members", AREA_ID).as_str(),
+                format!("DELETE /v1/operator/area/{}", AREA_ID).as_str(),
+                "GET /v1/operator/segment",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_encode_area() {
+        let area = Area {
+            id: String::from(AREA_ID),
+            peer_datacenter: String::from("dc2"),
+            retry_join: Vec::new(),
+            use_tls: true,
+        };
+        assert_eq!(
+            serde_json::to_string(&area).unwrap(),
+            r#"{"PeerDatacenter":"dc2","RetryJoin":[],"UseTLS":true}"#
+        );
+    }
+}
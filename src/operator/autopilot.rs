@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::{sealed::Sealed, Client, ConsulResult, QueryOptions, WriteMeta, WriteOptions};
+
+/// The autopilot configuration of the cluster.
+///
+/// For more information, see the [API documentation].
+///
+/// [API documentation]: https://www.consul.io/api-docs/operator/autopilot#update-configuration
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AutopilotConfiguration {
+    /// Specifies automatic removal of dead server nodes periodically and
+    /// whenever a new server is added to the cluster.
+    pub cleanup_dead_servers: bool,
+    /// Specifies the maximum amount of time a server can go without contact
+    /// from the leader before being considered unhealthy, such as "200ms".
+    pub last_contact_threshold: String,
+    /// Specifies the maximum number of log entries that a server can trail the
+    /// leader by before being considered unhealthy.
+    pub max_trailing_logs: u64,
+    /// Specifies the minimum number of servers needed before autopilot can
+    /// prune dead servers.
+    pub min_quorum: u64,
+    /// Specifies the minimum amount of time a server must be stable in the
+    /// healthy state before being added to the cluster, such as "10s".
+    pub server_stabilization_time: String,
+    /// Controls the node-meta key to use when autopilot is separating servers
+    /// into zones for redundancy (Enterprise-only).
+    pub redundancy_zone_tag: String,
+    /// Disables autopilot's upgrade migration strategy (Enterprise-only).
+    pub disable_upgrade_migration: bool,
+    /// Controls the node-meta key to use for version info when performing
+    /// upgrade migrations (Enterprise-only).
+    pub upgrade_version_tag: String,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+/// The health of a single server, as returned by
+/// [OperatorAutopilot::get_autopilot_health].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServerHealth {
+    /// The Raft ID of the server.
+    #[serde(rename = "ID")]
+    pub id: String,
+    /// The node name of the server.
+    pub name: String,
+    /// The address of the server.
+    pub address: String,
+    /// The status of the server according to Serf.
+    pub serf_status: String,
+    /// The Consul version of the server.
+    pub version: String,
+    /// Whether this server is currently the leader.
+    pub leader: bool,
+    /// The time elapsed since this server's last contact with the leader, such
+    /// as "12ms".
+    pub last_contact: String,
+    /// The server's last known Raft leader term.
+    pub last_term: u64,
+    /// The index of the server's last committed Raft log entry.
+    pub last_index: u64,
+    /// Whether the server is healthy according to the current autopilot
+    /// configuration.
+    pub healthy: bool,
+    /// Whether the server is a voting member of the Raft cluster.
+    pub voter: bool,
+    /// The time this server has been in its current healthy state.
+    pub stable_since: String,
+}
+
+/// Response payload for the [OperatorAutopilot::get_autopilot_health] method.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AutopilotHealth {
+    /// Whether all servers are currently healthy.
+    pub healthy: bool,
+    /// The number of redundant healthy servers that could fail without causing
+    /// an outage.
+    pub failure_tolerance: u64,
+    /// The health of each server.
+    pub servers: Vec<ServerHealth>,
+}
+
+/// The state of a single server, as tracked by autopilot.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AutopilotServer {
+    /// The Raft ID of the server.
+    #[serde(rename = "ID")]
+    pub id: String,
+    /// The node name of the server.
+    pub name: String,
+    /// The address of the server.
+    pub address: String,
+    /// The status of the server according to Serf.
+    pub node_status: String,
+    /// The Consul version of the server.
+    pub version: String,
+    /// The time elapsed since this server's last contact with the leader.
+    pub last_contact: String,
+    /// The server's last known Raft leader term.
+    pub last_term: u64,
+    /// The index of the server's last committed Raft log entry.
+    pub last_index: u64,
+    /// Whether the server is healthy according to the current autopilot
+    /// configuration.
+    pub healthy: bool,
+    /// The time this server has been in its current healthy state.
+    pub stable_since: String,
+    /// The name of the redundancy zone this server is in (Enterprise-only).
+    pub redundancy_zone: String,
+    /// The version used for upgrade migrations (Enterprise-only).
+    pub upgrade_version: String,
+    /// Whether this server is a read replica (Enterprise-only).
+    pub read_replica: bool,
+    /// The status of the server within Raft, such as `leader`, `voter`,
+    /// `non-voter` or `staging`.
+    pub status: String,
+    /// The node metadata of the server.
+    pub meta: HashMap<String, String>,
+    /// The type of the node, which is always `voter` or `read-replica`.
+    pub node_type: String,
+}
+
+/// A redundancy zone tracked by autopilot (Enterprise-only).
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AutopilotZone {
+    /// The IDs of all servers in the zone.
+    pub servers: Vec<String>,
+    /// The IDs of the voting servers in the zone.
+    pub voters: Vec<String>,
+    /// The number of servers in the zone that could fail without the zone
+    /// losing its voter.
+    pub failure_tolerance: u64,
+}
+
+/// The server IDs involved in an upgrade in a redundancy zone
+/// (Enterprise-only).
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AutopilotZoneUpgradeVersions {
+    pub target_version_voters: Vec<String>,
+    pub target_version_non_voters: Vec<String>,
+    pub other_version_voters: Vec<String>,
+    pub other_version_non_voters: Vec<String>,
+}
+
+/// The state of an automated upgrade migration (Enterprise-only).
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AutopilotUpgrade {
+    /// The status of the upgrade, such as `idle`, `promoting` or `demoting`.
+    pub status: String,
+    /// The version being upgraded to.
+    pub target_version: String,
+    pub target_version_voters: Vec<String>,
+    pub target_version_non_voters: Vec<String>,
+    pub target_version_read_replicas: Vec<String>,
+    pub other_version_voters: Vec<String>,
+    pub other_version_non_voters: Vec<String>,
+    pub other_version_read_replicas: Vec<String>,
+    /// The upgrade state of each redundancy zone.
+    pub redundancy_zones: HashMap<String, AutopilotZoneUpgradeVersions>,
+}
+
+/// Response payload for the [OperatorAutopilot::get_autopilot_state] method.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct AutopilotState {
+    /// Whether all servers are currently healthy.
+    pub healthy: bool,
+    /// The number of redundant healthy servers that could fail without causing
+    /// an outage.
+    pub failure_tolerance: u64,
+    /// The number of healthy servers that could fail without causing an outage,
+    /// assuming all failed servers are in the same redundancy zone.
+    pub optimistic_failure_tolerance: u64,
+    /// The state of each server, keyed by server ID.
+    pub servers: HashMap<String, AutopilotServer>,
+    /// The ID of the current leader.
+    pub leader: String,
+    /// The IDs of the voting servers.
+    pub voters: Vec<String>,
+    /// The IDs of the read replicas (Enterprise-only).
+    pub read_replicas: Vec<String>,
+    /// The redundancy zones, keyed by zone name (Enterprise-only).
+    pub redundancy_zones: HashMap<String, AutopilotZone>,
+    /// The state of any automated upgrade (Enterprise-only).
+    pub upgrade: Option<AutopilotUpgrade>,
+}
+
+/// This trait provides methods for interacting with the `/operator/autopilot`
+/// endpoints.
+#[async_trait]
+pub trait OperatorAutopilot: Sealed {
+    /// This method retrieves the autopilot configuration of the cluster.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/autopilot#read-configuration
+    async fn get_autopilot_configuration(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<AutopilotConfiguration>;
+
+    /// This method updates the autopilot configuration of the cluster.
+    ///
+    /// If `cas` is provided, the update only succeeds if the configuration's
+    /// current `modify_index` matches, and `false` is returned otherwise.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/autopilot#update-configuration
+    async fn update_autopilot_configuration(
+        &self,
+        config: AutopilotConfiguration,
+        cas: Option<u64>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
+
+    /// This method retrieves the health of the servers in the cluster.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/autopilot#read-health
+    async fn get_autopilot_health(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<AutopilotHealth>;
+
+    /// This method retrieves the full state of autopilot's view of the
+    /// cluster.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/autopilot#read-the-autopilot-state
+    async fn get_autopilot_state(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<AutopilotState>;
+}
+
+#[async_trait]
+impl OperatorAutopilot for Client {
+    #[tracing::instrument]
+    async fn get_autopilot_configuration(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<AutopilotConfiguration> {
+        self.get("/v1/operator/autopilot/configuration", options).await
+    }
+
+    #[tracing::instrument]
+    async fn update_autopilot_configuration(
+        &self,
+        config: AutopilotConfiguration,
+        cas: Option<u64>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
+        let mut params = HashMap::new();
+        if let Some(cas) = cas {
+            params.insert(String::from("cas"), cas.to_string());
+        }
+        // consul only returns a body when performing a check-and-set
+        self.put_with_empty("/v1/operator/autopilot/configuration", config, Some(params), options)
+            .await
+            .map(|(r, meta): (Option<bool>, _)| (r.unwrap_or(true), meta))
+    }
+
+    #[tracing::instrument]
+    async fn get_autopilot_health(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<AutopilotHealth> {
+        // consul responds with a 429 if the cluster is unhealthy, but still
+        // includes the health report
+        self.get("/v1/operator/autopilot/health", options).await
+    }
+
+    #[tracing::instrument]
+    async fn get_autopilot_state(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<AutopilotState> {
+        self.get("/v1/operator/autopilot/state", options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Client, Config, OperatorAutopilot};
+
+    #[tokio::test]
+    async fn test_autopilot_configuration() {
+        let client = Client::new(Config::default());
+        let config = client.get_autopilot_configuration(None).await.unwrap();
+        let index = config.modify_index;
+        assert!(client.update_autopilot_configuration(config, Some(index), None).await.unwrap().0);
+        let health = client.get_autopilot_health(None).await.unwrap();
+        assert!(health.healthy);
+    }
+}
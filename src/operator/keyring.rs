@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use http::Method;
+
+use crate::{sealed::Sealed, Client, ConsulResult, QueryOptions, WriteMeta, WriteOptions};
+
+/// The gossip encryption keys installed in a single gossip pool. Returned by
+/// [OperatorKeyring::list_keys].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct KeyringResponse {
+    /// Whether this response is for the WAN gossip pool.
+    #[serde(rename = "WAN")]
+    pub wan: bool,
+    /// The datacenter the gossip pool belongs to.
+    pub datacenter: String,
+    /// The network segment the gossip pool belongs to (Enterprise-only).
+    pub segment: String,
+    /// The admin partition the gossip pool belongs to (Enterprise-only).
+    pub partition: String,
+    /// A map of each installed key to the number of nodes it is installed on.
+    pub keys: HashMap<String, u64>,
+    /// A map of each primary key to the number of nodes using it as their
+    /// primary key.
+    pub primary_keys: HashMap<String, u64>,
+    /// The total number of nodes in the gossip pool.
+    pub num_nodes: u64,
+    /// Any messages returned by the nodes, keyed by node name.
+    pub messages: HashMap<String, String>,
+}
+
+/// Options for the [OperatorKeyring] methods.
+#[derive(Clone, Default, Debug)]
+pub struct KeyringOptions {
+    /// The number of additional nodes to relay the response through, which
+    /// can be used to increase the probability of the response reaching the
+    /// requester. Must be between 0 and 5.
+    pub relay_factor: Option<u8>,
+    /// Whether to only query the local datacenter's servers. Only applicable
+    /// to [OperatorKeyring::list_keys].
+    pub local_only: bool,
+}
+
+impl KeyringOptions {
+    /// This method converts the options into request parameters.
+    fn into_params(self) -> HashMap<String, String> {
+        let mut params = HashMap::new();
+        if let Some(relay_factor) = self.relay_factor {
+            params.insert(String::from("relay-factor"), relay_factor.to_string());
+        }
+        if self.local_only {
+            params.insert(String::from("local-only"), String::from("true"));
+        }
+        params
+    }
+}
+
+/// Request payload for the keyring modification methods.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct KeyringPayload<'a> {
+    key: &'a str,
+}
+
+/// This trait provides methods for interacting with the `/operator/keyring`
+/// endpoints, which manage the gossip encryption keys of the cluster.
+///
+/// For more information, see the [API documentation].
+///
+/// [API documentation]: https://www.consul.io/api-docs/operator/keyring
+#[async_trait]
+pub trait OperatorKeyring: Sealed {
+    /// This method lists the gossip encryption keys installed on both the WAN
+    /// and LAN gossip pools of every known datacenter.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/keyring#list-gossip-encryption-keys
+    async fn list_keys(
+        &self,
+        keyring_options: Option<KeyringOptions>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Vec<KeyringResponse>>;
+
+    /// This method installs a new gossip encryption key into the cluster.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/keyring#add-a-gossip-encryption-key
+    async fn install_key(
+        &self,
+        key: &str,
+        keyring_options: Option<KeyringOptions>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>;
+
+    /// This method changes the primary gossip encryption key. The key must
+    /// already be installed before this operation can succeed.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/keyring#change-primary-gossip-encryption-key
+    async fn use_key(
+        &self,
+        key: &str,
+        keyring_options: Option<KeyringOptions>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>;
+
+    /// This method removes a gossip encryption key from the cluster. This
+    /// operation may only be performed on keys which are not currently the
+    /// primary key.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/keyring#delete-a-gossip-encryption-key
+    async fn remove_key(
+        &self,
+        key: &str,
+        keyring_options: Option<KeyringOptions>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>;
+}
+
+#[async_trait]
+impl OperatorKeyring for Client {
+    #[tracing::instrument]
+    async fn list_keys(
+        &self,
+        keyring_options: Option<KeyringOptions>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Vec<KeyringResponse>> {
+        let params = keyring_options.unwrap_or_default().into_params();
+        self.get_with_params("/v1/operator/keyring", Some(params), options).await
+    }
+
+    #[tracing::instrument]
+    async fn install_key(
+        &self,
+        key: &str,
+        keyring_options: Option<KeyringOptions>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta> {
+        let params = keyring_options.unwrap_or_default().into_params();
+        self.post_with_empty("/v1/operator/keyring", KeyringPayload { key }, Some(params), options)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn use_key(
+        &self,
+        key: &str,
+        keyring_options: Option<KeyringOptions>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta> {
+        let params = keyring_options.unwrap_or_default().into_params();
+        self.put_with_empty("/v1/operator/keyring", KeyringPayload { key }, Some(params), options)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn remove_key(
+        &self,
+        key: &str,
+        keyring_options: Option<KeyringOptions>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta> {
+        let params = keyring_options.unwrap_or_default().into_params();
+        self.write_with_empty(
+            Method::DELETE,
+            "/v1/operator/keyring",
+            Some(params),
+            Some(KeyringPayload { key }),
+            options,
+        )
+        .await
+        .map(|(_, meta): (Option<()>, _)| meta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyringResponse;
+
+    #[test]
+    fn test_decode_keyring_response() {
+        let responses: Vec<KeyringResponse> = serde_json::from_str(
+            r#"[
+                {
+                    "WAN": true,
+                    "Datacenter": "dc1",
+                    "Segment": "",
+                    "Keys": {"pUqJrVyVRj5jsiYEkM/tFQYfWyJIv4s3XkvDwy7Cu5s=": 1},
+                    "PrimaryKeys": {"pUqJrVyVRj5jsiYEkM/tFQYfWyJIv4s3XkvDwy7Cu5s=": 1},
+                    "NumNodes": 1
+                },
+                {
+                    "WAN": false,
+                    "Datacenter": "dc1",
+                    "Segment": "",
+                    "Keys": {"pUqJrVyVRj5jsiYEkM/tFQYfWyJIv4s3XkvDwy7Cu5s=": 3},
+                    "PrimaryKeys": {"pUqJrVyVRj5jsiYEkM/tFQYfWyJIv4s3XkvDwy7Cu5s=": 3},
+                    "NumNodes": 3
+                }
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(responses.len(), 2);
+        assert!(responses[0].wan);
+        assert_eq!(responses[1].num_nodes, 3);
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::{sealed::Sealed, Client, ConsulResult, QueryOptions, WriteMeta, WriteOptions};
+
+/// The details of a Consul Enterprise license.
+///
+/// Unlike most Consul payloads, the fields of a license are encoded in
+/// snake case. Timestamps are RFC 3339 strings, such as
+/// "2020-05-22T04:00:00Z".
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct License {
+    /// The ID of the license.
+    pub license_id: String,
+    /// The ID of the customer the license was issued to.
+    pub customer_id: String,
+    /// The installation the license is bound to, or `*` for any.
+    pub installation_id: String,
+    /// The time the license was issued.
+    pub issue_time: String,
+    /// The time the license becomes valid.
+    pub start_time: String,
+    /// The time the license expires, after which Enterprise features stop
+    /// being available to new operations.
+    pub expiration_time: String,
+    /// The time after which the agent stops functioning entirely.
+    pub termination_time: String,
+    /// The product the license was issued for, which is always `consul`.
+    pub product: String,
+    /// Additional licensing flags, such as the package.
+    pub flags: HashMap<String, Value>,
+    /// The licensed modules.
+    pub modules: Vec<String>,
+    /// The licensed Enterprise features.
+    pub features: Vec<String>,
+}
+
+/// Response payload for the [OperatorLicense] methods.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct LicenseReply {
+    /// Whether the license is currently valid.
+    pub valid: bool,
+    /// The details of the license.
+    pub license: License,
+    /// Any warnings about the license, such as its upcoming expiration.
+    pub warnings: Vec<String>,
+}
+
+/// This trait provides methods for interacting with the `/operator/license`
+/// endpoints, which manage the license of a Consul Enterprise cluster.
+///
+/// For more information, see the [API documentation].
+///
+/// [API documentation]: https://www.consul.io/api-docs/operator/license
+#[async_trait]
+pub trait OperatorLicense: Sealed {
+    /// This method retrieves the details of the license of the datacenter.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/license#getting-the-consul-license
+    async fn get_license(&self, options: Option<QueryOptions>) -> ConsulResult<LicenseReply>;
+
+    /// This method retrieves the signed license blob of the datacenter, which
+    /// can be used to apply the same license elsewhere.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/license#getting-the-consul-license
+    async fn get_signed_license(&self, options: Option<QueryOptions>) -> ConsulResult<String>;
+
+    /// This method applies the given signed license blob to the datacenter,
+    /// returning the details of the new license.
+    ///
+    /// Consul 1.10 and later load licenses from the agent configuration
+    /// instead, and no longer support this endpoint.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/license#updating-the-consul-license
+    async fn put_license(
+        &self,
+        license: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(LicenseReply, WriteMeta)>;
+
+    /// This method resets the license of the datacenter to the license the
+    /// agents were started with, returning the details of that license.
+    ///
+    /// Consul 1.10 and later load licenses from the agent configuration
+    /// instead, and no longer support this endpoint.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/license#resetting-the-consul-license
+    async fn reset_license(
+        &self,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(LicenseReply, WriteMeta)>;
+}
+
+#[async_trait]
+impl OperatorLicense for Client {
+    #[tracing::instrument]
+    async fn get_license(&self, options: Option<QueryOptions>) -> ConsulResult<LicenseReply> {
+        self.get("/v1/operator/license", options).await
+    }
+
+    #[tracing::instrument]
+    async fn get_signed_license(&self, options: Option<QueryOptions>) -> ConsulResult<String> {
+        let mut params = HashMap::new();
+        params.insert(String::from("signed"), String::from("1"));
+        self.get_text("/v1/operator/license", Some(params), options).await
+    }
+
+    #[tracing::instrument(skip(license))]
+    async fn put_license(
+        &self,
+        license: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(LicenseReply, WriteMeta)> {
+        self.put_raw("/v1/operator/license", license.to_owned(), None, options).await
+    }
+
+    #[tracing::instrument]
+    async fn reset_license(
+        &self,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(LicenseReply, WriteMeta)> {
+        self.delete("/v1/operator/license", None, options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OperatorLicense;
+    use crate::request::tests::recording_client;
+
+    const LICENSE: &str = r#"{
+        "Valid": true,
+        "License": {
+            "license_id": "2afbf681-0d1a-0649-cb6c-5e8ba4b2b2b5",
+            "customer_id": "0259271d-8ffc-e85e-0830-c0822c1f5f2b",
+            "installation_id": "*",
+            "issue_time": "2019-05-22T15:25:37.000000376Z",
+            "start_time": "2019-05-22T05:00:00Z",
+            "expiration_time": "2020-05-22T04:00:00Z",
+            "termination_time": "2020-05-22T04:00:00Z",
+            "product": "consul",
+            "flags": {"package": "premium"},
+            "features": ["Automated Backups", "Namespaces", "Network Segments"]
+        },
+        "Warnings": []
+    }"#;
+
+    #[tokio::test]
+    async fn test_license() {
+        let (client, requests) = recording_client(&[
+            (200, &[], LICENSE),
+            (200, &[], "01ABCDEFG"),
+            (200, &[], LICENSE),
+            (200, &[], LICENSE),
+        ]);
+        let reply = client.get_license(None).await.unwrap();
+        assert!(reply.valid);
+        assert_eq!(reply.license.expiration_time, "2020-05-22T04:00:00Z");
+        assert_eq!(reply.license.features[1], "Namespaces");
+        assert_eq!(reply.license.flags["package"], "premium");
+        assert_eq!(client.get_signed_license(None).await.unwrap(), "01ABCDEFG");
+        let (reply, _) = client.put_license("01ABCDEFG", None).await.unwrap();
+        assert_eq!(reply.license.product, "consul");
+        client.reset_license(None).await.unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "GET /v1/operator/license",
+                "GET /v1/operator/license?signed=1",
+                "PUT /v1/operator/license",
+                "DELETE /v1/operator/license",
+            ]
+        );
+    }
+}
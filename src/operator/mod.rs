@@ -0,0 +1,26 @@
+//! Implementations of the `/operator` endpoints.
+//!
+//! These endpoints provide cluster-level tools for Consul operators, and
+//! generally require an operator ACL token.
+
+#[cfg(feature = "enterprise")]
+mod area;
+mod autopilot;
+mod keyring;
+#[cfg(feature = "enterprise")]
+mod license;
+mod raft;
+#[cfg(feature = "enterprise")]
+mod segment;
+mod usage;
+
+#[cfg(feature = "enterprise")]
+pub use area::*;
+pub use autopilot::*;
+pub use keyring::*;
+#[cfg(feature = "enterprise")]
+pub use license::*;
+pub use raft::*;
+#[cfg(feature = "enterprise")]
+pub use segment::*;
+pub use usage::*;
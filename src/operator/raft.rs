@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::{sealed::Sealed, Client, ConsulResult, QueryOptions, WriteMeta, WriteOptions};
+
+/// A server in the Raft peer set.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct RaftServer {
+    /// The unique ID of the server. This is the node ID of the server.
+    #[serde(rename = "ID")]
+    pub id: String,
+    /// The node name of the server.
+    pub node: String,
+    /// The `address:port` of the server's RPC endpoint.
+    pub address: String,
+    /// Whether this server is the current Raft leader.
+    pub leader: bool,
+    /// The Raft protocol version used by the server.
+    pub protocol_version: String,
+    /// Whether this server has a vote in the Raft cluster. Non-voters are
+    /// read replicas or servers still being promoted by autopilot.
+    pub voter: bool,
+}
+
+/// Response payload for the [OperatorRaft::get_raft_configuration] method.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct RaftConfiguration {
+    /// The servers in the Raft peer set.
+    pub servers: Vec<RaftServer>,
+    /// The Raft index at which the configuration was generated.
+    pub index: u64,
+}
+
+/// This trait provides methods for interacting with the `/operator/raft`
+/// endpoints.
+#[async_trait]
+pub trait OperatorRaft: Sealed {
+    /// This method reads the current Raft configuration, which corresponds to
+    /// the current peer set.
+    ///
+    /// The `stale` parameter can be used to read the configuration from a
+    /// non-leader server, which is useful when the cluster has lost quorum.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/raft#read-configuration
+    async fn get_raft_configuration(
+        &self,
+        stale: bool,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<RaftConfiguration>;
+
+    /// This method removes the Consul server with the given ID from the Raft
+    /// configuration.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/raft#delete-raft-peer
+    async fn remove_raft_peer_by_id(
+        &self,
+        id: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>;
+
+    /// This method removes the Consul server with the given `address:port`
+    /// from the Raft configuration.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/raft#delete-raft-peer
+    async fn remove_raft_peer_by_address(
+        &self,
+        address: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>;
+}
+
+#[async_trait]
+impl OperatorRaft for Client {
+    #[tracing::instrument]
+    async fn get_raft_configuration(
+        &self,
+        stale: bool,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<RaftConfiguration> {
+        let mut params = HashMap::new();
+        if stale {
+            params.insert(String::from("stale"), String::from(""));
+        }
+        self.get_with_params("/v1/operator/raft/configuration", Some(params), options).await
+    }
+
+    #[tracing::instrument]
+    async fn remove_raft_peer_by_id(
+        &self,
+        id: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta> {
+        let mut params = HashMap::new();
+        params.insert(String::from("id"), id.to_owned());
+        self.delete_with_empty("/v1/operator/raft/peer", Some(params), options)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn remove_raft_peer_by_address(
+        &self,
+        address: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta> {
+        let mut params = HashMap::new();
+        params.insert(String::from("address"), address.to_owned());
+        self.delete_with_empty("/v1/operator/raft/peer", Some(params), options)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Client, Config, OperatorRaft};
+
+    #[tokio::test]
+    async fn test_get_raft_configuration() {
+        let client = Client::new(Config::default());
+        let config = client.get_raft_configuration(false, None).await.unwrap();
+        assert_eq!(config.servers.len(), 1);
+        assert!(config.servers[0].leader);
+        assert!(config.servers[0].voter);
+    }
+}
@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+
+use crate::{sealed::Sealed, Client, ConsulResult, QueryOptions};
+
+/// This trait provides methods for interacting with the `/operator/segment`
+/// endpoint, which is only available in Consul Enterprise.
+#[async_trait]
+pub trait OperatorSegments: Sealed {
+    /// This method lists the network segments of the datacenter. The default
+    /// segment is always present, and is named by an empty string.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/segment#list-network-segments
+    async fn list_segments(&self, options: Option<QueryOptions>) -> ConsulResult<Vec<String>>;
+}
+
+#[async_trait]
+impl OperatorSegments for Client {
+    #[tracing::instrument]
+    async fn list_segments(&self, options: Option<QueryOptions>) -> ConsulResult<Vec<String>> {
+        self.get("/v1/operator/segment", options).await
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::{sealed::Sealed, Client, ConsulResult, QueryMeta, QueryOptions};
+
+/// The usage of a single datacenter, as returned by
+/// [OperatorUsage::get_usage].
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceUsage {
+    /// The number of unique service names registered in the datacenter.
+    pub services: u64,
+    /// The number of service instances registered in the datacenter.
+    pub service_instances: u64,
+    /// The number of Connect service instances, keyed by service kind, such
+    /// as `connect-native` or `connect-proxy`.
+    pub connect_service_instances: HashMap<String, u64>,
+    /// The number of service instances that count towards licensing, which
+    /// excludes Connect proxies and gateways.
+    pub billable_service_instances: u64,
+    /// The number of nodes registered in the datacenter.
+    pub nodes: u64,
+    /// The number of unique service names, keyed by partition and then by
+    /// namespace (Enterprise-only).
+    pub partition_namespace_services: HashMap<String, HashMap<String, u64>>,
+    /// The number of service instances, keyed by partition and then by
+    /// namespace (Enterprise-only).
+    pub partition_namespace_service_instances: HashMap<String, HashMap<String, u64>>,
+    /// The number of billable service instances, keyed by partition and then
+    /// by namespace (Enterprise-only).
+    pub partition_namespace_billable_service_instances: HashMap<String, HashMap<String, u64>>,
+}
+
+/// Response payload for the `/operator/usage` endpoint.
+#[derive(Default, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+struct UsageResponse {
+    usage: HashMap<String, ServiceUsage>,
+}
+
+/// This trait provides methods for interacting with the `/operator/usage`
+/// endpoint.
+#[async_trait]
+pub trait OperatorUsage: Sealed {
+    /// This method retrieves the number of services, service instances and
+    /// nodes registered in the cluster, keyed by datacenter.
+    ///
+    /// If `global` is set, the usage of all known datacenters is returned
+    /// instead of only the usage of the local datacenter.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/operator/usage#list-usage
+    async fn get_usage(
+        &self,
+        global: bool,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(HashMap<String, ServiceUsage>, QueryMeta)>;
+}
+
+#[async_trait]
+impl OperatorUsage for Client {
+    #[tracing::instrument]
+    async fn get_usage(
+        &self,
+        global: bool,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(HashMap<String, ServiceUsage>, QueryMeta)> {
+        let mut params = HashMap::new();
+        if global {
+            params.insert(String::from("global"), String::from("true"));
+        }
+        self.get_with_meta("/v1/operator/usage", Some(params), options)
+            .await
+            .map(|(r, meta): (Option<UsageResponse>, _)| (r.unwrap_or_default().usage, meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OperatorUsage;
+    use crate::request::tests::recording_client;
+
+    #[tokio::test]
+    async fn test_get_usage() {
+        const USAGE: &str = r#"{
+            "Usage": {
+                "dc1": {
+                    "Services": 2,
+                    "ServiceInstances": 5,
+                    "ConnectServiceInstances": {"connect-proxy": 2, "connect-native": 0},
+                    "BillableServiceInstances": 3,
+                    "Nodes": 3
+                },
+                "dc2": {"Services": 1, "ServiceInstances": 1, "Nodes": 1}
+            },
+            "Index": 13,
+            "LastContact": 0,
+            "KnownLeader": true
+        }"#;
+        let (client, requests) = recording_client(&[(200, &[("X-Consul-Index", "13")], USAGE)]);
+        let (usage, meta) = client.get_usage(true, None).await.unwrap();
+        assert_eq!(meta.last_index, Some(13));
+        assert_eq!(usage["dc1"].service_instances, 5);
+        assert_eq!(usage["dc1"].connect_service_instances["connect-proxy"], 2);
+        assert_eq!(usage["dc1"].billable_service_instances, 3);
+        assert_eq!(usage["dc2"].nodes, 1);
+        assert_eq!(*requests.lock().unwrap(), ["GET /v1/operator/usage?global=true"]);
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use http::{Request, Uri};
+use rand::Rng;
+
+/// The default time an address is skipped for after failing to connect.
+pub(crate) const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Enumeration of the strategies used by an [AddressPool] to choose the next
+/// address after one failed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RotationStrategy {
+    /// Addresses are tried in the order they were given.
+    #[default]
+    RoundRobin,
+    /// Addresses are tried in a random order, spreading the load of many
+    /// clients over the remaining agents.
+    Random,
+}
+
+/// A pool of agent addresses, set with [ConfigBuilder::addresses].
+///
+/// Requests are sent to one address until it fails to connect, then the pool
+/// rotates to the next address according to its [RotationStrategy]. Failed
+/// addresses are marked dead and skipped for a cooldown window, unless every
+/// address is dead. Clones of a pool share which addresses are dead.
+///
+/// [ConfigBuilder::addresses]: crate::ConfigBuilder::addresses
+#[derive(Clone, Debug)]
+pub struct AddressPool {
+    addresses: Vec<String>,
+    strategy: RotationStrategy,
+    cooldown: Duration,
+    state: Arc<Mutex<PoolState>>,
+}
+
+/// The state of an [AddressPool], shared between its clones.
+#[derive(Debug)]
+struct PoolState {
+    /// The index of the address requests are sent to.
+    current: usize,
+    /// The time until which each address is dead, if it failed.
+    dead_until: Vec<Option<Instant>>,
+}
+
+impl AddressPool {
+    /// This method creates a pool of the given addresses, which must be
+    /// normalized and non-empty.
+    pub(crate) fn new(
+        addresses: Vec<String>,
+        strategy: RotationStrategy,
+        cooldown: Duration,
+    ) -> Self {
+        let state = PoolState { current: 0, dead_until: vec![None; addresses.len()] };
+        AddressPool { addresses, strategy, cooldown, state: Arc::new(Mutex::new(state)) }
+    }
+
+    /// This method returns the addresses of the pool.
+    pub fn addresses(&self) -> &[String] {
+        &self.addresses
+    }
+
+    /// This method returns the strategy used to choose the next address.
+    pub fn strategy(&self) -> RotationStrategy {
+        self.strategy
+    }
+
+    /// This method returns the time failed addresses are skipped for.
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+
+    /// This method returns the number of addresses in the pool.
+    pub(crate) fn len(&self) -> usize {
+        self.addresses.len()
+    }
+
+    /// This method returns the index of the address the next request should
+    /// be sent to, rotating away from the current address if it is dead.
+    pub(crate) fn select(&self) -> usize {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let live: Vec<usize> = (0..self.len())
+            .filter(|&i| state.dead_until[i].is_none_or(|until| until <= now))
+            .collect();
+        if live.contains(&state.current) {
+            return state.current;
+        }
+        state.current = match (self.strategy, live.as_slice()) {
+            // every address is dead, so use the one recovering soonest
+            (_, []) => (0..self.len()).min_by_key(|&i| state.dead_until[i]).unwrap_or(0),
+            (RotationStrategy::RoundRobin, live) => {
+                live.iter().copied().find(|&i| i > state.current).unwrap_or(live[0])
+            }
+            (RotationStrategy::Random, live) => live[rand::thread_rng().gen_range(0..live.len())],
+        };
+        state.current
+    }
+
+    /// This method marks the address with the given index as dead for the
+    /// cooldown window.
+    pub(crate) fn mark_dead(&self, index: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.dead_until[index] = Some(Instant::now() + self.cooldown);
+    }
+
+    /// This method sends the given request, built against the first address of
+    /// the pool, to the address with the given index instead.
+    pub(crate) fn redirect(&self, request: &mut Request<Bytes>, index: usize) {
+        if index == 0 {
+            return;
+        }
+        let url = request.uri().to_string();
+        let rest = url.strip_prefix(self.addresses[0].as_str());
+        if let Some(uri) =
+            rest.and_then(|rest| (self.addresses[index].clone() + rest).parse::<Uri>().ok())
+        {
+            *request.uri_mut() = uri;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use http::Request;
+
+    use super::{AddressPool, RotationStrategy};
+
+    fn pool(strategy: RotationStrategy) -> AddressPool {
+        let addresses = ["http://10.0.0.1:8500", "http://10.0.0.2:8500", "http://10.0.0.3:8500"];
+        AddressPool::new(addresses.map(String::from).to_vec(), strategy, Duration::from_secs(60))
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let pool = pool(RotationStrategy::RoundRobin);
+        assert_eq!(pool.select(), 0);
+        pool.mark_dead(0);
+        assert_eq!(pool.select(), 1);
+        assert_eq!(pool.select(), 1);
+        pool.mark_dead(1);
+        assert_eq!(pool.select(), 2);
+        // when every address is dead, the first one to fail is tried again
+        pool.mark_dead(2);
+        assert_eq!(pool.select(), 0);
+    }
+
+    #[test]
+    fn test_random() {
+        let pool = pool(RotationStrategy::Random);
+        pool.mark_dead(0);
+        let index = pool.select();
+        assert_ne!(index, 0);
+        assert_eq!(pool.select(), index);
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let pool = AddressPool::new(
+            vec![String::from("http://10.0.0.1:8500"), String::from("http://10.0.0.2:8500")],
+            RotationStrategy::RoundRobin,
+            Duration::ZERO,
+        );
+        pool.mark_dead(0);
+        assert_eq!(pool.select(), 0);
+    }
+
+    #[test]
+    fn test_redirect() {
+        let pool = pool(RotationStrategy::RoundRobin);
+        let mut request = Request::new(Bytes::new());
+        *request.uri_mut() = "http://10.0.0.1:8500/v1/kv/a?dc=dc1".parse().unwrap();
+        pool.redirect(&mut request, 2);
+        assert_eq!(request.uri(), "http://10.0.0.3:8500/v1/kv/a?dc=dc1");
+    }
+}
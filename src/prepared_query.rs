@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::{
+    sealed::Sealed, Client, ConsulResult, QueryOptions, ServiceEntry, WriteMeta, WriteOptions,
+};
+
+/// A datacenter or cluster peer to fail over to.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct QueryFailoverTarget {
+    /// The name of the cluster peer to fail over to.
+    pub peer: String,
+    /// The name of the datacenter to fail over to.
+    pub datacenter: String,
+}
+
+/// The failover policy of a prepared query, used if no healthy nodes are
+/// available in the local datacenter.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct QueryFailoverOptions {
+    /// The number of remote datacenters to try, ordered by their estimated
+    /// round trip time from the local datacenter.
+    pub nearest_n: u32,
+    /// A fixed list of remote datacenters to forward the query to, tried in
+    /// order after any `nearest_n` datacenters.
+    pub datacenters: Vec<String>,
+    /// A fixed list of datacenters and cluster peers to try. This cannot be
+    /// used with `nearest_n` or `datacenters`.
+    pub targets: Vec<QueryFailoverTarget>,
+}
+
+/// The service a prepared query resolves to.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceQuery {
+    /// The name of the service to query.
+    pub service: String,
+    /// The failover policy to use when no healthy instances are available.
+    pub failover: QueryFailoverOptions,
+    /// Whether to only return instances with all checks passing. If unset,
+    /// instances with warning checks are returned too.
+    pub only_passing: bool,
+    /// A list of check IDs to ignore when filtering unhealthy instances.
+    #[serde(rename = "IgnoreCheckIDs")]
+    pub ignore_check_ids: Vec<String>,
+    /// A node to sort results by estimated round trip time to, or `_agent` to
+    /// sort by distance to the agent serving the request.
+    pub near: String,
+    /// A list of service tags to filter on. Tags prefixed with `!` must not be
+    /// present.
+    pub tags: Vec<String>,
+    /// A map of node metadata to filter on.
+    pub node_meta: HashMap<String, String>,
+    /// A map of service metadata to filter on.
+    pub service_meta: HashMap<String, String>,
+    /// Whether to only return Connect-capable instances.
+    pub connect: bool,
+}
+
+/// DNS options for a prepared query.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct QueryDNSOptions {
+    /// The TTL to use for DNS results of the query, such as "10s".
+    #[serde(rename = "TTL")]
+    pub ttl: String,
+}
+
+/// Template options for a prepared query, used to match many service names
+/// with a single query.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct QueryTemplate {
+    /// The template type, which must be `name_prefix_match`.
+    #[serde(rename = "Type")]
+    pub kind: String,
+    /// A regular expression used to extract fields from the query name for
+    /// interpolation into the query definition.
+    pub regexp: String,
+    /// Whether to remove empty tags after interpolation.
+    pub remove_empty_tags: bool,
+}
+
+/// The definition of a prepared query.
+///
+/// For more information, see the [API documentation].
+///
+/// [API documentation]: https://www.consul.io/api-docs/query#create-prepared-query
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct PreparedQueryDefinition {
+    /// The ID of the prepared query. This is assigned by Consul on creation.
+    #[serde(rename = "ID")]
+    pub id: String,
+    /// An optional name for the query, which can be used in place of the ID.
+    pub name: String,
+    /// A session to tie the query's lifetime to. The query is deleted when the
+    /// session is invalidated.
+    pub session: String,
+    /// The ACL token to use when executing the query.
+    pub token: String,
+    /// The service the query resolves to.
+    pub service: ServiceQuery,
+    /// DNS options for the query.
+    #[serde(rename = "DNS")]
+    pub dns: QueryDNSOptions,
+    /// Template options for the query.
+    pub template: QueryTemplate,
+    pub create_index: u64,
+    pub modify_index: u64,
+}
+
+/// Response payload for the [PreparedQuery::execute_prepared_query] method.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct PreparedQueryExecuteResponse {
+    /// The name of the service the query resolved to.
+    pub service: String,
+    /// The healthy instances of the service.
+    pub nodes: Vec<ServiceEntry>,
+    /// DNS options for the query.
+    #[serde(rename = "DNS")]
+    pub dns: QueryDNSOptions,
+    /// The datacenter the results were returned from, which may differ from
+    /// the local datacenter after a failover.
+    pub datacenter: String,
+    /// The number of remote datacenters that were tried before returning the
+    /// results.
+    pub failovers: u32,
+}
+
+/// Response payload for the [PreparedQuery::explain_prepared_query] method.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct PreparedQueryExplainResponse {
+    /// The fully rendered query definition, after template interpolation.
+    pub query: PreparedQueryDefinition,
+}
+
+/// This trait provides methods for interacting with the `/query` endpoints.
+///
+/// For more information, see the [API documentation].
+///
+/// [API documentation]: https://www.consul.io/api-docs/query
+#[async_trait]
+pub trait PreparedQuery: Sealed {
+    /// This method creates a new prepared query, returning its ID.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/query#create-prepared-query
+    async fn create_prepared_query(
+        &self,
+        query: PreparedQueryDefinition,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(String, WriteMeta)>;
+
+    /// This method returns a list of all prepared queries.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/query#read-prepared-query
+    async fn list_prepared_queries(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Vec<PreparedQueryDefinition>>;
+
+    /// This method reads the prepared query with the given ID.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/query#read-prepared-query-1
+    async fn read_prepared_query(
+        &self,
+        id: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Vec<PreparedQueryDefinition>>;
+
+    /// This method updates an existing prepared query, identified by its `id`
+    /// field.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/query#update-prepared-query
+    async fn update_prepared_query(
+        &self,
+        query: PreparedQueryDefinition,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>;
+
+    /// This method deletes the prepared query with the given ID.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/query#delete-prepared-query
+    async fn delete_prepared_query(
+        &self,
+        id: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>;
+
+    /// This method executes the prepared query with the given ID or name,
+    /// returning at most `limit` instances if provided.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/query#execute-prepared-query
+    async fn execute_prepared_query(
+        &self,
+        id_or_name: &str,
+        limit: Option<usize>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<PreparedQueryExecuteResponse>;
+
+    /// This method generates a fully rendered query for the given ID or name,
+    /// without executing it. This is useful for debugging prepared query
+    /// templates.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/query#explain-prepared-query
+    async fn explain_prepared_query(
+        &self,
+        id_or_name: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<PreparedQueryExplainResponse>;
+}
+
+#[async_trait]
+impl PreparedQuery for Client {
+    #[tracing::instrument]
+    async fn create_prepared_query(
+        &self,
+        query: PreparedQueryDefinition,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(String, WriteMeta)> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Response {
+            #[serde(rename = "ID")]
+            id: String,
+        }
+        self.post("/v1/query", query, None, options)
+            .await
+            .map(|(r, meta): (Response, _)| (r.id, meta))
+    }
+
+    #[tracing::instrument]
+    async fn list_prepared_queries(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Vec<PreparedQueryDefinition>> {
+        self.get("/v1/query", options).await
+    }
+
+    #[tracing::instrument]
+    async fn read_prepared_query(
+        &self,
+        id: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<Vec<PreparedQueryDefinition>> {
+        self.get(format!("/v1/query/{}", id), options).await
+    }
+
+    #[tracing::instrument]
+    async fn update_prepared_query(
+        &self,
+        query: PreparedQueryDefinition,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta> {
+        let path = format!("/v1/query/{}", query.id);
+        self.put_with_empty(&path, query, None, options)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn delete_prepared_query(
+        &self,
+        id: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta> {
+        self.delete_with_empty(format!("/v1/query/{}", id), None, options)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+
+    #[tracing::instrument]
+    async fn execute_prepared_query(
+        &self,
+        id_or_name: &str,
+        limit: Option<usize>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<PreparedQueryExecuteResponse> {
+        let mut params = HashMap::new();
+        if let Some(limit) = limit {
+            params.insert(String::from("limit"), limit.to_string());
+        }
+        let path = format!("/v1/query/{}/execute", id_or_name);
+        self.get_with_params(&path, Some(params), options).await
+    }
+
+    #[tracing::instrument]
+    async fn explain_prepared_query(
+        &self,
+        id_or_name: &str,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<PreparedQueryExplainResponse> {
+        self.get(format!("/v1/query/{}/explain", id_or_name), options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Client, Config, PreparedQuery, PreparedQueryDefinition, QueryFailoverOptions, ServiceQuery,
+    };
+
+    #[tokio::test]
+    async fn test_prepared_query_methods() {
+        let client = Client::new(Config::default());
+        let query = PreparedQueryDefinition {
+            name: String::from("consul-failover"),
+            service: ServiceQuery {
+                service: String::from("consul"),
+                failover: QueryFailoverOptions { nearest_n: 3, ..Default::default() },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (id, _) = client.create_prepared_query(query, None).await.unwrap();
+        let response = client.execute_prepared_query("consul-failover", None, None).await.unwrap();
+        assert_eq!(response.service, "consul");
+        assert!(!response.nodes.is_empty());
+        client.delete_prepared_query(&id, None).await.unwrap();
+    }
+}
@@ -0,0 +1,243 @@
+use std::{fmt, time::Duration};
+
+use crate::{
+    watch::{self, WatchStream},
+    Client, ConsulError, ConsulResult, KVPair, QueryMeta, QueryOptions, SessionEntry,
+    SessionHandle, KV,
+};
+
+/// The name of the sessions created by presence set members.
+const SESSION_NAME: &str = "Consul API Presence";
+/// The TTL of the sessions created by presence set members.
+const SESSION_TTL: Duration = Duration::from_secs(15);
+
+/// A member of a [PresenceSet], as seen by the other members.
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct Member {
+    /// The ID the member joined with.
+    pub id: String,
+    /// The value the member joined with.
+    pub value: Vec<u8>,
+    /// The ID of the session of the member.
+    pub session: String,
+}
+
+/// A set of live members sharing a KV prefix, such as the workers of a
+/// sharded service.
+///
+/// Each member writes the key `<prefix>/<id>` locked by its own session, which
+/// is renewed for as long as the member is present. When a member leaves, or
+/// its process dies and its session expires, its key is deleted, so the keys
+/// under the prefix always list the live members.
+///
+/// ```no_run
+/// # async fn run() -> consul_oxide::ConsulResult<()> {
+/// use consul_oxide::{Client, Config, PresenceSet};
+/// use futures::StreamExt;
+///
+/// let client = Client::new(Config::default());
+/// let workers = PresenceSet::new(&client, "service/workers");
+/// let presence = workers.join("worker-1", b"10.0.0.1:8080").await?;
+/// let mut members = workers.watch_members();
+/// while let Some(members) = members.next().await {
+///     println!("{} workers are present", members?.len());
+/// }
+/// presence.leave().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct PresenceSet {
+    client: Client,
+    prefix: String,
+}
+
+impl PresenceSet {
+    /// This method creates a presence set with the given prefix.
+    pub fn new(client: &Client, prefix: &str) -> Self {
+        PresenceSet { client: client.clone(), prefix: prefix.trim_end_matches('/').to_owned() }
+    }
+
+    /// This method joins the set with the given ID and value, which stay
+    /// visible to the other members until the returned [Presence] is
+    /// dropped or leaves.
+    ///
+    /// Joining fails with [ConsulError::MemberConflict] if another member is
+    /// present with the same ID.
+    #[tracing::instrument(skip(value))]
+    pub async fn join(&self, id: &str, value: &[u8]) -> ConsulResult<Presence> {
+        let session = SessionEntry {
+            name: Some(SESSION_NAME.to_owned()),
+            // a member rejoining after its session expired can lock its key
+            // again without waiting for the lock delay
+            lock_delay: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        let session = SessionHandle::create(&self.client, session, SESSION_TTL).await?;
+        let key = self.member_key(id);
+        let (locked, _) = self.client.put_ephemeral(&key, value, session.id(), None).await?;
+        if !locked {
+            // dropping the session destroys it in the background
+            return Err(ConsulError::MemberConflict(id.to_owned()));
+        }
+        Ok(Presence { client: self.client.clone(), key, session })
+    }
+
+    /// This method returns the members currently present, sorted by ID.
+    #[tracing::instrument]
+    pub async fn members(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<Member>, QueryMeta)> {
+        let prefix = format!("{}/", self.prefix);
+        let (pairs, meta) = self.client.list_entries(&prefix, options).await?;
+        Ok((decode_members(&prefix, pairs), meta))
+    }
+
+    /// This method returns a [WatchStream] that yields the members currently
+    /// present, sorted by ID, every time they change.
+    pub fn watch_members(&self) -> WatchStream<ConsulResult<Vec<Member>>> {
+        let set = self.clone();
+        watch::watch(None, move |options| {
+            let set = set.clone();
+            async move { set.members(Some(options)).await }
+        })
+    }
+
+    /// This method returns the key of the member with the given ID.
+    fn member_key(&self, id: &str) -> String {
+        format!("{}/{}", self.prefix, id)
+    }
+}
+
+impl fmt::Debug for PresenceSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PresenceSet").field("prefix", &self.prefix).finish()
+    }
+}
+
+/// The membership of a [PresenceSet], which keeps the member present until it
+/// is dropped.
+///
+/// Dropping the presence destroys its session in the background, which
+/// deletes the key of the member. To wait for the member to be gone, use
+/// [Presence::leave] instead.
+pub struct Presence {
+    client: Client,
+    key: String,
+    session: SessionHandle,
+}
+
+impl Presence {
+    /// This method returns the ID of the session keeping the member present.
+    pub fn session_id(&self) -> &str {
+        self.session.id()
+    }
+
+    /// This method waits for the member to be found absent, because its
+    /// session was invalidated, such as after failing to renew it for longer
+    /// than its TTL. The member must join the set again to be present.
+    pub async fn lost(&self) {
+        self.session.invalidated().await
+    }
+
+    /// This method removes the member from the set, deleting its key and
+    /// destroying its session.
+    #[tracing::instrument]
+    pub async fn leave(self) -> ConsulResult<()> {
+        self.client.delete_entry(&self.key, None, None).await?;
+        self.session.destroy().await
+    }
+}
+
+impl fmt::Debug for Presence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Presence")
+            .field("key", &self.key)
+            .field("session", &self.session.id())
+            .finish()
+    }
+}
+
+/// This function decodes the members from the keys under the given prefix,
+/// ignoring the keys that are not locked by a session or are nested deeper.
+fn decode_members(prefix: &str, pairs: Vec<KVPair>) -> Vec<Member> {
+    let mut members: Vec<Member> = pairs
+        .into_iter()
+        .filter_map(|pair| {
+            let id = pair.key.strip_prefix(prefix).filter(|id| !id.is_empty() && !id.contains('/'));
+            let session = pair.session.filter(|session| !session.is_empty());
+            Some(Member { id: id?.to_owned(), value: pair.value, session: session? })
+        })
+        .collect();
+    members.sort_by(|a, b| a.id.cmp(&b.id));
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::{decode_members, Member, PresenceSet};
+    use crate::{request::tests::recording_client, ConsulError, KVPair};
+
+    const SESSION: &str = "adf4238a-882b-9ddc-4a9d-5b6758e4159e";
+
+    #[test]
+    fn test_decode_members() {
+        let pair = |key: &str, session: Option<&str>| KVPair {
+            key: String::from(key),
+            value: b"10.0.0.1".to_vec(),
+            session: session.map(str::to_owned),
+            ..Default::default()
+        };
+        let members = decode_members(
+            "workers/",
+            vec![
+                pair("workers/b", Some("b")),
+                pair("workers/a", Some("a")),
+                // left behind by a session with the release behavior
+                pair("workers/c", None),
+                pair("workers/nested/d", Some("d")),
+            ],
+        );
+        let ids: Vec<&str> = members.iter().map(|member| member.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_presence_set() {
+        const MEMBERS: &str = r#"[
+            {"Key": "workers/worker-1", "Value": "MTAuMC4wLjE=", "Session": "adf4238a-882b-9ddc-4a9d-5b6758e4159e"}
+        ]"#;
+        let (client, requests) = recording_client(&[
+            (200, &[], r#"{"ID": "adf4238a-882b-9ddc-4a9d-5b6758e4159e"}"#),
+            (200, &[], "true"),
+            (200, &[("X-Consul-Index", "10")], MEMBERS),
+            (200, &[], "true"),
+            (200, &[], "true"),
+            (200, &[], r#"{"ID": "3a2f8d0c-5e21-4f4b-8f4e-2cf0ab5d8f10"}"#),
+            (200, &[], "false"),
+        ]);
+        let workers = PresenceSet::new(&client, "workers/");
+        let presence = workers.join("worker-1", b"10.0.0.1").await.unwrap();
+        assert_eq!(presence.session_id(), SESSION);
+        let members = workers.watch_members().next().await.unwrap().unwrap();
+        assert_eq!(
+            members,
+            [Member {
+                id: String::from("worker-1"),
+                value: b"10.0.0.1".to_vec(),
+                session: String::from(SESSION)
+            }]
+        );
+        presence.leave().await.unwrap();
+        let conflict = workers.join("worker-1", b"10.0.0.2").await;
+        assert!(matches!(conflict, Err(ConsulError::MemberConflict(id)) if id == "worker-1"));
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[1], format!("PUT /v1/kv/workers/worker-1?acquire={}", SESSION));
+        assert_eq!(requests[2], "GET /v1/kv/workers/?recurse=");
+        assert_eq!(requests[3], "DELETE /v1/kv/workers/worker-1");
+        assert_eq!(requests[4], format!("PUT /v1/session/destroy/{}", SESSION));
+    }
+}
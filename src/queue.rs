@@ -0,0 +1,295 @@
+//! An experimental work queue stored in the Consul KV store.
+//!
+//! Items are enqueued as keys under `<prefix>/items/`, named so that they sort
+//! in the order they were enqueued. A worker leases an item by locking the
+//! matching key under `<prefix>/leases/` with its session, and acknowledges it
+//! by deleting both keys. Sessions of workers are expected to have the delete
+//! behavior, as those of a [SessionHandle] do by default, so when a worker
+//! dies or loses its session, its leases are deleted and their items are
+//! leased again by other workers.
+//!
+//! Items are delivered at least once: an item whose worker loses its session
+//! before acknowledging it is processed again. The queue suits small numbers
+//! of items, since leasing an item lists all the keys of the queue.
+//!
+//! ```no_run
+//! # async fn run() -> consul_oxide::ConsulResult<()> {
+//! use std::time::Duration;
+//!
+//! use consul_oxide::{queue::Queue, Client, Config, SessionEntry, SessionHandle};
+//!
+//! let client = Client::new(Config::default());
+//! let queue = Queue::new(&client, "service/jobs/queue");
+//! queue.enqueue(b"resize image-1").await?;
+//!
+//! let session = SessionHandle::create(&client, SessionEntry::default(), Duration::from_secs(15))
+//!     .await?;
+//! let item = queue.lease(&session).await?;
+//! println!("processing {}", String::from_utf8_lossy(&item.value));
+//! queue.ack(item).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    Client, ConsulError, ConsulResult, KVPair, KVPutParams, KVTxnOp, QueryMeta, QueryOptions,
+    SessionHandle, Txn, TxnOp, KV,
+};
+
+/// The folder of the items of a queue, relative to its prefix.
+const ITEMS: &str = "items/";
+/// The folder of the leases of a queue, relative to its prefix.
+const LEASES: &str = "leases/";
+
+/// A queue of items stored under a KV prefix. See the [module
+/// documentation](self) for how items are stored and leased.
+#[derive(Clone)]
+pub struct Queue {
+    client: Client,
+    prefix: String,
+}
+
+/// An item leased from a [Queue], which must be acknowledged with [Queue::ack]
+/// once processed, or returned with [Queue::requeue].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct QueueItem {
+    /// The ID of the item, assigned when it was enqueued.
+    pub id: String,
+    /// The value the item was enqueued with.
+    pub value: Vec<u8>,
+    /// The ID of the session holding the lease of the item.
+    pub session: String,
+}
+
+impl Queue {
+    /// This method creates a queue with the given prefix.
+    pub fn new(client: &Client, prefix: &str) -> Self {
+        Queue { client: client.clone(), prefix: prefix.trim_end_matches('/').to_owned() }
+    }
+
+    /// This method adds an item with the given value to the end of the queue,
+    /// returning its ID.
+    #[tracing::instrument(skip(value))]
+    pub async fn enqueue(&self, value: &[u8]) -> ConsulResult<String> {
+        loop {
+            let id = item_id();
+            let pair =
+                KVPair { key: self.key(ITEMS, &id), value: value.to_vec(), ..Default::default() };
+            // an ID colliding with an existing item is generated again
+            let params = KVPutParams { cas: Some(0), ..Default::default() };
+            if self.client.put_entry(&pair, Some(params), None).await?.0 {
+                return Ok(id);
+            }
+        }
+    }
+
+    /// This method leases the first item of the queue that is not leased
+    /// yet, with the given session, or returns [None] if there is none.
+    #[tracing::instrument]
+    pub async fn try_lease(&self, session: &SessionHandle) -> ConsulResult<Option<QueueItem>> {
+        self.lease_with(session, None).await.map(|(item, _)| item)
+    }
+
+    /// This method leases the first item of the queue that is not leased
+    /// yet, with the given session, waiting for one to be enqueued or
+    /// returned if there is none.
+    #[tracing::instrument]
+    pub async fn lease(&self, session: &SessionHandle) -> ConsulResult<QueueItem> {
+        let mut wait_index = None;
+        loop {
+            let options = QueryOptions { wait_index, ..Default::default() };
+            let (item, meta) = self.lease_with(session, Some(options)).await?;
+            if let Some(item) = item {
+                return Ok(item);
+            }
+            // wait for the keys of the queue to change before trying again
+            wait_index = meta.last_index;
+        }
+    }
+
+    /// This method acknowledges the given item, removing it from the queue.
+    ///
+    /// This fails with [ConsulError::LeaseLost] if the session of the lease
+    /// was invalidated, in which case the item may be processed again.
+    #[tracing::instrument]
+    pub async fn ack(&self, item: QueueItem) -> ConsulResult<()> {
+        let ops = vec![
+            self.check_lease(&item),
+            TxnOp::Kv(KVTxnOp::Delete { key: self.key(ITEMS, &item.id) }),
+            TxnOp::Kv(KVTxnOp::Delete { key: self.key(LEASES, &item.id) }),
+        ];
+        self.release(item, ops).await
+    }
+
+    /// This method returns the given item to the queue, so that it can be
+    /// leased again.
+    ///
+    /// This fails with [ConsulError::LeaseLost] if the session of the lease
+    /// was invalidated, in which case the item was already returned.
+    #[tracing::instrument]
+    pub async fn requeue(&self, item: QueueItem) -> ConsulResult<()> {
+        let ops = vec![
+            self.check_lease(&item),
+            TxnOp::Kv(KVTxnOp::Delete { key: self.key(LEASES, &item.id) }),
+        ];
+        self.release(item, ops).await
+    }
+
+    /// This method lists the keys of the queue, and leases the first item
+    /// that is not leased yet.
+    async fn lease_with(
+        &self,
+        session: &SessionHandle,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<QueueItem>, QueryMeta)> {
+        let prefix = format!("{}/", self.prefix);
+        let (keys, meta) = self.client.list_keys(&prefix, None, options).await?;
+        let leased: Vec<&str> =
+            keys.iter().filter_map(|key| key.strip_prefix(&prefix)?.strip_prefix(LEASES)).collect();
+        let available = keys
+            .iter()
+            .filter_map(|key| key.strip_prefix(&prefix)?.strip_prefix(ITEMS))
+            .filter(|id| !leased.contains(id));
+        for id in available {
+            let lease = self.key(LEASES, id);
+            let (locked, _) = self.client.put_ephemeral(&lease, &[], session.id(), None).await?;
+            if !locked {
+                continue;
+            }
+            // the item may have been acknowledged since the keys were listed
+            let (pairs, _) = self.client.get_entry(&self.key(ITEMS, id), None).await?;
+            match pairs.into_iter().next() {
+                Some(pair) => {
+                    let item = QueueItem {
+                        id: id.to_owned(),
+                        value: pair.value,
+                        session: session.id().to_owned(),
+                    };
+                    return Ok((Some(item), meta));
+                }
+                None => {
+                    self.client.delete_entry(&lease, None, None).await?;
+                }
+            }
+        }
+        Ok((None, meta))
+    }
+
+    /// This method returns the operation checking that the lease of the given
+    /// item is still held by its session.
+    fn check_lease(&self, item: &QueueItem) -> TxnOp {
+        TxnOp::Kv(KVTxnOp::CheckSession {
+            key: self.key(LEASES, &item.id),
+            session: item.session.clone(),
+        })
+    }
+
+    /// This method executes the given operations releasing the lease of an
+    /// item.
+    async fn release(&self, item: QueueItem, ops: Vec<TxnOp>) -> ConsulResult<()> {
+        match self.client.txn(ops, None).await {
+            Ok(_) => Ok(()),
+            // only the check of the lease can fail
+            Err(ConsulError::TxnFailed(_)) => Err(ConsulError::LeaseLost(item.id)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// This method returns the key of the given ID within the given folder.
+    fn key(&self, folder: &str, id: &str) -> String {
+        format!("{}/{}{}", self.prefix, folder, id)
+    }
+}
+
+impl fmt::Debug for Queue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Queue").field("prefix", &self.prefix).finish()
+    }
+}
+
+/// This function generates the ID of a new item, made of the current time so
+/// that items sort in the order they were enqueued, and of a random suffix
+/// distinguishing items enqueued at the same time.
+fn item_id() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    format!("{:020}-{:08x}", now.as_nanos(), rand::random::<u32>())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{item_id, Queue};
+    use crate::{request::tests::recording_client, ConsulError, SessionEntry, SessionHandle};
+
+    const SESSION: &str = "adf4238a-882b-9ddc-4a9d-5b6758e4159e";
+
+    #[test]
+    fn test_item_id() {
+        let id = item_id();
+        assert_eq!(id.len(), 29);
+        assert!(id[..20].bytes().all(|c| c.is_ascii_digit()));
+        assert_ne!(id, item_id());
+    }
+
+    #[tokio::test]
+    async fn test_queue() {
+        const KEYS: &str = r#"[
+            "jobs/items/00000000000000000001-00000000",
+            "jobs/items/00000000000000000002-00000000",
+            "jobs/items/00000000000000000003-00000000",
+            "jobs/leases/00000000000000000001-00000000"
+        ]"#;
+        const ITEM: &str =
+            r#"[{"Key": "jobs/items/00000000000000000003-00000000", "Value": "MQ=="}]"#;
+        const LEASE_LOST: &str = r#"{"Errors": [{"OpIndex": 0, "What": "failed session check"}]}"#;
+        let (client, requests) = recording_client(&[
+            (200, &[], r#"{"ID": "adf4238a-882b-9ddc-4a9d-5b6758e4159e"}"#),
+            (200, &[], "true"),
+            (200, &[("X-Consul-Index", "10")], KEYS),
+            // the second item was leased since the keys were listed
+            (200, &[], "false"),
+            (200, &[], "true"),
+            (200, &[], ITEM),
+            (200, &[], r#"{"Results": []}"#),
+            (409, &[], LEASE_LOST),
+        ]);
+        let session = SessionHandle::create(
+            &client,
+            SessionEntry::default(),
+            // long enough not to be renewed during the test
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+        let queue = Queue::new(&client, "jobs");
+        let id = queue.enqueue(b"1").await.unwrap();
+        let item = queue.lease(&session).await.unwrap();
+        assert_eq!(item.id, "00000000000000000003-00000000");
+        assert_eq!(item.value, b"1");
+        assert_eq!(item.session, SESSION);
+        queue.ack(item.clone()).await.unwrap();
+        let lost = queue.requeue(item).await;
+        assert!(
+            matches!(lost, Err(ConsulError::LeaseLost(id)) if id == "00000000000000000003-00000000")
+        );
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[1], format!("PUT /v1/kv/jobs/items/{}?cas=0", id));
+        assert_eq!(requests[2], "GET /v1/kv/jobs/?keys=");
+        assert_eq!(
+            requests[3],
+            format!("PUT /v1/kv/jobs/leases/00000000000000000002-00000000?acquire={}", SESSION)
+        );
+        assert_eq!(
+            requests[4],
+            format!("PUT /v1/kv/jobs/leases/00000000000000000003-00000000?acquire={}", SESSION)
+        );
+        assert_eq!(requests[5], "GET /v1/kv/jobs/items/00000000000000000003-00000000");
+        assert_eq!(requests[6..], ["PUT /v1/txn", "PUT /v1/txn"]);
+    }
+}
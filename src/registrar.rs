@@ -0,0 +1,320 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use tokio::{runtime::Handle, sync::watch, task::JoinHandle};
+
+use crate::{
+    AgentChecks, AgentServices, CheckStatus, Client, ConsulResult, RegisterCheckPayload,
+    ServiceRegistrationPayload,
+};
+
+/// The status reported by a [ServiceRegistrar], alongside its optional output.
+type ReportedStatus = (CheckStatus, Option<String>);
+
+/// A guard deregistering a service from the local agent when dropped.
+///
+/// Since deregistration is asynchronous, dropping the guard spawns it onto the
+/// current runtime. To wait for the service to be deregistered, use
+/// [RegistrationGuard::deregister] instead.
+///
+/// ```no_run
+/// # async fn run() -> consul_oxide::ConsulResult<()> {
+/// use consul_oxide::{Client, Config, RegistrationGuard, ServiceRegistrationPayload};
+///
+/// let client = Client::new(Config::default());
+/// let payload = ServiceRegistrationPayload {
+///     name: String::from("web"),
+///     port: 8080,
+///     ..Default::default()
+/// };
+/// let guard = RegistrationGuard::register(&client, payload).await?;
+/// // serve requests until ctrl-c or SIGTERM is received
+/// guard.deregister_on_signal().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RegistrationGuard {
+    client: Client,
+    service_id: String,
+    registered: bool,
+}
+
+impl RegistrationGuard {
+    /// This method registers the given service with the local agent.
+    #[tracing::instrument]
+    pub async fn register(
+        client: &Client,
+        payload: ServiceRegistrationPayload,
+    ) -> ConsulResult<Self> {
+        let service_id = payload.id.clone().unwrap_or_else(|| payload.name.clone());
+        client.register_service(payload).await?;
+        Ok(RegistrationGuard { client: client.clone(), service_id, registered: true })
+    }
+
+    /// This method returns the ID of the registered service.
+    pub fn service_id(&self) -> &str {
+        &self.service_id
+    }
+
+    /// This method deregisters the service from the local agent.
+    #[tracing::instrument]
+    pub async fn deregister(mut self) -> ConsulResult<()> {
+        self.release().await
+    }
+
+    /// This method waits for ctrl-c, or SIGTERM on Unix platforms, then
+    /// deregisters the service from the local agent.
+    ///
+    /// Listening for these signals disables their default behaviour of
+    /// terminating the process, so this method is meant to be awaited at the
+    /// end of the application's main function, or raced against its own
+    /// shutdown logic.
+    pub async fn deregister_on_signal(self) -> ConsulResult<()> {
+        shutdown_signal().await;
+        self.deregister().await
+    }
+
+    /// This method deregisters the service, so that dropping the guard does
+    /// nothing.
+    async fn release(&mut self) -> ConsulResult<()> {
+        self.registered = false;
+        self.client.deregister_service(&self.service_id).await.map(|_| ())
+    }
+}
+
+impl Drop for RegistrationGuard {
+    fn drop(&mut self) {
+        if !self.registered {
+            return;
+        }
+        // deregistration is asynchronous, so it can only be spawned onto the
+        // current runtime, if any
+        match Handle::try_current() {
+            Ok(handle) => {
+                let client = self.client.clone();
+                let service_id = self.service_id.clone();
+                handle.spawn(async move {
+                    if let Err(e) = client.deregister_service(&service_id).await {
+                        tracing::warn!(service_id, error = %e, "failed to deregister service");
+                    }
+                });
+            }
+            Err(_) => {
+                tracing::warn!(service_id = %self.service_id, "dropped outside of a runtime")
+            }
+        }
+    }
+}
+
+impl fmt::Debug for RegistrationGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegistrationGuard").field("service_id", &self.service_id).finish()
+    }
+}
+
+/// A service registered with the local agent, whose health is reported using
+/// a TTL check updated in the background.
+///
+/// The registrar registers the service alongside a TTL check, then updates the
+/// check at a third of the TTL for as long as it is alive. The reported status
+/// is passing by default, and can be changed using a [StatusHandle]. The
+/// service is deregistered by [ServiceRegistrar::shutdown], or in the
+/// background when the registrar is dropped, as with a [RegistrationGuard].
+///
+/// ```no_run
+/// # async fn run() -> consul_oxide::ConsulResult<()> {
+/// use std::time::Duration;
+///
+/// use consul_oxide::{Client, Config, ServiceRegistrar, ServiceRegistrationPayload};
+///
+/// let client = Client::new(Config::default());
+/// let payload = ServiceRegistrationPayload {
+///     name: String::from("web"),
+///     port: 8080,
+///     ..Default::default()
+/// };
+/// let registrar = ServiceRegistrar::register(&client, payload, Duration::from_secs(15)).await?;
+/// registrar.status_handle().warning(Some("degraded"));
+/// registrar.shutdown().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ServiceRegistrar {
+    guard: RegistrationGuard,
+    check_id: String,
+    status: Arc<watch::Sender<ReportedStatus>>,
+    task: JoinHandle<()>,
+}
+
+impl ServiceRegistrar {
+    /// This method registers the given service with a TTL check of the given
+    /// duration, and starts reporting it as passing.
+    ///
+    /// The check is added to any checks already present in the payload, and
+    /// its ID is `service:<service ID>`.
+    #[tracing::instrument]
+    pub async fn register(
+        client: &Client,
+        mut payload: ServiceRegistrationPayload,
+        ttl: Duration,
+    ) -> ConsulResult<Self> {
+        let service_id = payload.id.clone().unwrap_or_else(|| payload.name.clone());
+        let check_id = format!("service:{}", service_id);
+        payload.checks.push(RegisterCheckPayload {
+            id: Some(check_id.clone()),
+            name: format!("Service '{}' TTL check", payload.name),
+            ttl: Some(format!("{}ms", ttl.as_millis())),
+            ..Default::default()
+        });
+        let guard = RegistrationGuard::register(client, payload).await?;
+
+        let (status, receiver) = watch::channel((CheckStatus::Passing, None));
+        let task = tokio::spawn(heartbeat(client.clone(), check_id.clone(), ttl / 3, receiver));
+        Ok(ServiceRegistrar { guard, check_id, status: Arc::new(status), task })
+    }
+
+    /// This method returns the ID of the registered service.
+    pub fn service_id(&self) -> &str {
+        self.guard.service_id()
+    }
+
+    /// This method returns the ID of the TTL check of the service.
+    pub fn check_id(&self) -> &str {
+        &self.check_id
+    }
+
+    /// This method returns a handle used to change the reported status of the
+    /// service.
+    pub fn status_handle(&self) -> StatusHandle {
+        StatusHandle { status: self.status.clone() }
+    }
+
+    /// This method stops reporting the status of the service and deregisters
+    /// it from the local agent.
+    #[tracing::instrument]
+    pub async fn shutdown(mut self) -> ConsulResult<()> {
+        self.task.abort();
+        self.guard.release().await
+    }
+
+    /// This method waits for ctrl-c, or SIGTERM on Unix platforms, then shuts
+    /// the registrar down. The caveats of
+    /// [RegistrationGuard::deregister_on_signal] apply.
+    pub async fn shutdown_on_signal(self) -> ConsulResult<()> {
+        shutdown_signal().await;
+        self.shutdown().await
+    }
+}
+
+impl Drop for ServiceRegistrar {
+    fn drop(&mut self) {
+        // the guard deregisters the service once the heartbeat is stopped
+        self.task.abort();
+    }
+}
+
+impl fmt::Debug for ServiceRegistrar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceRegistrar")
+            .field("service_id", &self.guard.service_id)
+            .field("check_id", &self.check_id)
+            .finish()
+    }
+}
+
+/// A handle used to change the status reported by a [ServiceRegistrar].
+///
+/// Changes are sent to the agent immediately. The handle may outlive the
+/// registrar, in which case changes are ignored.
+#[derive(Clone, Debug)]
+pub struct StatusHandle {
+    status: Arc<watch::Sender<ReportedStatus>>,
+}
+
+impl StatusHandle {
+    /// This method reports the service as passing.
+    pub fn passing(&self, output: Option<&str>) {
+        self.set(CheckStatus::Passing, output)
+    }
+
+    /// This method reports the service as warning.
+    pub fn warning(&self, output: Option<&str>) {
+        self.set(CheckStatus::Warning, output)
+    }
+
+    /// This method reports the service as critical.
+    pub fn critical(&self, output: Option<&str>) {
+        self.set(CheckStatus::Critical, output)
+    }
+
+    /// This method reports the given status and output.
+    pub fn set(&self, status: CheckStatus, output: Option<&str>) {
+        self.status.send_replace((status, output.map(str::to_owned)));
+    }
+}
+
+/// This function updates the TTL check with the latest reported status, at the
+/// given interval or whenever the status changes.
+async fn heartbeat(
+    client: Client,
+    check_id: String,
+    interval: Duration,
+    mut receiver: watch::Receiver<ReportedStatus>,
+) {
+    loop {
+        let (status, output) = receiver.borrow_and_update().clone();
+        if let Err(e) = client.update_check(&check_id, status, output.as_deref()).await {
+            tracing::warn!(check_id, error = %e, "failed to update ttl check");
+        }
+        // a timeout means the interval has elapsed without a status change
+        if let Ok(Err(_)) = tokio::time::timeout(interval, receiver.changed()).await {
+            return;
+        }
+    }
+}
+
+/// This function waits for ctrl-c, or SIGTERM on Unix platforms.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use futures::future::{select, FutureExt};
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                select(tokio::signal::ctrl_c().boxed(), terminate.recv().boxed()).await;
+                return;
+            }
+            Err(e) => tracing::warn!(error = %e, "failed to listen for SIGTERM"),
+        }
+    }
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        tracing::warn!(error = %e, "failed to listen for ctrl-c");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        AgentChecks, CheckStatus, Client, Config, ServiceRegistrar, ServiceRegistrationPayload,
+    };
+
+    #[tokio::test]
+    async fn test_service_registrar() {
+        let client = Client::new(Config::default());
+        let payload =
+            ServiceRegistrationPayload { name: String::from("registrar"), ..Default::default() };
+        let registrar =
+            ServiceRegistrar::register(&client, payload, Duration::from_secs(3)).await.unwrap();
+        registrar.status_handle().warning(Some("degraded"));
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let checks = client.list_checks(None).await.unwrap();
+        assert_eq!(checks[registrar.check_id()].status, CheckStatus::Warning);
+        assert_eq!(checks[registrar.check_id()].output, "degraded");
+
+        registrar.shutdown().await.unwrap();
+        assert!(!client.list_checks(None).await.unwrap().contains_key("service:registrar"));
+    }
+}
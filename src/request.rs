@@ -1,86 +1,508 @@
-use std::{collections::HashMap, fmt::Debug, future::Future, str};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    str,
+    time::{Duration, Instant},
+};
 
-use async_trait::async_trait;
-use reqwest::{Method, StatusCode};
+use bytes::Bytes;
+use futures::Stream;
+use http::{
+    header::{HeaderMap, HeaderValue, CACHE_CONTROL, CONTENT_TYPE},
+    Method, Request, StatusCode,
+};
 use serde::{de::DeserializeOwned, Serialize};
-use url::Url;
+use tracing::{field, Instrument, Span};
+use url::{form_urlencoded, Url};
 
-use crate::{Client, ConsulError, ConsulResult, QueryOptions};
+use crate::{
+    retry::retry_after, transport::ClientResponse, Client, Config, ConsistencyMode, ConsulError,
+    ConsulResult, ErrorResponse, QueryMeta, QueryOptions, RequestObservation, WriteMeta,
+    WriteOptions,
+};
 
-#[async_trait]
-trait AndThenAsync<T: Send, E: Send> {
-    async fn and_then_async<U, F, Fut>(self, f: F) -> std::result::Result<U, E>
-    where
-        F: FnOnce(T) -> Fut + Send,
-        Fut: Future<Output = Result<U, E>> + Send,
-        Self: Sized;
+impl QueryMeta {
+    /// This method parses the query metadata from the given response headers.
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+        QueryMeta {
+            last_index: header("X-Consul-Index").and_then(|v| v.parse().ok()),
+            known_leader: header("X-Consul-KnownLeader") == Some("true"),
+            last_contact: header("X-Consul-LastContact")
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or_default(),
+            cache_hit: header("X-Cache") == Some("HIT"),
+            cache_age: header("Age").and_then(|v| v.parse().ok()).map(Duration::from_secs),
+            address_translation_enabled: header("X-Consul-Translate-Addresses") == Some("true"),
+            last_content_hash: header("X-Consul-ContentHash").map(str::to_owned),
+            ..Default::default()
+        }
+    }
 }
 
-#[async_trait]
-impl<T: Send, E: Send> AndThenAsync<T, E> for std::result::Result<T, E> {
-    async fn and_then_async<U, F, Fut>(self, f: F) -> std::result::Result<U, E>
-    where
-        F: FnOnce(T) -> Fut + Send,
-        Fut: Future<Output = Result<U, E>> + Send,
-        Self: Sized,
-    {
-        match self {
-            Ok(inner) => Ok(f(inner).await?),
-            Err(e) => Err(e),
+/// The header used to authenticate requests with an ACL token.
+const CONSUL_TOKEN: &str = "X-Consul-Token";
+
+/// The wait time of blocking queries without one, which is the agent's default.
+const DEFAULT_WAIT_TIME: Duration = Duration::from_secs(300);
+
+/// The time a blocking query may take to respond in addition to its wait time
+/// and jitter, such as to send a large response.
+const BLOCKING_QUERY_GRACE: Duration = Duration::from_secs(10);
+
+/// The timeout of a request, stored in its extensions, which overrides the
+/// timeout of the `reqwest` client.
+#[derive(Clone, Copy, Debug)]
+struct Timeout(Duration);
+
+/// Options merged into the parameters and headers of a request, implemented by
+/// [QueryOptions] for reads and [WriteOptions] for writes.
+pub(crate) trait RequestOptions: Default + Debug + Send {
+    /// This method merges the options into the parameters and headers of a
+    /// request made with the given configuration.
+    fn apply(self, config: &Config, params: &mut HashMap<String, String>, headers: &mut HeaderMap);
+
+    /// This method returns the timeout of a request made with the given
+    /// configuration, overriding the timeout of its HTTP client, if any.
+    fn timeout(&self, _config: &Config) -> Option<Duration> {
+        None
+    }
+}
+
+impl RequestOptions for QueryOptions {
+    /// Blocking queries respond after up to their wait time plus a sixteenth
+    /// of it as jitter, so their timeout is extended past it rather than
+    /// killing long polls with the timeout of the client.
+    fn timeout(&self, config: &Config) -> Option<Duration> {
+        if self.wait_index.is_none() && self.wait_hash.is_none() {
+            return None;
+        }
+        let wait = self.wait_time.or(config.wait_time).unwrap_or(DEFAULT_WAIT_TIME);
+        Some(wait + wait / 16 + BLOCKING_QUERY_GRACE)
+    }
+
+    fn apply(self, config: &Config, params: &mut HashMap<String, String>, headers: &mut HeaderMap) {
+        #[cfg(feature = "enterprise")]
+        {
+            let scope = (self.namespace, self.partition);
+            apply_common(config, self.datacenter, scope, self.token, params, headers);
+        }
+        #[cfg(not(feature = "enterprise"))]
+        apply_common(config, self.datacenter, self.token, params, headers);
+        if let Some(filter) = self.filter {
+            params.insert(String::from("filter"), filter);
+        }
+        if let Some(near) = self.near {
+            params.insert(String::from("near"), near.as_str().to_owned());
+        }
+        match self.consistency {
+            ConsistencyMode::Default => {}
+            ConsistencyMode::Stale => {
+                params.insert(String::from("stale"), String::new());
+            }
+            ConsistencyMode::Consistent => {
+                params.insert(String::from("consistent"), String::new());
+            }
+            ConsistencyMode::Cached { max_age, stale_if_error } => {
+                params.insert(String::from("cached"), String::new());
+                let mut cache_control = Vec::new();
+                if let Some(max_age) = max_age {
+                    cache_control.push(format!("max-age={}", max_age.as_secs()));
+                }
+                if let Some(stale_if_error) = stale_if_error {
+                    cache_control.push(format!("stale-if-error={}", stale_if_error.as_secs()));
+                }
+                if !cache_control.is_empty() {
+                    if let Ok(value) = HeaderValue::from_str(&cache_control.join(", ")) {
+                        headers.insert(CACHE_CONTROL, value);
+                    }
+                }
+            }
+        }
+        // if wait index or hash is specified, make this a blocking query
+        let blocking = self.wait_index.is_some() || self.wait_hash.is_some();
+        if let Some(index) = self.wait_index {
+            params.insert(String::from("index"), index.to_string());
+        }
+        if let Some(hash) = self.wait_hash {
+            params.insert(String::from("hash"), hash);
+        }
+        if let Some(wait) = self.wait_time.or(config.wait_time).filter(|_| blocking) {
+            params.insert(String::from("wait"), format!("{}ms", wait.as_millis()));
+        }
+    }
+}
+
+impl RequestOptions for WriteOptions {
+    fn apply(self, config: &Config, params: &mut HashMap<String, String>, headers: &mut HeaderMap) {
+        #[cfg(feature = "enterprise")]
+        {
+            let scope = (self.namespace, self.partition);
+            apply_common(config, self.datacenter, scope, self.token, params, headers);
+        }
+        #[cfg(not(feature = "enterprise"))]
+        apply_common(config, self.datacenter, self.token, params, headers);
+        if let Some(relay_factor) = self.relay_factor {
+            params.insert(String::from("relay-factor"), relay_factor.to_string());
+        }
+    }
+}
+
+/// This function merges the options shared by reads and writes, falling back
+/// to the client's configuration.
+fn apply_common(
+    config: &Config,
+    datacenter: Option<String>,
+    #[cfg(feature = "enterprise")] (namespace, partition): (Option<String>, Option<String>),
+    token: Option<String>,
+    params: &mut HashMap<String, String>,
+    headers: &mut HeaderMap,
+) {
+    if let Some(dc) = datacenter.or_else(|| config.datacenter.clone()) {
+        params.insert(String::from("dc"), dc);
+    }
+    #[cfg(feature = "enterprise")]
+    if let Some(ns) = namespace.or_else(|| config.namespace.clone()) {
+        params.insert(String::from("ns"), ns);
+    }
+    #[cfg(feature = "enterprise")]
+    if let Some(partition) = partition.or_else(|| config.partition.clone()) {
+        params.insert(String::from("partition"), partition);
+    }
+    // the token is sent as a header, since the `token` parameter is deprecated
+    if let Some(token) = token.and_then(|token| HeaderValue::from_str(&token).ok()) {
+        headers.insert(CONSUL_TOKEN, token);
+    }
+}
+
+/// This function returns the span of the given request, recording its method,
+/// endpoint and datacenter. Its result is recorded by [record_result].
+fn request_span(request: &Request<Bytes>) -> Span {
+    let datacenter = datacenter(request);
+    tracing::info_span!(
+        "consul_request",
+        method = %request.method(),
+        endpoint = request.uri().path(),
+        datacenter = datacenter.as_deref(),
+        status = field::Empty,
+        index = field::Empty,
+        latency_ms = field::Empty,
+        error = field::Empty,
+    )
+}
+
+/// This function returns the datacenter of the given request, if one was set.
+fn datacenter(request: &Request<Bytes>) -> Option<String> {
+    let query = request.uri().query()?;
+    form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == "dc")
+        .map(|(_, dc)| dc.into_owned())
+}
+
+/// This function records the result of a request in its span.
+fn record_result(span: &Span, result: &ConsulResult<ClientResponse>, latency: Duration) {
+    if span.is_disabled() {
+        return;
+    }
+    span.record("latency_ms", latency.as_millis() as u64);
+    match result {
+        Ok(response) => {
+            span.record("status", response.status().as_u16());
+            if let Some(index) = QueryMeta::from_headers(response.headers()).last_index {
+                span.record("index", index);
+            }
         }
+        Err(e) => {
+            span.record("error", field::display(e));
+        }
+    }
+}
+
+/// This function returns whether the status code of responses to the given
+/// request conveys its result rather than a failure, such as the agent health
+/// endpoints reporting a critical service with a 503, so they are not retried.
+fn status_is_result(request: &Request<Bytes>) -> bool {
+    request.uri().path().starts_with("/v1/agent/health/service/")
+}
+
+/// This function returns a copy of the given request, to send it again.
+fn clone_request(request: &Request<Bytes>) -> Request<Bytes> {
+    let mut clone = Request::new(request.body().clone());
+    *clone.method_mut() = request.method().clone();
+    *clone.uri_mut() = request.uri().clone();
+    *clone.version_mut() = request.version();
+    *clone.headers_mut() = request.headers().clone();
+    *clone.extensions_mut() = request.extensions().clone();
+    clone
+}
+
+/// The maximum length of the error message kept from a response body.
+const MAX_ERROR_MESSAGE_LEN: usize = 512;
+
+/// This function returns the error for an unsuccessful response, mapping the
+/// common errors of the Consul API to dedicated variants.
+pub(crate) async fn response_error(response: ClientResponse) -> ConsulError {
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return ConsulError::RateLimited { retry_after: retry_after(response.headers()) };
+    }
+    let url = response.url().to_owned();
+    let body = response.text().await.unwrap_or_default();
+    let response = ErrorResponse { status, url, message: error_message(&body) };
+    // consul reports most errors as plain text, so some are only identified
+    // by their message
+    let message = response.message.to_lowercase();
+    if message.contains("session") && (message.contains("not found") || message.contains("invalid"))
+    {
+        ConsulError::SessionExpired(response)
+    } else if message.contains("unknown check") {
+        ConsulError::CheckNotFound(response)
+    } else if status == StatusCode::FORBIDDEN || message.contains("permission denied") {
+        ConsulError::AclPermissionDenied(response)
+    } else if status == StatusCode::NOT_FOUND {
+        ConsulError::NotFound(response)
+    } else if status == StatusCode::BAD_REQUEST {
+        ConsulError::InvalidRequest(response)
+    } else {
+        ConsulError::RequestFailed(response)
+    }
+}
+
+/// This function extracts the error message from the body of an unsuccessful
+/// response, which is either plain text or a JSON object with a message.
+fn error_message(body: &str) -> String {
+    let body = body.trim();
+    let message = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|value| {
+            ["message", "Message", "error", "Error"]
+                .iter()
+                .find_map(|key| value.get(key)?.as_str().map(str::to_owned))
+        })
+        .unwrap_or_else(|| body.to_owned());
+    let message = message.trim_start_matches("rpc error making call: ");
+    match message.char_indices().nth(MAX_ERROR_MESSAGE_LEN) {
+        Some((end, _)) => format!("{}...", &message[..end]),
+        None => message.to_owned(),
     }
 }
 
 impl Client {
-    #[tracing::instrument]
-    pub(crate) async fn send_with_empty<
-        Path: AsRef<str> + Debug,
-        Body: Serialize + Debug,
-        Response: DeserializeOwned,
-    >(
+    /// This method builds a request to the Consul API.
+    ///
+    /// Parameters derived from the query or write options, such as the
+    /// datacenter and blocking query index, are merged with the given
+    /// parameters.
+    pub(crate) fn build_request<Path: AsRef<str>, Body: Serialize, Options: RequestOptions>(
         &self,
         method: Method,
         path: Path,
         params: Option<HashMap<String, String>>,
         body: Option<Body>,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<Option<Response>> {
+        options: Option<Options>,
+    ) -> Request<Bytes> {
         // unwrap parameters
         let mut params = params.unwrap_or_default();
-        // if datacenter option is specified, set
-        let datacenter: Option<String> =
-            options.and_then(|o| o.datacenter).or_else(|| self.config.datacenter.as_ref().cloned());
-        if let Some(dc) = datacenter {
-            params.insert(String::from("dc"), dc);
+        let mut headers = HeaderMap::new();
+        // the token of the options takes precedence over the client's token,
+        // which takes precedence over its shared token and then its token file
+        let token = self
+            .config
+            .token
+            .clone()
+            .or_else(|| Some(self.config.shared_token.as_ref()?.get()))
+            .or_else(|| self.config.token_file.as_ref()?.token());
+        if let Some(token) = token.and_then(|token| HeaderValue::from_str(&token).ok()) {
+            headers.insert(CONSUL_TOKEN, token);
         }
+        let options = options.unwrap_or_default();
+        let timeout = options.timeout(&self.config);
+        options.apply(&self.config, &mut params, &mut headers);
         // parse url and create builder
         let url = Url::parse_with_params(
             &format!("{}{}", self.config.address, path.as_ref()),
             params.iter(),
         )
         .unwrap();
-        let builder = self.config.http_client.request(method, url);
+        let mut request = Request::new(Bytes::new());
+        *request.method_mut() = method;
+        *request.uri_mut() = url.as_str().parse().expect("a parsed url is a valid uri");
         // add body if specified
-        let builder = if let Some(b) = body { builder.json(&b) } else { builder };
-        // add query options
-        let builder = match &self.config.token {
-            Some(val) => builder.header("X-Consul-Token", val),
-            None => builder,
+        if let Some(body) = body {
+            let body = serde_json::to_vec(&body).expect("request bodies are valid json");
+            *request.body_mut() = Bytes::from(body);
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        }
+        if let Some(timeout) = timeout {
+            request.extensions_mut().insert(Timeout(timeout));
+        }
+        *request.headers_mut() = headers;
+        request
+    }
+
+    /// This method sends the given request, retrying it according to the retry
+    /// policy of the configuration.
+    ///
+    /// With the `tracing` feature, the request is instrumented with a span
+    /// recording its endpoint, status, index and latency.
+    pub(crate) async fn execute(&self, request: Request<Bytes>) -> ConsulResult<ClientResponse> {
+        let span = if cfg!(feature = "tracing") { request_span(&request) } else { Span::none() };
+        let mut observation = self
+            .config
+            .metrics_sink
+            .as_ref()
+            .map(|sink| (sink, RequestObservation::new(&request, datacenter(&request))));
+        let start = Instant::now();
+        let result = self.execute_retrying(request).instrument(span.clone()).await;
+        record_result(&span, &result, start.elapsed());
+        if let Some((sink, observation)) = &mut observation {
+            observation.finish(&result, start.elapsed());
+            sink.observe(observation);
+        }
+        result
+    }
+
+    /// This method sends the given request, retrying it according to the retry
+    /// policy of the configuration.
+    async fn execute_retrying(&self, request: Request<Bytes>) -> ConsulResult<ClientResponse> {
+        let policy = match &self.config.retry_policy {
+            Some(policy) if policy.allows(request.method()) => policy,
+            _ => return self.attempt(request).await,
+        };
+        let status_is_result = status_is_result(&request);
+        let mut attempts = 1;
+        loop {
+            // the last attempt consumes the original request
+            if attempts >= policy.max_attempts {
+                return self.attempt(request).await;
+            }
+            let result = self.attempt(clone_request(&request)).await;
+            let delay = match policy.retry_delay(attempts, &result) {
+                Some(_) if status_is_result && result.is_ok() => return result,
+                Some(delay) => delay,
+                None => return result,
+            };
+            tracing::debug!(attempts, ?delay, "retrying request");
+            tokio::time::sleep(delay).await;
+            attempts += 1;
+        }
+    }
+
+    /// This method sends the given request once. If the configuration has an
+    /// address pool, the request is sent to its current address, and sent to
+    /// the next one if it fails to connect.
+    async fn attempt(&self, mut request: Request<Bytes>) -> ConsulResult<ClientResponse> {
+        let pool = match &self.config.address_pool {
+            Some(pool) => pool,
+            None => return self.dispatch(request).await,
         };
+        // a request that failed to connect was never received, so it is safe
+        // to send it again, even if it is a write
+        for _ in 1..pool.len() {
+            let index = pool.select();
+            let mut attempt = clone_request(&request);
+            pool.redirect(&mut attempt, index);
+            match self.dispatch(attempt).await {
+                Err(e) if e.is_connect() => {
+                    tracing::debug!(address = %pool.addresses()[index], "agent address is dead");
+                    pool.mark_dead(index);
+                }
+                result => return result,
+            }
+        }
+        let index = pool.select();
+        pool.redirect(&mut request, index);
+        let result = self.dispatch(request).await;
+        if matches!(&result, Err(e) if e.is_connect()) {
+            pool.mark_dead(index);
+        }
+        result
+    }
+
+    /// This method sends the given request, calling the middleware of the
+    /// configuration around it. Requests are sent by the transport of the
+    /// configuration if it has one, and by its `reqwest` client otherwise.
+    async fn dispatch(&self, mut request: Request<Bytes>) -> ConsulResult<ClientResponse> {
+        for middleware in &self.config.middleware {
+            middleware.before_request(&mut request).await?;
+        }
+        let response = match &self.config.transport {
+            Some(transport) => {
+                let url = request.uri().to_string();
+                ClientResponse::buffered(transport.execute(request).await?, url)
+            }
+            #[cfg(feature = "reqwest")]
+            None => {
+                let timeout = request.extensions().get::<Timeout>().map(|timeout| timeout.0);
+                let mut request = reqwest::Request::try_from(request)?;
+                if timeout.is_some() {
+                    *request.timeout_mut() = timeout;
+                }
+                ClientResponse::streamed(self.config.http_client.execute(request).await?)
+            }
+            #[cfg(not(feature = "reqwest"))]
+            None => return Err(ConsulError::TransportError("no transport is configured".into())),
+        };
+        for middleware in &self.config.middleware {
+            middleware.after_response(response.head()).await?;
+        }
+        Ok(response)
+    }
+
+    /// This method sends a request to the Consul API, returning the response
+    /// alongside the metadata parsed from its headers. The response may be
+    /// empty.
+    #[tracing::instrument]
+    pub(crate) async fn send_with_meta<
+        Path: AsRef<str> + Debug,
+        Body: Serialize + Debug,
+        Response: DeserializeOwned,
+        Options: RequestOptions,
+    >(
+        &self,
+        method: Method,
+        path: Path,
+        params: Option<HashMap<String, String>>,
+        body: Option<Body>,
+        options: Option<Options>,
+    ) -> ConsulResult<(Option<Response>, QueryMeta)> {
+        let request = self.build_request(method, path, params, body, options);
         // send request
-        let response = builder.send().await?;
+        let start = Instant::now();
+        let response = self.execute(request).await?;
+        let mut meta = QueryMeta::from_headers(response.headers());
         if response.status() == StatusCode::NOT_FOUND {
-            return Ok(None);
+            meta.request_time = start.elapsed();
+            return Ok((None, meta));
         }
         if !response.status().is_success() {
-            return Err(ConsulError::RequestFailed(response.status()));
+            return Err(response_error(response).await);
         }
         let response = response.text().await?;
+        meta.request_time = start.elapsed();
         if response.is_empty() {
-            return Ok(None);
+            return Ok((None, meta));
         }
         let response = serde_json::from_str(&response).map_err(ConsulError::DecodeError)?;
-        Ok(response)
+        Ok((Some(response), meta))
+    }
+
+    #[tracing::instrument]
+    pub(crate) async fn send_with_empty<
+        Path: AsRef<str> + Debug,
+        Body: Serialize + Debug,
+        Response: DeserializeOwned,
+        Options: RequestOptions,
+    >(
+        &self,
+        method: Method,
+        path: Path,
+        params: Option<HashMap<String, String>>,
+        body: Option<Body>,
+        options: Option<Options>,
+    ) -> ConsulResult<Option<Response>> {
+        self.send_with_meta(method, path, params, body, options).await.map(|(r, _)| r)
     }
 
     /// This method sends a request to the Consul API.
@@ -96,42 +518,21 @@ impl Client {
         Path: AsRef<str> + Debug,
         Body: Serialize + Debug,
         Response: DeserializeOwned,
+        Options: RequestOptions,
     >(
         &self,
         method: Method,
         path: Path,
         params: Option<HashMap<String, String>>,
         body: Option<Body>,
-        options: Option<QueryOptions>,
+        options: Option<Options>,
     ) -> ConsulResult<Response> {
-        // unwrap parameters
-        let mut params = params.unwrap_or_default();
-        // if datacenter option is specified, set
-        let datacenter: Option<String> =
-            options.and_then(|o| o.datacenter).or_else(|| self.config.datacenter.as_ref().cloned());
-        if let Some(dc) = datacenter {
-            params.insert(String::from("dc"), dc);
+        let response =
+            self.execute(self.build_request(method, path, params, body, options)).await?;
+        if !response.status().is_success() {
+            return Err(response_error(response).await);
         }
-        // parse url and create builder
-        let url = Url::parse_with_params(
-            &format!("{}{}", self.config.address, path.as_ref()),
-            params.iter(),
-        )
-        .unwrap();
-        let builder = self.config.http_client.request(method, url);
-        // add body if specified
-        let builder = if let Some(b) = body { builder.json(&b) } else { builder };
-        // add query options
-        let builder = match &self.config.token {
-            Some(val) => builder.header("X-Consul-Token", val),
-            None => builder,
-        };
-        builder
-            .send()
-            .await
-            .and_then_async(|x| async { x.json::<Response>().await })
-            .await
-            .map_err(ConsulError::HttpError)
+        response.json().await
     }
     /// This method makes a GET request with query parameters to the given path.
     #[tracing::instrument]
@@ -141,7 +542,7 @@ impl Client {
         params: Option<HashMap<String, String>>,
         options: Option<QueryOptions>,
     ) -> ConsulResult<T> {
-        self.send::<Path, (), T>(Method::GET, path, params, None, options).await
+        self.send::<Path, (), T, _>(Method::GET, path, params, None, options).await
     }
 
     /// This method makes a GET request to the given path.
@@ -154,16 +555,117 @@ impl Client {
         self.get_with_params(path, None, options).await
     }
 
-    /// This method makes a GET request to the given path, with the response
-    /// potentially being empty.
+    /// This method makes a GET request to the given path, returning the
+    /// response body as text rather than decoding it as JSON.
     #[tracing::instrument]
-    pub(crate) async fn get_with_empty<Path: AsRef<str> + Debug, Response: DeserializeOwned>(
+    pub(crate) async fn get_text<Path: AsRef<str> + Debug>(
         &self,
         path: Path,
         params: Option<HashMap<String, String>>,
         options: Option<QueryOptions>,
-    ) -> ConsulResult<Option<Response>> {
-        self.send_with_empty::<Path, (), Response>(Method::GET, path, params, None, options).await
+    ) -> ConsulResult<String> {
+        let request = self.build_request(Method::GET, path, params, None as Option<()>, options);
+        let response = self.execute(request).await?;
+        if !response.status().is_success() {
+            return Err(response_error(response).await);
+        }
+        response.text().await
+    }
+
+    /// This method makes a GET request to the given path, returning the raw
+    /// response body alongside its [QueryMeta], or [None] if the path was not
+    /// found.
+    #[tracing::instrument]
+    pub(crate) async fn get_bytes<Path: AsRef<str> + Debug>(
+        &self,
+        path: Path,
+        params: Option<HashMap<String, String>>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<Bytes>, QueryMeta)> {
+        let start = Instant::now();
+        let request = self.build_request(Method::GET, path, params, None as Option<()>, options);
+        let response = self.execute(request).await?;
+        let mut meta = QueryMeta::from_headers(response.headers());
+        if response.status() == StatusCode::NOT_FOUND {
+            meta.request_time = start.elapsed();
+            return Ok((None, meta));
+        }
+        if !response.status().is_success() {
+            return Err(response_error(response).await);
+        }
+        let response = response.bytes().await?;
+        meta.request_time = start.elapsed();
+        Ok((Some(response), meta))
+    }
+
+    /// This method makes a GET request to the given path, returning the
+    /// response body as a stream of bytes alongside its [QueryMeta].
+    #[tracing::instrument]
+    pub(crate) async fn get_stream<Path: AsRef<str> + Debug>(
+        &self,
+        path: Path,
+        params: Option<HashMap<String, String>>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(impl Stream<Item = ConsulResult<Bytes>>, QueryMeta)> {
+        let start = Instant::now();
+        let request = self.build_request(Method::GET, path, params, None as Option<()>, options);
+        let response = self.execute(request).await?;
+        if !response.status().is_success() {
+            return Err(response_error(response).await);
+        }
+        let mut meta = QueryMeta::from_headers(response.headers());
+        meta.request_time = start.elapsed();
+        Ok((response.bytes_stream(), meta))
+    }
+
+    /// This method makes a GET request to the given path, returning the
+    /// response alongside its [QueryMeta]. The response may be empty.
+    #[tracing::instrument]
+    pub(crate) async fn get_with_meta<Path: AsRef<str> + Debug, Response: DeserializeOwned>(
+        &self,
+        path: Path,
+        params: Option<HashMap<String, String>>,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Option<Response>, QueryMeta)> {
+        self.send_with_meta::<Path, (), Response, _>(Method::GET, path, params, None, options).await
+    }
+
+    /// This method sends a write to the Consul API, returning the response
+    /// alongside its [WriteMeta].
+    async fn write<
+        Path: AsRef<str> + Debug,
+        Body: Serialize + Debug,
+        Response: DeserializeOwned,
+    >(
+        &self,
+        method: Method,
+        path: Path,
+        params: Option<HashMap<String, String>>,
+        body: Option<Body>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Response, WriteMeta)> {
+        let start = Instant::now();
+        let response = self.send(method, path, params, body, options).await?;
+        Ok((response, WriteMeta { request_time: start.elapsed() }))
+    }
+
+    /// This method sends a write to the Consul API, returning the potentially
+    /// empty response alongside its [WriteMeta].
+    pub(crate) async fn write_with_empty<
+        Path: AsRef<str> + Debug,
+        Body: Serialize + Debug,
+        Response: DeserializeOwned,
+    >(
+        &self,
+        method: Method,
+        path: Path,
+        params: Option<HashMap<String, String>>,
+        body: Option<Body>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Option<Response>, WriteMeta)> {
+        let start = Instant::now();
+        let response = self.send_with_empty(method, path, params, body, options).await?;
+        Ok((response, WriteMeta { request_time: start.elapsed() }))
     }
 
     /// This method makes a POST request to the given path.
@@ -177,9 +679,9 @@ impl Client {
         path: Path,
         body: Body,
         params: Option<HashMap<String, String>>,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<Response> {
-        self.send::<Path, Body, Response>(Method::POST, path, params, Some(body), options).await
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Response, WriteMeta)> {
+        self.write(Method::POST, path, params, Some(body), options).await
     }
 
     /// This method makes a POST request to the given path, with the response
@@ -194,16 +696,9 @@ impl Client {
         path: Path,
         body: Body,
         params: Option<HashMap<String, String>>,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<Option<Response>> {
-        self.send_with_empty::<Path, Body, Response>(
-            Method::POST,
-            path,
-            params,
-            Some(body),
-            options,
-        )
-        .await
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Option<Response>, WriteMeta)> {
+        self.write_with_empty(Method::POST, path, params, Some(body), options).await
     }
 
     /// This method makes a PUT request to the given path.
@@ -217,9 +712,67 @@ impl Client {
         path: Path,
         body: Body,
         params: Option<HashMap<String, String>>,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<Response> {
-        self.send::<Path, Body, Response>(Method::PUT, path, params, Some(body), options).await
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Response, WriteMeta)> {
+        self.write(Method::PUT, path, params, Some(body), options).await
+    }
+
+    /// This method makes a PUT request to the given path, with the body sent
+    /// as-is rather than encoded as JSON.
+    #[tracing::instrument(skip(body))]
+    pub(crate) async fn put_raw<
+        Path: AsRef<str> + Debug,
+        RawBody: Into<Bytes>,
+        Response: DeserializeOwned,
+    >(
+        &self,
+        path: Path,
+        body: RawBody,
+        params: Option<HashMap<String, String>>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Response, WriteMeta)> {
+        let mut request =
+            self.build_request(Method::PUT, path, params, None as Option<()>, options);
+        *request.body_mut() = body.into();
+        let start = Instant::now();
+        let response = self.execute(request).await?;
+        if !response.status().is_success() {
+            return Err(response_error(response).await);
+        }
+        let response = response.json().await?;
+        Ok((response, WriteMeta { request_time: start.elapsed() }))
+    }
+
+    /// This method makes a PUT request to the given path, with the body sent
+    /// as-is rather than encoded as JSON, and the response potentially being
+    /// empty.
+    #[tracing::instrument(skip(body))]
+    pub(crate) async fn put_raw_with_empty<
+        Path: AsRef<str> + Debug,
+        RawBody: Into<Bytes>,
+        Response: DeserializeOwned,
+    >(
+        &self,
+        path: Path,
+        body: RawBody,
+        params: Option<HashMap<String, String>>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Option<Response>, WriteMeta)> {
+        let mut request =
+            self.build_request(Method::PUT, path, params, None as Option<()>, options);
+        *request.body_mut() = body.into();
+        let start = Instant::now();
+        let response = self.execute(request).await?;
+        if !response.status().is_success() {
+            return Err(response_error(response).await);
+        }
+        let response = response.text().await?;
+        let meta = WriteMeta { request_time: start.elapsed() };
+        if response.is_empty() {
+            return Ok((None, meta));
+        }
+        let response = serde_json::from_str(&response).map_err(ConsulError::DecodeError)?;
+        Ok((Some(response), meta))
     }
 
     /// This method makes a PUT request to the given path, with the response
@@ -233,10 +786,9 @@ impl Client {
         path: Path,
         body: Body,
         params: Option<HashMap<String, String>>,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<Option<Response>> {
-        self.send_with_empty::<Path, Body, Response>(Method::PUT, path, params, Some(body), options)
-            .await
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Option<Response>, WriteMeta)> {
+        self.write_with_empty(Method::PUT, path, params, Some(body), options).await
     }
 
     /// This method makes a DELETE request to the given path.
@@ -245,8 +797,582 @@ impl Client {
         &self,
         path: Path,
         params: Option<HashMap<String, String>>,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<Response> {
-        self.send::<Path, (), Response>(Method::DELETE, path, params, None, options).await
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Response, WriteMeta)> {
+        self.write(Method::DELETE, path, params, None::<()>, options).await
+    }
+
+    /// This method makes a DELETE request to the given path, with the response
+    /// potentially being empty.
+    #[tracing::instrument]
+    pub(crate) async fn delete_with_empty<Path: AsRef<str> + Debug, Response: DeserializeOwned>(
+        &self,
+        path: Path,
+        params: Option<HashMap<String, String>>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Option<Response>, WriteMeta)> {
+        self.write_with_empty(Method::DELETE, path, params, None::<()>, options).await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::time::Duration;
+
+    use http::{
+        header::{HeaderMap, HeaderValue},
+        Method, Request,
+    };
+    use url::form_urlencoded;
+
+    use super::{error_message, Timeout};
+    use crate::{Client, Config, ConsistencyMode, QueryMeta, QueryOptions, WriteOptions};
+
+    #[test]
+    fn test_consistency_mode() {
+        let client = Client::new(Config::default());
+        let build = |consistency| {
+            let options = QueryOptions { consistency, ..Default::default() };
+            client.build_request(Method::GET, "/v1/kv/a", None, None::<()>, Some(options))
+        };
+        let request = build(ConsistencyMode::Stale);
+        assert_eq!(request.uri().query(), Some("stale="));
+        let request = build(ConsistencyMode::Cached {
+            max_age: Some(Duration::from_secs(30)),
+            stale_if_error: Some(Duration::from_secs(600)),
+        });
+        assert_eq!(request.uri().query(), Some("cached="));
+        assert_eq!(request.headers()["Cache-Control"], "max-age=30, stale-if-error=600");
+    }
+
+    #[test]
+    fn test_blocking_query_timeout() {
+        let client = Client::new(Config::default());
+        let build = |options| {
+            client.build_request(Method::GET, "/v1/kv/a", None, None::<()>, Some(options))
+        };
+        let timeout = |request: &Request<_>| request.extensions().get::<Timeout>().map(|t| t.0);
+        let request = build(QueryOptions::default());
+        assert_eq!(timeout(&request), None);
+        let options = QueryOptions {
+            wait_index: Some(42),
+            wait_time: Some(Duration::from_secs(600)),
+            ..Default::default()
+        };
+        let request = build(options);
+        assert_eq!(timeout(&request), Some(Duration::from_millis(647_500)));
+        let request = build(QueryOptions { wait_index: Some(42), ..Default::default() });
+        assert_eq!(timeout(&request), Some(Duration::from_millis(328_750)));
+        let options = QueryOptions {
+            wait_hash: Some(String::from("2a2ab2a1b7df9b3c")),
+            wait_time: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let request = build(options);
+        let query = request.uri().query().unwrap_or_default();
+        let params: Vec<_> = form_urlencoded::parse(query.as_bytes()).collect();
+        assert!(params.contains(&("hash".into(), "2a2ab2a1b7df9b3c".into())));
+        assert!(params.contains(&("wait".into(), "60000ms".into())));
+        assert_eq!(timeout(&request), Some(Duration::from_millis(73_750)));
+    }
+
+    #[test]
+    fn test_write_options() {
+        let config = Config { token: Some(String::from("client")), ..Config::default() };
+        let client = Client::new(config);
+        let build =
+            |options| client.build_request(Method::PUT, "/v1/kv/a", None, None::<()>, options);
+        let request = build(None);
+        assert_eq!(request.headers()["X-Consul-Token"], "client");
+        let options = WriteOptions {
+            token: Some(String::from("override")),
+            relay_factor: Some(2),
+            ..Default::default()
+        };
+        let request = build(Some(options));
+        assert_eq!(request.headers().get_all("X-Consul-Token").iter().count(), 1);
+        assert_eq!(request.headers()["X-Consul-Token"], "override");
+        assert_eq!(request.uri().query(), Some("relay-factor=2"));
+    }
+
+    #[test]
+    fn test_query_options_token() {
+        let client = Client::new(Config::default());
+        let options = QueryOptions { token: Some(String::from("caller")), ..Default::default() };
+        let request =
+            client.build_request(Method::GET, "/v1/kv/a", None, None::<()>, Some(options));
+        assert_eq!(request.headers()["X-Consul-Token"], "caller");
+        let query = request.uri().query().unwrap_or_default();
+        assert!(form_urlencoded::parse(query.as_bytes()).all(|(key, _)| key != "token"));
+    }
+
+    #[cfg(all(unix, feature = "reqwest", feature = "status"))]
+    #[tokio::test]
+    async fn test_unix_socket() {
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::UnixListener,
+        };
+
+        use crate::Status;
+
+        let path = std::env::temp_dir().join(format!("consul-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 1024];
+            let len = stream.read(&mut request).await.unwrap();
+            assert!(request[..len].starts_with(b"GET /v1/status/leader"));
+            let body = r#""10.0.0.1:8300""#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let config = Config { address: format!("unix://{}", path.display()), ..Config::default() };
+        let client = Client::new(config);
+        assert_eq!(client.get_leader(None).await.unwrap(), "10.0.0.1:8300");
+        server.await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// The body of a successful response to `/v1/status/leader`.
+    #[cfg(all(feature = "reqwest", feature = "status"))]
+    pub(crate) const LEADER: &str = r#""10.0.0.1:8300""#;
+
+    /// This function serves the given responses to `/v1/status/leader` in
+    /// order, each with the given status line, headers and body, returning the
+    /// address of the server.
+    #[cfg(all(feature = "reqwest", feature = "status"))]
+    pub(crate) async fn serve(
+        responses: &'static [(&'static str, &'static str, &'static str)],
+    ) -> (String, tokio::task::JoinHandle<()>) {
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpListener,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            for (status, headers, body) in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = vec![0; 1024];
+                let len = stream.read(&mut request).await.unwrap();
+                assert!(request[..len].starts_with(b"GET /v1/status/leader"));
+                let response = format!(
+                    "HTTP/1.1 {}\r\nConnection: close\r\n{}Content-Length: {}\r\n\r\n{}",
+                    status,
+                    headers,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (address, server)
+    }
+
+    /// A transport recording the method and url of every request, followed by
+    /// its token if it has one, responding
+    /// with the given statuses, headers and bodies in order. Once they are
+    /// exhausted, requests never complete, like blocking queries without
+    /// changes.
+    #[derive(Debug)]
+    pub(crate) struct Recorder {
+        responses: std::sync::Mutex<std::collections::VecDeque<Reply>>,
+        requests: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    /// The status, headers and body of a response sent by a [Recorder].
+    pub(crate) type Reply = (u16, &'static [(&'static str, &'static str)], &'static str);
+
+    #[async_trait::async_trait]
+    impl crate::HttpTransport for Recorder {
+        async fn execute(
+            &self,
+            request: http::Request<bytes::Bytes>,
+        ) -> crate::ConsulResult<http::Response<bytes::Bytes>> {
+            let uri = request.uri();
+            let query = uri.query().filter(|query| !query.is_empty());
+            let path = query.map_or(uri.path().to_owned(), |q| format!("{}?{}", uri.path(), q));
+            let mut recorded = format!("{} {}", request.method(), path);
+            if let Some(token) = request.headers().get(super::CONSUL_TOKEN) {
+                recorded = format!("{} token={}", recorded, token.to_str().unwrap());
+            }
+            self.requests.lock().unwrap().push(recorded);
+            let reply = self.responses.lock().unwrap().pop_front();
+            let (status, headers, body) = match reply {
+                Some(reply) => reply,
+                None => futures::future::pending().await,
+            };
+            let mut response = http::Response::builder().status(status);
+            for (name, value) in headers {
+                response = response.header(*name, *value);
+            }
+            Ok(response.body(bytes::Bytes::from_static(body.as_bytes())).unwrap())
+        }
+    }
+
+    /// This function returns a client whose requests are sent to a
+    /// [Recorder] with the given responses, alongside the requests it
+    /// records.
+    pub(crate) fn recording_client(
+        responses: &[Reply],
+    ) -> (Client, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        recording_client_with(Config::builder(), responses)
+    }
+
+    /// This function returns a client like [recording_client], configured by
+    /// the given builder.
+    pub(crate) fn recording_client_with(
+        builder: crate::ConfigBuilder,
+        responses: &[Reply],
+    ) -> (Client, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        let requests = std::sync::Arc::default();
+        let recorder = Recorder {
+            responses: std::sync::Mutex::new(responses.iter().copied().collect()),
+            requests: std::sync::Arc::clone(&requests),
+        };
+        (Client::new(builder.transport(recorder).build().unwrap()), requests)
+    }
+
+    #[cfg(all(feature = "reqwest", feature = "status"))]
+    #[tokio::test]
+    async fn test_retry() {
+        use crate::{RetryPolicy, Status};
+
+        let (address, server) = serve(&[
+            ("503 Service Unavailable", "", ""),
+            ("429 Too Many Requests", "Retry-After: 0\r\n", ""),
+            ("200 OK", "", LEADER),
+        ])
+        .await;
+        let policy = RetryPolicy { base_delay: Duration::from_millis(1), ..Default::default() };
+        let config = Config::builder().address(address).retry_policy(policy).build().unwrap();
+        let client = Client::new(config);
+        assert_eq!(client.get_leader(None).await.unwrap(), "10.0.0.1:8300");
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_retry_status_is_result() {
+        use crate::RetryPolicy;
+
+        let requests = std::sync::Arc::default();
+        let responses: &[Reply] = &[(503, &[], "[]")];
+        let recorder = Recorder {
+            responses: std::sync::Mutex::new(responses.iter().copied().collect()),
+            requests: std::sync::Arc::clone(&requests),
+        };
+        let policy = RetryPolicy { base_delay: Duration::from_millis(1), ..Default::default() };
+        let config = Config::builder().retry_policy(policy).transport(recorder).build().unwrap();
+        let client = Client::new(config);
+        let path = "/v1/agent/health/service/name/web";
+        let request =
+            client.build_request(Method::GET, path, None, None::<()>, None::<QueryOptions>);
+        // a critical service is reported with a 503, which is not retried
+        let response = client.execute(request).await.unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[cfg(all(feature = "reqwest", feature = "status"))]
+    #[tokio::test]
+    async fn test_address_pool() {
+        use crate::Status;
+
+        // bind and drop a listener to find a port nothing is listening on
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let dead = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let (address, server) = serve(&[("200 OK", "", LEADER), ("200 OK", "", LEADER)]).await;
+        let client = Client::new(Config::builder().addresses([dead, address]).build().unwrap());
+        assert_eq!(client.get_leader(None).await.unwrap(), "10.0.0.1:8300");
+        // the dead address is skipped for the following requests
+        assert_eq!(client.get_leader(None).await.unwrap(), "10.0.0.1:8300");
+        assert_eq!(client.config.address_pool.as_ref().unwrap().select(), 1);
+        server.await.unwrap();
+    }
+
+    #[cfg(all(feature = "reqwest", feature = "status"))]
+    #[tokio::test]
+    async fn test_rate_limited() {
+        use crate::{ConsulError, Status};
+
+        let (address, server) = serve(&[("429 Too Many Requests", "Retry-After: 2\r\n", "")]).await;
+        let client = Client::new(Config::builder().address(address).build().unwrap());
+        let error = client.get_leader(None).await.unwrap_err();
+        assert!(matches!(
+            error,
+            ConsulError::RateLimited { retry_after: Some(d) } if d == Duration::from_secs(2)
+        ));
+        server.await.unwrap();
+    }
+
+    #[cfg(all(feature = "reqwest", feature = "status"))]
+    #[tokio::test]
+    async fn test_response_error() {
+        use crate::{ConsulError, Status};
+
+        let (address, server) = serve(&[
+            ("403 Forbidden", "", "rpc error making call: Permission denied"),
+            ("404 Not Found", "", "Session id 'abc' not found"),
+            ("500 Internal Server Error", "", r#"{"Message": "internal error"}"#),
+        ])
+        .await;
+        let client = Client::new(Config::builder().address(address).build().unwrap());
+        match client.get_leader(None).await {
+            Err(ConsulError::AclPermissionDenied(response)) => {
+                assert_eq!(response.status, 403);
+                assert!(response.url.contains("/v1/status/leader"));
+                assert_eq!(response.message, "Permission denied");
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert!(matches!(client.get_leader(None).await, Err(ConsulError::SessionExpired(_))));
+        match client.get_leader(None).await {
+            Err(ConsulError::RequestFailed(response)) => {
+                assert_eq!(response.message, "internal error")
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        server.await.unwrap();
+    }
+
+    #[cfg(all(feature = "reqwest", feature = "status"))]
+    #[tokio::test]
+    async fn test_middleware() {
+        use std::sync::Mutex;
+
+        use async_trait::async_trait;
+        use bytes::Bytes;
+        use http::response::Parts;
+
+        use crate::{ConsulResult, Middleware, Status};
+
+        #[derive(Debug, Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        #[async_trait]
+        impl Middleware for &'static Recorder {
+            async fn before_request(&self, request: &mut Request<Bytes>) -> ConsulResult<()> {
+                self.0.lock().unwrap().push(format!(
+                    "{} {}",
+                    request.method(),
+                    request.uri().path()
+                ));
+                Ok(())
+            }
+
+            async fn after_response(&self, response: &Parts) -> ConsulResult<()> {
+                self.0.lock().unwrap().push(response.status.to_string());
+                Ok(())
+            }
+        }
+
+        let recorder: &'static Recorder = Box::leak(Box::default());
+        let (address, server) = serve(&[("200 OK", "", LEADER)]).await;
+        let config = Config::builder().address(address).middleware(recorder).build().unwrap();
+        Client::new(config).get_leader(None).await.unwrap();
+        assert_eq!(*recorder.0.lock().unwrap(), ["GET /v1/status/leader", "200 OK"]);
+        server.await.unwrap();
+    }
+
+    #[cfg(all(feature = "reqwest", feature = "status"))]
+    #[tokio::test]
+    async fn test_metrics_sink() {
+        use std::sync::Mutex;
+
+        use crate::{MetricsSink, RequestObservation, Status};
+
+        #[derive(Debug, Default)]
+        struct Recorder(Mutex<Vec<RequestObservation>>);
+
+        impl MetricsSink for &'static Recorder {
+            fn observe(&self, observation: &RequestObservation) {
+                self.0.lock().unwrap().push(observation.clone());
+            }
+        }
+
+        let recorder: &'static Recorder = Box::leak(Box::default());
+        let (address, server) = serve(&[("404 Not Found", "", "")]).await;
+        let config = Config::builder()
+            .address(address)
+            .datacenter("dc1")
+            .metrics_sink(recorder)
+            .build()
+            .unwrap();
+        assert!(Client::new(config).get_leader(None).await.is_err());
+        server.await.unwrap();
+        let observations = recorder.0.lock().unwrap();
+        assert_eq!(observations.len(), 1);
+        assert_eq!(observations[0].method, Method::GET);
+        assert_eq!(observations[0].path, "/v1/status/leader");
+        assert_eq!(observations[0].datacenter.as_deref(), Some("dc1"));
+        assert_eq!(observations[0].status, Some(http::StatusCode::NOT_FOUND));
+        assert!(!observations[0].is_success());
+    }
+
+    #[cfg(all(feature = "compression", feature = "status"))]
+    #[tokio::test]
+    async fn test_compression() {
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpListener,
+        };
+
+        use crate::Status;
+
+        // the leader response compressed with gzip
+        const GZIP_LEADER: &[u8] = b"\x1f\x8b\x08\x00\x00\x00\x00\x00\x02\x03\x53\x32\x34\xd0\x03\
+            \x41\x43\x2b\x0b\x63\x03\x03\x25\x00\x88\xb8\x74\x7a\x0f\x00\x00\x00";
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 1024];
+            let len = stream.read(&mut request).await.unwrap();
+            let request = String::from_utf8_lossy(&request[..len]).to_lowercase();
+            let accept_encoding = request.lines().find(|line| line.starts_with("accept-encoding"));
+            assert!(accept_encoding.unwrap().contains("gzip"));
+            let headers = format!(
+                "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Encoding: gzip\r\n\
+                Content-Length: {}\r\n\r\n",
+                GZIP_LEADER.len()
+            );
+            stream.write_all(headers.as_bytes()).await.unwrap();
+            stream.write_all(GZIP_LEADER).await.unwrap();
+        });
+
+        let client = Client::new(Config::builder().address(address).build().unwrap());
+        assert_eq!(client.get_leader(None).await.unwrap(), "10.0.0.1:8300");
+        server.await.unwrap();
+        assert!(Config::builder().compression(false).build().is_ok());
+    }
+
+    #[cfg(all(feature = "reqwest", feature = "status"))]
+    #[tokio::test]
+    async fn test_reqwest_transport() {
+        use crate::Status;
+
+        let (address, server) = serve(&[("200 OK", "", LEADER)]).await;
+        let config =
+            Config::builder().address(address).transport(reqwest::Client::new()).build().unwrap();
+        let client = Client::new(config);
+        assert_eq!(client.get_leader(None).await.unwrap(), "10.0.0.1:8300");
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "status")]
+    #[tokio::test]
+    async fn test_transport() {
+        use std::sync::Mutex;
+
+        use async_trait::async_trait;
+        use bytes::Bytes;
+
+        use crate::{ConsulError, ConsulResult, HttpTransport, Status};
+
+        #[derive(Debug, Default)]
+        struct Double(Mutex<Vec<String>>);
+
+        #[async_trait]
+        impl HttpTransport for &'static Double {
+            async fn execute(
+                &self,
+                request: http::Request<Bytes>,
+            ) -> ConsulResult<http::Response<Bytes>> {
+                let token = request.headers()["X-Consul-Token"].to_str().unwrap().to_owned();
+                let mut requests = self.0.lock().unwrap();
+                requests.push(format!("{} {} {}", request.method(), request.uri().path(), token));
+                let response = if requests.len() == 1 {
+                    http::Response::new(Bytes::from_static(br#""10.0.0.1:8300""#))
+                } else {
+                    let mut response =
+                        http::Response::new(Bytes::from_static(b"No cluster leader"));
+                    *response.status_mut() = http::StatusCode::INTERNAL_SERVER_ERROR;
+                    response
+                };
+                Ok(response)
+            }
+        }
+
+        let double: &'static Double = Box::leak(Box::default());
+        let config = Config::builder().token("secret").transport(double).build().unwrap();
+        let client = Client::new(config);
+        assert_eq!(client.get_leader(None).await.unwrap(), "10.0.0.1:8300");
+        let error = client.get_leader(None).await.unwrap_err();
+        assert!(matches!(error, ConsulError::RequestFailed(e) if e.message == "No cluster leader"));
+        assert_eq!(*double.0.lock().unwrap(), ["GET /v1/status/leader secret"; 2]);
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_token_file() {
+        let path =
+            std::env::temp_dir().join(format!("consul-request-token-{}", std::process::id()));
+        std::fs::write(&path, "from-file\n").unwrap();
+        let token = |config| {
+            let request = Client::new(config).build_request(
+                Method::GET,
+                "/v1/kv/a",
+                None,
+                None::<()>,
+                None::<QueryOptions>,
+            );
+            request.headers()["X-Consul-Token"].to_str().unwrap().to_owned()
+        };
+        assert_eq!(token(Config::builder().token_file(&path).build().unwrap()), "from-file");
+        let config = Config::builder().token("explicit").token_file(&path).build().unwrap();
+        assert_eq!(token(config), "explicit");
+        std::fs::remove_file(&path).unwrap();
+        assert!(Config::builder().token_file(&path).build().is_err());
+    }
+
+    #[test]
+    fn test_with_datacenter() {
+        let client = Client::new(Config::default());
+        let scoped = client.with_datacenter("dc2");
+        let query = |client: &Client, options| {
+            let request = client.build_request(Method::GET, "/v1/kv/a", None, None::<()>, options);
+            request.uri().query().unwrap_or_default().to_owned()
+        };
+        assert_eq!(query(&client, None), "");
+        assert_eq!(query(&scoped, None), "dc=dc2");
+        let options = QueryOptions { datacenter: Some(String::from("dc3")), ..Default::default() };
+        assert_eq!(query(&scoped, Some(options)), "dc=dc3");
+    }
+
+    #[test]
+    fn test_error_message() {
+        assert_eq!(error_message("  Unknown check ID \"web\"\n"), r#"Unknown check ID "web""#);
+        assert_eq!(error_message(r#"{"error": "bad"}"#), "bad");
+        assert_eq!(error_message(&"a".repeat(600)), format!("{}...", "a".repeat(512)));
+    }
+
+    #[test]
+    fn test_query_meta_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Consul-Index", HeaderValue::from_static("42"));
+        headers.insert("X-Consul-KnownLeader", HeaderValue::from_static("true"));
+        headers.insert("X-Consul-LastContact", HeaderValue::from_static("150"));
+        headers.insert("X-Cache", HeaderValue::from_static("HIT"));
+        headers.insert("Age", HeaderValue::from_static("12"));
+        headers.insert("X-Consul-Translate-Addresses", HeaderValue::from_static("true"));
+        headers.insert("X-Consul-ContentHash", HeaderValue::from_static("2a2ab2a1b7df9b3c"));
+        let meta = QueryMeta::from_headers(&headers);
+        assert_eq!(meta.last_index, Some(42));
+        assert!(meta.known_leader);
+        assert_eq!(meta.last_contact, Duration::from_millis(150));
+        assert!(meta.cache_hit);
+        assert_eq!(meta.cache_age, Some(Duration::from_secs(12)));
+        assert!(meta.address_translation_enabled);
+        assert_eq!(meta.last_content_hash.as_deref(), Some("2a2ab2a1b7df9b3c"));
     }
 }
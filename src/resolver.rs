@@ -0,0 +1,174 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rand::Rng;
+
+use crate::{CheckStatus, Near, QueryOptions, ServiceEntry};
+
+/// Enumeration of the strategies used by a [ResolvedService] to select one of
+/// its instances.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SelectionStrategy {
+    /// Instances are selected in turn.
+    #[default]
+    RoundRobin,
+    /// Instances are selected uniformly at random.
+    Random,
+    /// Instances are selected at random, proportionally to their
+    /// [ServiceWeights], as done by the Consul DNS interface. Instances with
+    /// warning checks use their warning weight.
+    ///
+    /// [ServiceWeights]: crate::ServiceWeights
+    WeightedByServiceWeights,
+    /// The instance with the lowest estimated round trip time from the agent
+    /// is selected, based on the [network coordinates] of the nodes.
+    ///
+    /// [network coordinates]: https://developer.hashicorp.com/consul/docs/architecture/coordinates
+    NearestRtt,
+}
+
+/// The options of [Health::resolve_service].
+///
+/// [Health::resolve_service]: crate::Health::resolve_service
+#[derive(Clone, Debug, Default)]
+pub struct ResolveOptions {
+    /// Only instances with all these tags are resolved.
+    pub tags: Vec<String>,
+    /// The strategy used to select an instance.
+    pub strategy: SelectionStrategy,
+    /// The options of the health query, such as its datacenter or filter.
+    pub query_options: Option<QueryOptions>,
+}
+
+impl ResolveOptions {
+    /// This method returns the options of the health query, sorted by round
+    /// trip time from the agent if the strategy requires it.
+    pub(crate) fn query_options(&self) -> QueryOptions {
+        let mut options = self.query_options.clone().unwrap_or_default();
+        if self.strategy == SelectionStrategy::NearestRtt {
+            options.near = Some(Near::Agent);
+        }
+        options
+    }
+}
+
+/// The healthy instances of a service, returned by [Health::resolve_service].
+///
+/// [Health::resolve_service]: crate::Health::resolve_service
+#[derive(Debug)]
+pub struct ResolvedService {
+    entries: Vec<ServiceEntry>,
+    strategy: SelectionStrategy,
+    next: AtomicUsize,
+}
+
+impl ResolvedService {
+    /// This method creates the resolved service from the given entries,
+    /// dropping the instances with critical checks or in maintenance mode.
+    pub(crate) fn new(mut entries: Vec<ServiceEntry>, strategy: SelectionStrategy) -> Self {
+        entries.retain(|entry| entry.aggregated_status().is_healthy());
+        ResolvedService { entries, strategy, next: AtomicUsize::new(0) }
+    }
+
+    /// This method returns the healthy instances of the service. With the
+    /// [SelectionStrategy::NearestRtt] strategy, they are sorted by round trip
+    /// time from the agent.
+    pub fn entries(&self) -> &[ServiceEntry] {
+        &self.entries
+    }
+
+    /// This method selects an instance with the strategy of the resolve
+    /// options, or returns [None] if the service has no healthy instances.
+    pub fn select(&self) -> Option<&ServiceEntry> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let index = match self.strategy {
+            SelectionStrategy::RoundRobin => {
+                self.next.fetch_add(1, Ordering::Relaxed) % self.entries.len()
+            }
+            SelectionStrategy::Random => rand::thread_rng().gen_range(0..self.entries.len()),
+            SelectionStrategy::WeightedByServiceWeights => self.weighted_index(),
+            SelectionStrategy::NearestRtt => 0,
+        };
+        self.entries.get(index)
+    }
+
+    /// This method returns the index of an instance selected proportionally
+    /// to its weight, or uniformly if every weight is zero.
+    fn weighted_index(&self) -> usize {
+        let total: u64 = self.entries.iter().map(|entry| weight(entry) as u64).sum();
+        if total == 0 {
+            return rand::thread_rng().gen_range(0..self.entries.len());
+        }
+        let mut point = rand::thread_rng().gen_range(0..total);
+        for (index, entry) in self.entries.iter().enumerate() {
+            let weight = weight(entry) as u64;
+            if point < weight {
+                return index;
+            }
+            point -= weight;
+        }
+        self.entries.len() - 1
+    }
+}
+
+/// This function returns the weight of the given instance, depending on
+/// whether it is warning.
+fn weight(entry: &ServiceEntry) -> u32 {
+    let weights = &entry.service.weights;
+    if entry.aggregated_status() == CheckStatus::Warning {
+        weights.warning
+    } else {
+        weights.passing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ResolveOptions, ResolvedService, SelectionStrategy};
+    use crate::{CheckStatus, HealthCheck, Near, ServiceEntry};
+
+    fn entry(id: &str, status: CheckStatus, passing: u32) -> ServiceEntry {
+        let mut entry = ServiceEntry::default();
+        entry.service.id = id.to_owned();
+        entry.service.weights.passing = passing;
+        entry.service.weights.warning = 1;
+        entry.checks.push(HealthCheck { status, ..Default::default() });
+        entry
+    }
+
+    fn entries() -> Vec<ServiceEntry> {
+        vec![
+            entry("a", CheckStatus::Passing, 1),
+            entry("b", CheckStatus::Critical, 1),
+            entry("c", CheckStatus::Passing, 0),
+        ]
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let resolved = ResolvedService::new(entries(), SelectionStrategy::RoundRobin);
+        assert_eq!(resolved.entries().len(), 2);
+        let ids: Vec<_> = (0..3).map(|_| resolved.select().unwrap().service.id.as_str()).collect();
+        assert_eq!(ids, ["a", "c", "a"]);
+    }
+
+    #[test]
+    fn test_weighted() {
+        let resolved = ResolvedService::new(entries(), SelectionStrategy::WeightedByServiceWeights);
+        for _ in 0..20 {
+            assert_eq!(resolved.select().unwrap().service.id, "a");
+        }
+        let empty = ResolvedService::new(Vec::new(), SelectionStrategy::WeightedByServiceWeights);
+        assert!(empty.select().is_none());
+    }
+
+    #[test]
+    fn test_nearest_rtt() {
+        let options =
+            ResolveOptions { strategy: SelectionStrategy::NearestRtt, ..Default::default() };
+        assert_eq!(options.query_options().near, Some(Near::Agent));
+        let resolved = ResolvedService::new(entries(), SelectionStrategy::NearestRtt);
+        assert_eq!(resolved.select().unwrap().service.id, "a");
+    }
+}
@@ -0,0 +1,151 @@
+use std::{cmp, time::Duration};
+
+use http::{
+    header::{HeaderMap, RETRY_AFTER},
+    Method, StatusCode,
+};
+use rand::Rng;
+
+use crate::{transport::ClientResponse, ConsulResult};
+
+/// The policy used to retry requests that failed due to transient errors, set
+/// with [ConfigBuilder::retry_policy].
+///
+/// Only requests that are safe to repeat are retried. Consul uses `PUT` for
+/// operations that are not idempotent, such as creating sessions or firing
+/// events, so writes are only retried if `retry_writes` is set.
+///
+/// [ConfigBuilder::retry_policy]: crate::ConfigBuilder::retry_policy
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// The delay before the first retry, which doubles with every attempt.
+    pub base_delay: Duration,
+    /// The maximum delay between attempts, before jitter is added.
+    pub max_delay: Duration,
+    /// Retries requests that failed with a 5xx status code.
+    pub retry_server_errors: bool,
+    /// Retries requests that failed to connect to the agent.
+    pub retry_connection_errors: bool,
+    /// Retries requests that were rate limited with a 429 status code. The
+    /// delay requested by the agent's `Retry-After` header is honored, up to
+    /// `max_delay`.
+    pub retry_too_many_requests: bool,
+    /// Retries writes as well as reads.
+    pub retry_writes: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+            retry_server_errors: true,
+            retry_connection_errors: true,
+            retry_too_many_requests: true,
+            retry_writes: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// This method returns whether requests with the given method may be
+    /// retried.
+    pub(crate) fn allows(&self, method: &Method) -> bool {
+        self.max_attempts > 1
+            && (self.retry_writes || matches!(*method, Method::GET | Method::HEAD))
+    }
+
+    /// This method returns the delay before retrying the given result of an
+    /// attempt, or [None] if it should not be retried.
+    pub(crate) fn retry_delay(
+        &self,
+        attempts: u32,
+        result: &ConsulResult<ClientResponse>,
+    ) -> Option<Duration> {
+        let retry = match result {
+            Ok(response) => self.should_retry_status(response.status()),
+            Err(e) => self.retry_connection_errors && e.is_connect(),
+        };
+        if !retry {
+            return None;
+        }
+        let delay = self.delay(attempts);
+        match result.as_ref().ok().and_then(|response| retry_after(response.headers())) {
+            Some(retry_after) => Some(cmp::max(delay, cmp::min(retry_after, self.max_delay))),
+            None => Some(delay),
+        }
+    }
+
+    /// This method returns whether a response with the given status code
+    /// should be retried.
+    fn should_retry_status(&self, status: StatusCode) -> bool {
+        (self.retry_server_errors && status.is_server_error())
+            || (self.retry_too_many_requests && status == StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    /// This method returns the delay before retrying after the given number of
+    /// failed attempts.
+    fn delay(&self, attempts: u32) -> Duration {
+        let delay = self.base_delay.saturating_mul(2u32.saturating_pow(attempts - 1));
+        let delay = cmp::min(delay, self.max_delay);
+        // add up to 50% jitter to avoid many clients retrying in lockstep
+        let jitter = rand::thread_rng().gen_range(0..=delay.as_millis() as u64 / 2);
+        delay + Duration::from_millis(jitter)
+    }
+}
+
+/// This function parses the delay requested by the `Retry-After` header of a
+/// rate limited response. Consul always sends the delay in seconds, so HTTP
+/// dates are not supported.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers.get(RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::{
+        header::{HeaderMap, HeaderValue, RETRY_AFTER},
+        Method, StatusCode,
+    };
+
+    use super::{retry_after, RetryPolicy};
+
+    #[test]
+    fn test_retry_policy() {
+        let policy = RetryPolicy::default();
+        assert!(policy.allows(&Method::GET));
+        assert!(!policy.allows(&Method::PUT));
+        assert!(RetryPolicy { retry_writes: true, ..policy.clone() }.allows(&Method::PUT));
+        assert!(!RetryPolicy { max_attempts: 1, ..policy.clone() }.allows(&Method::GET));
+
+        assert!(policy.should_retry_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(policy.should_retry_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!policy.should_retry_status(StatusCode::NOT_FOUND));
+        let policy = RetryPolicy { retry_server_errors: false, ..policy };
+        assert!(!policy.should_retry_status(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy::default();
+        assert!(policy.delay(1) >= Duration::from_millis(100));
+        assert!(policy.delay(1) <= Duration::from_millis(150));
+        assert!(policy.delay(100) <= Duration::from_millis(7500));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(3)));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(retry_after(&headers), None);
+    }
+}
@@ -0,0 +1,407 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    time::Duration,
+};
+
+use tokio::{runtime::Handle, sync::watch as channel, task::JoinHandle};
+
+use crate::{
+    watch::{self, WatchStream},
+    Client, ConsulError, ConsulResult, KVPair, QueryMeta, QueryOptions, Session, SessionEntry, KV,
+};
+
+/// The flags set on the keys of a semaphore, which are the same as those used
+/// by the Go client so that both can share a semaphore.
+const SEMAPHORE_FLAGS: u64 = 0xe0f69a2baa414de0;
+/// The key holding the state of a semaphore, relative to its prefix.
+const LOCK_KEY: &str = ".lock";
+/// The name of the sessions created by a semaphore.
+const SESSION_NAME: &str = "Consul API Semaphore";
+/// The TTL of the sessions created by a semaphore.
+const SESSION_TTL: Duration = Duration::from_secs(15);
+
+/// The state of a semaphore, stored as JSON in its lock key.
+#[derive(Default, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+struct SemaphoreLock {
+    limit: usize,
+    holders: HashMap<String, bool>,
+}
+
+/// The keys stored under the prefix of a semaphore.
+#[derive(Default, Debug)]
+struct SemaphoreState {
+    /// The decoded lock key, if it exists.
+    lock: Option<SemaphoreLock>,
+    /// The modify index of the lock key, or 0 if it does not exist.
+    modify_index: u64,
+    /// The sessions whose contender key is still locked.
+    contenders: HashSet<String>,
+}
+
+impl SemaphoreState {
+    /// This method decodes the state of the semaphore with the given prefix
+    /// from the keys stored under it.
+    fn decode(prefix: &str, pairs: Vec<KVPair>) -> ConsulResult<Self> {
+        let mut state = SemaphoreState::default();
+        for pair in pairs {
+            let name = match pair.key.strip_prefix(prefix).and_then(|key| key.strip_prefix('/')) {
+                Some(name) if pair.flags == Some(SEMAPHORE_FLAGS) => name,
+                _ => continue,
+            };
+            if name == LOCK_KEY {
+                state.lock = Some(serde_json::from_slice(&pair.value)?);
+                state.modify_index = pair.modifyindex.unwrap_or_default();
+            } else if pair.session.filter(|session| !session.is_empty()).is_some() {
+                state.contenders.insert(name.to_owned());
+            }
+        }
+        // holders whose session was invalidated no longer hold a slot
+        if let Some(lock) = &mut state.lock {
+            lock.holders.retain(|session, _| state.contenders.contains(session));
+        }
+        Ok(state)
+    }
+
+    /// This method returns the sessions currently holding a slot, sorted by
+    /// ID.
+    fn holders(&self) -> Vec<String> {
+        let mut holders: Vec<_> =
+            self.lock.iter().flat_map(|lock| lock.holders.keys().cloned()).collect();
+        holders.sort();
+        holders
+    }
+}
+
+/// A semaphore allowing up to a fixed number of holders across the cluster,
+/// compatible with the semaphore of the Go client.
+///
+/// Each contender creates a session and locks a contender key under the
+/// prefix of the semaphore with it. The holders are tracked in a lock key
+/// under the same prefix, updated using check-and-set operations. When the
+/// session of a holder is invalidated, its contender key is unlocked and its
+/// slot is freed.
+///
+/// ```no_run
+/// # async fn run() -> consul_oxide::ConsulResult<()> {
+/// use consul_oxide::{Client, Config, Semaphore};
+///
+/// let client = Client::new(Config::default());
+/// let semaphore = Semaphore::new(&client, "service/jobs/semaphore", 3);
+/// let guard = semaphore.acquire().await?;
+/// // at most 3 holders run this at the same time
+/// guard.release().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Semaphore {
+    client: Client,
+    prefix: String,
+    limit: usize,
+}
+
+impl Semaphore {
+    /// This method creates a semaphore with the given prefix, allowing up to
+    /// `limit` holders. All contenders must use the same limit.
+    pub fn new(client: &Client, prefix: &str, limit: usize) -> Self {
+        Semaphore { client: client.clone(), prefix: prefix.trim_end_matches('/').to_owned(), limit }
+    }
+
+    /// This method waits for a slot of the semaphore to be free, then acquires
+    /// it. The slot is held until the returned guard is released or dropped.
+    ///
+    /// If a slot cannot be acquired immediately, this method blocks on the
+    /// prefix of the semaphore until one is freed. Dropping the returned
+    /// future stops waiting.
+    #[tracing::instrument]
+    pub async fn acquire(&self) -> ConsulResult<SemaphoreGuard> {
+        let session = SessionEntry {
+            name: Some(SESSION_NAME.to_owned()),
+            ttl: Some(format!("{}s", SESSION_TTL.as_secs())),
+            ..Default::default()
+        };
+        let (session, _) = self.client.create_session(session, None).await?;
+        let session =
+            session.id.ok_or_else(|| ConsulError::MissingParameter("session_id".to_owned()))?;
+        let (sender, lost) = channel::channel(false);
+        let renew =
+            tokio::spawn(renew_session(self.client.clone(), session.clone(), SESSION_TTL, sender));
+        // the guard destroys the session if acquisition fails or is cancelled
+        let guard =
+            SemaphoreGuard { semaphore: self.clone(), session, lost, renew, released: false };
+
+        let mut params = HashMap::new();
+        params.insert(String::from("flags"), SEMAPHORE_FLAGS.to_string());
+        params.insert(String::from("acquire"), guard.session.clone());
+        let path = format!("/v1/kv/{}", self.contender_key(&guard.session));
+        let (acquired, _) =
+            self.client.put_raw::<_, _, bool>(&path, Vec::new(), Some(params), None).await?;
+        if !acquired {
+            return Err(ConsulError::SemaphoreContenderConflict);
+        }
+
+        let mut wait_index = None;
+        loop {
+            let options = QueryOptions { wait_index, ..Default::default() };
+            let (state, meta) = self.state(Some(options)).await?;
+            let mut lock = match state.lock {
+                Some(lock) if lock.limit != self.limit => {
+                    return Err(ConsulError::SemaphoreLimitConflict(lock.limit))
+                }
+                Some(lock) => lock,
+                None => SemaphoreLock { limit: self.limit, ..Default::default() },
+            };
+            if lock.holders.len() >= self.limit {
+                wait_index = meta.last_index;
+                continue;
+            }
+            lock.holders.insert(guard.session.clone(), true);
+            if self.put_lock(&lock, state.modify_index).await? {
+                return Ok(guard);
+            }
+            // the lock key changed since it was read, so read it again
+            wait_index = None;
+        }
+    }
+
+    /// This method returns a [WatchStream] that yields the sessions holding a
+    /// slot of the semaphore, sorted by ID, every time they change.
+    pub fn watch_holders(&self) -> WatchStream<ConsulResult<Vec<String>>> {
+        let semaphore = self.clone();
+        watch::watch(None, move |options| {
+            let semaphore = semaphore.clone();
+            async move {
+                let (state, meta) = semaphore.state(Some(options)).await?;
+                Ok((state.holders(), meta))
+            }
+        })
+    }
+
+    /// This method returns the contender key of the given session.
+    fn contender_key(&self, session: &str) -> String {
+        format!("{}/{}", self.prefix, session)
+    }
+
+    /// This method reads the keys stored under the prefix of the semaphore.
+    async fn state(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(SemaphoreState, QueryMeta)> {
+        let prefix = format!("{}/", self.prefix);
+        let (pairs, meta) = self.client.list_entries(&prefix, options).await?;
+        Ok((SemaphoreState::decode(&self.prefix, pairs)?, meta))
+    }
+
+    /// This method writes the lock key, if its modify index still matches the
+    /// given index.
+    async fn put_lock(&self, lock: &SemaphoreLock, index: u64) -> ConsulResult<bool> {
+        let mut params = HashMap::new();
+        params.insert(String::from("flags"), SEMAPHORE_FLAGS.to_string());
+        params.insert(String::from("cas"), index.to_string());
+        let path = format!("/v1/kv/{}/{}", self.prefix, LOCK_KEY);
+        let lock = serde_json::to_vec(lock).map_err(ConsulError::EncodeError)?;
+        self.client.put_raw(&path, lock, Some(params), None).await.map(|(r, _)| r)
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Semaphore")
+            .field("prefix", &self.prefix)
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+/// A guard holding a slot of a [Semaphore], freeing it when dropped.
+///
+/// The slot is lost if the session holding it is invalidated, such as after
+/// failing to renew it for longer than its TTL, which [SemaphoreGuard::lost]
+/// waits for.
+///
+/// Since releasing the slot is asynchronous, dropping the guard destroys its
+/// session in the background, which frees the slot. To wait for the slot to
+/// be freed, use [SemaphoreGuard::release] instead.
+pub struct SemaphoreGuard {
+    semaphore: Semaphore,
+    session: String,
+    lost: channel::Receiver<bool>,
+    renew: JoinHandle<()>,
+    released: bool,
+}
+
+impl SemaphoreGuard {
+    /// This method returns the ID of the session holding the slot.
+    pub fn session_id(&self) -> &str {
+        &self.session
+    }
+
+    /// This method returns whether the session holding the slot was found to
+    /// be invalidated while renewing it, in which case the slot is lost.
+    pub fn is_lost(&self) -> bool {
+        *self.lost.borrow()
+    }
+
+    /// This method waits for the session holding the slot to be found
+    /// invalidated while renewing it. Other contenders may hold the slot by
+    /// then.
+    pub async fn lost(&self) {
+        let mut lost = self.lost.clone();
+        // the sender is only dropped once the session is invalidated
+        let _ = lost.wait_for(|lost| *lost).await;
+    }
+
+    /// This method frees the slot, removing the session from the holders of
+    /// the semaphore and destroying it.
+    #[tracing::instrument]
+    pub async fn release(mut self) -> ConsulResult<()> {
+        self.free().await
+    }
+
+    /// This method frees the slot, so that dropping the guard does nothing.
+    /// If freeing the slot fails, dropping the guard still destroys the
+    /// session.
+    async fn free(&mut self) -> ConsulResult<()> {
+        self.renew.abort();
+        loop {
+            let (state, _) = self.semaphore.state(None).await?;
+            let mut lock = match state.lock {
+                Some(lock) => lock,
+                None => break,
+            };
+            if lock.holders.remove(&self.session).is_none() {
+                break;
+            }
+            if self.semaphore.put_lock(&lock, state.modify_index).await? {
+                break;
+            }
+        }
+        let client = &self.semaphore.client;
+        client.delete_entry(&self.semaphore.contender_key(&self.session), None, None).await?;
+        client.destroy_session(&self.session, None).await?;
+        self.released = true;
+        Ok(())
+    }
+}
+
+impl Drop for SemaphoreGuard {
+    fn drop(&mut self) {
+        self.renew.abort();
+        if self.released {
+            return;
+        }
+        // destroying the session unlocks the contender key, which frees the
+        // slot, but can only be spawned onto the current runtime, if any
+        match Handle::try_current() {
+            Ok(handle) => {
+                let client = self.semaphore.client.clone();
+                let session = self.session.clone();
+                handle.spawn(async move {
+                    if let Err(e) = client.destroy_session(&session, None).await {
+                        tracing::warn!(session, error = %e, "failed to destroy semaphore session");
+                    }
+                });
+            }
+            Err(_) => tracing::warn!(session = %self.session, "dropped outside of a runtime"),
+        }
+    }
+}
+
+impl fmt::Debug for SemaphoreGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SemaphoreGuard")
+            .field("prefix", &self.semaphore.prefix)
+            .field("session", &self.session)
+            .finish()
+    }
+}
+
+/// This function renews the given session at half its TTL, until the session
+/// is invalidated or the task is aborted.
+async fn renew_session(
+    client: Client,
+    session: String,
+    ttl: Duration,
+    sender: channel::Sender<bool>,
+) {
+    loop {
+        tokio::time::sleep(ttl / 2).await;
+        match client.renew_session(&session, None).await {
+            Ok(_) => {}
+            Err(ConsulError::SessionExpired(_)) => {
+                sender.send_replace(true);
+                return;
+            }
+            Err(e) => tracing::warn!(session, error = %e, "failed to renew semaphore session"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use tokio::sync::watch;
+
+    use super::{renew_session, SemaphoreState, SEMAPHORE_FLAGS};
+    use crate::{request::tests::recording_client, Client, Config, KVPair, Semaphore};
+
+    const SESSION: &str = "adf4238a-882b-9ddc-4a9d-5b6758e4159e";
+
+    #[test]
+    fn test_decode_state() {
+        let pair = |key: &str, value: &str, session: Option<&str>| KVPair {
+            key: key.to_owned(),
+            value: value.as_bytes().to_vec(),
+            flags: Some(SEMAPHORE_FLAGS),
+            session: session.map(str::to_owned),
+            modifyindex: Some(7),
+            ..Default::default()
+        };
+        let pairs = vec![
+            pair("jobs/.lock", r#"{"Limit": 2, "Holders": {"a": true, "b": true}}"#, None),
+            pair("jobs/a", "", Some("a")),
+            // the session of b was invalidated, so it no longer holds a slot
+            pair("jobs/b", "", None),
+            pair("jobs/c", "", Some("c")),
+            KVPair { key: String::from("jobs/other"), ..Default::default() },
+        ];
+        let state = SemaphoreState::decode("jobs", pairs).unwrap();
+        assert_eq!(state.modify_index, 7);
+        assert_eq!(state.lock.as_ref().unwrap().limit, 2);
+        assert_eq!(state.holders(), ["a"]);
+        assert_eq!(state.contenders.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_renew_session() {
+        let (client, requests) = recording_client(&[
+            (200, &[], r#"[{"ID": "adf4238a-882b-9ddc-4a9d-5b6758e4159e"}]"#),
+            (404, &[], "Session id 'adf4238a-882b-9ddc-4a9d-5b6758e4159e' not found"),
+        ]);
+        let (sender, mut lost) = watch::channel(false);
+        let ttl = Duration::from_millis(20);
+        renew_session(client, String::from(SESSION), ttl, sender).await;
+        assert!(*lost.borrow_and_update());
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_semaphore() {
+        let client = Client::new(Config::default());
+        let semaphore = Semaphore::new(&client, "test/semaphore", 2);
+        let first = semaphore.acquire().await.unwrap();
+        let second = semaphore.acquire().await.unwrap();
+        let mut holders = semaphore.watch_holders();
+        let mut expected = vec![first.session_id().to_owned(), second.session_id().to_owned()];
+        expected.sort();
+        assert_eq!(holders.next().await.unwrap().unwrap(), expected);
+
+        first.release().await.unwrap();
+        assert_eq!(holders.next().await.unwrap().unwrap(), [second.session_id()]);
+        second.release().await.unwrap();
+    }
+}
@@ -1,6 +1,10 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
 
-use crate::{sealed::Sealed, Client, ConsulResult, QueryOptions};
+use crate::{
+    sealed::Sealed, Client, ConsulResult, QueryMeta, QueryOptions, WriteMeta, WriteOptions,
+};
 
 /// A wrapper struct for session IDs.
 #[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
@@ -10,21 +14,96 @@ pub struct SessionID {
     pub id: String,
 }
 
+/// The behavior of a session when it is invalidated, determining what happens
+/// to the locks it holds.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionBehavior {
+    /// The locks held by the session are released.
+    #[default]
+    Release,
+    /// The keys locked by the session are deleted.
+    Delete,
+}
+
+/// A service check a session is associated with.
+#[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct ServiceCheck {
+    /// The ID of the check.
+    #[serde(rename = "ID")]
+    pub id: String,
+    /// The namespace of the service the check belongs to.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub namespace: String,
+}
+
 /// A session defined on the agent.
 #[derive(Clone, Default, Eq, PartialEq, Serialize, Deserialize, Debug)]
 #[serde(default, rename_all = "PascalCase")]
 pub struct SessionEntry {
-    pub createindex: Option<u64>,
-    #[serde(rename = "ID")]
+    #[serde(skip_serializing)]
+    pub create_index: Option<u64>,
+    #[serde(skip_serializing)]
+    pub modify_index: Option<u64>,
+    /// The ID of the session.
+    #[serde(rename = "ID", skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
+    /// The human readable name of the session.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// The node the session is associated with. Defaults to the agent's node.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub node: Option<String>,
-    pub lockdelay: Option<u64>, //delay: Change this to a Durations
-    pub behavior: Option<String>,
+    /// The time during which locks released by the session cannot be
+    /// acquired again, from 0 to 60 seconds. Defaults to 15 seconds.
+    #[serde(with = "lock_delay", skip_serializing_if = "Option::is_none")]
+    pub lock_delay: Option<Duration>,
+    /// The behavior of the session when it is invalidated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub behavior: Option<SessionBehavior>,
+    /// The checks the session is associated with, which invalidate the
+    /// session when they become critical. Deprecated in favor of
+    /// `node_checks` and `service_checks`.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub checks: Option<Vec<String>>,
+    /// The node checks the session is associated with. Defaults to the
+    /// `serfHealth` check of the node.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_checks: Option<Vec<String>>,
+    /// The service checks the session is associated with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_checks: Option<Vec<ServiceCheck>>,
+    /// The time to live of the session, such as "30s", after which it is
+    /// invalidated unless renewed. Must be between 10 seconds and 24 hours.
+    #[serde(rename = "TTL", skip_serializing_if = "Option::is_none")]
     pub ttl: Option<String>,
 }
 
+/// This module encodes the lock delay of a session as nanoseconds, which is
+/// the format Consul returns it in and accepts on creation.
+mod lock_delay {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        lock_delay: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match lock_delay {
+            Some(lock_delay) => serializer.serialize_u64(lock_delay.as_nanos() as u64),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_nanos))
+    }
+}
+
 /// This trait provides methods for interacting with the agent's session store.
 #[async_trait]
 pub trait Session: Sealed {
@@ -38,8 +117,8 @@ pub trait Session: Sealed {
     async fn create_session(
         &self,
         session: SessionEntry,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<SessionEntry>;
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(SessionEntry, WriteMeta)>;
 
     /// This method destroys the session with the given name. If the session
     /// UUID is malformed, an error is returned. If the session UUID does not
@@ -50,7 +129,11 @@ pub trait Session: Sealed {
     /// documentation].
     ///
     /// [API documentation]: https://www.consul.io/api-docs/session#delete-session
-    async fn destroy_session(&self, id: &str, options: Option<QueryOptions>) -> ConsulResult<bool>;
+    async fn destroy_session(
+        &self,
+        id: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)>;
 
     /// This method returns the requested session information.
     ///
@@ -62,7 +145,7 @@ pub trait Session: Sealed {
         &self,
         id: &str,
         options: Option<QueryOptions>,
-    ) -> ConsulResult<Vec<SessionEntry>>;
+    ) -> ConsulResult<(Vec<SessionEntry>, QueryMeta)>;
 
     /// This endpoint returns the list of active sessions.
     ///
@@ -70,10 +153,12 @@ pub trait Session: Sealed {
     /// documentation].
     ///
     /// [API documentation]: https://www.consul.io/api-docs/session#list-sessions
-    async fn list_sessions(&self, options: Option<QueryOptions>)
-        -> ConsulResult<Vec<SessionEntry>>;
+    async fn list_sessions(
+        &self,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(Vec<SessionEntry>, QueryMeta)>;
 
-    /// This method method returns the active sessions for a given node.
+    /// This method returns the active sessions for a given node.
     ///
     /// For more information, consult the relevant endpoint's [API
     /// documentation].
@@ -83,7 +168,7 @@ pub trait Session: Sealed {
         &self,
         node: &str,
         options: Option<QueryOptions>,
-    ) -> ConsulResult<Vec<SessionEntry>>;
+    ) -> ConsulResult<(Vec<SessionEntry>, QueryMeta)>;
 
     /// This method renews the given session. This should be used with sessions
     /// that have a TTL, and it extends the expiration by the TTL.
@@ -95,8 +180,8 @@ pub trait Session: Sealed {
     async fn renew_session(
         &self,
         id: &str,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<Vec<SessionEntry>>;
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Vec<SessionEntry>, WriteMeta)>;
 }
 
 #[async_trait]
@@ -105,13 +190,17 @@ impl Session for Client {
     async fn create_session(
         &self,
         session: SessionEntry,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<SessionEntry> {
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(SessionEntry, WriteMeta)> {
         self.put("/v1/session/create", session, None, options).await
     }
 
     #[tracing::instrument]
-    async fn destroy_session(&self, id: &str, options: Option<QueryOptions>) -> ConsulResult<bool> {
+    async fn destroy_session(
+        &self,
+        id: &str,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(bool, WriteMeta)> {
         let path = format!("/v1/session/destroy/{}", id);
         self.put(&path, None as Option<&()>, None, options).await
     }
@@ -121,17 +210,21 @@ impl Session for Client {
         &self,
         id: &str,
         options: Option<QueryOptions>,
-    ) -> ConsulResult<Vec<SessionEntry>> {
+    ) -> ConsulResult<(Vec<SessionEntry>, QueryMeta)> {
         let path = format!("/v1/session/info/{}", id);
-        self.get(&path, options).await
+        self.get_with_meta(&path, None, options)
+            .await
+            .map(|(r, meta): (Option<Vec<SessionEntry>>, _)| (r.unwrap_or_default(), meta))
     }
 
     #[tracing::instrument]
     async fn list_sessions(
         &self,
         options: Option<QueryOptions>,
-    ) -> ConsulResult<Vec<SessionEntry>> {
-        self.get("/v1/session/list", options).await
+    ) -> ConsulResult<(Vec<SessionEntry>, QueryMeta)> {
+        self.get_with_meta("/v1/session/list", None, options)
+            .await
+            .map(|(r, meta): (Option<Vec<SessionEntry>>, _)| (r.unwrap_or_default(), meta))
     }
 
     #[tracing::instrument]
@@ -139,18 +232,51 @@ impl Session for Client {
         &self,
         node: &str,
         options: Option<QueryOptions>,
-    ) -> ConsulResult<Vec<SessionEntry>> {
+    ) -> ConsulResult<(Vec<SessionEntry>, QueryMeta)> {
         let path = format!("/v1/session/node/{}", node);
-        self.get(&path, options).await
+        self.get_with_meta(&path, None, options)
+            .await
+            .map(|(r, meta): (Option<Vec<SessionEntry>>, _)| (r.unwrap_or_default(), meta))
     }
 
     #[tracing::instrument]
     async fn renew_session(
         &self,
         id: &str,
-        options: Option<QueryOptions>,
-    ) -> ConsulResult<Vec<SessionEntry>> {
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Vec<SessionEntry>, WriteMeta)> {
         let path = format!("/v1/session/renew/{}", id);
         self.put(&path, None as Option<&()>, None, options).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::{SessionBehavior, SessionEntry};
+
+    #[test]
+    fn test_encode_session() {
+        let session = SessionEntry {
+            name: Some(String::from("lock")),
+            lock_delay: Some(Duration::from_secs(5)),
+            behavior: Some(SessionBehavior::Delete),
+            node_checks: Some(vec![String::from("serfHealth")]),
+            ttl: Some(String::from("30s")),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(session).unwrap(),
+            json!({
+                "Name": "lock",
+                "LockDelay": 5_000_000_000u64,
+                "Behavior": "delete",
+                "NodeChecks": ["serfHealth"],
+                "TTL": "30s"
+            })
+        );
+    }
+}
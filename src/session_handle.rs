@@ -0,0 +1,217 @@
+use std::{fmt, time::Duration};
+
+use http::StatusCode;
+use tokio::{runtime::Handle, sync::watch, task::JoinHandle};
+
+use crate::{
+    Client, ConsulError, ConsulResult, ErrorResponse, Session, SessionBehavior, SessionEntry,
+};
+
+/// The range of TTLs accepted by Consul for sessions.
+const TTL_RANGE: std::ops::RangeInclusive<Duration> =
+    Duration::from_secs(10)..=Duration::from_secs(24 * 60 * 60);
+
+/// A session renewed in the background for as long as its handle is alive.
+///
+/// Sessions created by a handle delete the keys they hold when they are
+/// invalidated, unless another behavior is requested, so keys written with
+/// [KV::put_ephemeral] vanish once the handle is dropped or destroyed, or once
+/// the process dies and the TTL of the session runs out.
+///
+/// Since destroying the session is asynchronous, dropping the handle destroys
+/// it in the background. To wait for the session to be destroyed, use
+/// [SessionHandle::destroy] instead.
+///
+/// ```no_run
+/// # async fn run() -> consul_oxide::ConsulResult<()> {
+/// use std::time::Duration;
+///
+/// use consul_oxide::{Client, Config, SessionEntry, SessionHandle, KV};
+///
+/// let client = Client::new(Config::default());
+/// let session = SessionHandle::create(&client, SessionEntry::default(), Duration::from_secs(15))
+///     .await?;
+/// client.put_ephemeral("workers/worker-1", b"10.0.0.1", session.id(), None).await?;
+/// // the key is deleted along with the session
+/// session.destroy().await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [KV::put_ephemeral]: crate::KV::put_ephemeral
+pub struct SessionHandle {
+    client: Client,
+    id: String,
+    invalidated: watch::Receiver<bool>,
+    renew: JoinHandle<()>,
+    destroyed: bool,
+}
+
+impl SessionHandle {
+    /// This method creates a session with the given TTL, and starts renewing
+    /// it at half its TTL. The TTL must be between 10 seconds and 24 hours,
+    /// otherwise [ConsulError::InvalidRequest] is returned without creating
+    /// the session.
+    ///
+    /// The TTL of the given entry is replaced, and its behavior defaults to
+    /// [SessionBehavior::Delete].
+    #[tracing::instrument(skip(client))]
+    pub async fn create(
+        client: &Client,
+        mut session: SessionEntry,
+        ttl: Duration,
+    ) -> ConsulResult<Self> {
+        if !TTL_RANGE.contains(&ttl) {
+            return Err(ConsulError::InvalidRequest(ErrorResponse {
+                status: StatusCode::BAD_REQUEST,
+                url: format!("{}/v1/session/create", client.config.address),
+                message: format!("session TTL must be between 10s and 24h, got {:?}", ttl),
+            }));
+        }
+        // durations are parsed by Consul like those of Go, which have no
+        // fractional seconds
+        session.ttl = Some(match ttl.subsec_nanos() {
+            0 => format!("{}s", ttl.as_secs()),
+            _ => format!("{}ms", ttl.as_millis()),
+        });
+        session.behavior.get_or_insert(SessionBehavior::Delete);
+        let (session, _) = client.create_session(session, None).await?;
+        let id =
+            session.id.ok_or_else(|| ConsulError::MissingParameter("session_id".to_owned()))?;
+        let (sender, invalidated) = watch::channel(false);
+        let renew = tokio::spawn(renew_session(client.clone(), id.clone(), ttl, sender));
+        Ok(SessionHandle { client: client.clone(), id, invalidated, renew, destroyed: false })
+    }
+
+    /// This method returns the ID of the session.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// This method returns whether the session was found to be invalidated
+    /// while renewing it, such as after its TTL ran out or one of its checks
+    /// became critical.
+    pub fn is_invalidated(&self) -> bool {
+        *self.invalidated.borrow()
+    }
+
+    /// This method waits for the session to be found invalidated while
+    /// renewing it. The locks held by the session are lost by then.
+    pub async fn invalidated(&self) {
+        let mut invalidated = self.invalidated.clone();
+        // the sender is only dropped once the session is invalidated
+        let _ = invalidated.wait_for(|invalidated| *invalidated).await;
+    }
+
+    /// This method stops renewing the session, and destroys it.
+    #[tracing::instrument]
+    pub async fn destroy(mut self) -> ConsulResult<()> {
+        self.stop().await
+    }
+
+    /// This method stops renewing the session and destroys it, so that
+    /// dropping the handle does nothing. If destroying the session fails,
+    /// dropping the handle tries again.
+    async fn stop(&mut self) -> ConsulResult<()> {
+        self.renew.abort();
+        self.client.destroy_session(&self.id, None).await?;
+        self.destroyed = true;
+        Ok(())
+    }
+}
+
+impl Drop for SessionHandle {
+    fn drop(&mut self) {
+        self.renew.abort();
+        if self.destroyed {
+            return;
+        }
+        // the session can only be destroyed on the current runtime, if any,
+        // and otherwise expires after its TTL
+        match Handle::try_current() {
+            Ok(handle) => {
+                let client = self.client.clone();
+                let session = self.id.clone();
+                handle.spawn(async move {
+                    if let Err(e) = client.destroy_session(&session, None).await {
+                        tracing::warn!(session, error = %e, "failed to destroy session");
+                    }
+                });
+            }
+            Err(_) => tracing::warn!(session = %self.id, "dropped outside of a runtime"),
+        }
+    }
+}
+
+impl fmt::Debug for SessionHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionHandle").field("id", &self.id).finish_non_exhaustive()
+    }
+}
+
+/// This function renews the given session at half its TTL, until the session
+/// is invalidated or the task is aborted.
+async fn renew_session(
+    client: Client,
+    session: String,
+    ttl: Duration,
+    sender: watch::Sender<bool>,
+) {
+    loop {
+        tokio::time::sleep(ttl / 2).await;
+        match client.renew_session(&session, None).await {
+            Ok(_) => {}
+            Err(ConsulError::SessionExpired(_)) => {
+                sender.send_replace(true);
+                return;
+            }
+            Err(e) => tracing::warn!(session, error = %e, "failed to renew session"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::SessionHandle;
+    use crate::{request::tests::recording_client, ConsulError, SessionEntry, KV};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_session_handle() {
+        let (client, requests) = recording_client(&[
+            (200, &[], r#"{"ID": "adf4238a-882b-9ddc-4a9d-5b6758e4159e"}"#),
+            (200, &[], "true"),
+            (200, &[], r#"[{"ID": "adf4238a-882b-9ddc-4a9d-5b6758e4159e"}]"#),
+            (404, &[], "Session id 'adf4238a-882b-9ddc-4a9d-5b6758e4159e' not found"),
+        ]);
+        let ttl = Duration::from_secs(10);
+        let session = SessionHandle::create(&client, SessionEntry::default(), ttl).await.unwrap();
+        assert_eq!(session.id(), "adf4238a-882b-9ddc-4a9d-5b6758e4159e");
+        let (locked, _) =
+            client.put_ephemeral("workers/1", b"10.0.0.1", session.id(), None).await.unwrap();
+        assert!(locked);
+        assert!(!session.is_invalidated());
+        session.invalidated().await;
+        assert!(session.is_invalidated());
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "PUT /v1/session/create",
+                "PUT /v1/kv/workers/1?acquire=adf4238a-882b-9ddc-4a9d-5b6758e4159e",
+                "PUT /v1/session/renew/adf4238a-882b-9ddc-4a9d-5b6758e4159e",
+                "PUT /v1/session/renew/adf4238a-882b-9ddc-4a9d-5b6758e4159e",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_ttl() {
+        let (client, requests) = recording_client(&[]);
+        for ttl in [Duration::from_secs(9), Duration::from_secs(24 * 60 * 60 + 1)] {
+            let result = SessionHandle::create(&client, SessionEntry::default(), ttl).await;
+            assert!(matches!(result, Err(ConsulError::InvalidRequest(_))));
+        }
+        assert!(requests.lock().unwrap().is_empty());
+    }
+}
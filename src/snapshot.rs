@@ -0,0 +1,98 @@
+use std::{collections::HashMap, error::Error, pin::Pin};
+
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, TryStream, TryStreamExt};
+
+use crate::{
+    sealed::Sealed, Client, ConsulError, ConsulResult, QueryMeta, QueryOptions, WriteMeta,
+    WriteOptions,
+};
+
+/// A boxed [Stream] of the bytes of a snapshot, as returned by
+/// [Snapshot::save_snapshot].
+pub type SnapshotStream = Pin<Box<dyn Stream<Item = ConsulResult<Bytes>> + Send>>;
+
+/// This trait provides methods for interacting with the `/snapshot` endpoints.
+///
+/// Snapshots are gzipped tar archives of the state of the Consul servers,
+/// and can be very large. As such, saved snapshots are streamed rather than
+/// buffered in memory, unless they are received by a custom transport.
+#[async_trait]
+pub trait Snapshot: Sealed {
+    /// This method generates and returns an atomic, point-in-time snapshot of
+    /// the Consul server state, as a stream of bytes. The returned
+    /// [QueryMeta] contains the index of the snapshot.
+    ///
+    /// If `stale` is set, any server may generate the snapshot, rather than
+    /// only the leader.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/snapshot#generate-snapshot
+    async fn save_snapshot(
+        &self,
+        stale: bool,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(SnapshotStream, QueryMeta)>;
+
+    /// This method restores a snapshot of the Consul server state from the
+    /// given stream of bytes, which is read in full before being sent. This is
+    /// a destructive operation, and should be used with caution.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/snapshot#restore-snapshot
+    async fn restore_snapshot<S>(
+        &self,
+        snapshot: S,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>
+    where
+        S: TryStream + Send + Sync + 'static,
+        S::Error: Into<Box<dyn Error + Send + Sync>>,
+        Bytes: From<S::Ok>;
+}
+
+#[async_trait]
+impl Snapshot for Client {
+    #[tracing::instrument]
+    async fn save_snapshot(
+        &self,
+        stale: bool,
+        options: Option<QueryOptions>,
+    ) -> ConsulResult<(SnapshotStream, QueryMeta)> {
+        let mut params = HashMap::new();
+        if stale {
+            params.insert(String::from("stale"), String::from(""));
+        }
+        let (stream, meta) = self.get_stream("/v1/snapshot", Some(params), options).await?;
+        Ok((Box::pin(stream), meta))
+    }
+
+    #[tracing::instrument(skip(snapshot))]
+    async fn restore_snapshot<S>(
+        &self,
+        snapshot: S,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<WriteMeta>
+    where
+        S: TryStream + Send + Sync + 'static,
+        S::Error: Into<Box<dyn Error + Send + Sync>>,
+        Bytes: From<S::Ok>,
+    {
+        let snapshot = snapshot
+            .map_ok(Bytes::from)
+            .map_err(|e| ConsulError::TransportError(e.into()))
+            .try_fold(BytesMut::new(), |mut buffer, chunk| async move {
+                buffer.extend_from_slice(&chunk);
+                Ok(buffer)
+            })
+            .await?;
+        self.put_raw_with_empty("/v1/snapshot", snapshot.freeze(), None, options)
+            .await
+            .map(|(_, meta): (Option<()>, _)| meta)
+    }
+}
@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+
+use crate::{sealed::Sealed, Client, ConsulResult, QueryOptions};
+
+/// This trait provides methods for interacting with the `/status` endpoints.
+///
+/// These endpoints return information about the Consul cluster, and are
+/// generally very low level and not often useful for clients.
+#[async_trait]
+pub trait Status: Sealed {
+    /// This method returns the Raft leader for the datacenter in which the
+    /// agent is running, in the form `address:port`. An empty string is
+    /// returned if the cluster has no leader.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/status#get-raft-leader
+    async fn get_leader(&self, options: Option<QueryOptions>) -> ConsulResult<String>;
+
+    /// This method returns the Raft peers for the datacenter in which the
+    /// agent is running, in the form `address:port`. This list of peers is
+    /// strongly consistent and can be useful in determining when a given
+    /// server has successfully joined the cluster.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/status#list-raft-peers
+    async fn list_peers(&self, options: Option<QueryOptions>) -> ConsulResult<Vec<String>>;
+}
+
+#[async_trait]
+impl Status for Client {
+    #[tracing::instrument]
+    async fn get_leader(&self, options: Option<QueryOptions>) -> ConsulResult<String> {
+        self.get("/v1/status/leader", options).await
+    }
+
+    #[tracing::instrument]
+    async fn list_peers(&self, options: Option<QueryOptions>) -> ConsulResult<Vec<String>> {
+        self.get("/v1/status/peers", options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Client, Config, Status};
+
+    #[tokio::test]
+    async fn test_status() {
+        let client = Client::new(Config::default());
+        let leader = client.get_leader(None).await.unwrap();
+        let peers = client.list_peers(None).await.unwrap();
+        assert!(peers.contains(&leader));
+    }
+}
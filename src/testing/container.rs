@@ -0,0 +1,126 @@
+use std::{
+    io::{Error, ErrorKind},
+    process::Stdio,
+    time::{Duration, Instant},
+};
+
+use tokio::process::Command;
+
+use crate::{Client, Config, Status};
+
+/// The image of the Consul agent run by [ConsulContainer::start].
+pub const DEFAULT_CONSUL_IMAGE: &str = "hashicorp/consul:1.15";
+
+/// The time a container is given to elect itself leader before failing.
+const READY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A Consul dev agent running in a Docker container, for integration tests.
+///
+/// The container is started with the `docker` command line, which must be
+/// installed and able to reach the Docker daemon. Its HTTP port is published
+/// to a free local port, and the container is removed when this value is
+/// dropped.
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use consul_oxide::{testing::ConsulContainer, Catalog};
+///
+/// let consul = ConsulContainer::start().await?;
+/// let datacenters = consul.client().list_datacenters().await.unwrap();
+/// assert_eq!(datacenters, ["dc1"]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ConsulContainer {
+    container: RemoveOnDrop,
+    address: String,
+    client: Client,
+}
+
+/// The ID of a container, which is removed when this value is dropped.
+#[derive(Debug)]
+struct RemoveOnDrop(String);
+
+impl ConsulContainer {
+    /// This method starts a container of the [DEFAULT_CONSUL_IMAGE], returning
+    /// once its agent has elected itself leader.
+    pub async fn start() -> std::io::Result<Self> {
+        Self::start_image(DEFAULT_CONSUL_IMAGE).await
+    }
+
+    /// This method starts a container of the given Consul image, returning once
+    /// its agent has elected itself leader.
+    pub async fn start_image(image: &str) -> std::io::Result<Self> {
+        let id =
+            docker(&["run", "--detach", "--rm", "--publish", "127.0.0.1::8500", image]).await?;
+        // the container is removed on any error from here on
+        let container = RemoveOnDrop(id.trim().to_owned());
+        let port = docker(&["port", &container.0, "8500/tcp"]).await?;
+        let address = format!("http://{}", port.lines().next().unwrap_or_default().trim());
+        let config = Config::builder()
+            .address(&address)
+            .build()
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        let container = ConsulContainer { container, address, client: Client::new(config) };
+        container.wait_ready().await?;
+        Ok(container)
+    }
+
+    /// This method returns the ID of the container.
+    pub fn id(&self) -> &str {
+        &self.container.0
+    }
+
+    /// This method returns the address of the agent's HTTP API, such as
+    /// `http://127.0.0.1:49153`.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// This method returns a client of the agent.
+    pub fn client(&self) -> Client {
+        self.client.clone()
+    }
+
+    /// This method waits for the agent to elect itself leader, which it does
+    /// shortly after starting in dev mode.
+    async fn wait_ready(&self) -> std::io::Result<()> {
+        let start = Instant::now();
+        loop {
+            match self.client.get_leader(None).await {
+                Ok(leader) if !leader.is_empty() => return Ok(()),
+                _ if start.elapsed() >= READY_TIMEOUT => {
+                    return Err(Error::new(
+                        ErrorKind::TimedOut,
+                        "consul agent did not elect a leader",
+                    ))
+                }
+                _ => tokio::time::sleep(Duration::from_millis(250)).await,
+            }
+        }
+    }
+}
+
+impl Drop for RemoveOnDrop {
+    fn drop(&mut self) {
+        // dropping cannot wait on asynchronous commands, and the container
+        // must be removed even if the runtime is shutting down
+        let _ = std::process::Command::new("docker")
+            .args(["rm", "--force", &self.0])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
+
+/// This function runs the `docker` command with the given arguments, returning
+/// its output, or its error output as an error if it fails.
+async fn docker(args: &[&str]) -> std::io::Result<String> {
+    let output = Command::new("docker").args(args).stdin(Stdio::null()).output().await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::other(format!("docker {} failed: {}", args[0], stderr.trim())));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
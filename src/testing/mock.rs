@@ -0,0 +1,672 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::Infallible,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::Incoming, header::CONTENT_TYPE, server::conn::http1, service::service_fn, Method,
+    Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use percent_encoding::percent_decode_str;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::{net::TcpListener, sync::watch, task::JoinSet};
+use url::form_urlencoded;
+
+use crate::{
+    AgentService, CatalogDeregistrationPayload, CatalogNode, CatalogNodeServiceList,
+    CatalogRegistrationPayload, CatalogService, CheckStatus, Client, Config, HealthCheck, KVPair,
+    Node, ServiceEntry, ServiceWeights,
+};
+
+/// The datacenter of the mock server.
+const DATACENTER: &str = "dc1";
+/// The address the mock server reports as the Raft leader and only peer.
+const LEADER: &str = "127.0.0.1:8300";
+/// The default and maximum time blocking queries wait for, as in Consul.
+const DEFAULT_WAIT: Duration = Duration::from_secs(300);
+const MAX_WAIT: Duration = Duration::from_secs(600);
+
+/// An in-memory Consul server for unit tests.
+///
+/// The server implements the KV store, and the catalog, health and status
+/// endpoints, which cover the data read and written by most services. A
+/// single index is shared by all the data, and incremented by every write, so
+/// blocking queries on any endpoint return after the next write. Endpoints the
+/// server does not implement respond with `404 Not Found`.
+///
+/// Sessions are not implemented, so locks on keys are acquired and released
+/// without validating the session. The server stops when it is dropped.
+#[derive(Debug)]
+pub struct MockConsul {
+    address: String,
+    shared: Arc<Shared>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+/// The state of a [MockConsul], shared with the tasks serving its
+/// connections.
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    /// The index of the last write.
+    index: watch::Sender<u64>,
+}
+
+/// The data stored by a [MockConsul].
+#[derive(Debug, Default)]
+struct State {
+    kv: BTreeMap<String, KVPair>,
+    nodes: BTreeMap<String, Node>,
+    /// The services of each node, keyed by ID.
+    services: BTreeMap<String, BTreeMap<String, AgentService>>,
+    /// The checks of each node, keyed by ID.
+    checks: BTreeMap<String, BTreeMap<String, HealthCheck>>,
+}
+
+impl MockConsul {
+    /// This method starts a mock server on a free local port.
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = format!("http://{}", listener.local_addr()?);
+        let shared = Arc::new(Shared { state: Mutex::default(), index: watch::Sender::new(1) });
+        let server = tokio::spawn(serve(listener, shared.clone()));
+        Ok(MockConsul { address, shared, server })
+    }
+
+    /// This method returns the address of the server, such as
+    /// `http://127.0.0.1:51234`.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// This method returns a configuration for a client of the server.
+    pub fn config(&self) -> Config {
+        Config::builder().address(&self.address).build().expect("the address is valid")
+    }
+
+    /// This method returns a client of the server.
+    pub fn client(&self) -> Client {
+        Client::new(self.config())
+    }
+
+    /// This method returns the index of the last write to the server.
+    pub fn index(&self) -> u64 {
+        *self.shared.index.borrow()
+    }
+
+    /// This method sets the value of the given key, returning the index of
+    /// the write.
+    pub fn put_key(&self, key: &str, value: impl AsRef<[u8]>) -> u64 {
+        self.shared.write(|state, index| {
+            state.put_key(key, value.as_ref(), None, index);
+        })
+    }
+
+    /// This method registers the node, service and checks of the given
+    /// payload in the catalog, as [crate::Catalog::register] would, returning
+    /// the index of the write.
+    pub fn register(&self, payload: CatalogRegistrationPayload) -> u64 {
+        self.shared.write(|state, index| state.register(payload, index))
+    }
+
+    /// This method sets the status of the check with the given ID on the given
+    /// node, returning the index of the write. Nothing is written if there is
+    /// no such check.
+    pub fn set_check_status(&self, node: &str, check_id: &str, status: CheckStatus) -> u64 {
+        self.shared.write(|state, _| {
+            if let Some(check) = state.checks.get_mut(node).and_then(|c| c.get_mut(check_id)) {
+                check.status = status;
+            }
+        })
+    }
+}
+
+impl Drop for MockConsul {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+impl Shared {
+    /// This method applies the given write to the state, passing it the index
+    /// of the write, and notifies blocking queries.
+    fn write(&self, write: impl FnOnce(&mut State, u64)) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let index = *self.index.borrow() + 1;
+        write(&mut state, index);
+        self.index.send_replace(index);
+        index
+    }
+}
+
+impl State {
+    /// This method sets the value of the given key, unless the check-and-set
+    /// index does not match its modify index.
+    fn put_key(&mut self, key: &str, value: &[u8], cas: Option<u64>, index: u64) -> bool {
+        let existing = self.kv.get(key);
+        if cas.is_some_and(|cas| cas != existing.and_then(|pair| pair.modifyindex).unwrap_or(0)) {
+            return false;
+        }
+        let pair = self.kv.entry(key.to_owned()).or_insert_with(|| KVPair {
+            key: key.to_owned(),
+            createindex: Some(index),
+            lockindex: Some(0),
+            flags: Some(0),
+            ..Default::default()
+        });
+        pair.value = value.to_vec();
+        pair.modifyindex = Some(index);
+        true
+    }
+
+    /// This method registers the contents of the given payload.
+    fn register(&mut self, payload: CatalogRegistrationPayload, index: u64) {
+        let name = payload.node.clone();
+        let exists = self.nodes.contains_key(&name);
+        if !(exists && payload.skip_node_update) {
+            let node = self.nodes.entry(name.clone()).or_insert_with(|| Node {
+                node: name.clone(),
+                create_index: index,
+                ..Default::default()
+            });
+            node.id = payload.id;
+            node.address = payload.address;
+            node.datacenter = String::from(DATACENTER);
+            node.tagged_addresses = payload.tagged_addresses;
+            node.meta = payload.node_meta;
+            node.modify_index = index;
+        }
+        if let Some(service) = payload.service {
+            let id = service.id.unwrap_or_else(|| service.service.clone());
+            let services = self.services.entry(name.clone()).or_default();
+            let create_index = services.get(&id).map_or(index, |s| s.create_index);
+            let service = AgentService {
+                id: id.clone(),
+                service: service.service,
+                tags: Some(service.tags),
+                port: service.port.unwrap_or_default(),
+                address: service.address.unwrap_or_default(),
+                enable_tag_override: service.enable_tag_override,
+                kind: String::new(),
+                meta: service.meta,
+                tagged_addresses: service.tagged_addresses,
+                weights: service.weights.unwrap_or(ServiceWeights { passing: 1, warning: 1 }),
+                proxy: None,
+                connect: None,
+                content_hash: String::new(),
+                create_index,
+                modify_index: index,
+            };
+            services.insert(id, service);
+        }
+        for check in payload.check.into_iter().chain(payload.checks) {
+            let service =
+                check.service_id.as_ref().and_then(|id| self.services.get(&name)?.get(id));
+            let check = HealthCheck {
+                node: name.clone(),
+                check_id: check.check_id.unwrap_or_else(|| check.name.clone()),
+                name: check.name,
+                status: check.status.unwrap_or_default(),
+                notes: check.notes.unwrap_or_default(),
+                output: String::new(),
+                service_id: check.service_id.clone().unwrap_or_default(),
+                service_name: service.map(|s| s.service.clone()).unwrap_or_default(),
+                service_tags: service.and_then(|s| s.tags.clone()).unwrap_or_default(),
+                ..Default::default()
+            };
+            self.checks.entry(name.clone()).or_default().insert(check.check_id.clone(), check);
+        }
+    }
+
+    /// This method removes the node, service or check of the given payload.
+    fn deregister(&mut self, payload: CatalogDeregistrationPayload) {
+        let node = payload.node;
+        if !payload.service_id.is_empty() {
+            if let Some(services) = self.services.get_mut(&node) {
+                services.remove(&payload.service_id);
+            }
+            if let Some(checks) = self.checks.get_mut(&node) {
+                checks.retain(|_, check| check.service_id != payload.service_id);
+            }
+        } else if !payload.check_id.is_empty() {
+            if let Some(checks) = self.checks.get_mut(&node) {
+                checks.remove(&payload.check_id);
+            }
+        } else {
+            self.nodes.remove(&node);
+            self.services.remove(&node);
+            self.checks.remove(&node);
+        }
+    }
+
+    /// This method returns the instances of the given service with every
+    /// given tag, alongside their nodes and checks.
+    fn service_entries(&self, service: &str, tags: &[String]) -> Vec<ServiceEntry> {
+        let mut entries = Vec::new();
+        for (name, services) in &self.services {
+            let checks = self.checks.get(name);
+            for instance in services.values() {
+                let instance_tags = instance.tags.as_deref().unwrap_or_default();
+                if instance.service != service || !tags.iter().all(|t| instance_tags.contains(t)) {
+                    continue;
+                }
+                let checks = checks.into_iter().flat_map(|checks| checks.values());
+                entries.push(ServiceEntry {
+                    node: self.nodes.get(name).cloned().unwrap_or_default(),
+                    service: instance.clone(),
+                    checks: checks
+                        .filter(|c| c.service_id.is_empty() || c.service_id == instance.id)
+                        .cloned()
+                        .collect(),
+                });
+            }
+        }
+        entries
+    }
+}
+
+/// This function accepts connections to the mock server until it is stopped,
+/// which also stops serving the open connections.
+async fn serve(listener: TcpListener, shared: Arc<Shared>) {
+    let mut connections = JoinSet::new();
+    while let Ok((stream, _)) = listener.accept().await {
+        while connections.try_join_next().is_some() {}
+        let shared = shared.clone();
+        connections.spawn(async move {
+            let service = service_fn(move |request| handle(shared.clone(), request));
+            // connections closed by clients are not an error of the server
+            let _ = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await;
+        });
+    }
+}
+
+/// A request to the mock server, with its query parameters decoded.
+struct MockRequest {
+    method: Method,
+    path: String,
+    params: Vec<(String, String)>,
+    body: Bytes,
+}
+
+impl MockRequest {
+    /// This method returns the value of the given parameter, if it is set.
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    /// This method returns every value of the given parameter.
+    fn params(&self, name: &str) -> Vec<String> {
+        self.params.iter().filter(|(key, _)| key == name).map(|(_, v)| v.clone()).collect()
+    }
+
+    /// This method decodes the JSON body of the request.
+    fn json<T: DeserializeOwned>(&self) -> Option<T> {
+        serde_json::from_slice(&self.body).ok()
+    }
+}
+
+/// This function handles a request to the mock server, first waiting for the
+/// index to pass that of a blocking query.
+async fn handle(
+    shared: Arc<Shared>,
+    request: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let request = MockRequest {
+        method: parts.method,
+        path: percent_decode_str(parts.uri.path()).decode_utf8_lossy().into_owned(),
+        params: form_urlencoded::parse(parts.uri.query().unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect(),
+        body: body.collect().await.map(|body| body.to_bytes()).unwrap_or_default(),
+    };
+    if request.method == Method::GET {
+        if let Some(index) = request.param("index").and_then(|index| index.parse().ok()) {
+            let wait = request.param("wait").and_then(parse_wait).unwrap_or(DEFAULT_WAIT);
+            let mut receiver = shared.index.subscribe();
+            let changed = receiver.wait_for(|current| *current > index);
+            let _ = tokio::time::timeout(wait.min(MAX_WAIT), changed).await;
+        }
+    }
+    let (status, body) = if request.method == Method::GET {
+        let state = shared.state.lock().unwrap();
+        read(&state, &request)
+    } else {
+        let mut result = None;
+        shared.write(|state, index| result = Some(write(state, &request, index)));
+        result.expect("writes are applied")
+    };
+    let mut response = Response::new(Full::new(body));
+    *response.status_mut() = status;
+    let headers = response.headers_mut();
+    headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+    headers.insert("X-Consul-Index", shared.index.borrow().to_string().parse().unwrap());
+    headers.insert("X-Consul-KnownLeader", "true".parse().unwrap());
+    headers.insert("X-Consul-LastContact", "0".parse().unwrap());
+    Ok(response)
+}
+
+/// This function parses the wait time of a blocking query, such as `100ms`,
+/// `10s` or `5m`.
+fn parse_wait(wait: &str) -> Option<Duration> {
+    let split = wait.find(|c: char| !c.is_ascii_digit())?;
+    let value: u64 = wait[..split].parse().ok()?;
+    match &wait[split..] {
+        "ms" => Some(Duration::from_millis(value)),
+        "s" => Some(Duration::from_secs(value)),
+        "m" => Some(Duration::from_secs(value * 60)),
+        _ => None,
+    }
+}
+
+/// This function returns a successful response with the given value.
+fn ok(value: impl Serialize) -> (StatusCode, Bytes) {
+    (StatusCode::OK, serde_json::to_vec(&value).expect("responses are serializable").into())
+}
+
+/// This function returns a failed response with the given message.
+fn error(status: StatusCode, message: &str) -> (StatusCode, Bytes) {
+    (status, Bytes::copy_from_slice(message.as_bytes()))
+}
+
+/// This function returns the response to the given read.
+fn read(state: &State, request: &MockRequest) -> (StatusCode, Bytes) {
+    let path = request.path.as_str();
+    if let Some(key) = path.strip_prefix("/v1/kv/") {
+        return read_kv(state, request, key);
+    }
+    if let Some(service) = path.strip_prefix("/v1/catalog/service/") {
+        let tags = request.params("tag");
+        let services =
+            state.service_entries(service, &tags).into_iter().map(|entry| CatalogService {
+                id: entry.node.id,
+                node: entry.node.node,
+                address: entry.node.address,
+                datacenter: entry.node.datacenter,
+                tagged_addresses: entry.node.tagged_addresses,
+                node_meta: entry.node.meta,
+                service_id: entry.service.id,
+                service_name: entry.service.service,
+                service_address: entry.service.address,
+                service_tags: entry.service.tags.unwrap_or_default(),
+                service_meta: entry.service.meta,
+                service_port: entry.service.port.into(),
+                service_weights: entry.service.weights,
+                service_enable_tag_override: entry.service.enable_tag_override,
+                create_index: entry.service.create_index,
+                modify_index: entry.service.modify_index,
+            });
+        return ok(services.collect::<Vec<_>>());
+    }
+    if let Some(name) = path.strip_prefix("/v1/catalog/node/") {
+        let node = state.nodes.get(name).map(|node| CatalogNode {
+            node: Some(node.clone()),
+            services: state.services.get(name).cloned().unwrap_or_default().into_iter().collect(),
+        });
+        return ok(node);
+    }
+    if let Some(name) = path.strip_prefix("/v1/catalog/node-services/") {
+        let node = state.nodes.get(name).map(|node| CatalogNodeServiceList {
+            node: node.clone(),
+            services: state
+                .services
+                .get(name)
+                .into_iter()
+                .flat_map(|s| s.values())
+                .cloned()
+                .collect(),
+        });
+        return ok(node);
+    }
+    if let Some(service) = path.strip_prefix("/v1/health/service/") {
+        let mut entries = state.service_entries(service, &request.params("tag"));
+        if request.param("passing").is_some() {
+            entries.retain(|entry| {
+                entry.checks.iter().all(|check| check.status == CheckStatus::Passing)
+            });
+        }
+        return ok(entries);
+    }
+    let checks = || state.checks.values().flat_map(|checks| checks.values());
+    if let Some(node) = path.strip_prefix("/v1/health/node/") {
+        let checks = state.checks.get(node).into_iter().flat_map(|checks| checks.values());
+        return ok(checks.collect::<Vec<_>>());
+    }
+    if let Some(service) = path.strip_prefix("/v1/health/checks/") {
+        return ok(checks().filter(|check| check.service_name == service).collect::<Vec<_>>());
+    }
+    if let Some(status) = path.strip_prefix("/v1/health/state/") {
+        let checks = checks().filter(|check| status == "any" || check.status.as_str() == status);
+        return ok(checks.collect::<Vec<_>>());
+    }
+    match path {
+        "/v1/catalog/datacenters" => ok([DATACENTER]),
+        "/v1/catalog/nodes" => ok(state.nodes.values().collect::<Vec<_>>()),
+        "/v1/catalog/services" => {
+            let mut services: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+            for service in state.services.values().flat_map(|services| services.values()) {
+                let tags = services.entry(&service.service).or_default();
+                tags.extend(service.tags.iter().flatten().map(String::as_str));
+            }
+            ok(services)
+        }
+        "/v1/status/leader" => ok(LEADER),
+        "/v1/status/peers" => ok([LEADER]),
+        _ => error(StatusCode::NOT_FOUND, "endpoint not implemented by the mock server"),
+    }
+}
+
+/// This function returns the response to a read of the KV store.
+fn read_kv(state: &State, request: &MockRequest, key: &str) -> (StatusCode, Bytes) {
+    let under = || state.kv.range(key.to_owned()..).take_while(|(k, _)| k.starts_with(key));
+    if request.param("keys").is_some() {
+        let separator = request.param("separator").filter(|separator| !separator.is_empty());
+        let keys: BTreeSet<&str> = under()
+            .map(|(k, _)| {
+                // keys are collapsed up to the first separator after the prefix
+                let end = separator
+                    .and_then(|separator| Some(k[key.len()..].find(separator)? + separator.len()));
+                end.map_or(k.as_str(), |end| &k[..key.len() + end])
+            })
+            .collect();
+        if keys.is_empty() {
+            return error(StatusCode::NOT_FOUND, "");
+        }
+        return ok(keys);
+    }
+    let pairs: Vec<&KVPair> = if request.param("recurse").is_some() {
+        under().map(|(_, pair)| pair).collect()
+    } else {
+        state.kv.get(key).into_iter().collect()
+    };
+    if pairs.is_empty() {
+        return error(StatusCode::NOT_FOUND, "");
+    }
+    ok(pairs)
+}
+
+/// This function applies the given write, returning its response.
+fn write(state: &mut State, request: &MockRequest, index: u64) -> (StatusCode, Bytes) {
+    let cas = request.param("cas").and_then(|cas| cas.parse().ok());
+    if let Some(key) = request.path.strip_prefix("/v1/kv/") {
+        return match request.method {
+            Method::PUT => ok(put_kv(state, request, key, cas, index)),
+            Method::DELETE => {
+                let modify_index = state.kv.get(key).and_then(|pair| pair.modifyindex);
+                if cas.is_some_and(|cas| cas != modify_index.unwrap_or(0)) {
+                    return ok(false);
+                }
+                if request.param("recurse").is_some() {
+                    state.kv.retain(|k, _| !k.starts_with(key));
+                } else {
+                    state.kv.remove(key);
+                }
+                ok(true)
+            }
+            _ => error(StatusCode::METHOD_NOT_ALLOWED, "method not allowed"),
+        };
+    }
+    match (&request.method, request.path.as_str()) {
+        (&Method::PUT, "/v1/catalog/register") => match request.json() {
+            Some(payload) => {
+                state.register(payload, index);
+                ok(true)
+            }
+            None => error(StatusCode::BAD_REQUEST, "invalid registration payload"),
+        },
+        (&Method::PUT, "/v1/catalog/deregister") => match request.json() {
+            Some(payload) => {
+                state.deregister(payload);
+                ok(true)
+            }
+            None => error(StatusCode::BAD_REQUEST, "invalid deregistration payload"),
+        },
+        _ => error(StatusCode::NOT_FOUND, "endpoint not implemented by the mock server"),
+    }
+}
+
+/// This function sets the value of a key, or acquires or releases its lock.
+fn put_kv(
+    state: &mut State,
+    request: &MockRequest,
+    key: &str,
+    cas: Option<u64>,
+    index: u64,
+) -> bool {
+    let session = state.kv.get(key).and_then(|pair| pair.session.clone());
+    let mut next_session = session.clone();
+    if let Some(acquire) = request.param("acquire") {
+        if session.as_deref().is_some_and(|session| session != acquire) {
+            return false;
+        }
+        next_session = Some(acquire.to_owned());
+    }
+    if let Some(release) = request.param("release") {
+        if session.as_deref() != Some(release) {
+            return false;
+        }
+        next_session = None;
+    }
+    if !state.put_key(key, &request.body, cas, index) {
+        return false;
+    }
+    let pair = state.kv.get_mut(key).expect("the key was written");
+    if let Some(flags) = request.param("flags").and_then(|flags| flags.parse().ok()) {
+        pair.flags = Some(flags);
+    }
+    if next_session.is_some() && session.is_none() {
+        pair.lockindex = Some(pair.lockindex.unwrap_or_default() + 1);
+    }
+    pair.session = next_session;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{parse_wait, MockConsul};
+    use crate::{
+        Catalog, CatalogCheckRegistration, CatalogRegistrationPayload, CatalogServiceRegistration,
+        CheckStatus, Health, KVPair, QueryOptions, KV,
+    };
+
+    #[test]
+    fn test_parse_wait() {
+        assert_eq!(parse_wait("150ms"), Some(Duration::from_millis(150)));
+        assert_eq!(parse_wait("10s"), Some(Duration::from_secs(10)));
+        assert_eq!(parse_wait("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_wait("5"), None);
+    }
+
+    #[tokio::test]
+    async fn test_kv() {
+        let consul = MockConsul::start().await.unwrap();
+        let client = consul.client();
+        consul.put_key("config/a", "1");
+        consul.put_key("config/nested/b", "2");
+
+        let (pairs, meta) = client.get_entry("config/a", None).await.unwrap();
+        assert_eq!(pairs[0].value, b"1");
+        assert_eq!(meta.last_index, Some(consul.index()));
+        let (keys, _) = client.list_keys("config/", Some("/"), None).await.unwrap();
+        assert_eq!(keys, ["config/a", "config/nested/"]);
+        let (pairs, _) = client.list_entries("config/", None).await.unwrap();
+        assert_eq!(pairs.len(), 2);
+
+        let pair = KVPair { key: String::from("config/a"), ..Default::default() };
+        let stale = crate::KVPutParams { cas: Some(1), ..Default::default() };
+        assert!(!client.put_entry(&pair, Some(stale), None).await.unwrap().0);
+        assert!(client.delete_entry("config/a", None, None).await.unwrap().0);
+        assert!(client.get_entry("config/a", None).await.unwrap().0.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_catalog_and_health() {
+        let consul = MockConsul::start().await.unwrap();
+        let client = consul.client();
+        let check = CatalogCheckRegistration {
+            name: String::from("web-alive"),
+            status: Some(CheckStatus::Passing),
+            service_id: Some(String::from("web-1")),
+            ..Default::default()
+        };
+        let service = CatalogServiceRegistration {
+            id: Some(String::from("web-1")),
+            service: String::from("web"),
+            tags: vec![String::from("v1")],
+            port: Some(8080),
+            ..Default::default()
+        };
+        let payload = CatalogRegistrationPayload::builder("node-1", "10.0.0.1")
+            .service(service)
+            .check(check)
+            .build();
+        client.register(payload, None).await.unwrap();
+
+        let (services, _) = client.list_datacenter_services(None).await.unwrap();
+        assert_eq!(services["web"], ["v1"]);
+        let (nodes, _) = client.list_nodes_for_service("web", Some("v1"), None).await.unwrap();
+        assert_eq!(nodes[0].address, "10.0.0.1");
+        assert_eq!(nodes[0].service_port, 8080);
+        let (entries, _) = client.list_service_instances("web", &[], true, None).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].address(), "10.0.0.1");
+
+        consul.set_check_status("node-1", "web-alive", CheckStatus::Critical);
+        let (entries, _) = client.list_service_instances("web", &[], true, None).await.unwrap();
+        assert!(entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_blocking_query() {
+        let consul = MockConsul::start().await.unwrap();
+        let client = consul.client();
+        let index = consul.put_key("a", "1");
+
+        let options = move |wait| QueryOptions {
+            wait_index: Some(index),
+            wait_time: Some(wait),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let (_, meta) =
+            client.get_entry("a", Some(options(Duration::from_millis(50)))).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(meta.last_index, Some(index));
+
+        let query = tokio::spawn(async move {
+            client.get_entry("a", Some(options(Duration::from_secs(10)))).await
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let next = consul.put_key("a", "2");
+        let (pairs, meta) = query.await.unwrap().unwrap();
+        assert_eq!(meta.last_index, Some(next));
+        assert_eq!(pairs[0].value, b"2");
+    }
+}
@@ -0,0 +1,30 @@
+//! Utilities for testing code using the Consul client.
+//!
+//! With the `testing` feature, [MockConsul] serves a subset of the Consul HTTP
+//! API from memory, on a local port. It can be seeded with fixtures, and used
+//! by the client like a real agent, without running Consul:
+//!
+//! ```
+//! # #[cfg(feature = "testing")]
+//! # async fn run() {
+//! use consul_oxide::{testing::MockConsul, KV};
+//!
+//! let consul = MockConsul::start().await.unwrap();
+//! consul.put_key("config/feature", "enabled");
+//! let (keys, _) = consul.client().list_keys("config/", None, None).await.unwrap();
+//! assert_eq!(keys, ["config/feature"]);
+//! # }
+//! ```
+//!
+//! With the `testcontainers` feature, [ConsulContainer] runs a Consul dev
+//! agent in Docker instead, for tests needing the complete API.
+
+#[cfg(feature = "testcontainers")]
+mod container;
+#[cfg(feature = "testing")]
+mod mock;
+
+#[cfg(feature = "testcontainers")]
+pub use container::*;
+#[cfg(feature = "testing")]
+pub use mock::*;
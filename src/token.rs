@@ -0,0 +1,171 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// The minimum delay between two checks of the modification time of a token
+/// file.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A file containing the ACL token of requests, set with
+/// [ConfigBuilder::token_file] or the `CONSUL_HTTP_TOKEN_FILE` environment
+/// variable.
+///
+/// The file is read again whenever its modification time changes, which is
+/// checked at most once per second, so tokens rotated on disk, such as those
+/// issued by Vault agents, are used without restarting the process. If the file
+/// cannot be read, the last token read is used. Clones of a token file share
+/// the last token read.
+///
+/// [ConfigBuilder::token_file]: crate::ConfigBuilder::token_file
+#[derive(Clone, Debug)]
+pub struct TokenFile {
+    path: PathBuf,
+    cached: Arc<Mutex<CachedToken>>,
+}
+
+/// The last token read from a [TokenFile].
+#[derive(Debug)]
+struct CachedToken {
+    checked: Option<Instant>,
+    modified: Option<SystemTime>,
+    token: Option<String>,
+}
+
+impl TokenFile {
+    /// This method reads the token of the file at the given path, failing if
+    /// it cannot be read.
+    pub fn new(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        let modified = fs::metadata(&path)?.modified().ok();
+        let token = read_token(&path)?;
+        let cached = CachedToken { checked: Some(Instant::now()), modified, token };
+        Ok(TokenFile { path, cached: Arc::new(Mutex::new(cached)) })
+    }
+
+    /// This method returns a token file for the given path without reading
+    /// it, whose token is read when it is first requested.
+    pub(crate) fn unread(path: impl Into<PathBuf>) -> Self {
+        let cached = CachedToken { checked: None, modified: None, token: None };
+        TokenFile { path: path.into(), cached: Arc::new(Mutex::new(cached)) }
+    }
+
+    /// This method returns the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// This method returns the token of the file, reading it again if it was
+    /// modified since it was last read. The modification time is only checked
+    /// if it was not checked within the last second.
+    pub fn token(&self) -> Option<String> {
+        let mut cached = self.cached.lock().unwrap();
+        if cached.checked.is_some_and(|checked| checked.elapsed() < CHECK_INTERVAL) {
+            return cached.token.clone();
+        }
+        cached.checked = Some(Instant::now());
+        let modified = fs::metadata(&self.path).and_then(|metadata| metadata.modified());
+        match modified {
+            Ok(modified) if cached.modified != Some(modified) => match read_token(&self.path) {
+                Ok(token) => {
+                    tracing::debug!(path = %self.path.display(), "reloaded token file");
+                    cached.modified = Some(modified);
+                    cached.token = token;
+                }
+                Err(e) => {
+                    tracing::warn!(path = %self.path.display(), "failed to read token file: {}", e)
+                }
+            },
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(path = %self.path.display(), "failed to read token file: {}", e)
+            }
+        }
+        cached.token.clone()
+    }
+}
+
+/// An ACL token shared by the clones of a configuration, which can be replaced
+/// while they are in use.
+///
+/// When set as the `shared_token` of a configuration, requests use the token
+/// held at the time they are sent, so a token renewed in the background, such
+/// as by a `TokenRenewer`, is used by every clone of the client.
+#[derive(Clone, Debug)]
+pub struct SharedToken(Arc<RwLock<String>>);
+
+impl SharedToken {
+    /// This method creates a new shared token holding the given token.
+    pub fn new(token: impl Into<String>) -> Self {
+        SharedToken(Arc::new(RwLock::new(token.into())))
+    }
+
+    /// This method returns the token currently held.
+    pub fn get(&self) -> String {
+        self.0.read().unwrap().clone()
+    }
+
+    /// This method replaces the token held, for every clone of this value.
+    pub fn set(&self, token: impl Into<String>) {
+        *self.0.write().unwrap() = token.into();
+    }
+}
+
+/// This function reads the token of the file at the given path, ignoring
+/// surrounding whitespace. An empty file has no token.
+fn read_token(path: &Path) -> std::io::Result<Option<String>> {
+    let token = fs::read_to_string(path)?;
+    let token = token.trim();
+    Ok((!token.is_empty()).then(|| token.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use super::{SharedToken, TokenFile};
+
+    #[test]
+    fn test_token_file() {
+        let path = std::env::temp_dir().join(format!("consul-token-{}", std::process::id()));
+        fs::write(&path, "first\n").unwrap();
+        let file = TokenFile::new(&path).unwrap();
+        assert_eq!(file.token().as_deref(), Some("first"));
+
+        // modification times may be as coarse as a second
+        std::thread::sleep(Duration::from_millis(1100));
+        fs::write(&path, "second").unwrap();
+        assert_eq!(file.clone().token().as_deref(), Some("second"));
+
+        // the file is not checked again within a second of the last check
+        std::thread::sleep(Duration::from_millis(1100));
+        assert_eq!(file.token().as_deref(), Some("second"));
+        fs::write(&path, "third").unwrap();
+        assert_eq!(file.token().as_deref(), Some("second"));
+        std::thread::sleep(Duration::from_millis(1100));
+        assert_eq!(file.token().as_deref(), Some("third"));
+
+        // the last token is kept if the file disappears
+        fs::remove_file(&path).unwrap();
+        std::thread::sleep(Duration::from_millis(1100));
+        assert_eq!(file.token().as_deref(), Some("third"));
+        assert!(TokenFile::new(&path).is_err());
+
+        // an unread file is read once it exists
+        let file = TokenFile::unread(&path);
+        assert_eq!(file.token(), None);
+        fs::write(&path, "fourth").unwrap();
+        std::thread::sleep(Duration::from_millis(1100));
+        assert_eq!(file.token().as_deref(), Some("fourth"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_shared_token() {
+        let token = SharedToken::new("first");
+        token.clone().set("second");
+        assert_eq!(token.get(), "second");
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+#[cfg(feature = "reqwest")]
+use futures::TryStreamExt;
+use futures::{future, stream, stream::BoxStream, StreamExt};
+use http::{header::HeaderMap, response::Parts, Request, Response, StatusCode};
+use serde::de::DeserializeOwned;
+
+use crate::{ConsulError, ConsulResult};
+
+/// The transport sending requests to the Consul API, registered with
+/// [ConfigBuilder::transport].
+///
+/// By default, requests are sent with the `reqwest` client of the
+/// configuration. A custom transport can send them with another HTTP client,
+/// such as `hyper`, or answer them directly as a test double. Without the
+/// `reqwest` feature, a transport is required. Requests and responses are
+/// types of the `http` crate, with their bodies buffered in full.
+///
+/// ```
+/// use async_trait::async_trait;
+/// use bytes::Bytes;
+/// use consul_oxide::{Client, Config, ConsulResult, HttpTransport, Status};
+/// use http::{Request, Response};
+///
+/// #[derive(Debug)]
+/// struct FixedLeader;
+///
+/// #[async_trait]
+/// impl HttpTransport for FixedLeader {
+///     async fn execute(&self, request: Request<Bytes>) -> ConsulResult<Response<Bytes>> {
+///         assert_eq!(request.uri().path(), "/v1/status/leader");
+///         Ok(Response::new(Bytes::from_static(br#""10.0.0.1:8300""#)))
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let client = Client::new(Config::builder().transport(FixedLeader).build().unwrap());
+/// assert_eq!(client.get_leader(None).await.unwrap(), "10.0.0.1:8300");
+/// # }
+/// ```
+///
+/// [ConfigBuilder::transport]: crate::ConfigBuilder::transport
+#[async_trait]
+pub trait HttpTransport: Debug + Send + Sync {
+    /// This method sends the given request, returning its response. Responses
+    /// with an error status should be returned as-is, as they are handled by
+    /// the client.
+    async fn execute(&self, request: Request<Bytes>) -> ConsulResult<Response<Bytes>>;
+}
+
+#[cfg(feature = "reqwest")]
+#[async_trait]
+impl HttpTransport for reqwest::Client {
+    async fn execute(&self, request: Request<Bytes>) -> ConsulResult<Response<Bytes>> {
+        let response = reqwest::Client::execute(self, request.try_into()?).await?;
+        let mut builder = Response::builder().status(response.status()).version(response.version());
+        if let Some(headers) = builder.headers_mut() {
+            *headers = response.headers().clone();
+        }
+        let body = response.bytes().await?;
+        Ok(builder.body(body).expect("the parts of a valid response are valid"))
+    }
+}
+
+/// A response received by the client. Its body is streamed when it was sent
+/// with the `reqwest` client of the configuration, and buffered in full when it
+/// was sent by a transport.
+#[derive(Debug)]
+pub(crate) struct ClientResponse {
+    head: Parts,
+    url: String,
+    body: ResponseBody,
+}
+
+/// The body of a [ClientResponse].
+#[derive(Debug)]
+enum ResponseBody {
+    Buffered(Bytes),
+    #[cfg(feature = "reqwest")]
+    Streamed(reqwest::Response),
+}
+
+impl ClientResponse {
+    /// This method returns the response of a transport to a request to the
+    /// given URL.
+    pub(crate) fn buffered(response: Response<Bytes>, url: String) -> Self {
+        let (head, body) = response.into_parts();
+        ClientResponse { head, url, body: ResponseBody::Buffered(body) }
+    }
+
+    /// This method returns the given `reqwest` response, whose body has not
+    /// been read yet.
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn streamed(response: reqwest::Response) -> Self {
+        let (mut head, ()) = Response::new(()).into_parts();
+        head.status = response.status();
+        head.version = response.version();
+        head.headers = response.headers().clone();
+        let url = response.url().to_string();
+        ClientResponse { head, url, body: ResponseBody::Streamed(response) }
+    }
+
+    /// This method returns the status code, version and headers of the
+    /// response.
+    pub(crate) fn head(&self) -> &Parts {
+        &self.head
+    }
+
+    /// This method returns the status code of the response.
+    pub(crate) fn status(&self) -> StatusCode {
+        self.head.status
+    }
+
+    /// This method returns the headers of the response.
+    pub(crate) fn headers(&self) -> &HeaderMap {
+        &self.head.headers
+    }
+
+    /// This method returns the URL of the request the response answers.
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// This method reads the body of the response in full.
+    pub(crate) async fn bytes(self) -> ConsulResult<Bytes> {
+        match self.body {
+            ResponseBody::Buffered(body) => Ok(body),
+            #[cfg(feature = "reqwest")]
+            ResponseBody::Streamed(response) => Ok(response.bytes().await?),
+        }
+    }
+
+    /// This method reads the body of the response as text, replacing invalid
+    /// UTF-8 sequences.
+    pub(crate) async fn text(self) -> ConsulResult<String> {
+        Ok(String::from_utf8_lossy(&self.bytes().await?).into_owned())
+    }
+
+    /// This method reads the body of the response, decoded from JSON.
+    pub(crate) async fn json<T: DeserializeOwned>(self) -> ConsulResult<T> {
+        serde_json::from_slice(&self.bytes().await?).map_err(ConsulError::DecodeError)
+    }
+
+    /// This method returns the body of the response as a stream of chunks.
+    pub(crate) fn bytes_stream(self) -> BoxStream<'static, ConsulResult<Bytes>> {
+        match self.body {
+            ResponseBody::Buffered(body) => stream::once(future::ready(Ok(body))).boxed(),
+            #[cfg(feature = "reqwest")]
+            ResponseBody::Streamed(response) => {
+                response.bytes_stream().map_err(ConsulError::HttpError).boxed()
+            }
+        }
+    }
+}
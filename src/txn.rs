@@ -0,0 +1,238 @@
+use std::time::Instant;
+
+use async_trait::async_trait;
+use http::{Method, StatusCode};
+use serde::Serializer;
+
+use crate::{
+    request::response_error, sealed::Sealed, AgentService, Client, ConsulError, ConsulResult,
+    HealthCheck, KVPair, Node, WriteMeta, WriteOptions,
+};
+
+/// The maximum number of operations Consul accepts in a single transaction.
+pub const MAX_TXN_OPS: usize = 64;
+
+/// This function encodes a KV value as base64, as expected by the
+/// transaction endpoint.
+fn encode_value<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&base64::encode(value))
+}
+
+/// A KV operation within a transaction.
+///
+/// For more information on the semantics of each verb, see the [API
+/// documentation].
+///
+/// [API documentation]: https://www.consul.io/api-docs/txn#tables-of-operations
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "Verb", rename_all = "kebab-case", rename_all_fields = "PascalCase")]
+pub enum KVTxnOp {
+    /// Sets the key to the given value.
+    Set {
+        key: String,
+        #[serde(serialize_with = "encode_value")]
+        value: Vec<u8>,
+        flags: u64,
+    },
+    /// Sets the key to the given value, only if its modify index matches.
+    Cas {
+        key: String,
+        #[serde(serialize_with = "encode_value")]
+        value: Vec<u8>,
+        flags: u64,
+        index: u64,
+    },
+    /// Locks the key with the given session.
+    Lock {
+        key: String,
+        #[serde(serialize_with = "encode_value")]
+        value: Vec<u8>,
+        flags: u64,
+        session: String,
+    },
+    /// Unlocks the key with the given session.
+    Unlock {
+        key: String,
+        #[serde(serialize_with = "encode_value")]
+        value: Vec<u8>,
+        flags: u64,
+        session: String,
+    },
+    /// Gets the key, failing the transaction if it does not exist.
+    Get { key: String },
+    /// Gets all keys with the given prefix.
+    GetTree { key: String },
+    /// Fails the transaction if the modify index of the key does not match.
+    CheckIndex { key: String, index: u64 },
+    /// Fails the transaction if the key is not locked by the given session.
+    CheckSession { key: String, session: String },
+    /// Fails the transaction if the key exists.
+    CheckNotExists { key: String },
+    /// Deletes the key.
+    Delete { key: String },
+    /// Deletes all keys with the given prefix.
+    DeleteTree { key: String },
+    /// Deletes the key, only if its modify index matches.
+    DeleteCas { key: String, index: u64 },
+}
+
+/// A node operation within a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "Verb", rename_all = "kebab-case", rename_all_fields = "PascalCase")]
+pub enum NodeTxnOp {
+    /// Gets the node, failing the transaction if it does not exist.
+    Get { node: Node },
+    /// Registers the node.
+    Set { node: Node },
+    /// Registers the node, only if its modify index matches.
+    Cas { node: Node },
+    /// Deregisters the node.
+    Delete { node: Node },
+    /// Deregisters the node, only if its modify index matches.
+    DeleteCas { node: Node },
+}
+
+/// A service operation within a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "Verb", rename_all = "kebab-case", rename_all_fields = "PascalCase")]
+pub enum ServiceTxnOp {
+    /// Gets the service, failing the transaction if it does not exist.
+    Get { node: String, service: AgentService },
+    /// Registers the service on the given node.
+    Set { node: String, service: AgentService },
+    /// Registers the service, only if its modify index matches.
+    Cas { node: String, service: AgentService },
+    /// Deregisters the service.
+    Delete { node: String, service: AgentService },
+    /// Deregisters the service, only if its modify index matches.
+    DeleteCas { node: String, service: AgentService },
+}
+
+/// A check operation within a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "Verb", rename_all = "kebab-case", rename_all_fields = "PascalCase")]
+pub enum CheckTxnOp {
+    /// Gets the check, failing the transaction if it does not exist.
+    Get { check: HealthCheck },
+    /// Registers the check.
+    Set { check: HealthCheck },
+    /// Registers the check, only if its modify index matches.
+    Cas { check: HealthCheck },
+    /// Deregisters the check.
+    Delete { check: HealthCheck },
+    /// Deregisters the check, only if its modify index matches.
+    DeleteCas { check: HealthCheck },
+}
+
+/// An operation within a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum TxnOp {
+    #[serde(rename = "KV")]
+    Kv(KVTxnOp),
+    Node(NodeTxnOp),
+    Service(ServiceTxnOp),
+    Check(Box<CheckTxnOp>),
+}
+
+/// The result of a single operation within a successful transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TxnResult {
+    #[serde(rename = "KV")]
+    Kv(KVPair),
+    Node(Node),
+    Service(AgentService),
+    Check(Box<HealthCheck>),
+}
+
+/// An error caused by a single operation within a transaction. Returned by
+/// [ConsulError::TxnFailed] when a transaction is rolled back.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub struct TxnError {
+    /// The index of the operation that failed.
+    pub op_index: usize,
+    /// A description of the error.
+    pub what: String,
+}
+
+/// The response body of the transaction endpoint.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct TxnResponse {
+    results: Option<Vec<TxnResult>>,
+    errors: Option<Vec<TxnError>>,
+}
+
+/// This trait provides methods for interacting with the `/txn` endpoint.
+#[async_trait]
+pub trait Txn: Sealed {
+    /// This method atomically executes a list of operations, returning the
+    /// result of each. At most [MAX_TXN_OPS] operations can be executed in a
+    /// single transaction.
+    ///
+    /// If any operation fails, the whole transaction is rolled back and
+    /// [ConsulError::TxnFailed] is returned with the errors of the individual
+    /// operations.
+    ///
+    /// For more information, consult the relevant endpoint's [API
+    /// documentation].
+    ///
+    /// [API documentation]: https://www.consul.io/api-docs/txn#create-transaction
+    async fn txn(
+        &self,
+        ops: Vec<TxnOp>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Vec<TxnResult>, WriteMeta)>;
+}
+
+#[async_trait]
+impl Txn for Client {
+    #[tracing::instrument]
+    async fn txn(
+        &self,
+        ops: Vec<TxnOp>,
+        options: Option<WriteOptions>,
+    ) -> ConsulResult<(Vec<TxnResult>, WriteMeta)> {
+        let builder = self.build_request(Method::PUT, "/v1/txn", None, Some(ops), options);
+        let start = Instant::now();
+        let response = self.execute(builder).await?;
+        let status = response.status();
+        // consul responds with a 409 and a list of errors if the transaction
+        // was rolled back
+        if !status.is_success() && status != StatusCode::CONFLICT {
+            return Err(response_error(response).await);
+        }
+        let response: TxnResponse = response.json().await?;
+        let meta = WriteMeta { request_time: start.elapsed() };
+        match response.errors {
+            Some(errors) if !errors.is_empty() => Err(ConsulError::TxnFailed(errors)),
+            _ => Ok((response.results.unwrap_or_default(), meta)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{KVTxnOp, TxnOp};
+
+    #[test]
+    fn test_serialize_kv_op() {
+        let op = TxnOp::Kv(KVTxnOp::Cas {
+            key: String::from("key"),
+            value: b"value".to_vec(),
+            flags: 0,
+            index: 12,
+        });
+        assert_eq!(
+            serde_json::to_value(op).unwrap(),
+            json!({"KV": {"Verb": "cas", "Key": "key", "Value": "dmFsdWU=", "Flags": 0, "Index": 12}})
+        );
+        let op = TxnOp::Kv(KVTxnOp::DeleteTree { key: String::from("prefix/") });
+        assert_eq!(
+            serde_json::to_value(op).unwrap(),
+            json!({"KV": {"Verb": "delete-tree", "Key": "prefix/"}})
+        );
+    }
+}
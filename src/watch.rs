@@ -0,0 +1,343 @@
+//! Utilities for watching Consul resources for changes.
+//!
+//! Watches are implemented as a loop of [blocking queries], and are exposed as
+//! [Stream]s that yield a new item every time the watched resource changes.
+//! Custom watches can be written with [blocking_query::loop_fn], and the
+//! watch types of Consul itself are available through [WatchPlan].
+//!
+//! [blocking queries]: https://www.consul.io/api-docs/features/blocking
+//! [blocking_query::loop_fn]: crate::blocking_query::loop_fn
+
+#[cfg(feature = "catalog")]
+use std::collections::HashMap;
+use std::pin::Pin;
+
+use futures::Stream;
+#[cfg(any(feature = "catalog", feature = "event", feature = "health", feature = "kv"))]
+use futures::TryStreamExt;
+#[cfg(feature = "event")]
+use futures::{future, StreamExt};
+#[cfg(feature = "health")]
+use serde::{de::value::StrDeserializer, Deserialize, Deserializer};
+
+#[cfg(any(
+    feature = "catalog",
+    feature = "connect",
+    feature = "event",
+    feature = "health",
+    feature = "kv"
+))]
+pub(crate) use crate::blocking_query::loop_fn as watch;
+#[cfg(feature = "catalog")]
+use crate::{Catalog, Node};
+#[cfg(feature = "health")]
+use crate::{CheckStatus, Health, HealthCheck, ServiceEntry};
+use crate::{Client, ConsulResult, QueryOptions};
+#[cfg(feature = "event")]
+use crate::{Event, UserEvent};
+#[cfg(feature = "kv")]
+use crate::{KVPair, KV};
+
+/// A boxed [Stream] of changes to a watched resource.
+pub type WatchStream<T> = Pin<Box<dyn Stream<Item = T> + Send>>;
+
+/// A watch of one of the resource types supported by [Consul watches].
+///
+/// Plans can be deserialized from the JSON definition of a Consul watch, so
+/// existing watch configurations can be reused as they are. Fields of the
+/// definition not listed here, such as the handler, are ignored.
+///
+/// ```no_run
+/// use consul_oxide::{watch::WatchPlan, Client, Config};
+/// use futures::StreamExt;
+///
+/// # async fn run() {
+/// let plan: WatchPlan =
+///     serde_json::from_str(r#"{"type": "service", "service": "redis", "passingonly": true}"#)
+///         .unwrap();
+/// let mut changes = plan.into_stream(&Client::new(Config::default()), None);
+/// while let Some(result) = changes.next().await {
+///     println!("{:?}", result);
+/// }
+/// # }
+/// ```
+///
+/// [Consul watches]: https://www.consul.io/docs/dynamic-app-config/watches
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum WatchPlan {
+    /// Watches a single key, as [KV::watch_key].
+    #[cfg(feature = "kv")]
+    Key { key: String },
+    /// Watches the entries sharing a prefix.
+    #[cfg(feature = "kv")]
+    KeyPrefix { prefix: String },
+    /// Watches the names and tags of the services in the datacenter.
+    #[cfg(feature = "catalog")]
+    Services,
+    /// Watches the nodes in the datacenter.
+    #[cfg(feature = "catalog")]
+    Nodes,
+    /// Watches the instances of a service, as [Health::watch_service].
+    #[cfg(feature = "health")]
+    Service {
+        service: String,
+        /// Only watch the instances with all of these tags.
+        #[serde(default, rename = "tag")]
+        tags: Vec<String>,
+        /// Only watch the instances with passing checks.
+        #[serde(default, rename = "passingonly")]
+        passing_only: bool,
+    },
+    /// Watches health checks, either those of a service or those in a given
+    /// state. If both are set, the checks of the service are filtered by
+    /// state, and if neither is, all checks are watched.
+    #[cfg(feature = "health")]
+    Checks {
+        #[serde(default)]
+        service: Option<String>,
+        /// The state of the watched checks, where `any` decodes to [None].
+        #[serde(default, deserialize_with = "deserialize_state")]
+        state: Option<CheckStatus>,
+    },
+    /// Watches the user events known by the agent, optionally only those with
+    /// the given name.
+    #[cfg(feature = "event")]
+    Event {
+        #[serde(default)]
+        name: Option<String>,
+    },
+}
+
+/// A change yielded by a [WatchPlan], with the variant matching the variant
+/// of the plan.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum WatchResult {
+    #[cfg(feature = "kv")]
+    Key(Option<KVPair>),
+    #[cfg(feature = "kv")]
+    KeyPrefix(Vec<KVPair>),
+    #[cfg(feature = "catalog")]
+    Services(HashMap<String, Vec<String>>),
+    #[cfg(feature = "catalog")]
+    Nodes(Vec<Node>),
+    #[cfg(feature = "health")]
+    Service(Vec<ServiceEntry>),
+    #[cfg(feature = "health")]
+    Checks(Vec<HealthCheck>),
+    #[cfg(feature = "event")]
+    Event(Vec<UserEvent>),
+}
+
+impl WatchPlan {
+    /// This method returns a [WatchStream] that yields the watched resource
+    /// every time it changes, using the given client and options.
+    ///
+    /// As with Consul watches, the event list is yielded in full, but only
+    /// when a new event is received.
+    #[cfg_attr(
+        not(any(feature = "catalog", feature = "event", feature = "health", feature = "kv")),
+        allow(unused_variables)
+    )]
+    pub fn into_stream(
+        self,
+        client: &Client,
+        options: Option<QueryOptions>,
+    ) -> WatchStream<ConsulResult<WatchResult>> {
+        let client = client.clone();
+        match self {
+            #[cfg(feature = "kv")]
+            WatchPlan::Key { key } => {
+                Box::pin(client.watch_key(&key, options).map_ok(WatchResult::Key))
+            }
+            #[cfg(feature = "kv")]
+            WatchPlan::KeyPrefix { prefix } => Box::pin(
+                watch(options, move |options| {
+                    let client = client.clone();
+                    let prefix = prefix.clone();
+                    async move { client.list_entries(&prefix, Some(options)).await }
+                })
+                .map_ok(WatchResult::KeyPrefix),
+            ),
+            #[cfg(feature = "catalog")]
+            WatchPlan::Services => Box::pin(
+                watch(options, move |options| {
+                    let client = client.clone();
+                    async move { client.list_datacenter_services(Some(options)).await }
+                })
+                .map_ok(WatchResult::Services),
+            ),
+            #[cfg(feature = "catalog")]
+            WatchPlan::Nodes => Box::pin(
+                watch(options, move |options| {
+                    let client = client.clone();
+                    async move { client.list_datacenter_nodes(Some(options)).await }
+                })
+                .map_ok(WatchResult::Nodes),
+            ),
+            #[cfg(feature = "health")]
+            WatchPlan::Service { service, tags, passing_only } => {
+                let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+                Box::pin(
+                    client
+                        .watch_service(&service, &tags, passing_only, options)
+                        .map_ok(WatchResult::Service),
+                )
+            }
+            #[cfg(feature = "health")]
+            WatchPlan::Checks { service, state } => Box::pin(
+                watch(options, move |options| {
+                    let client = client.clone();
+                    let service = service.clone();
+                    async move {
+                        let service = match service {
+                            Some(service) => service,
+                            None => return client.checks_in_state(state, Some(options)).await,
+                        };
+                        let (mut checks, meta) =
+                            client.service_checks(&service, Some(options)).await?;
+                        match state {
+                            Some(CheckStatus::Maintenance) => {
+                                checks.retain(HealthCheck::is_maintenance)
+                            }
+                            Some(state) => checks.retain(|check| check.status == state),
+                            None => {}
+                        }
+                        Ok((checks, meta))
+                    }
+                })
+                .map_ok(WatchResult::Checks),
+            ),
+            #[cfg(feature = "event")]
+            WatchPlan::Event { name } => {
+                let events = watch(options, move |options| {
+                    let client = client.clone();
+                    let name = name.clone();
+                    async move { client.list_events(name.as_deref(), None, Some(options)).await }
+                });
+                // the index of the event list is a hash of the latest event
+                // rather than a Raft index, so an index going backwards resets
+                // the watch and repeats the same events. The first result is
+                // always yielded, even when there are no events yet.
+                let mut first = true;
+                let mut latest = None;
+                Box::pin(
+                    events
+                        .filter(move |result| {
+                            let events = match result {
+                                Ok(events) => events,
+                                Err(_) => return future::ready(true),
+                            };
+                            let id = events.last().map(|event| event.id.clone());
+                            let changed = first || id != latest;
+                            first = false;
+                            latest = id;
+                            future::ready(changed)
+                        })
+                        .map_ok(WatchResult::Event),
+                )
+            }
+        }
+    }
+}
+
+/// This function decodes the state of a checks watch, which is `any` to watch
+/// the checks in every state.
+#[cfg(feature = "health")]
+fn deserialize_state<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<CheckStatus>, D::Error> {
+    match Option::<String>::deserialize(deserializer)?.as_deref() {
+        None | Some("any") => Ok(None),
+        Some(state) => CheckStatus::deserialize(StrDeserializer::<D::Error>::new(state)).map(Some),
+    }
+}
+
+#[cfg(all(test, feature = "catalog", feature = "event", feature = "health", feature = "kv"))]
+mod tests {
+    use futures::StreamExt;
+
+    use super::{WatchPlan, WatchResult};
+    use crate::{request::tests::recording_client, CheckStatus};
+
+    #[test]
+    fn test_decode_watch_plan() {
+        let plan = |json| serde_json::from_str::<WatchPlan>(json).unwrap();
+        assert_eq!(
+            plan(r#"{"type": "keyprefix", "prefix": "foo/", "args": ["/usr/bin/my-handler"]}"#),
+            WatchPlan::KeyPrefix { prefix: String::from("foo/") }
+        );
+        assert_eq!(
+            plan(r#"{"type": "service", "service": "redis", "tag": ["bar"], "passingonly": true}"#),
+            WatchPlan::Service {
+                service: String::from("redis"),
+                tags: vec![String::from("bar")],
+                passing_only: true
+            }
+        );
+        assert_eq!(
+            plan(r#"{"type": "checks", "state": "critical"}"#),
+            WatchPlan::Checks { service: None, state: Some(CheckStatus::Critical) }
+        );
+        assert_eq!(
+            plan(r#"{"type": "checks", "service": "redis", "state": "any"}"#),
+            WatchPlan::Checks { service: Some(String::from("redis")), state: None }
+        );
+        assert_eq!(plan(r#"{"type": "nodes"}"#), WatchPlan::Nodes);
+        assert_eq!(plan(r#"{"type": "event"}"#), WatchPlan::Event { name: None });
+    }
+
+    #[tokio::test]
+    async fn test_watch_events() {
+        const EVENTS: &str = r#"[{"ID": "b54fe110", "Name": "deploy"}]"#;
+        const NEW_EVENTS: &str = r#"[
+            {"ID": "b54fe110", "Name": "deploy"},
+            {"ID": "6b3a4a42", "Name": "deploy"}
+        ]"#;
+        const NEWER_EVENTS: &str = r#"[
+            {"ID": "b54fe110", "Name": "deploy"},
+            {"ID": "6b3a4a42", "Name": "deploy"},
+            {"ID": "0c7e2f19", "Name": "deploy"}
+        ]"#;
+        let (client, requests) = recording_client(&[
+            (200, &[("X-Consul-Index", "900")], EVENTS),
+            // a new event with a lower hash resets the index, which repeats
+            // the same events without blocking
+            (200, &[("X-Consul-Index", "700")], NEW_EVENTS),
+            (200, &[("X-Consul-Index", "700")], NEW_EVENTS),
+            (200, &[("X-Consul-Index", "950")], NEWER_EVENTS),
+        ]);
+        let plan = WatchPlan::Event { name: Some(String::from("deploy")) };
+        let events: Vec<_> = plan.into_stream(&client, None).take(3).collect().await;
+        let lengths: Vec<_> = events
+            .into_iter()
+            .map(|result| match result.unwrap() {
+                WatchResult::Event(events) => events.len(),
+                result => panic!("unexpected result {:?}", result),
+            })
+            .collect();
+        assert_eq!(lengths, [1, 2, 3]);
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[2], "GET /v1/event/list?name=deploy");
+        assert_eq!(requests.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_watch_no_events() {
+        let (client, requests) = recording_client(&[
+            (200, &[("X-Consul-Index", "1")], "[]"),
+            (200, &[("X-Consul-Index", "900")], r#"[{"ID": "b54fe110", "Name": "deploy"}]"#),
+        ]);
+        let plan = WatchPlan::Event { name: None };
+        let events: Vec<_> = plan.into_stream(&client, None).take(2).collect().await;
+        let lengths: Vec<_> = events
+            .into_iter()
+            .map(|result| match result.unwrap() {
+                WatchResult::Event(events) => events.len(),
+                result => panic!("unexpected result {:?}", result),
+            })
+            .collect();
+        assert_eq!(lengths, [0, 1]);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+}
@@ -12,7 +12,7 @@ async fn test_register_check() {
     };
     client.register_check(check).await.expect("failed to register check");
 
-    let checks = client.list_checks().await.unwrap();
+    let checks = client.list_checks(None).await.unwrap();
     assert!(checks.contains_key("test_check"));
 
     client.deregister_check("test_check").await.expect("failed to deregister check");
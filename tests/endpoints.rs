@@ -0,0 +1,338 @@
+//! Integration tests exercising every API area against a Consul dev agent,
+//! started with `consul agent -dev`. Since they modify the state of the
+//! agent, they are ignored by default, and can be run with:
+//!
+//! ```text
+//! cargo test --all-features --test endpoints -- --ignored --test-threads=1
+//! ```
+//!
+//! With the `testcontainers` feature, each test runs against its own agent in
+//! Docker instead.
+
+use consul_oxide::{
+    Agent, AgentServices, Catalog, CatalogDeregistrationPayload, CatalogRegistrationPayload,
+    CatalogServiceRegistration, Client, ConfigEntries, ConfigEntry, ConnectCA, ConnectIntentions,
+    Coordinates, Event, Health, IntentionAction, KVPair, KVTxnOp, OperatorAutopilot, OperatorRaft,
+    PreparedQuery, PreparedQueryDefinition, ServiceDefaultsConfigEntry, ServiceQuery,
+    ServiceRegistrationPayload, Session, SessionEntry, Snapshot, Status, Txn, TxnOp, TxnResult,
+    UpsertIntentionPayload, KV,
+};
+use futures::TryStreamExt;
+
+/// The agent a test runs against, which is kept running until the end of the
+/// test.
+struct TestAgent {
+    client: Client,
+    #[cfg(feature = "testcontainers")]
+    _container: consul_oxide::testing::ConsulContainer,
+}
+
+#[cfg(not(feature = "testcontainers"))]
+async fn agent() -> TestAgent {
+    TestAgent { client: Client::new(consul_oxide::Config::new_from_env()) }
+}
+
+#[cfg(feature = "testcontainers")]
+async fn agent() -> TestAgent {
+    let container = consul_oxide::testing::ConsulContainer::start().await.unwrap();
+    TestAgent { client: container.client(), _container: container }
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_catalog() {
+    let agent = agent().await;
+    let client = &agent.client;
+    assert_eq!(client.list_datacenters().await.unwrap(), ["dc1"]);
+    let (nodes, _) = client.list_datacenter_nodes(None).await.unwrap();
+    assert!(!nodes.is_empty());
+
+    let payload = CatalogRegistrationPayload {
+        node: String::from("endpoints-external"),
+        address: String::from("192.0.2.1"),
+        service: Some(CatalogServiceRegistration {
+            service: String::from("endpoints-external-web"),
+            port: Some(80),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    client.register(payload, None).await.unwrap();
+    let (services, _) = client.list_datacenter_services(None).await.unwrap();
+    assert!(services.contains_key("endpoints-external-web"));
+    let (instances, _) =
+        client.list_nodes_for_service("endpoints-external-web", None, None).await.unwrap();
+    assert_eq!(instances.len(), 1);
+    let (node, _) = client.list_node_services("endpoints-external", None).await.unwrap();
+    assert_eq!(node.unwrap().services.len(), 1);
+    let (node, _) = client.get_node("endpoints-external", None).await.unwrap();
+    assert!(node.is_some());
+
+    let payload = CatalogDeregistrationPayload {
+        node: String::from("endpoints-external"),
+        ..Default::default()
+    };
+    client.deregister(payload, None).await.unwrap();
+    let (node, _) = client.get_node("endpoints-external", None).await.unwrap();
+    assert!(node.is_none());
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_agent() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let member = client.agent_self().await.unwrap().member;
+    assert!(!member.name.is_empty());
+    client.list_members(false).await.unwrap();
+    client.get_metrics().await.unwrap();
+
+    let payload = ServiceRegistrationPayload {
+        name: String::from("endpoints-agent-web"),
+        port: 8080,
+        ..Default::default()
+    };
+    client.register_service(payload).await.unwrap();
+    let services = client.list_local_services(None).await.unwrap();
+    assert!(services.iter().any(|service| service.id == "endpoints-agent-web"));
+    let (config, meta) =
+        client.get_local_service_config("endpoints-agent-web", None).await.unwrap();
+    assert_eq!(config.unwrap().port, 8080);
+    assert!(meta.last_content_hash.is_some());
+    client.deregister_service("endpoints-agent-web").await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_health() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let (instances, _) = client.list_service_instances("consul", &[], true, None).await.unwrap();
+    assert_eq!(instances.len(), 1);
+    let node = &instances[0].node.node;
+    let (checks, _) = client.node_checks(node, None).await.unwrap();
+    assert!(checks.iter().any(|check| check.check_id == "serfHealth"));
+    client.checks_in_state(None, None).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_status() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let leader = client.get_leader(None).await.unwrap();
+    assert!(client.list_peers(None).await.unwrap().contains(&leader));
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_coordinates() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let datacenters = client.list_datacenter_coordinates().await.unwrap();
+    assert_eq!(datacenters.len(), 1);
+    client.list_node_coordinates(None).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_event() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let payload = Some(b"payload".to_vec());
+    let (event, _) = client.fire_event("endpoints-event", payload, None, None).await.unwrap();
+    let (events, _) = client.list_events(Some("endpoints-event"), None, None).await.unwrap();
+    assert!(events.iter().any(|e| e.id == event.id));
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_config_entries() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let entry = ConfigEntry::ServiceDefaults(ServiceDefaultsConfigEntry {
+        name: String::from("endpoints-defaults"),
+        protocol: String::from("http"),
+        ..Default::default()
+    });
+    assert!(client.apply_config_entry(entry, None, None).await.unwrap().0);
+    let (entry, _) =
+        client.get_config_entry("service-defaults", "endpoints-defaults", None).await.unwrap();
+    assert!(matches!(entry, Some(ConfigEntry::ServiceDefaults(e)) if e.protocol == "http"));
+    let (entries, _) = client.list_config_entries("service-defaults", None).await.unwrap();
+    assert!(!entries.is_empty());
+    let deleted = client
+        .delete_config_entry("service-defaults", "endpoints-defaults", None, None)
+        .await
+        .unwrap();
+    assert!(deleted.0);
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_operator() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let configuration = client.get_raft_configuration(false, None).await.unwrap();
+    assert_eq!(configuration.servers.len(), 1);
+    client.get_autopilot_configuration(None).await.unwrap();
+    client.get_autopilot_health(None).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_kv() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let pair = KVPair {
+        key: String::from("endpoints/kv/key"),
+        value: b"value".to_vec(),
+        ..Default::default()
+    };
+    assert!(client.put_entry(&pair, None, None).await.unwrap().0);
+    let (pairs, _) = client.get_entry("endpoints/kv/key", None).await.unwrap();
+    assert_eq!(pairs[0].value, b"value");
+    let (value, _) = client.get_raw("endpoints/kv/key", None).await.unwrap();
+    assert_eq!(value.unwrap(), "value");
+    let (keys, _) = client.list_keys("endpoints/kv/", None, None).await.unwrap();
+    assert_eq!(keys, ["endpoints/kv/key"]);
+    assert!(client.delete_tree("endpoints/kv/", None).await.unwrap().0);
+    let (value, _) = client.get_raw("endpoints/kv/key", None).await.unwrap();
+    assert!(value.is_none());
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_session() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let session = SessionEntry {
+        name: Some(String::from("endpoints-session")),
+        ttl: Some(String::from("30s")),
+        ..Default::default()
+    };
+    let (session, _) = client.create_session(session, None).await.unwrap();
+    let id = session.id.unwrap();
+    let (sessions, _) = client.get_session_info(&id, None).await.unwrap();
+    assert_eq!(sessions[0].name.as_deref(), Some("endpoints-session"));
+    let (sessions, _) = client.list_sessions(None).await.unwrap();
+    assert!(sessions.iter().any(|session| session.id.as_ref() == Some(&id)));
+    let (sessions, _) = client.renew_session(&id, None).await.unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert!(client.destroy_session(&id, None).await.unwrap().0);
+}
+
+// `consul agent -dev` does not enable ACLs, so this test needs an agent
+// started with ACLs enabled, and a management token in `CONSUL_HTTP_TOKEN`.
+#[cfg(not(feature = "testcontainers"))]
+#[tokio::test]
+#[ignore = "requires a Consul dev agent with ACLs enabled"]
+async fn test_acl() {
+    use consul_oxide::{Acl, AclPolicies, AclPolicyPayload};
+
+    let agent = agent().await;
+    let client = &agent.client;
+    client.check_acl_replication().await.unwrap();
+    let payload = AclPolicyPayload {
+        name: String::from("endpoints-policy"),
+        rules: Some(String::from(r#"key_prefix "endpoints/" { policy = "read" }"#)),
+        ..Default::default()
+    };
+    let (policy, _) = client.create_policy(payload.clone(), None).await.unwrap();
+    let read = client.read_policy_by_name("endpoints-policy").await.unwrap();
+    assert_eq!(read.id, policy.id);
+    let payload = AclPolicyPayload { description: Some(String::from("updated")), ..payload };
+    let (updated, _) = client.update_policy(&policy.id, payload, None).await.unwrap();
+    assert_eq!(updated.description, "updated");
+    assert!(client.list_policies().await.unwrap().iter().any(|p| p.id == policy.id));
+    assert!(client.delete_policy(&policy.id, None).await.unwrap().0);
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_txn() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let ops = vec![
+        TxnOp::Kv(KVTxnOp::Set {
+            key: String::from("endpoints/txn/key"),
+            value: b"value".to_vec(),
+            flags: 0,
+        }),
+        TxnOp::Kv(KVTxnOp::Get { key: String::from("endpoints/txn/key") }),
+    ];
+    let (results, _) = client.txn(ops, None).await.unwrap();
+    assert!(matches!(&results[1], TxnResult::Kv(pair) if pair.value == b"value"));
+    let ops = vec![TxnOp::Kv(KVTxnOp::DeleteTree { key: String::from("endpoints/txn/") })];
+    client.txn(ops, None).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_prepared_query() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let query = PreparedQueryDefinition {
+        name: String::from("endpoints-query"),
+        service: ServiceQuery { service: String::from("consul"), ..Default::default() },
+        ..Default::default()
+    };
+    let (id, _) = client.create_prepared_query(query, None).await.unwrap();
+    let queries = client.read_prepared_query(&id, None).await.unwrap();
+    assert_eq!(queries[0].name, "endpoints-query");
+    assert!(client.list_prepared_queries(None).await.unwrap().iter().any(|q| q.id == id));
+    let response = client.execute_prepared_query("endpoints-query", None, None).await.unwrap();
+    assert_eq!(response.nodes.len(), 1);
+    let explained = client.explain_prepared_query(&id, None).await.unwrap();
+    assert_eq!(explained.query.service.service, "consul");
+    client.delete_prepared_query(&id, None).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_snapshot() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let (stream, meta) = client.save_snapshot(false, None).await.unwrap();
+    assert!(meta.last_index.is_some());
+    let chunks: Vec<_> = stream.try_collect().await.unwrap();
+    let snapshot = chunks.concat();
+    // snapshots are gzipped tar archives
+    assert_eq!(snapshot[..2], [0x1f, 0x8b]);
+    let stream = futures::stream::iter([Ok::<_, std::io::Error>(snapshot)]);
+    client.restore_snapshot(stream, None).await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_intentions() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let payload =
+        UpsertIntentionPayload { action: Some(IntentionAction::Allow), ..Default::default() };
+    let (created, _) = client
+        .upsert_intention_by_name("endpoints-web", "endpoints-db", payload, None)
+        .await
+        .unwrap();
+    assert!(created);
+    let intention =
+        client.read_intention_by_name("endpoints-web", "endpoints-db", None).await.unwrap();
+    assert_eq!(intention.unwrap().action, Some(IntentionAction::Allow));
+    assert!(client.check_intention("endpoints-web", "endpoints-db", None).await.unwrap());
+    let (intentions, _) = client.list_intentions(None).await.unwrap();
+    assert!(intentions.iter().any(|i| i.destination_name == "endpoints-db"));
+    let (deleted, _) =
+        client.delete_intention_by_name("endpoints-web", "endpoints-db", None).await.unwrap();
+    assert!(deleted);
+}
+
+#[tokio::test]
+#[ignore = "requires a Consul dev agent"]
+async fn test_connect_ca() {
+    let agent = agent().await;
+    let client = &agent.client;
+    let (roots, _) = client.list_ca_root_certs(None).await.unwrap();
+    assert!(roots.active_root().is_some());
+    let config = client.get_ca_config(None).await.unwrap();
+    assert_eq!(config.provider, "consul");
+}
@@ -2,32 +2,52 @@
 //! other, they are tested in a single integration test.
 
 extern crate consul_oxide;
-use consul_oxide::{Client, Config, KVPair, KV};
+use consul_oxide::{Client, Config, KVPair, KVPutParams, TypedKV, KV};
 
 #[tokio::test]
 async fn test_kv_methods() {
     let config = Config::default();
     let client = Client::new(config);
-    let r = client.list_entries("", None).await.unwrap();
+    let (r, _) = client.list_entries("", None).await.unwrap();
     assert!(r.is_empty());
 
-    let pair = KVPair {
-        key: String::from("testkey"),
-        value: String::from("testvalue"),
-        ..Default::default()
-    };
+    let pair =
+        KVPair { key: String::from("testkey"), value: b"testvalue".to_vec(), ..Default::default() };
 
-    assert!(client.put_entry(&pair, None).await.unwrap());
+    assert!(client.put_entry(&pair, None, None).await.unwrap().0);
+    // check-and-set with an index of 0 fails, since the key already exists
+    let cas = KVPutParams { cas: Some(0), ..Default::default() };
+    assert!(!client.put_entry(&pair, Some(cas), None).await.unwrap().0);
 
-    let b64val = client.get_entry("testkey", None).await.unwrap().into_iter().next().unwrap().value;
-    let bytes = base64::decode(b64val).unwrap();
-    assert_eq!(std::str::from_utf8(&bytes).unwrap(), "\"testvalue\"");
+    let (entries, meta) = client.get_entry("testkey", None).await.unwrap();
+    assert!(meta.last_index.is_some());
+    assert_eq!(entries.into_iter().next().unwrap().value, b"testvalue");
+    let (value, _) = client.get_raw("testkey", None).await.unwrap();
+    assert_eq!(value.unwrap().as_ref(), b"testvalue");
 
-    let r = client.list_entries("t", None).await.unwrap();
+    let (r, _) = client.list_entries("t", None).await.unwrap();
     assert!(!r.is_empty());
 
-    client.delete_entry("testkey", None).await.unwrap();
-
-    let r = client.list_entries("", None).await.unwrap();
+    let nested = KVPair { key: String::from("testdir/a/b"), ..Default::default() };
+    assert!(client.put_entry(&nested, None, None).await.unwrap().0);
+    let (keys, _) = client.list_keys("", Some("/"), None).await.unwrap();
+    assert_eq!(keys, ["testdir/", "testkey"]);
+    let entries = client.export_tree("testdir/", None).await.unwrap();
+    assert_eq!(entries.len(), 1);
+    client.import_tree(entries, Some("testcopy/"), None).await.unwrap();
+    let (keys, _) = client.list_keys("testcopy/", None, None).await.unwrap();
+    assert_eq!(keys, ["testcopy/testdir/a/b"]);
+    assert!(client.delete_tree("testcopy/", None).await.unwrap().0);
+    assert!(client.delete_tree("testdir/", None).await.unwrap().0);
+
+    assert!(client.put_json("testjson", &[1, 2, 3], None).await.unwrap().0);
+    let (value, _) = client.get_json::<Vec<u64>>("testjson", None).await.unwrap();
+    assert_eq!(value, Some(vec![1, 2, 3]));
+    client.delete_entry("testjson", None, None).await.unwrap();
+
+    assert!(!client.delete_entry("testkey", Some(0), None).await.unwrap().0);
+    client.delete_entry("testkey", None, None).await.unwrap();
+
+    let (r, _) = client.list_entries("", None).await.unwrap();
     assert!(r.is_empty());
 }
@@ -13,7 +13,7 @@ async fn test_session_create() {
     let entry =
         SessionEntry { name: Some(unique_test_identifier.to_string()), ..Default::default() };
 
-    let created_session_entry = client.create_session(entry, None).await.unwrap();
+    let (created_session_entry, _) = client.create_session(entry, None).await.unwrap();
 
     assert_eq!(
         get_number_of_session_entries_with_matching_name(&client, &unique_test_identifier).await,
@@ -30,7 +30,7 @@ async fn test_session_destory() {
     let entry =
         SessionEntry { name: Some(unique_test_identifier.to_string()), ..Default::default() };
 
-    let created_session_entry = client.create_session(entry, None).await.unwrap();
+    let (created_session_entry, _) = client.create_session(entry, None).await.unwrap();
 
     assert_eq!(
         get_number_of_session_entries_with_matching_name(&client, &unique_test_identifier).await,
@@ -56,15 +56,16 @@ async fn test_session_info() {
     let entry =
         SessionEntry { name: Some(unique_test_identifier.to_string()), ..Default::default() };
 
-    let created_session_entry = client.create_session(entry, None).await.unwrap();
+    let (created_session_entry, _) = client.create_session(entry, None).await.unwrap();
 
     let created_session_entry_id = created_session_entry.id.unwrap();
 
-    let session_entries = client.get_session_info(&created_session_entry_id, None).await.unwrap();
+    let (session_entries, _) =
+        client.get_session_info(&created_session_entry_id, None).await.unwrap();
 
     assert_eq!(session_entries.len(), 1);
 
-    let session_entry = session_entries.get(0);
+    let session_entry = session_entries.first();
 
     assert_eq!(*session_entry.as_ref().unwrap().name.as_ref().unwrap(), unique_test_identifier);
 
@@ -86,12 +87,12 @@ async fn test_session_list() {
     for entry_name in &entry_names {
         let entry = SessionEntry { name: Some(entry_name.to_string()), ..Default::default() };
 
-        let created_session_entry = client.create_session(entry, None).await.unwrap();
+        let (created_session_entry, _) = client.create_session(entry, None).await.unwrap();
 
         session_ids.push(created_session_entry.id.unwrap());
     }
 
-    let session_entries = client.list_sessions(None).await.unwrap();
+    let (session_entries, _) = client.list_sessions(None).await.unwrap();
 
     let filtered_session_entries = session_entries
         .iter()
@@ -147,7 +148,7 @@ async fn test_renew_session() {
 
     let entry = SessionEntry { name: Some(unique_test_identifier), ..Default::default() };
 
-    let created_session_entry = client.create_session(entry, None).await.unwrap();
+    let (created_session_entry, _) = client.create_session(entry, None).await.unwrap();
 
     let created_session_entry_id = created_session_entry.id.unwrap();
 
@@ -174,6 +175,6 @@ async fn get_number_of_session_entries_with_matching_name(
     client: &Client,
     unique_test_identifier: &str,
 ) -> usize {
-    let session_entries = client.list_sessions(None).await.unwrap();
+    let (session_entries, _) = client.list_sessions(None).await.unwrap();
     session_entries.iter().filter(|s| s.name.as_ref().unwrap() == unique_test_identifier).count()
 }